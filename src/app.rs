@@ -1,18 +1,80 @@
-use crossterm::event::KeyCode;
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers, MouseButton, MouseEvent, MouseEventKind};
 use ratatui::{
     Frame,
-    layout::{Alignment, Constraint, Flex, Layout},
+    layout::{Alignment, Constraint, Flex, Layout, Position, Rect},
     style::{Color, Style, Stylize},
     symbols,
-    text::Line,
-    widgets::{Block, Borders, List, ListItem, Padding, Paragraph},
+    text::{Line, Span},
+    widgets::{Block, Borders, Clear, List, ListItem, Padding, Paragraph, Wrap},
 };
-use std::io;
+use std::collections::HashSet;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
 
+use serde::{Deserialize, Serialize};
+use tokio::sync::broadcast::{self, error::TryRecvError};
+use tracing::warn;
+
+use crate::config::SharedConfig;
+use crate::content::Content;
+use crate::easter_eggs::{EasterEgg, SequenceMatcher};
+use crate::event_bus::Event;
+use crate::graphics::Placement;
+use crate::intro::Intro;
+use crate::keymap::{self, Input, KeyBindings, Keymap, Keys, VIM_BINDINGS};
+use crate::notifier::Visit;
+use crate::now_playing::Track;
 use crate::pages::{
-    page::Page,
-    style::{GRAY, dimmed_white_span_owned, gray_span, white_span, white_span_owned},
+    admin::AdminPage,
+    arcade::{ArcadePage, Player},
+    blog::BlogPage,
+    chat::ChatPage,
+    content_page::ContentPage,
+    exit::ExitPage,
+    github::GitHubPage,
+    guestbook::GuestbookPage,
+    keys::KeysPage,
+    locomotive::LocomotivePage,
+    message::MessagePage,
+    page::{Navigation, Page},
+    poll::PollPage,
+    settings::Settings,
+    shell::ShellPage,
+    snake::SnakePage,
+    stats::StatsPage,
+    style::{
+        GRAY, LINK_COLOR, dimmed_white_span_owned, gray_span, gray_span_owned, white_span,
+        white_span_owned,
+    },
+    text_input::TextInput,
+    tic_tac_toe::{self, TicTacToePage},
 };
+use crate::palette::{Action, Command, CommandPalette, PaletteOutcome};
+use crate::preferences::{FrameRateLimit, SessionPreferences};
+use crate::server::public_files::PublicFiles;
+use crate::server::session_registry::{SessionId, SessionRegistry};
+use crate::server::tui_app::{FrameRate, TuiApp};
+use crate::services::Services;
+use crate::stats::Stats;
+use crate::store::Store;
+use crate::theme::THEMES;
+use crate::tic_tac_toe::PlayerId;
+
+// How long the app keeps the full frame rate after the last input
+const INPUT_ACTIVITY_WINDOW: Duration = Duration::from_secs(1);
+// How long an operator's wall message stays up unless dismissed
+const WALL_DURATION: Duration = Duration::from_secs(60);
+const MAX_SEARCH_CHARS: usize = 60;
+// Longer songs are cut off in the footer, leaving room for the rest of it
+const MAX_TRACK_CHARS: usize = 48;
+
+/// Whether the session carries on after a key press. Ctrl+C, `q` on a top-level
+/// page and the exit entry in the menu all end it the same way.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum KeyOutcome {
+    Continue,
+    Quit,
+}
 
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub enum FocusMode {
@@ -20,10 +82,43 @@ pub enum FocusMode {
     ContentFocus,
 }
 
+/// Where a visitor with a key was when they left, to pick up there next time.
+#[derive(Serialize, Deserialize)]
+struct Snapshot {
+    page: String,
+    position: usize,
+    content_focused: bool,
+}
+
+/// What sits on top of the page and takes the next key, until it's closed.
+enum Modal {
+    Palette(CommandPalette),
+    /// What the palette's last command had to say, with the box's title.
+    Notice(&'static str, String),
+    /// The keys listed by `?`.
+    Help(Vec<Line<'static>>),
+}
+
+/// Routes input and drawing to the current page. `pages` are the top-level screens
+/// listed in the menu; pages they open are stacked on top of the selected one and
+/// popped again with Esc.
 pub struct App {
     pub running: bool,
     pub selected_page: usize,
     pub pages: Vec<Box<dyn Page>>,
+    stack: Vec<Box<dyn Page>>,
+    // Neither is set for the admin view, which has no content pages and whose
+    // browsing isn't counted
+    content: Option<Arc<Content>>,
+    stats: Option<Arc<Stats>>,
+    content_generation: u64,
+    // Where the content directory's pages sit among `pages`, between the built-in
+    // pages and the guestbook
+    content_start: usize,
+    content_len: usize,
+    preferences: Arc<SessionPreferences>,
+    // Pages already counted as viewed in this session
+    viewed: HashSet<String>,
     pub show_left: bool,
     pub show_center: bool,
     pub show_right: bool,
@@ -31,31 +126,306 @@ pub struct App {
     pub show_aa1: bool,
     pub show_additional: bool,
     pub focus_mode: FocusMode,
+    menu_area: Rect,
+    content_area: Rect,
+    disconnect_countdown: Option<u64>,
+    session_ending: Option<u64>,
+    // Only known when served, the local TUI has no footer
+    visitors_online: Option<usize>,
+    events: broadcast::Receiver<Event>,
+    wall: Option<(String, Instant)>,
+    modal: Option<Modal>,
+    keymap: Keymap,
+    // The vim keys' `/` prompt while it's open, and what it last looked for
+    search: Option<TextInput>,
+    last_search: Option<String>,
+    // Shown where the prompt was until the next key, e.g. that nothing matched
+    search_message: Option<String>,
+    config: SharedConfig,
+    // Played before the home page, and gone once it's over or skipped
+    intro: Option<Intro>,
+    // For what the easter eggs need when they're opened
+    services: Services,
+    // Spots the key sequences that open the easter eggs
+    easter_eggs: SequenceMatcher,
+    // The visitor's seat at tic-tac-toe, to tell them it's their move while
+    // they're on another page. None for the admin view
+    tic_tac_toe: Option<PlayerId>,
+    dirty: bool,
+    animating: bool,
+    last_input: Instant,
+    connected_at: Instant,
+    // Minutes connected as the footer shows them, to redraw when they tick over
+    connected_minutes: u64,
+    // The song in the footer, redrawn when it changes
+    now_playing_generation: u64,
+    // The weather on the about page, likewise
+    weather_generation: u64,
+    // Where a visitor with a key is saved when the session ends, by fingerprint
+    resume: Option<(Arc<Store>, String)>,
+    // Told what the visitor looks at, and that they left once the app is gone.
+    // None for admins and the local TUI
+    visit: Option<Visit>,
+}
+
+impl Drop for App {
+    fn drop(&mut self) {
+        let Some((store, fingerprint)) = self.resume.take() else {
+            return;
+        };
+        // The last entry is the exit page, which is no place to come back to
+        let Some(page) = self
+            .pages
+            .get(self.selected_page)
+            .filter(|_| self.selected_page + 1 < self.pages.len())
+        else {
+            return;
+        };
+        let snapshot = Snapshot {
+            page: page.title().to_string(),
+            position: page.position(),
+            content_focused: self.focus_mode == FocusMode::ContentFocus,
+        };
+        let saved = serde_json::to_string(&snapshot).expect("snapshots always serialize");
+        // Dropped on a runtime worker, which mustn't wait on the database
+        tokio::task::spawn_blocking(move || {
+            if let Err(e) = store.set_snapshot(&fingerprint, &saved) {
+                warn!("Failed to save snapshot for {}: {}", fingerprint, e);
+            }
+        });
+    }
+}
+
+/// The portfolio for a visitor, or the admin view for admin keys, as
+/// `Portfolio` makes it.
+impl TuiApp for App {
+    fn draw(&mut self, frame: &mut Frame) {
+        self.draw(frame);
+    }
+
+    fn handle_key(&mut self, key_event: KeyEvent) {
+        self.handle_key_event(key_event);
+    }
+
+    fn handle_tick(&mut self, tick: u64) -> bool {
+        self.handle_tick(tick)
+    }
+
+    fn wants_close(&self) -> bool {
+        !self.running
+    }
+
+    fn handle_mouse(&mut self, mouse_event: MouseEvent) {
+        self.handle_mouse_event(mouse_event);
+    }
+
+    fn handle_paste(&mut self, text: &str) {
+        self.handle_paste(text);
+    }
+
+    fn handle_resize(&mut self, width: u16, height: u16) {
+        self.handle_resize(width, height);
+    }
+
+    fn take_dirty(&mut self) -> bool {
+        self.take_dirty()
+    }
+
+    fn mark_dirty(&mut self) {
+        self.mark_dirty();
+    }
+
+    fn frame_rate(&self) -> FrameRate {
+        self.frame_rate()
+    }
+
+    fn images(&self) -> Vec<Placement> {
+        self.images()
+    }
+
+    fn current_page(&self) -> String {
+        self.current_page_title()
+    }
+
+    fn show_wall(&mut self, message: String) {
+        self.show_wall(message);
+    }
+
+    fn set_disconnect_countdown(&mut self, seconds: Option<u64>) {
+        self.set_disconnect_countdown(seconds);
+    }
+
+    fn set_session_ending(&mut self, seconds: Option<u64>) {
+        self.set_session_ending(seconds);
+    }
+
+    fn set_visitors_online(&mut self, count: usize) {
+        self.set_visitors_online(count);
+    }
 }
 
 impl App {
-    pub fn new() -> Self {
-        let show_widgets = std::env::var("SHOW_WIDGETS").unwrap_or_default();
-        let show_left = show_widgets == "LEFT" || show_widgets == "ALL";
-        let show_center = show_widgets == "CENTER" || show_widgets == "ALL";
-        let show_right = show_widgets == "RIGHT" || show_widgets == "ALL";
-        let show_menu = show_widgets == "MENU" || show_widgets == "ALL";
-        let show_aa1 = show_widgets == "AA1" || show_widgets == "ALL";
-        let show_additional = show_widgets == "ADDITIONAL" || show_widgets == "ALL";
+    /// `visitor` identifies the session's visitor to the guestbook's rate limit.
+    pub fn new(services: &Services, preferences: Arc<SessionPreferences>, visitor: String) -> Self {
         let debug_frames = std::env::var("FRAME_DEBUG").unwrap_or_default();
         let show_debug_frames = debug_frames == "TRUE" || debug_frames == "true";
 
-        let pages: Vec<Box<dyn Page>> = vec![
-            Box::new(crate::pages::about::About::new(show_debug_frames)),
+        let bindings = Arc::new(services.config.read().unwrap().keys.clone());
+        let mut pages: Vec<Box<dyn Page>> = vec![
+            Box::new(crate::pages::about::About::new(
+                show_debug_frames,
+                services.content.avatar(),
+                services.weather.clone(),
+                preferences.clone(),
+            )),
             Box::new(crate::pages::experience::Experience::new()),
             Box::new(crate::pages::projects::Projects::new()),
             Box::new(crate::pages::leadership::Leadership::new()),
         ];
+        if services.github.enabled() {
+            pages.push(Box::new(GitHubPage::new(services.github.clone())));
+        }
+        let content_start = pages.len();
+        let content = services.content.clone();
+        let content_generation = content.generation();
+        pages.extend(content_pages(&content));
+        let content_len = pages.len() - content_start;
+        pages.push(Box::new(GuestbookPage::new(
+            services.guestbook.clone(),
+            visitor.clone(),
+        )));
+        if services.contact.enabled() {
+            pages.push(Box::new(MessagePage::new(
+                services.contact.clone(),
+                visitor.clone(),
+            )));
+        }
+        pages.push(Box::new(ChatPage::new(
+            services.chat.clone(),
+            &services.events,
+        )));
+        pages.push(Box::new(ArcadePage::new(Player {
+            high_scores: services.high_scores.clone(),
+            preferences: preferences.clone(),
+        })));
+        let seat = services.tic_tac_toe.seat();
+        let player = seat.id();
+        pages.push(Box::new(TicTacToePage::new(seat, &services.events)));
+        pages.push(Box::new(PollPage::new(services.poll.clone(), visitor)));
+        pages.push(Box::new(StatsPage::new(services.stats.clone())));
+        pages.push(Box::new(KeysPage::new(
+            bindings.clone(),
+            preferences.clone(),
+        )));
+        pages.push(Box::new(Settings::new(preferences.clone())));
+        pages.push(Box::new(ExitPage));
+
+        let mut app = Self::with_pages(services, preferences, bindings, pages);
+        app.content_generation = content_generation;
+        app.content = Some(content);
+        app.content_start = content_start;
+        app.content_len = content_len;
+        app.stats = Some(services.stats.clone());
+        app.tic_tac_toe = Some(player);
+        if app.preferences.get().animations {
+            app.intro = Some(Intro::new());
+        }
+        app.record_view();
+        app
+    }
+
+    /// Picks up where the visitor with `fingerprint` left off last time, past the
+    /// intro, and saves where they are when the session ends. Settings come back
+    /// with their preferences.
+    pub fn resume(&mut self, store: Arc<Store>, fingerprint: String) {
+        match store.snapshot(&fingerprint) {
+            Ok(Some(saved)) => match serde_json::from_str(&saved) {
+                Ok(snapshot) => self.restore(snapshot),
+                Err(e) => warn!("Ignoring unreadable snapshot for {}: {}", fingerprint, e),
+            },
+            Ok(None) => {}
+            Err(e) => warn!("Failed to load snapshot for {}: {}", fingerprint, e),
+        }
+        self.resume = Some((store, fingerprint));
+    }
+
+    fn restore(&mut self, snapshot: Snapshot) {
+        // The page may have gone with a change to the content directory
+        let Some(index) = self
+            .pages
+            .iter()
+            .position(|page| page.title() == snapshot.page)
+        else {
+            return;
+        };
+        self.intro = None;
+        self.select_page(index);
+        self.pages[index].set_position(snapshot.position);
+        if snapshot.content_focused {
+            self.focus_mode = FocusMode::ContentFocus;
+        }
+    }
+
+    /// Opens the page `ssh <page>@host` asks for, past the intro, with dashes for
+    /// the spaces in its title. Any other username, like the visitor's own, leaves
+    /// the app where it was.
+    pub fn start_on(&mut self, user: &str) {
+        let user = user.to_lowercase();
+        let Some(index) = self
+            .pages
+            .iter()
+            .position(|page| page.title().replace(' ', "-") == user)
+        else {
+            return;
+        };
+        self.intro = None;
+        self.select_page(index);
+        self.focus_mode = FocusMode::ContentFocus;
+    }
+
+    /// The operator's view for admin keys: the live sessions instead of the site.
+    pub fn admin(
+        services: &Services,
+        preferences: Arc<SessionPreferences>,
+        sessions: SessionRegistry,
+        own_session: SessionId,
+    ) -> Self {
+        let bindings = Arc::new(services.config.read().unwrap().keys.clone());
+        let pages: Vec<Box<dyn Page>> = vec![
+            Box::new(AdminPage::new(sessions, own_session)),
+            Box::new(KeysPage::new(bindings.clone(), preferences.clone())),
+            Box::new(Settings::new(preferences.clone())),
+            Box::new(ExitPage),
+        ];
+        Self::with_pages(services, preferences, bindings, pages)
+    }
+
+    fn with_pages(
+        services: &Services,
+        preferences: Arc<SessionPreferences>,
+        bindings: Arc<KeyBindings>,
+        pages: Vec<Box<dyn Page>>,
+    ) -> Self {
+        let show_widgets = std::env::var("SHOW_WIDGETS").unwrap_or_default();
+        let show_left = show_widgets == "LEFT" || show_widgets == "ALL";
+        let show_center = show_widgets == "CENTER" || show_widgets == "ALL";
+        let show_right = show_widgets == "RIGHT" || show_widgets == "ALL";
+        let show_menu = show_widgets == "MENU" || show_widgets == "ALL";
+        let show_aa1 = show_widgets == "AA1" || show_widgets == "ALL";
+        let show_additional = show_widgets == "ADDITIONAL" || show_widgets == "ALL";
 
         Self {
             running: true,
             selected_page: 0,
             pages,
+            stack: Vec::new(),
+            content: None,
+            stats: None,
+            content_generation: 0,
+            content_start: 0,
+            content_len: 0,
+            preferences,
+            viewed: HashSet::new(),
             show_left,
             show_center,
             show_right,
@@ -63,10 +433,73 @@ impl App {
             show_aa1,
             show_additional,
             focus_mode: FocusMode::PageFocus,
+            menu_area: Rect::default(),
+            content_area: Rect::default(),
+            disconnect_countdown: None,
+            session_ending: None,
+            visitors_online: None,
+            events: services.events.subscribe(),
+            wall: None,
+            modal: None,
+            keymap: Keymap::new(bindings),
+            search: None,
+            last_search: None,
+            search_message: None,
+            config: services.config.clone(),
+            intro: None,
+            services: services.clone(),
+            easter_eggs: SequenceMatcher::new(),
+            tic_tac_toe: None,
+            dirty: true,
+            animating: false,
+            last_input: Instant::now(),
+            connected_at: Instant::now(),
+            connected_minutes: 0,
+            now_playing_generation: services.now_playing.generation(),
+            weather_generation: services.weather.generation(),
+            resume: None,
+            visit: None,
         }
     }
 
+    /// Lists the pages the visitor sees in `visit`, whose notification about them
+    /// leaving goes out along with the app.
+    pub fn set_visit(&mut self, visit: Visit) {
+        self.visit = Some(visit);
+    }
+
     pub fn draw(&mut self, frame: &mut Frame) {
+        match &self.intro {
+            Some(intro) => intro.render(frame),
+            None => {
+                self.draw_layout(frame);
+                self.draw_search_line(frame);
+            }
+        }
+
+        match &self.modal {
+            Some(Modal::Palette(palette)) => palette.render(frame),
+            Some(Modal::Notice(title, message)) => draw_notice(frame, title, message),
+            Some(Modal::Help(lines)) => draw_help(frame, lines),
+            None => {}
+        }
+        if let Some((message, _)) = &self.wall {
+            draw_notice(frame, " message from the operator ", message);
+        }
+        if let Some(seconds) = self.session_ending {
+            self.draw_session_ending(frame, seconds);
+        }
+        if let Some(seconds) = self.disconnect_countdown {
+            self.draw_disconnect_warning(frame, seconds);
+        }
+
+        self.preferences.get().theme.apply(frame.buffer_mut());
+        if let Some(visit) = &self.visit {
+            visit.viewed(&self.current_page_title());
+        }
+    }
+
+    fn draw_layout(&mut self, frame: &mut Frame) {
         let terminal_width = frame.area().width;
         if terminal_width < 150 {
             let centered_area = Layout::vertical([
@@ -82,6 +515,8 @@ impl App {
                     .alignment(Alignment::Center),
                 centered_area,
             );
+            self.menu_area = Rect::default();
+            self.content_area = Rect::default();
             return;
         }
 
@@ -163,80 +598,698 @@ impl App {
             );
         }
 
+        self.menu_area = menu_area;
+        self.content_area = center_area;
+
         let menu_widget = self.build_menu_widget();
         frame.render_widget(menu_widget, menu_area);
 
         let nav_widget = self.build_nav_widget();
         frame.render_widget(nav_widget, below_menu_area);
 
+        self.draw_footer(frame);
+
         let content_focused = self.focus_mode == FocusMode::ContentFocus;
-        if let Some(current_page) = self.pages.get(self.selected_page) {
+        let on_first_page = self.selected_page == 0 && self.stack.is_empty();
+        if let Some(current_page) = self.current_page() {
             current_page.render(frame, center_area, content_focused);
-            match self.selected_page == 0 {
+            match on_first_page {
                 true => current_page.render_additional(frame, canvas_area, content_focused),
                 false => current_page.render_additional(frame, additional_area, content_focused),
             }
         }
     }
 
-    pub fn handle_key_event(&mut self, key_event: KeyCode) -> io::Result<()> {
-        match key_event {
-            KeyCode::Char('q') => {
-                self.running = false;
-                return Err(io::Error::new(
-                    io::ErrorKind::ConnectionAborted,
-                    "Quit requested",
-                ));
+    /// The bottom row: where the visitor is, the keys that work everywhere, and how
+    /// long they've been here and with how many others.
+    fn draw_footer(&self, frame: &mut Frame) {
+        let [_, area] =
+            Layout::vertical([Constraint::Fill(1), Constraint::Length(1)]).areas(frame.area());
+
+        let titles = self
+            .pages
+            .get(self.selected_page)
+            .into_iter()
+            .chain(&self.stack)
+            .map(|page| page.title())
+            .collect::<Vec<_>>();
+        let mut location = Line::from(gray_span_owned(format!(" {}", titles.join(" › "))));
+        if let Some(track) = self.services.now_playing.track() {
+            location.spans.push(gray_span("   "));
+            location.spans.extend(now_playing_spans(&track));
+        }
+
+        let mut hints = vec![
+            white_span("?"),
+            gray_span(" keys  "),
+            white_span(":"),
+            gray_span(" commands"),
+        ];
+        if let Some(label) = self.keymap.bindings().label(keymap::Action::Quit) {
+            hints.push(white_span_owned(format!("  {}", label)));
+            hints.push(gray_span(" quit"));
+        }
+
+        let minutes = self.connected_minutes;
+        let mut status = vec![gray_span_owned(match minutes {
+            0 => String::from("just connected"),
+            1..60 => format!("connected {}m", minutes),
+            _ => format!("connected {}h {:02}m", minutes / 60, minutes % 60),
+        })];
+        if let Some(count) = self.visitors_online {
+            status.push(gray_span("  "));
+            status.extend(presence_spans(count));
+        }
+        status.push(Span::raw(" "));
+
+        let hints = Line::from(hints);
+        let [left, center, right] = Layout::horizontal([
+            Constraint::Fill(1),
+            Constraint::Length(hints.width() as u16),
+            Constraint::Fill(1),
+        ])
+        .spacing(2)
+        .areas(area);
+        frame.render_widget(Paragraph::new(location), left);
+        frame.render_widget(Paragraph::new(hints), center);
+        frame.render_widget(
+            Paragraph::new(Line::from(status)).alignment(Alignment::Right),
+            right,
+        );
+    }
+
+    /// The search prompt or its outcome across the bottom row, like vim's.
+    fn draw_search_line(&self, frame: &mut Frame) {
+        let line = match (&self.search, &self.search_message) {
+            (Some(search), _) => {
+                let mut line = search.line("", true);
+                line.spans.insert(0, white_span("/"));
+                line
             }
-            KeyCode::Left | KeyCode::Char('h') => {
-                self.focus_mode = FocusMode::PageFocus;
+            (None, Some(message)) => Line::from(gray_span_owned(message.clone())),
+            (None, None) => return,
+        };
+        let [_, area] =
+            Layout::vertical([Constraint::Fill(1), Constraint::Length(1)]).areas(frame.area());
+        frame.render_widget(Clear, area);
+        frame.render_widget(Paragraph::new(line), area);
+    }
+
+    fn draw_disconnect_warning(&self, frame: &mut Frame, seconds: u64) {
+        let [area] = Layout::vertical([Constraint::Length(5)])
+            .flex(Flex::Center)
+            .areas(frame.area());
+        let [area] = Layout::horizontal([Constraint::Length(40)])
+            .flex(Flex::Center)
+            .areas(area);
+
+        let text = vec![
+            Line::from(white_span_owned(format!("disconnecting in {}s", seconds))),
+            Line::from(gray_span("press any key to stay")),
+        ];
+        frame.render_widget(Clear, area);
+        frame.render_widget(
+            Paragraph::new(text).alignment(Alignment::Center).block(
+                Block::new()
+                    .borders(Borders::ALL)
+                    .border_style(Style::new().fg(GRAY))
+                    .padding(Padding::top(1)),
+            ),
+            area,
+        );
+    }
+
+    // Along the top rather than in the middle, since there's nothing to dismiss and
+    // the page still takes keys
+    fn draw_session_ending(&self, frame: &mut Frame, seconds: u64) {
+        let [area] = Layout::vertical([Constraint::Length(4)]).areas(frame.area());
+        let [area] = Layout::horizontal([Constraint::Length(50)])
+            .flex(Flex::Center)
+            .areas(area);
+
+        let text = vec![
+            Line::from(white_span_owned(format!(
+                "this session ends in {}s",
+                seconds
+            ))),
+            Line::from(gray_span("sessions are time limited, come back anytime")),
+        ];
+        frame.render_widget(Clear, area);
+        frame.render_widget(
+            Paragraph::new(text).alignment(Alignment::Center).block(
+                Block::new()
+                    .borders(Borders::ALL)
+                    .border_style(Style::new().fg(GRAY)),
+            ),
+            area,
+        );
+    }
+
+    /// Set by the server while an idle session is about to be closed. The next input
+    /// only dismisses the warning instead of acting on the page.
+    pub fn set_disconnect_countdown(&mut self, seconds: Option<u64>) {
+        if self.disconnect_countdown != seconds {
+            self.disconnect_countdown = seconds;
+            self.dirty = true;
+        }
+    }
+
+    /// Set by the server in the last minute before the session's time limit.
+    pub fn set_session_ending(&mut self, seconds: Option<u64>) {
+        if self.session_ending != seconds {
+            self.session_ending = seconds;
+            self.dirty = true;
+        }
+    }
+
+    /// Set by the server before every frame, from the number of open sessions.
+    pub fn set_visitors_online(&mut self, count: usize) {
+        if self.visitors_online != Some(count) {
+            self.visitors_online = Some(count);
+            self.services.stats.set_online(count);
+            self.dirty = true;
+        }
+    }
+
+    /// Forces the next frame to be drawn, e.g. after the terminal was resized.
+    pub fn mark_dirty(&mut self) {
+        self.dirty = true;
+    }
+
+    /// Whether anything changed since the last call that needs a redraw.
+    pub fn take_dirty(&mut self) -> bool {
+        std::mem::take(&mut self.dirty)
+    }
+
+    pub fn frame_rate(&self) -> FrameRate {
+        if !self.animating && self.last_input.elapsed() >= INPUT_ACTIVITY_WINDOW {
+            return FrameRate::Idle;
+        }
+        match self.preferences.get().frame_rate {
+            FrameRateLimit::Full => FrameRate::Full,
+            FrameRateLimit::Half => FrameRate::Half,
+            FrameRateLimit::Low => FrameRate::Idle,
+        }
+    }
+
+    fn note_input(&mut self) {
+        self.dirty = true;
+        self.last_input = Instant::now();
+    }
+
+    pub fn handle_key_event(&mut self, key_event: KeyEvent) -> KeyOutcome {
+        self.handle_key(key_event);
+        match self.running {
+            true => KeyOutcome::Continue,
+            false => KeyOutcome::Quit,
+        }
+    }
+
+    fn handle_key(&mut self, key_event: KeyEvent) {
+        self.note_input();
+        self.search_message = None;
+        if self.disconnect_countdown.take().is_some() || self.wall.take().is_some() {
+            return;
+        }
+
+        let ctrl = key_event.modifiers.contains(KeyModifiers::CONTROL);
+        if ctrl && key_event.code == KeyCode::Char('c') {
+            self.running = false;
+            return;
+        }
+        if self.intro.take().is_some() {
+            return;
+        }
+        if let Some(modal) = &mut self.modal {
+            match modal {
+                Modal::Palette(palette) => match palette.handle_key(key_event) {
+                    PaletteOutcome::Open => {}
+                    PaletteOutcome::Closed => self.modal = None,
+                    PaletteOutcome::Run(action) => {
+                        self.modal = None;
+                        self.run(action);
+                    }
+                },
+                Modal::Notice(..) | Modal::Help(_) => self.modal = None,
             }
-            KeyCode::Right | KeyCode::Char('l') => {
-                self.focus_mode = FocusMode::ContentFocus;
+            return;
+        }
+        if let Some(search) = &mut self.search {
+            match key_event.code {
+                KeyCode::Esc => self.search = None,
+                KeyCode::Enter => {
+                    let query = search.value();
+                    self.search = None;
+                    if !query.trim().is_empty() {
+                        self.last_search = Some(query);
+                        self.search_next();
+                    }
+                }
+                _ => {
+                    search.handle_key_event(key_event);
+                }
             }
-            KeyCode::Up | KeyCode::Char('k') => match self.focus_mode {
-                FocusMode::PageFocus => self.previous_page(),
-                FocusMode::ContentFocus => {
-                    if let Some(current_page) = self.pages.get_mut(self.selected_page) {
-                        current_page.keyboard_event_handler(KeyCode::Up);
+            return;
+        }
+
+        let captures_input = self
+            .current_page()
+            .is_some_and(|page| page.captures_input());
+        // `:` and `?` are left to pages that take text
+        if (ctrl && key_event.code == KeyCode::Char('k'))
+            || (!captures_input && key_event.code == KeyCode::Char(':'))
+        {
+            let palette = CommandPalette::new(self.palette_commands());
+            self.modal = Some(Modal::Palette(palette));
+            return;
+        }
+        if !captures_input && key_event.code == KeyCode::Char('?') {
+            self.modal = Some(Modal::Help(self.help_lines()));
+            return;
+        }
+        if captures_input {
+            self.send_key_to_page(key_event);
+            return;
+        }
+
+        let bound = self.keymap.binds(self.preferences.get().keys, &key_event);
+        if !ctrl && let Some(egg) = self.easter_eggs.push(key_event.code, bound) {
+            self.open_easter_egg(egg);
+            return;
+        }
+
+        match self
+            .keymap
+            .translate(self.preferences.get().keys, key_event)
+        {
+            Some(Input::Action(action)) => self.run_key_action(action),
+            // Other shortcuts only mean something to pages that take text
+            Some(Input::Key(_)) if ctrl => {}
+            Some(Input::Key(key_event)) => match key_event.code {
+                KeyCode::Enter if self.focus_mode == FocusMode::PageFocus => {
+                    self.focus_mode = FocusMode::ContentFocus;
+                }
+                _ => {
+                    if self.focus_mode == FocusMode::ContentFocus {
+                        self.send_key_to_page(key_event);
                     }
                 }
             },
-            KeyCode::Down | KeyCode::Char('j') => match self.focus_mode {
+            None => {}
+        }
+    }
+
+    fn run_key_action(&mut self, action: keymap::Action) {
+        match action {
+            // A page opened on top of another one is left like with Esc
+            keymap::Action::Quit if !self.stack.is_empty() => self.navigate(Navigation::Pop),
+            keymap::Action::Quit => self.running = false,
+            keymap::Action::Back => self.navigate(Navigation::Pop),
+            keymap::Action::NextPage => self.next_page(),
+            keymap::Action::PreviousPage => self.previous_page(),
+            keymap::Action::FocusMenu => self.focus_mode = FocusMode::PageFocus,
+            keymap::Action::FocusContent => self.focus_mode = FocusMode::ContentFocus,
+            keymap::Action::ScrollUp => match self.focus_mode {
+                FocusMode::PageFocus => self.previous_page(),
+                FocusMode::ContentFocus => self.send_key_to_page(KeyEvent::from(KeyCode::Up)),
+            },
+            keymap::Action::ScrollDown => match self.focus_mode {
                 FocusMode::PageFocus => self.next_page(),
-                FocusMode::ContentFocus => {
-                    if let Some(current_page) = self.pages.get_mut(self.selected_page) {
-                        current_page.keyboard_event_handler(KeyCode::Down);
-                    }
-                }
+                FocusMode::ContentFocus => self.send_key_to_page(KeyEvent::from(KeyCode::Down)),
             },
-            _ => {
-                if self.focus_mode == FocusMode::ContentFocus {
-                    if let Some(current_page) = self.pages.get_mut(self.selected_page) {
-                        current_page.keyboard_event_handler(key_event);
-                    }
+            // Only used for scrolling, so they reach the page whatever has focus
+            keymap::Action::PageUp => self.send_key_to_page(KeyEvent::from(KeyCode::PageUp)),
+            keymap::Action::PageDown => self.send_key_to_page(KeyEvent::from(KeyCode::PageDown)),
+            keymap::Action::Top => self.send_key_to_page(KeyEvent::from(KeyCode::Home)),
+            keymap::Action::Bottom => self.send_key_to_page(KeyEvent::from(KeyCode::End)),
+            keymap::Action::Search => self.search = Some(TextInput::new(MAX_SEARCH_CHARS)),
+            keymap::Action::SearchNext => self.search_next(),
+        }
+    }
+
+    fn search_next(&mut self) {
+        let Some(query) = self.last_search.clone() else {
+            self.search_message = Some(String::from("nothing to search for yet, press / first"));
+            return;
+        };
+        match self
+            .current_page_mut()
+            .is_some_and(|page| page.search(&query))
+        {
+            true => self.focus_mode = FocusMode::ContentFocus,
+            false => self.search_message = Some(format!("pattern not found: {}", query)),
+        }
+    }
+
+    /// A command per menu entry and theme, and per file offered for download.
+    fn palette_commands(&self) -> Vec<Command> {
+        let mut commands = self
+            .pages
+            .iter()
+            .enumerate()
+            .map(|(i, page)| Command::new(format!("go to {}", page.title()), Action::GoTo(i)))
+            .collect::<Vec<_>>();
+        commands.extend(THEMES.into_iter().map(|theme| {
+            Command::new(
+                format!("change theme to {}", theme.name),
+                Action::SetTheme(theme),
+            )
+        }));
+        let public_dir = self.config.read().unwrap().public_dir.clone();
+        if let Some(public_dir) = public_dir {
+            commands.extend(
+                PublicFiles::new(public_dir)
+                    .list()
+                    .into_iter()
+                    .map(|(name, _)| {
+                        Command::new(format!("download {}", name), Action::Download(name))
+                    }),
+            );
+        }
+        commands
+    }
+
+    fn run(&mut self, action: Action) {
+        match action {
+            Action::GoTo(index) => {
+                self.stack.clear();
+                self.select_page(index);
+                self.focus_mode = FocusMode::ContentFocus;
+            }
+            Action::SetTheme(theme) => self.preferences.set_theme(theme),
+            // Files only travel over their own connection, not this terminal
+            Action::Download(name) => {
+                let message = format!(
+                    "run `scp <host>:{} .` or `sftp <host>:{} .` from your own terminal, with the host you connected to",
+                    name, name
+                );
+                self.modal = Some(Modal::Notice(" download ", message));
+            }
+        }
+    }
+
+    /// The current page's keys, then the ones that work everywhere.
+    fn help_lines(&self) -> Vec<Line<'static>> {
+        let heading = |text: &'static str| Line::from(white_span(text));
+        let key = |(keys, description): (String, &'static str)| {
+            Line::from(vec![
+                white_span_owned(format!("{:<12}", keys)),
+                gray_span(description),
+            ])
+        };
+
+        let mut lines = Vec::new();
+        let page_items = self
+            .current_page()
+            .map(|page| page.nav_items())
+            .unwrap_or_default();
+        if !page_items.is_empty() {
+            lines.push(heading("this page"));
+            lines.extend(page_items);
+            lines.push(Line::from(""));
+        }
+        lines.push(heading("everywhere"));
+        lines.extend(self.keymap.bindings().describe().into_iter().map(key));
+        if self.preferences.get().keys == Keys::Vim {
+            lines.push(Line::from(""));
+            lines.push(heading("vim"));
+            lines.extend(
+                VIM_BINDINGS
+                    .iter()
+                    .map(|(keys, action)| key((keys.to_string(), action.description()))),
+            );
+        }
+        lines
+    }
+
+    fn open_easter_egg(&mut self, egg: EasterEgg) {
+        let page: Box<dyn Page> = match egg {
+            EasterEgg::Snake => Box::new(SnakePage::new(self.services.high_scores.clone())),
+            EasterEgg::Locomotive => Box::new(LocomotivePage::new()),
+            EasterEgg::Shell => Box::new(ShellPage::new(&self.services)),
+        };
+        self.navigate(Navigation::Push(page));
+    }
+
+    pub fn handle_mouse_event(&mut self, mouse_event: MouseEvent) {
+        self.note_input();
+        if self.disconnect_countdown.take().is_some()
+            || self.wall.take().is_some()
+            || self.intro.take().is_some()
+            || self.modal.take().is_some()
+        {
+            return;
+        }
+
+        let position = Position::new(mouse_event.column, mouse_event.row);
+        let over_menu = self.menu_area.contains(position);
+
+        match mouse_event.kind {
+            MouseEventKind::Down(MouseButton::Left) if over_menu => {
+                // The menu list has one row of top padding before the first item
+                let row = mouse_event.row.saturating_sub(self.menu_area.y + 1) as usize;
+                if mouse_event.row > self.menu_area.y && row < self.pages.len() {
+                    self.select_page(row);
+                    self.focus_mode = FocusMode::PageFocus;
+                }
+            }
+            MouseEventKind::Down(MouseButton::Left) if self.content_area.contains(position) => {
+                self.focus_mode = FocusMode::ContentFocus;
+            }
+            MouseEventKind::ScrollUp | MouseEventKind::ScrollDown => {
+                if over_menu {
+                    self.focus_mode = FocusMode::PageFocus;
+                } else if self.content_area.contains(position) {
+                    self.focus_mode = FocusMode::ContentFocus;
+                }
+
+                self.run_key_action(match mouse_event.kind {
+                    MouseEventKind::ScrollUp => keymap::Action::ScrollUp,
+                    _ => keymap::Action::ScrollDown,
+                });
+            }
+            _ => {}
+        }
+    }
+
+    pub fn handle_resize(&mut self, width: u16, height: u16) {
+        for page in self.pages.iter_mut().chain(self.stack.iter_mut()) {
+            page.handle_resize(width, height);
+        }
+        self.mark_dirty();
+    }
+
+    pub fn handle_paste(&mut self, text: &str) {
+        self.note_input();
+        if self.intro.take().is_some() {
+            return;
+        }
+        if let Some(modal) = &mut self.modal {
+            if let Modal::Palette(palette) = modal {
+                palette.paste(text);
+            }
+            return;
+        }
+        if let Some(search) = &mut self.search {
+            search.insert_str(text);
+            return;
+        }
+        let content_focused = self.focus_mode == FocusMode::ContentFocus;
+        if let Some(current_page) = self.current_page_mut()
+            && (content_focused || current_page.captures_input())
+        {
+            current_page.paste_event_handler(text);
+        }
+    }
+
+    /// Advances the current page and returns whether it changed, so callers can
+    /// skip drawing frames that would look the same as the last one.
+    pub fn handle_tick(&mut self, tick: u64) -> bool {
+        self.reload_content();
+        self.receive_events();
+        let minutes = self.connected_at.elapsed().as_secs() / 60;
+        if minutes != self.connected_minutes {
+            self.connected_minutes = minutes;
+            self.dirty = true;
+        }
+        let now_playing = self.services.now_playing.generation();
+        if now_playing != self.now_playing_generation {
+            self.now_playing_generation = now_playing;
+            self.dirty = true;
+        }
+        let weather = self.services.weather.generation();
+        if weather != self.weather_generation {
+            self.weather_generation = weather;
+            self.dirty = true;
+        }
+        if let Some(intro) = &mut self.intro {
+            self.animating = intro.on_tick(tick);
+            if self.animating {
+                return true;
+            }
+            self.intro = None;
+            self.dirty = true;
+        }
+        self.animating = self
+            .current_page_mut()
+            .is_some_and(|page| page.on_tick(tick));
+        self.animating
+    }
+
+    fn receive_events(&mut self) {
+        loop {
+            match self.events.try_recv() {
+                Ok(Event::TicTacToe(player)) if self.tic_tac_toe == Some(player) => {
+                    self.notify_tic_tac_toe(player);
                 }
+                Ok(_) | Err(TryRecvError::Lagged(_)) => {}
+                Err(TryRecvError::Empty | TryRecvError::Closed) => break,
+            }
+        }
+        if self
+            .wall
+            .as_ref()
+            .is_some_and(|(_, shown_at)| shown_at.elapsed() >= WALL_DURATION)
+        {
+            self.wall = None;
+            self.dirty = true;
+        }
+    }
+
+    /// Tells a visitor browsing elsewhere that their game needs them. Anything
+    /// else open, like the palette, is left alone.
+    fn notify_tic_tac_toe(&mut self, player: PlayerId) {
+        if self.modal.is_some() || self.current_page_title() == tic_tac_toe::TITLE {
+            return;
+        }
+        if let Some(notice) = self.services.tic_tac_toe.notice(player) {
+            self.modal = Some(Modal::Notice(" tic-tac-toe ", notice));
+            self.dirty = true;
+        }
+    }
+
+    /// Shows a message from the operator until it is dismissed or expires.
+    pub fn show_wall(&mut self, message: String) {
+        self.wall = Some((message, Instant::now()));
+        self.dirty = true;
+    }
+
+    /// Swaps in the content pages again after the directory changed on disk.
+    fn reload_content(&mut self) {
+        let Some(content) = &self.content else {
+            return;
+        };
+        let generation = content.generation();
+        if generation == self.content_generation {
+            return;
+        }
+        self.content_generation = generation;
+
+        let old_range = self.content_start..self.content_start + self.content_len;
+        let new_pages = content_pages(content);
+        let new_len = new_pages.len();
+        self.pages.splice(old_range.clone(), new_pages);
+        self.content_len = new_len;
+
+        if old_range.contains(&self.selected_page) {
+            self.stack.clear();
+            let last_content = self.content_start + new_len.saturating_sub(1);
+            self.selected_page = self.selected_page.min(last_content);
+        } else if self.selected_page >= old_range.end {
+            // Keep the pages after them selected as the content pages come and go
+            self.selected_page = self.selected_page - old_range.len() + new_len;
+        }
+        self.dirty = true;
+    }
+
+    /// The images in the frame just drawn, except while something is drawn over
+    /// the page, which a picture on top of its cells would hide.
+    pub fn images(&self) -> Vec<Placement> {
+        let images = self
+            .current_page()
+            .map(|page| page.images())
+            .unwrap_or_default();
+        let covered = self.intro.is_some()
+            || self.modal.is_some()
+            || self.wall.is_some()
+            || self.disconnect_countdown.is_some()
+            || self.session_ending.is_some();
+        match covered {
+            true => Vec::new(),
+            false => images,
+        }
+    }
+
+    /// The title of what the visitor is looking at, for the admin view.
+    pub fn current_page_title(&self) -> String {
+        self.current_page()
+            .map(|page| page.title().to_string())
+            .unwrap_or_default()
+    }
+
+    fn current_page(&self) -> Option<&dyn Page> {
+        match self.stack.last() {
+            Some(page) => Some(page.as_ref()),
+            None => self.pages.get(self.selected_page).map(|page| page.as_ref()),
+        }
+    }
+
+    fn current_page_mut(&mut self) -> Option<&mut Box<dyn Page>> {
+        if self.stack.is_empty() {
+            self.pages.get_mut(self.selected_page)
+        } else {
+            self.stack.last_mut()
+        }
+    }
+
+    fn send_key_to_page(&mut self, key_event: KeyEvent) {
+        let navigation = self
+            .current_page_mut()
+            .and_then(|page| page.key_event_handler(key_event));
+        if let Some(navigation) = navigation {
+            self.navigate(navigation);
+        }
+    }
+
+    fn navigate(&mut self, navigation: Navigation) {
+        match navigation {
+            Navigation::Push(page) => self.stack.push(page),
+            Navigation::Pop => {
+                self.stack.pop();
             }
+            Navigation::Quit => self.running = false,
         }
+    }
 
-        Ok(())
+    /// Switching to another menu entry drops whatever was opened on top of the
+    /// current one.
+    fn select_page(&mut self, index: usize) {
+        if index != self.selected_page {
+            self.stack.clear();
+            self.selected_page = index;
+            self.record_view();
+        }
     }
 
-    pub fn handle_tick(&mut self, tick: u64) {
-        if let Some(page) = self.pages.get_mut(self.selected_page) {
-            let _ = page.on_tick(tick);
+    /// Counts the selected page once per session, however often it is revisited.
+    fn record_view(&mut self) {
+        if let Some(stats) = &self.stats
+            && let Some(page) = self.pages.get(self.selected_page)
+            && self.viewed.insert(page.title().to_string())
+        {
+            stats.record_page_view(page.title());
         }
     }
 
     fn previous_page(&mut self) {
         if self.selected_page > 0 {
-            self.selected_page -= 1;
+            self.select_page(self.selected_page - 1);
         }
     }
 
     fn next_page(&mut self) {
         if self.selected_page + 1 < self.pages.len() {
-            self.selected_page += 1;
+            self.select_page(self.selected_page + 1);
         }
     }
 
@@ -254,14 +1307,14 @@ impl App {
                         dimmed_white_span_owned(format!("[ {} ]", title))
                     }
                 } else {
-                    gray_span(&title)
+                    gray_span(title)
                 };
 
                 ListItem::new(span.bold().into_right_aligned_line())
             })
             .collect();
 
-        let final_list = List::new(menu_items).block(
+        List::new(menu_items).block(
             Block::new()
                 .borders(Borders::RIGHT)
                 .border_set(symbols::border::ONE_EIGHTH_TALL)
@@ -272,9 +1325,7 @@ impl App {
                     right: 2,
                     left: 0,
                 }),
-        );
-
-        final_list
+        )
     }
 
     fn build_nav_widget(&self) -> List<'_> {
@@ -283,27 +1334,146 @@ impl App {
             FocusMode::ContentFocus => "content",
         };
 
-        let mut nav_lines: Vec<ListItem> = vec![
-            ListItem::new(Line::from(vec![white_span("↑/↓ "), gray_span(focus_text)])),
-            ListItem::new(Line::from(vec![white_span("←/→ "), gray_span("focus")])),
-        ];
+        // Whatever the config binds, a hint only shows while its keys are bound
+        let bindings = self.keymap.bindings();
+        let pair = |first: keymap::Action, second: keymap::Action| {
+            Some(format!(
+                "{}/{}",
+                bindings.label(first)?,
+                bindings.label(second)?
+            ))
+        };
+        let hint = |label: Option<String>, text: &'static str| {
+            label.map(|label| ListItem::new(Line::from(vec![nav_key(label), gray_span(text)])))
+        };
+
+        let mut nav_lines: Vec<ListItem> = Vec::new();
+        nav_lines.extend(hint(
+            pair(keymap::Action::ScrollUp, keymap::Action::ScrollDown),
+            focus_text,
+        ));
+        nav_lines.extend(hint(
+            pair(keymap::Action::FocusMenu, keymap::Action::FocusContent),
+            "focus",
+        ));
 
-        if let Some(current_page) = self.pages.get(self.selected_page) {
+        if let Some(current_page) = self.current_page() {
             let page_nav_items = current_page.nav_items();
             nav_lines.extend(page_nav_items.into_iter().map(ListItem::new));
         }
 
-        let quit_nav_item = ListItem::new(Line::from(vec![white_span(" q  "), gray_span("quit")]));
+        if !self.stack.is_empty() {
+            nav_lines.extend(hint(bindings.label(keymap::Action::Back), "back"));
+        }
 
-        nav_lines.push(quit_nav_item);
+        if self.preferences.get().keys == Keys::Vim {
+            nav_lines.push(ListItem::new(Line::from(vec![
+                white_span(" /  "),
+                gray_span("search"),
+            ])));
+        }
 
-        let final_list = List::new(nav_lines).block(Block::new().padding(Padding {
+        List::new(nav_lines).block(Block::new().padding(Padding {
             left: 4,
             right: 0,
             top: 0,
             bottom: 0,
-        }));
+        }))
+    }
+}
+
+/// The keys from `?` in a box in the middle of the screen.
+fn draw_help(frame: &mut Frame, lines: &[Line<'static>]) {
+    let [area] = Layout::vertical([Constraint::Length(lines.len() as u16 + 4)])
+        .flex(Flex::Center)
+        .areas(frame.area());
+    let [area] = Layout::horizontal([Constraint::Max(60)])
+        .flex(Flex::Center)
+        .areas(area);
+
+    frame.render_widget(Clear, area);
+    frame.render_widget(
+        Paragraph::new(lines.to_vec()).block(
+            Block::new()
+                .title(" keys ")
+                .title_bottom(" press any key to close ")
+                .borders(Borders::ALL)
+                .border_style(Style::new().fg(LINK_COLOR))
+                .padding(Padding::uniform(1)),
+        ),
+        area,
+    );
+}
+
+/// A key for the hints under the menu, padded so their descriptions line up.
+fn nav_key(label: String) -> Span<'static> {
+    match label.chars().count() {
+        1 => white_span_owned(format!(" {}  ", label)),
+        _ => white_span_owned(format!("{} ", label)),
+    }
+}
+
+/// A message in a box across the top of the screen, like the operator's.
+fn draw_notice(frame: &mut Frame, title: &str, message: &str) {
+    let [_, area] =
+        Layout::vertical([Constraint::Length(1), Constraint::Length(5)]).areas(frame.area());
+    let [area] = Layout::horizontal([Constraint::Max(80)])
+        .flex(Flex::Center)
+        .areas(area);
+
+    let text = vec![
+        Line::from(white_span_owned(message.to_string())),
+        Line::from(gray_span("press any key to dismiss")),
+    ];
+    frame.render_widget(Clear, area);
+    frame.render_widget(
+        Paragraph::new(text)
+            .alignment(Alignment::Center)
+            .wrap(Wrap { trim: true })
+            .block(
+                Block::new()
+                    .title(title)
+                    .borders(Borders::ALL)
+                    .border_style(Style::new().fg(LINK_COLOR))
+                    .padding(Padding::top(1)),
+            ),
+        area,
+    );
+}
+
+fn presence_spans(count: usize) -> [Span<'static>; 2] {
+    let text = match count {
+        1 => String::from(" just you online"),
+        _ => format!(" {} visitors online", count),
+    };
+    [
+        Span::styled("●", Style::new().fg(LINK_COLOR)),
+        gray_span_owned(text),
+    ]
+}
+
+fn now_playing_spans(track: &Track) -> [Span<'static>; 2] {
+    let mut text = format!(" {} · {}", track.title, track.artist);
+    if text.chars().count() > MAX_TRACK_CHARS {
+        text = text.chars().take(MAX_TRACK_CHARS - 1).collect::<String>() + "…";
+    }
+    [
+        Span::styled("♪", Style::new().fg(LINK_COLOR)),
+        gray_span_owned(text),
+    ]
+}
 
-        final_list
+/// A page per Markdown file in the content directory, and the blog if there are
+/// any posts.
+pub fn content_pages(content: &Content) -> Vec<Box<dyn Page>> {
+    let mut pages = content
+        .entries()
+        .iter()
+        .map(|entry| Box::new(ContentPage::new(entry)) as Box<dyn Page>)
+        .collect::<Vec<_>>();
+    let posts = content.posts();
+    if !posts.is_empty() {
+        pages.push(Box::new(BlogPage::new(posts)));
     }
+    pages
 }
@@ -0,0 +1,253 @@
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use rand::Rng;
+use russh::client::{self, KeyboardInteractiveAuthResponse};
+use russh::keys::PublicKeyOrCertificate;
+use russh::{ChannelMsg, Disconnect};
+use tokio::task::JoinSet;
+
+// Wide enough that the app draws its full layout
+const WIDTH: u32 = 160;
+const HEIGHT: u32 = 45;
+// Moves around the menu and pages without leaving, unlike q, Esc or Enter on
+// the exit entry
+const KEYS: &[&[u8]] = &[b"j", b"k", b"h", b"l", b"\t", b"\x1b[A", b"\x1b[B"];
+const MEMORY_SAMPLE_INTERVAL: Duration = Duration::from_millis(250);
+
+pub struct BenchOptions {
+    pub target: String,
+    pub clients: usize,
+    pub duration: Duration,
+    /// Time between keystrokes, per client.
+    pub input_interval: Duration,
+    /// The server's pid, to follow its memory. Only works for a local server.
+    pub server_pid: Option<u32>,
+}
+
+#[derive(Default)]
+struct ClientReport {
+    connect_time: Option<Duration>,
+    first_frame: Option<Duration>,
+    input_latencies: Vec<Duration>,
+    connect_error: Option<String>,
+    // The server closed the session before the run was over
+    dropped: bool,
+}
+
+/// Opens `clients` sessions against the target at once, like a visitor each,
+/// and prints how the server held up.
+pub async fn run(options: BenchOptions) -> Result<(), anyhow::Error> {
+    println!(
+        "{} clients against {} for {:?}",
+        options.clients, options.target, options.duration
+    );
+    let options = Arc::new(options);
+    let deadline = Instant::now() + options.duration;
+    let memory = options
+        .server_pid
+        .map(|pid| tokio::spawn(sample_memory(pid, deadline)));
+
+    let mut clients = JoinSet::new();
+    for _ in 0..options.clients {
+        clients.spawn(run_client(options.clone(), deadline));
+    }
+    let mut reports = Vec::with_capacity(options.clients);
+    while let Some(report) = clients.join_next().await {
+        reports.push(report?);
+    }
+    let memory = match memory {
+        Some(sampler) => Some(sampler.await?),
+        None => None,
+    };
+
+    print_summary(&reports, memory);
+    Ok(())
+}
+
+async fn run_client(options: Arc<BenchOptions>, deadline: Instant) -> ClientReport {
+    let mut report = ClientReport::default();
+    if let Err(e) = drive_session(&options, deadline, &mut report).await {
+        match report.connect_time {
+            None => report.connect_error = Some(e.to_string()),
+            Some(_) => report.dropped = true,
+        }
+    }
+    report
+}
+
+/// Fills in `report` as the session goes, so what was measured before a failure
+/// still counts.
+async fn drive_session(
+    options: &BenchOptions,
+    deadline: Instant,
+    report: &mut ClientReport,
+) -> Result<(), anyhow::Error> {
+    let started = Instant::now();
+    let config = Arc::new(client::Config::default());
+    let mut handle = client::connect(config, options.target.as_str(), AcceptAnyHostKey).await?;
+    // The auth a visitor without a key gets, which needs auth_mode none
+    match handle
+        .authenticate_keyboard_interactive_start("bench", None)
+        .await?
+    {
+        KeyboardInteractiveAuthResponse::Success => {}
+        _ => return Err(anyhow::anyhow!("Not let in, is auth_mode none?")),
+    }
+    let mut channel = handle.channel_open_session().await?;
+    channel
+        .request_pty(false, "xterm-256color", WIDTH, HEIGHT, 0, 0, &[])
+        .await?;
+    channel.request_shell(false).await?;
+    report.connect_time = Some(started.elapsed());
+
+    if wait_for_frame(&mut channel, deadline).await? {
+        report.first_frame = Some(started.elapsed());
+    }
+
+    while Instant::now() < deadline {
+        // Spread out, so the clients don't all type in lockstep
+        let jitter = options
+            .input_interval
+            .mul_f64(rand::rng().next_u32() as f64 / u32::MAX as f64);
+        tokio::time::sleep(options.input_interval / 2 + jitter).await;
+
+        let key = KEYS[rand::rng().next_u32() as usize % KEYS.len()];
+        let sent = Instant::now();
+        channel.data(key).await?;
+        // The next frame, which is the one drawn for the key unless an animation
+        // got there first
+        if wait_for_frame(&mut channel, deadline).await? {
+            report.input_latencies.push(sent.elapsed());
+        }
+    }
+
+    let _ = handle
+        .disconnect(Disconnect::ByApplication, "bench over", "")
+        .await;
+    Ok(())
+}
+
+/// Waits for the next output. False if the run was over first.
+async fn wait_for_frame(
+    channel: &mut russh::Channel<client::Msg>,
+    deadline: Instant,
+) -> Result<bool, anyhow::Error> {
+    let frame = tokio::time::timeout_at(deadline.into(), async {
+        loop {
+            match channel.wait().await {
+                Some(ChannelMsg::Data { .. }) => return Ok(true),
+                Some(ChannelMsg::Close | ChannelMsg::Eof) | None => {
+                    return Err(anyhow::anyhow!("The server closed the session"));
+                }
+                Some(_) => {}
+            }
+        }
+    });
+    frame.await.unwrap_or(Ok(false))
+}
+
+struct AcceptAnyHostKey;
+
+impl client::Handler for AcceptAnyHostKey {
+    type Error = russh::Error;
+
+    async fn check_server_key(&mut self, _: &PublicKeyOrCertificate) -> Result<bool, Self::Error> {
+        Ok(true)
+    }
+}
+
+#[derive(Default)]
+struct MemoryUsage {
+    first_kib: Option<u64>,
+    peak_kib: u64,
+    last_kib: u64,
+}
+
+/// Follows the process's resident memory until the run is over.
+async fn sample_memory(pid: u32, deadline: Instant) -> MemoryUsage {
+    let mut usage = MemoryUsage::default();
+    while Instant::now() < deadline {
+        if let Some(kib) = resident_kib(pid) {
+            usage.first_kib.get_or_insert(kib);
+            usage.peak_kib = usage.peak_kib.max(kib);
+            usage.last_kib = kib;
+        }
+        tokio::time::sleep(MEMORY_SAMPLE_INTERVAL).await;
+    }
+    usage
+}
+
+fn resident_kib(pid: u32) -> Option<u64> {
+    let status = std::fs::read_to_string(format!("/proc/{}/status", pid)).ok()?;
+    let line = status.lines().find(|line| line.starts_with("VmRSS:"))?;
+    line.split_whitespace().nth(1)?.parse().ok()
+}
+
+fn print_summary(reports: &[ClientReport], memory: Option<MemoryUsage>) {
+    let failures = reports
+        .iter()
+        .filter_map(|report| report.connect_error.as_deref())
+        .collect::<Vec<_>>();
+    let dropped = reports.iter().filter(|report| report.dropped).count();
+
+    println!();
+    println!("connected        {}", reports.len() - failures.len());
+    println!("connect failures {}", failures.len());
+    if let Some(error) = failures.first() {
+        println!("  first: {}", error);
+    }
+    println!("dropped          {}", dropped);
+    print_latencies(
+        "connect time",
+        reports.iter().filter_map(|report| report.connect_time),
+    );
+    print_latencies(
+        "first frame",
+        reports.iter().filter_map(|report| report.first_frame),
+    );
+    print_latencies(
+        "input latency",
+        reports
+            .iter()
+            .flat_map(|report| report.input_latencies.iter().copied()),
+    );
+
+    match memory {
+        Some(MemoryUsage {
+            first_kib: Some(first_kib),
+            peak_kib,
+            last_kib,
+        }) => println!(
+            "server memory    {} MiB at the start, {} MiB peak, {} MiB at the end",
+            first_kib / 1024,
+            peak_kib / 1024,
+            last_kib / 1024
+        ),
+        Some(_) => println!("server memory    unknown, no such process"),
+        None => {}
+    }
+}
+
+fn print_latencies(label: &str, samples: impl Iterator<Item = Duration>) {
+    let mut samples = samples.collect::<Vec<_>>();
+    if samples.is_empty() {
+        println!("{:<17}no samples", label);
+        return;
+    }
+    samples.sort();
+    let percentile = |p: usize| millis(samples[(samples.len() - 1) * p / 100]);
+    println!(
+        "{:<17}p50 {}  p95 {}  p99 {}  max {}  ({} samples)",
+        label,
+        percentile(50),
+        percentile(95),
+        percentile(99),
+        millis(samples[samples.len() - 1]),
+        samples.len()
+    );
+}
+
+fn millis(duration: Duration) -> String {
+    format!("{:.1}ms", duration.as_secs_f64() * 1000.0)
+}
@@ -0,0 +1,105 @@
+use ratatui::buffer::Buffer;
+
+/// Whether a visitor's terminal can show the box drawing, block and arrow
+/// characters the pages use. Pages always draw with them, and for terminals that
+/// can't, frames are brought down to plain ASCII before they are sent.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Charset {
+    Unicode,
+    Ascii,
+}
+
+impl Charset {
+    /// A best guess from `TERM` and the locale the client sent. Most clients
+    /// don't send one, so without it the terminal is taken to be UTF-8.
+    pub fn detect(term: &str, locale: Option<&str>) -> Self {
+        let utf8 = |locale: &str| {
+            let locale = locale.to_ascii_lowercase();
+            locale.contains("utf-8") || locale.contains("utf8")
+        };
+        let plain_term = term == "dumb" || term.starts_with("vt1") || term.starts_with("vt2");
+        match locale {
+            Some(locale) if !utf8(locale) => Self::Ascii,
+            None if plain_term => Self::Ascii,
+            _ => Self::Unicode,
+        }
+    }
+
+    pub fn degrade(self, buffer: &mut Buffer) {
+        if self == Self::Unicode {
+            return;
+        }
+
+        let mut symbol = [0; 4];
+        for cell in buffer.content.iter_mut() {
+            let Some(c) = cell.symbol().chars().next().filter(|c| !c.is_ascii()) else {
+                continue;
+            };
+            cell.set_symbol(ascii(c).encode_utf8(&mut symbol));
+        }
+    }
+}
+
+/// The locale variables a client sent. `LC_ALL` wins over `LC_CTYPE`, which wins
+/// over `LANG`, like in a shell.
+#[derive(Debug, Clone, Default)]
+pub struct Locale {
+    lc_all: Option<String>,
+    lc_ctype: Option<String>,
+    lang: Option<String>,
+}
+
+impl Locale {
+    /// Keeps `value` if `name` is one of the locale variables. Empty ones count as
+    /// unset.
+    pub fn set(&mut self, name: &str, value: &str) {
+        let variable = match name {
+            "LC_ALL" => &mut self.lc_all,
+            "LC_CTYPE" => &mut self.lc_ctype,
+            "LANG" => &mut self.lang,
+            _ => return,
+        };
+        *variable = Some(value.to_string()).filter(|value| !value.is_empty());
+    }
+
+    /// The one that decides the character set.
+    pub fn ctype(&self) -> Option<&str> {
+        self.lc_all
+            .as_deref()
+            .or(self.lc_ctype.as_deref())
+            .or(self.lang.as_deref())
+    }
+}
+
+fn ascii(c: char) -> char {
+    match c {
+        '─' | '━' | '═' | '┄' | '┅' | '┈' | '┉' | '╌' | '╍' | '▔' | '‑' | '–' | '—' => {
+            '-'
+        }
+        '│' | '┃' | '║' | '┆' | '┇' | '┊' | '┋' | '╎' | '╏' | '▏' | '▕' => {
+            '|'
+        }
+        // Corners, tees and crosses
+        '\u{2500}'..='\u{257f}' => '+',
+        '▁' => '_',
+        '░' => '.',
+        '\u{2580}'..='\u{259f}' => '#',
+        '●' | '•' | '★' | '♪' | '❄' => '*',
+        '·' | '…' => '.',
+        '←' | '↵' => '<',
+        '→' | '›' => '>',
+        '↑' | '⇞' => '^',
+        '↓' | '⇟' => 'v',
+        '✔' | '✓' => 'x',
+        // The weather's
+        '☀' | '°' => 'o',
+        '☾' => 'c',
+        '☁' => '~',
+        '☂' => '/',
+        '☈' => '!',
+        '≡' => '=',
+        '‘' | '’' => '\'',
+        '“' | '”' => '"',
+        _ => '?',
+    }
+}
@@ -0,0 +1,176 @@
+use std::collections::{HashSet, VecDeque};
+use std::fmt;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
+
+use crate::event_bus::{Event, EventBus};
+
+pub const MAX_MESSAGE_CHARS: usize = 200;
+const MAX_NICK_CHARS: usize = 16;
+/// Lines kept for visitors who join later, and by each chat page.
+pub const SCROLLBACK: usize = 200;
+// Each member can send a burst of BURST messages, then one every REFILL_SECS
+const BURST: f64 = 5.0;
+const REFILL_SECS: f64 = 2.0;
+
+#[derive(Debug)]
+pub enum ChatLine {
+    Message { nick: String, text: String },
+    Notice(String),
+}
+
+#[derive(Debug)]
+pub enum ChatError {
+    TooLong,
+    Flooding,
+    InvalidNick,
+    NickTaken,
+}
+
+impl fmt::Display for ChatError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::TooLong => write!(f, "messages are up to {} characters", MAX_MESSAGE_CHARS),
+            Self::Flooding => write!(f, "slow down a little"),
+            Self::InvalidNick => write!(
+                f,
+                "nicks are up to {} letters, digits, - or _",
+                MAX_NICK_CHARS
+            ),
+            Self::NickTaken => write!(f, "that nick is taken"),
+        }
+    }
+}
+
+/// A single room every live session can talk in. New lines are published on the
+/// event bus, and the last few are kept so newcomers see what was said.
+pub struct ChatRoom {
+    events: EventBus,
+    scrollback: Mutex<VecDeque<Arc<ChatLine>>>,
+    nicks: Mutex<HashSet<String>>,
+    next_guest: AtomicUsize,
+}
+
+impl ChatRoom {
+    pub fn new(events: EventBus) -> Arc<Self> {
+        Arc::new(Self {
+            events,
+            scrollback: Mutex::new(VecDeque::new()),
+            nicks: Mutex::new(HashSet::new()),
+            next_guest: AtomicUsize::new(1),
+        })
+    }
+
+    pub fn scrollback(&self) -> Vec<Arc<ChatLine>> {
+        self.scrollback.lock().unwrap().iter().cloned().collect()
+    }
+
+    /// Enters the room under a fresh guest nick, which is given back once the
+    /// member is dropped.
+    pub fn join(self: &Arc<Self>) -> ChatMember {
+        let nick = loop {
+            let nick = format!("guest{}", self.next_guest.fetch_add(1, Ordering::Relaxed));
+            if self.nicks.lock().unwrap().insert(nick.clone()) {
+                break nick;
+            }
+        };
+        ChatMember {
+            room: self.clone(),
+            nick,
+            tokens: BURST,
+            refilled_at: Instant::now(),
+        }
+    }
+
+    fn post(&self, line: ChatLine) {
+        let line = Arc::new(line);
+        {
+            let mut scrollback = self.scrollback.lock().unwrap();
+            if scrollback.len() == SCROLLBACK {
+                scrollback.pop_front();
+            }
+            scrollback.push_back(line.clone());
+        }
+        self.events.publish(Event::Chat(line));
+    }
+}
+
+/// One session's seat in the room.
+pub struct ChatMember {
+    room: Arc<ChatRoom>,
+    nick: String,
+    // Flood protection, a token bucket refilled over time
+    tokens: f64,
+    refilled_at: Instant,
+}
+
+impl ChatMember {
+    pub fn nick(&self) -> &str {
+        &self.nick
+    }
+
+    pub fn send(&mut self, text: &str) -> Result<(), ChatError> {
+        let text = text.trim();
+        if text.is_empty() {
+            return Ok(());
+        }
+        if text.chars().count() > MAX_MESSAGE_CHARS {
+            return Err(ChatError::TooLong);
+        }
+        self.take_token()?;
+
+        self.room.post(ChatLine::Message {
+            nick: self.nick.clone(),
+            text: text.to_string(),
+        });
+        Ok(())
+    }
+
+    pub fn rename(&mut self, nick: &str) -> Result<(), ChatError> {
+        let nick = nick.trim();
+        let valid = !nick.is_empty()
+            && nick.chars().count() <= MAX_NICK_CHARS
+            && nick
+                .chars()
+                .all(|c| c.is_alphanumeric() || c == '-' || c == '_');
+        if !valid {
+            return Err(ChatError::InvalidNick);
+        }
+        if nick == self.nick {
+            return Ok(());
+        }
+        self.take_token()?;
+
+        {
+            let mut nicks = self.room.nicks.lock().unwrap();
+            if !nicks.insert(nick.to_string()) {
+                return Err(ChatError::NickTaken);
+            }
+            nicks.remove(&self.nick);
+        }
+        let old_nick = std::mem::replace(&mut self.nick, nick.to_string());
+        self.room.post(ChatLine::Notice(format!(
+            "{} is now {}",
+            old_nick, self.nick
+        )));
+        Ok(())
+    }
+
+    fn take_token(&mut self) -> Result<(), ChatError> {
+        let refill = self.refilled_at.elapsed().as_secs_f64() / REFILL_SECS;
+        self.tokens = (self.tokens + refill).min(BURST);
+        self.refilled_at = Instant::now();
+        if self.tokens < 1.0 {
+            return Err(ChatError::Flooding);
+        }
+        self.tokens -= 1.0;
+        Ok(())
+    }
+}
+
+impl Drop for ChatMember {
+    fn drop(&mut self) {
+        self.room.nicks.lock().unwrap().remove(&self.nick);
+    }
+}
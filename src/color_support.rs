@@ -0,0 +1,205 @@
+use ratatui::buffer::Buffer;
+use ratatui::style::Color;
+
+/// Sets a 24-bit foreground and asks the terminal to report the current SGR state
+/// back (DECRQSS). A terminal with truecolor repeats the exact color; one without
+/// reports a rounded color or nothing at all. The attributes are reset afterwards.
+pub const TRUECOLOR_PROBE: &[u8] = b"\x1b[38;2;1;2;3m\x1bP$qm\x1b\\\x1b[0m";
+
+// The standard VGA values most terminals use for the 16 ANSI colors
+const ANSI_COLORS: [(Color, (u8, u8, u8)); 16] = [
+    (Color::Black, (0, 0, 0)),
+    (Color::Red, (170, 0, 0)),
+    (Color::Green, (0, 170, 0)),
+    (Color::Yellow, (170, 85, 0)),
+    (Color::Blue, (0, 0, 170)),
+    (Color::Magenta, (170, 0, 170)),
+    (Color::Cyan, (0, 170, 170)),
+    (Color::Gray, (170, 170, 170)),
+    (Color::DarkGray, (85, 85, 85)),
+    (Color::LightRed, (255, 85, 85)),
+    (Color::LightGreen, (85, 255, 85)),
+    (Color::LightYellow, (255, 255, 85)),
+    (Color::LightBlue, (85, 85, 255)),
+    (Color::LightMagenta, (255, 85, 255)),
+    (Color::LightCyan, (85, 255, 255)),
+    (Color::White, (255, 255, 255)),
+];
+const CUBE_LEVELS: [u8; 6] = [0, 95, 135, 175, 215, 255];
+
+/// How many colors a visitor's terminal can show. Pages always draw in 24-bit
+/// color, and frames are brought down to what the terminal supports before
+/// they are sent.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum ColorSupport {
+    Ansi16,
+    Ansi256,
+    TrueColor,
+}
+
+impl ColorSupport {
+    /// A best guess from `TERM` and `COLORTERM`. Plenty of truecolor terminals
+    /// still call themselves `xterm-256color`, which `TRUECOLOR_PROBE` can correct.
+    pub fn detect(term: &str, colorterm: Option<&str>) -> Self {
+        let truecolor = |value: &str| value.contains("truecolor") || value.contains("24bit");
+        if colorterm.is_some_and(truecolor) || truecolor(term) || term.ends_with("-direct") {
+            Self::TrueColor
+        } else if term.contains("256color") {
+            Self::Ansi256
+        } else {
+            Self::Ansi16
+        }
+    }
+
+    /// Whether a DECRQSS reply to `TRUECOLOR_PROBE` repeated the probe color.
+    pub fn is_truecolor_reply(reply: &[u8]) -> bool {
+        let reply = String::from_utf8_lossy(reply);
+        // xterm answers with colons (38:2::1:2:3), others with semicolons
+        reply.starts_with("1$r") && (reply.contains("1:2:3") || reply.contains("1;2;3"))
+    }
+
+    pub fn degrade(self, buffer: &mut Buffer) {
+        if self == Self::TrueColor {
+            return;
+        }
+
+        for cell in buffer.content.iter_mut() {
+            cell.fg = self.convert(cell.fg);
+            cell.bg = self.convert(cell.bg);
+        }
+    }
+
+    fn convert(self, color: Color) -> Color {
+        let rgb = match color {
+            Color::Rgb(r, g, b) => (r, g, b),
+            Color::Indexed(index) if self == Self::Ansi16 && index >= 16 => indexed_rgb(index),
+            other => return other,
+        };
+        match self {
+            Self::Ansi256 => Color::Indexed(nearest_indexed(rgb)),
+            _ => nearest_ansi(rgb),
+        }
+    }
+}
+
+/// crossterm writes even the 16 basic colors as 256 color parameters
+/// (`38;5;N`), which 16 color terminals like the Linux console ignore. This swaps
+/// them for the classic 30-37/90-97 and 40-47/100-107 forms in every SGR sequence.
+pub fn classic_sgr(frame: &[u8]) -> Vec<u8> {
+    let mut output = Vec::with_capacity(frame.len());
+    let mut rest = frame;
+    while let Some(start) = rest.windows(2).position(|w| w == b"\x1b[") {
+        output.extend_from_slice(&rest[..start + 2]);
+        rest = &rest[start + 2..];
+
+        let params_len = rest
+            .iter()
+            .take_while(|b| b.is_ascii_digit() || **b == b';')
+            .count();
+        if rest.get(params_len) == Some(&b'm') {
+            output.extend_from_slice(classic_params(&rest[..params_len]).as_bytes());
+        } else {
+            output.extend_from_slice(&rest[..params_len]);
+        }
+        rest = &rest[params_len..];
+    }
+    output.extend_from_slice(rest);
+    output
+}
+
+fn classic_params(params: &[u8]) -> String {
+    let params = String::from_utf8_lossy(params);
+    let params: Vec<&str> = params.split(';').collect();
+    let mut output = Vec::with_capacity(params.len());
+    let mut i = 0;
+    while i < params.len() {
+        let base = match params[i] {
+            "38" => Some(30),
+            "48" => Some(40),
+            _ => None,
+        };
+        let index = params.get(i + 2).and_then(|index| index.parse::<u8>().ok());
+        match (base, params.get(i + 1), index) {
+            (Some(base), Some(&"5"), Some(index @ 0..=7)) => {
+                output.push((base + index).to_string());
+                i += 3;
+            }
+            (Some(base), Some(&"5"), Some(index @ 8..=15)) => {
+                output.push((base + 60 + index - 8).to_string());
+                i += 3;
+            }
+            _ => {
+                output.push(params[i].to_string());
+                i += 1;
+            }
+        }
+    }
+    output.join(";")
+}
+
+/// The 24-bit value of any color but `Reset`, which is whatever the terminal's
+/// default is.
+pub fn rgb(color: Color) -> Option<(u8, u8, u8)> {
+    match color {
+        Color::Reset => None,
+        Color::Rgb(r, g, b) => Some((r, g, b)),
+        Color::Indexed(index) => Some(indexed_rgb(index)),
+        named => ANSI_COLORS
+            .iter()
+            .find(|(color, _)| *color == named)
+            .map(|(_, rgb)| *rgb),
+    }
+}
+
+fn distance((r1, g1, b1): (u8, u8, u8), (r2, g2, b2): (u8, u8, u8)) -> u32 {
+    let d = |a: u8, b: u8| (a as i32 - b as i32).pow(2) as u32;
+    d(r1, r2) + d(g1, g2) + d(b1, b2)
+}
+
+fn nearest_ansi(rgb: (u8, u8, u8)) -> Color {
+    ANSI_COLORS
+        .iter()
+        .min_by_key(|(_, ansi)| distance(rgb, *ansi))
+        .map(|(color, _)| *color)
+        .unwrap_or(Color::Reset)
+}
+
+/// The closest of the 6x6x6 color cube and the 24 step grayscale ramp.
+fn nearest_indexed((r, g, b): (u8, u8, u8)) -> u8 {
+    let level = |value: u8| {
+        (0..CUBE_LEVELS.len())
+            .min_by_key(|&i| (CUBE_LEVELS[i] as i32 - value as i32).abs())
+            .unwrap_or(0) as u8
+    };
+    let (r6, g6, b6) = (level(r), level(g), level(b));
+    let cube_index = 16 + 36 * r6 + 6 * g6 + b6;
+
+    let average = (r as u32 + g as u32 + b as u32) / 3;
+    let gray_step = (average.saturating_sub(8) / 10).min(23) as u8;
+    let gray_index = 232 + gray_step;
+
+    match distance((r, g, b), indexed_rgb(gray_index))
+        < distance((r, g, b), indexed_rgb(cube_index))
+    {
+        true => gray_index,
+        false => cube_index,
+    }
+}
+
+fn indexed_rgb(index: u8) -> (u8, u8, u8) {
+    match index {
+        0..=15 => ANSI_COLORS[index as usize].1,
+        16..=231 => {
+            let index = index - 16;
+            (
+                CUBE_LEVELS[(index / 36) as usize],
+                CUBE_LEVELS[(index / 6 % 6) as usize],
+                CUBE_LEVELS[(index % 6) as usize],
+            )
+        }
+        232..=255 => {
+            let value = 8 + 10 * (index - 232);
+            (value, value, value)
+        }
+    }
+}
@@ -0,0 +1,204 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use lettre::{Message, SmtpTransport, Transport};
+use tokio::sync::oneshot;
+use tracing::{info, warn};
+
+use crate::config::SharedConfig;
+
+pub const MAX_NAME_CHARS: usize = 40;
+pub const MAX_REPLY_TO_CHARS: usize = 100;
+pub const MAX_MESSAGE_CHARS: usize = 1000;
+const DELIVERY_TIMEOUT: Duration = Duration::from_secs(15);
+
+#[derive(Debug)]
+pub enum ContactError {
+    MissingName,
+    MissingMessage,
+    TooLong,
+    TooSoon(Duration),
+}
+
+impl fmt::Display for ContactError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::MissingName => write!(f, "please enter a name"),
+            Self::MissingMessage => write!(f, "please enter a message"),
+            Self::TooLong => write!(
+                f,
+                "names are up to {} characters, messages up to {}",
+                MAX_NAME_CHARS, MAX_MESSAGE_CHARS
+            ),
+            Self::TooSoon(wait) => write!(
+                f,
+                "you just sent one, try again in {}m",
+                wait.as_secs().div_ceil(60).max(1)
+            ),
+        }
+    }
+}
+
+pub struct Submission {
+    pub name: String,
+    /// However the visitor would like an answer, if at all.
+    pub reply_to: String,
+    pub message: String,
+}
+
+impl Submission {
+    fn text(&self) -> String {
+        match self.reply_to.is_empty() {
+            true => format!("{} wrote:\n{}", self.name, self.message),
+            false => format!("{} ({}) wrote:\n{}", self.name, self.reply_to, self.message),
+        }
+    }
+}
+
+/// Delivers messages from the contact form to the configured webhook and mailbox.
+/// Delivery runs in the background, so a slow server never holds up a session.
+pub struct Contact {
+    config: SharedConfig,
+    agent: ureq::Agent,
+    // When each visitor last sent a message, keyed by key fingerprint or IP
+    last_sent: Mutex<HashMap<String, Instant>>,
+}
+
+impl Contact {
+    pub fn new(config: SharedConfig) -> Arc<Self> {
+        let agent = ureq::Agent::config_builder()
+            .timeout_global(Some(DELIVERY_TIMEOUT))
+            .build()
+            .into();
+        Arc::new(Self {
+            config,
+            agent,
+            last_sent: Mutex::new(HashMap::new()),
+        })
+    }
+
+    /// Whether there is anywhere to deliver to, and so whether sessions show the form.
+    pub fn enabled(&self) -> bool {
+        let config = self.config.read().unwrap();
+        config.contact_webhook.is_some() || config.contact_smtp_url.is_some()
+    }
+
+    /// Checks the submission and that `visitor` didn't send one within the
+    /// configured interval, then starts delivering it. The receiver learns whether
+    /// it arrived.
+    pub fn send(
+        self: &Arc<Self>,
+        visitor: &str,
+        submission: Submission,
+    ) -> Result<oneshot::Receiver<bool>, ContactError> {
+        let submission = Submission {
+            name: submission.name.trim().to_string(),
+            reply_to: submission.reply_to.trim().to_string(),
+            message: submission.message.trim().to_string(),
+        };
+        if submission.name.is_empty() {
+            return Err(ContactError::MissingName);
+        }
+        if submission.message.is_empty() {
+            return Err(ContactError::MissingMessage);
+        }
+        if submission.name.chars().count() > MAX_NAME_CHARS
+            || submission.reply_to.chars().count() > MAX_REPLY_TO_CHARS
+            || submission.message.chars().count() > MAX_MESSAGE_CHARS
+        {
+            return Err(ContactError::TooLong);
+        }
+
+        let interval = self.config.read().unwrap().contact_interval();
+        {
+            let mut last_sent = self.last_sent.lock().unwrap();
+            last_sent.retain(|_, sent_at| sent_at.elapsed() < interval);
+            if let Some(sent_at) = last_sent.get(visitor) {
+                return Err(ContactError::TooSoon(interval - sent_at.elapsed()));
+            }
+            last_sent.insert(visitor.to_string(), Instant::now());
+        }
+
+        let (delivered, receiver) = oneshot::channel();
+        let contact = self.clone();
+        let visitor = visitor.to_string();
+        tokio::task::spawn_blocking(move || {
+            let ok = contact.deliver(&submission);
+            if !ok {
+                // Nothing arrived, so they may try again right away
+                contact.last_sent.lock().unwrap().remove(&visitor);
+            }
+            let _ = delivered.send(ok);
+        });
+        Ok(receiver)
+    }
+
+    /// Sends to every configured destination. True if any of them took it.
+    fn deliver(&self, submission: &Submission) -> bool {
+        let (webhook, smtp_url, email) = {
+            let config = self.config.read().unwrap();
+            (
+                config.contact_webhook.clone(),
+                config.contact_smtp_url.clone(),
+                config.contact_email.clone(),
+            )
+        };
+
+        let mut delivered = false;
+        if let Some(url) = webhook {
+            match post_webhook(&self.agent, &url, &submission.text()) {
+                Ok(()) => delivered = true,
+                Err(e) => warn!("Failed to post a contact message to the webhook: {}", e),
+            }
+        }
+        if let (Some(url), Some(email)) = (smtp_url, email) {
+            match send_mail(&url, &email, submission) {
+                Ok(()) => delivered = true,
+                Err(e) => warn!("Failed to mail a contact message: {}", e),
+            }
+        }
+        if delivered {
+            info!("Delivered a contact message from {}", submission.name);
+        }
+        delivered
+    }
+}
+
+/// Posts `text` to a Discord or Slack webhook, without letting it ping anyone.
+pub(crate) fn post_webhook(
+    agent: &ureq::Agent,
+    url: &str,
+    text: &str,
+) -> Result<(), anyhow::Error> {
+    // Discord reads `content`, Slack and most others `text`
+    let body = match url.contains("discord.com") || url.contains("discordapp.com") {
+        true => serde_json::json!({ "content": text, "allowed_mentions": { "parse": [] } }),
+        // Slack has no such switch, but only pings for `<!here>`, `<@U…>` and
+        // the like, which escaping the brackets turns into plain text
+        false => serde_json::json!({ "text": escape_slack(text) }),
+    };
+    agent.post(url).send_json(body)?;
+    Ok(())
+}
+
+/// Escapes the three characters Slack reads markup from, as its docs ask.
+fn escape_slack(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+fn send_mail(url: &str, email: &str, submission: &Submission) -> Result<(), anyhow::Error> {
+    let message = Message::builder()
+        .from(email.parse()?)
+        .to(email.parse()?)
+        .subject(format!("Message from {} over ssh", submission.name))
+        .body(submission.text())?;
+    SmtpTransport::from_url(url)?
+        .timeout(Some(DELIVERY_TIMEOUT))
+        .build()
+        .send(&message)?;
+    Ok(())
+}
@@ -0,0 +1,221 @@
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, RwLock, mpsc};
+use std::time::Duration;
+
+use notify::{EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use tracing::warn;
+
+use crate::graphics::Image;
+use crate::pages::markdown::Markdown;
+
+// Blog posts live in this subdirectory of the content directory
+const POSTS_DIR: &str = "posts";
+// How long the directory has to be quiet before changes to it are reloaded
+const RELOAD_DELAY: Duration = Duration::from_millis(200);
+// A photo of its owner for the about page, the first of these found
+const AVATAR_FILES: [&str; 3] = ["avatar.png", "avatar.jpg", "avatar.jpeg"];
+
+pub struct ContentEntry {
+    pub title: String,
+    pub markdown: Arc<Markdown>,
+}
+
+pub struct Post {
+    pub title: String,
+    /// As written in the front matter, `YYYY-MM-DD` so it sorts as text.
+    pub date: String,
+    pub tags: Vec<String>,
+    pub markdown: Arc<Markdown>,
+}
+
+/// Pages written as Markdown files in the content directory, one page per `.md`
+/// file ordered by file name and titled after it, the blog posts in its `posts`
+/// directory and an optional avatar. The directory is watched, and every reload
+/// bumps the generation so open sessions can pick up the new pages.
+pub struct Content {
+    dir: PathBuf,
+    entries: RwLock<Arc<Vec<ContentEntry>>>,
+    posts: RwLock<Arc<Vec<Post>>>,
+    avatar: RwLock<Option<Arc<Image>>>,
+    generation: AtomicU64,
+}
+
+impl Content {
+    pub fn load(dir: &Path) -> Arc<Self> {
+        let content = Self {
+            dir: dir.to_path_buf(),
+            entries: RwLock::new(Arc::new(Vec::new())),
+            posts: RwLock::new(Arc::new(Vec::new())),
+            avatar: RwLock::new(None),
+            generation: AtomicU64::new(0),
+        };
+        content.reload();
+        Arc::new(content)
+    }
+
+    pub fn entries(&self) -> Arc<Vec<ContentEntry>> {
+        self.entries.read().unwrap().clone()
+    }
+
+    /// Newest first.
+    pub fn posts(&self) -> Arc<Vec<Post>> {
+        self.posts.read().unwrap().clone()
+    }
+
+    pub fn avatar(&self) -> Option<Arc<Image>> {
+        self.avatar.read().unwrap().clone()
+    }
+
+    pub fn generation(&self) -> u64 {
+        self.generation.load(Ordering::Acquire)
+    }
+
+    fn reload(&self) {
+        *self.entries.write().unwrap() = Arc::new(read_entries(&self.dir));
+        *self.posts.write().unwrap() = Arc::new(read_posts(&self.dir.join(POSTS_DIR)));
+        *self.avatar.write().unwrap() = read_avatar(&self.dir).map(Arc::new);
+        self.generation.fetch_add(1, Ordering::Release);
+    }
+
+    /// Reloads the pages whenever files in the directory change, for as long as
+    /// the returned watcher is kept alive. Changes that come in together, like an
+    /// editor's save or a `git pull`, are reloaded once.
+    pub fn watch(self: &Arc<Self>) -> Result<RecommendedWatcher, anyhow::Error> {
+        let (changed, changes) = mpsc::channel();
+        let mut watcher =
+            notify::recommended_watcher(move |event: notify::Result<notify::Event>| {
+                if let Ok(event) = event
+                    && matches!(
+                        event.kind,
+                        EventKind::Create(_) | EventKind::Modify(_) | EventKind::Remove(_)
+                    )
+                {
+                    let _ = changed.send(());
+                }
+            })?;
+        watcher
+            .watch(&self.dir, RecursiveMode::Recursive)
+            .map_err(|e| anyhow::anyhow!("Failed to watch {}: {}", self.dir.display(), e))?;
+
+        let content = Arc::downgrade(self);
+        // Ends once the watcher is dropped, which drops the sender with it
+        std::thread::spawn(move || {
+            while changes.recv().is_ok() {
+                while changes.recv_timeout(RELOAD_DELAY).is_ok() {}
+                let Some(content) = content.upgrade() else {
+                    return;
+                };
+                content.reload();
+            }
+        });
+        Ok(watcher)
+    }
+}
+
+fn read_avatar(dir: &Path) -> Option<Image> {
+    let path = AVATAR_FILES
+        .iter()
+        .map(|name| dir.join(name))
+        .find(|path| path.is_file())?;
+    match image::open(&path) {
+        Ok(image) => Some(Image::new(image.to_rgb8())),
+        Err(e) => {
+            warn!("Failed to read {}: {}", path.display(), e);
+            None
+        }
+    }
+}
+
+fn read_entries(dir: &Path) -> Vec<ContentEntry> {
+    read_markdown_files(dir)
+        .into_iter()
+        .map(|(title, source)| ContentEntry {
+            title,
+            markdown: Arc::new(Markdown::new(&source)),
+        })
+        .collect()
+}
+
+fn read_posts(dir: &Path) -> Vec<Post> {
+    let mut posts = read_markdown_files(dir)
+        .into_iter()
+        .map(|(file_stem, source)| {
+            let (front_matter, body) = split_front_matter(&source);
+            let mut post = Post {
+                title: file_stem,
+                date: String::new(),
+                tags: Vec::new(),
+                markdown: Arc::new(Markdown::new(body)),
+            };
+            for (key, value) in front_matter {
+                match key {
+                    "title" => post.title = value.to_string(),
+                    "date" => post.date = value.to_string(),
+                    "tags" => {
+                        post.tags = value
+                            .trim_start_matches('[')
+                            .trim_end_matches(']')
+                            .split(',')
+                            .map(|tag| tag.trim().trim_matches('"').to_string())
+                            .filter(|tag| !tag.is_empty())
+                            .collect()
+                    }
+                    _ => {}
+                }
+            }
+            post
+        })
+        .collect::<Vec<_>>();
+    posts.sort_by(|a, b| b.date.cmp(&a.date).then_with(|| a.title.cmp(&b.title)));
+    posts
+}
+
+/// The `key: value` lines between the `---` lines that open a post, and the rest
+/// of it. A post without them is all body.
+fn split_front_matter(source: &str) -> (Vec<(&str, &str)>, &str) {
+    let Some(rest) = source.strip_prefix("---\n") else {
+        return (Vec::new(), source);
+    };
+    let Some(end) = rest.find("\n---") else {
+        return (Vec::new(), source);
+    };
+    let fields = rest[..end]
+        .lines()
+        .filter_map(|line| line.split_once(':'))
+        .map(|(key, value)| (key.trim(), value.trim().trim_matches('"')))
+        .collect();
+    // Skips the rest of the closing `---` line
+    let body = rest[end + 4..]
+        .split_once('\n')
+        .map_or("", |(_, body)| body);
+    (fields, body)
+}
+
+/// The `.md` files in `dir` by file name, without the extension, and contents.
+fn read_markdown_files(dir: &Path) -> Vec<(String, String)> {
+    let Ok(read_dir) = std::fs::read_dir(dir) else {
+        return Vec::new();
+    };
+
+    let mut paths = read_dir
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| path.extension().is_some_and(|extension| extension == "md"))
+        .collect::<Vec<_>>();
+    paths.sort();
+
+    paths
+        .into_iter()
+        .filter_map(|path| {
+            let source = match std::fs::read_to_string(&path) {
+                Ok(source) => source,
+                Err(e) => {
+                    warn!("Skipping {}: {}", path.display(), e);
+                    return None;
+                }
+            };
+            let title = path.file_stem()?.to_string_lossy().into_owned();
+            Some((title, source))
+        })
+        .collect()
+}
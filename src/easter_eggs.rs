@@ -0,0 +1,91 @@
+use std::collections::VecDeque;
+
+use crossterm::event::KeyCode;
+
+/// What a hidden key sequence opens.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum EasterEgg {
+    Snake,
+    Locomotive,
+    Shell,
+}
+
+const KONAMI_CODE: &[KeyCode] = &[
+    KeyCode::Up,
+    KeyCode::Up,
+    KeyCode::Down,
+    KeyCode::Down,
+    KeyCode::Left,
+    KeyCode::Right,
+    KeyCode::Left,
+    KeyCode::Right,
+    KeyCode::Char('b'),
+    KeyCode::Char('a'),
+];
+
+/// The sequences and what they open. The keys on the way still do what they
+/// normally do, and the last one opens the egg instead, so it has to be one the
+/// keymap leaves alone: `h` and `l` move the focus, hence the Enter after them.
+pub const EASTER_EGGS: &[(&[KeyCode], EasterEgg)] = &[
+    (KONAMI_CODE, EasterEgg::Snake),
+    // Like the `sl` you get for mistyping `ls`
+    (
+        &[KeyCode::Char('s'), KeyCode::Char('l'), KeyCode::Enter],
+        EasterEgg::Locomotive,
+    ),
+    (
+        &[KeyCode::Char('s'), KeyCode::Char('h'), KeyCode::Enter],
+        EasterEgg::Shell,
+    ),
+];
+
+/// Watches the keys pressed outside of text fields for the sequences in
+/// `EASTER_EGGS`.
+pub struct SequenceMatcher {
+    recent_keys: VecDeque<KeyCode>,
+    longest: usize,
+}
+
+impl SequenceMatcher {
+    pub fn new() -> Self {
+        let longest = EASTER_EGGS
+            .iter()
+            .map(|(sequence, _)| sequence.len())
+            .max()
+            .unwrap_or(0);
+        Self {
+            recent_keys: VecDeque::with_capacity(longest),
+            longest,
+        }
+    }
+
+    /// The easter egg `key_code` completes the sequence for, if any. `bound` is
+    /// whether the keymap has an action for the key, which then wins: a rebound
+    /// key still does what the visitor's config says.
+    pub fn push(&mut self, key_code: KeyCode, bound: bool) -> Option<EasterEgg> {
+        if self.recent_keys.len() == self.longest {
+            self.recent_keys.pop_front();
+        }
+        self.recent_keys.push_back(key_code);
+        let (_, egg) = EASTER_EGGS.iter().find(|(sequence, _)| {
+            sequence.len() <= self.recent_keys.len()
+                && self
+                    .recent_keys
+                    .iter()
+                    .rev()
+                    .zip(sequence.iter().rev())
+                    .all(|(pressed, expected)| pressed == expected)
+        })?;
+        if bound {
+            return None;
+        }
+        self.recent_keys.clear();
+        Some(*egg)
+    }
+}
+
+impl Default for SequenceMatcher {
+    fn default() -> Self {
+        Self::new()
+    }
+}
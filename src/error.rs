@@ -0,0 +1,50 @@
+use std::path::PathBuf;
+
+/// What ends a connection. A broken transport only needs logging, since the
+/// client is already gone, while a render failure is the server's fault and
+/// disconnects the visitor with an apology.
+#[derive(Debug, thiserror::Error)]
+pub enum SessionError {
+    #[error("Client disconnected: {0}")]
+    Disconnected(#[from] russh::Error),
+    #[error(transparent)]
+    Render(#[from] RenderError),
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum RenderError {
+    #[error("Failed to draw the terminal: {0}")]
+    Terminal(#[from] std::io::Error),
+    #[error("The app panicked: {0}")]
+    Panicked(String),
+}
+
+/// A config that can't be used. Startup aborts on it, and a reload keeps the
+/// config already running.
+#[derive(Debug, thiserror::Error)]
+pub enum ConfigError {
+    #[error("Failed to read config at {}: {source}", path.display())]
+    Read {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+    #[error("Invalid config at {}: {source}", path.display())]
+    Parse {
+        path: PathBuf,
+        source: toml::de::Error,
+    },
+    #[error("Invalid {name} '{value}': {reason}")]
+    Env {
+        name: String,
+        value: String,
+        reason: String,
+    },
+    #[error("Key '{key}' is bound to both {first} and {second}")]
+    KeyConflict {
+        key: String,
+        first: &'static str,
+        second: &'static str,
+    },
+    #[error("{0}")]
+    Invalid(&'static str),
+}
@@ -0,0 +1,46 @@
+use std::sync::Arc;
+
+use tokio::sync::broadcast;
+
+use crate::chat::ChatLine;
+use crate::tic_tac_toe::PlayerId;
+
+// Events a session hasn't picked up yet before it starts missing some
+const CAPACITY: usize = 256;
+
+#[derive(Clone, Debug)]
+pub enum Event {
+    Chat(Arc<ChatLine>),
+    /// Something changed for this player at tic-tac-toe. Lobbies also take it to
+    /// mean the counts of who is waiting and playing may have.
+    TicTacToe(PlayerId),
+}
+
+/// Lets sessions tell each other about things as they happen. Every subscriber
+/// gets every event published after it subscribed.
+#[derive(Clone)]
+pub struct EventBus {
+    sender: broadcast::Sender<Event>,
+}
+
+impl EventBus {
+    pub fn new() -> Self {
+        let (sender, _) = broadcast::channel(CAPACITY);
+        Self { sender }
+    }
+
+    pub fn publish(&self, event: Event) {
+        // Nobody listening is fine, there is just no one to tell
+        let _ = self.sender.send(event);
+    }
+
+    pub fn subscribe(&self) -> broadcast::Receiver<Event> {
+        self.sender.subscribe()
+    }
+}
+
+impl Default for EventBus {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,279 @@
+use std::fmt::Write;
+
+use ratatui::{
+    Terminal,
+    backend::TestBackend,
+    buffer::{Buffer, Cell},
+    crossterm::style::{Attribute, SetAttribute, SetBackgroundColor, SetForegroundColor},
+    style::{Color, Modifier, Style},
+};
+use unicode_width::UnicodeWidthStr;
+
+use crate::app::content_pages;
+use crate::color_support::{self, ColorSupport, classic_sgr};
+use crate::pages::{
+    about::About, experience::Experience, leadership::Leadership, page::Page, projects::Projects,
+    stats::StatsPage,
+};
+use crate::preferences::SessionPreferences;
+use crate::services::Services;
+
+const WIDTH: u16 = 80;
+// Taller than any page, the blank rows below the content are trimmed
+const HEIGHT: u16 = 300;
+const USAGE: &str = "usage:
+  ls            list the pages
+  cat <page>    print a page, e.g. `ssh krayon.dev cat about`
+  help          show this
+
+connect without a command for the full site";
+
+// The colors `Reset` stands for in HTML, those of a dark terminal
+pub(crate) const HTML_FOREGROUND: (u8, u8, u8) = (229, 229, 229);
+pub(crate) const HTML_BACKGROUND: (u8, u8, u8) = (0, 0, 0);
+
+const MODIFIERS: [(Modifier, Attribute); 6] = [
+    (Modifier::BOLD, Attribute::Bold),
+    (Modifier::DIM, Attribute::Dim),
+    (Modifier::ITALIC, Attribute::Italic),
+    (Modifier::UNDERLINED, Attribute::Underlined),
+    (Modifier::REVERSED, Attribute::Reverse),
+    (Modifier::CROSSED_OUT, Attribute::CrossedOut),
+];
+
+/// Runs a command sent with `ssh host <command>` and returns what goes to stdout,
+/// or to stderr with a failing exit status. Pages are drawn in color for clients
+/// that asked for a pty, and as plain text otherwise so they can be piped.
+pub fn run(
+    command: &str,
+    services: &Services,
+    colors: Option<ColorSupport>,
+) -> Result<String, String> {
+    let mut words = command.split_whitespace();
+    let output = match words.next() {
+        None | Some("help") => String::from(USAGE),
+        Some("ls") => pages(services)
+            .iter()
+            .map(|page| page.title().to_string())
+            .collect::<Vec<_>>()
+            .join("\n"),
+        Some("cat") => {
+            let title = words.collect::<Vec<_>>().join(" ");
+            let page = pages(services)
+                .into_iter()
+                .find(|page| page.title() == title)
+                .ok_or_else(|| format!("no page called '{}', see `ls`", title))?;
+            render(page.as_ref(), colors)
+        }
+        Some(other) => return Err(format!("unknown command '{}'\n\n{}", other, USAGE)),
+    };
+    Ok(output)
+}
+
+/// The pages that read well on their own. The guestbook, chat and settings need
+/// the interactive session.
+pub(crate) fn pages(services: &Services) -> Vec<Box<dyn Page>> {
+    let mut pages: Vec<Box<dyn Page>> = vec![
+        // The avatar's half blocks would only be noise in a command's output
+        Box::new(About::new(
+            false,
+            None,
+            services.weather.clone(),
+            SessionPreferences::unsaved(services.config.read().unwrap().theme()),
+        )),
+        Box::new(Experience::new()),
+        Box::new(Projects::new()),
+        Box::new(Leadership::new()),
+    ];
+    pages.extend(content_pages(&services.content));
+    pages.push(Box::new(StatsPage::new(services.stats.clone())));
+    pages
+}
+
+pub(crate) fn render(page: &dyn Page, colors: Option<ColorSupport>) -> String {
+    let mut buffer = draw(page);
+    match colors {
+        Some(color_support) => {
+            color_support.degrade(&mut buffer);
+            let text = to_ansi(&buffer);
+            match color_support {
+                ColorSupport::Ansi16 => {
+                    String::from_utf8_lossy(&classic_sgr(text.as_bytes())).into_owned()
+                }
+                _ => text,
+            }
+        }
+        None => to_plain(&buffer),
+    }
+}
+
+/// The page as the inside of a `<pre>`, with a styled span wherever the style
+/// changes.
+pub(crate) fn render_html(page: &dyn Page) -> String {
+    let buffer = draw(page);
+    let lines = rows(&buffer)
+        .map(|row| {
+            let cells = &buffer.content[row];
+            let used = cells
+                .iter()
+                .rposition(|cell| cell.symbol() != " " || cell.bg != Color::Reset)
+                .map_or(0, |last| last + 1);
+
+            let mut line = String::new();
+            // Styles that only differ in ways HTML can't show share a span
+            let mut style = String::new();
+            for cell in visible_cells(&cells[..used]) {
+                let cell_style = css(cell.style());
+                if cell_style != style {
+                    if !style.is_empty() {
+                        line.push_str("</span>");
+                    }
+                    style = cell_style;
+                    if !style.is_empty() {
+                        let _ = write!(line, "<span style=\"{}\">", style);
+                    }
+                }
+                line.push_str(&escape_html(cell.symbol()));
+            }
+            if !style.is_empty() {
+                line.push_str("</span>");
+            }
+            line
+        })
+        .collect::<Vec<_>>();
+    trim_trailing_blank_lines(lines).join("\n")
+}
+
+pub(crate) fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+fn draw(page: &dyn Page) -> Buffer {
+    let mut terminal =
+        Terminal::new(TestBackend::new(WIDTH, HEIGHT)).expect("the test backend never fails");
+    let mut frame = terminal.get_frame();
+    let area = frame.area();
+    page.render(&mut frame, area, false);
+    frame.buffer_mut().clone()
+}
+
+fn to_plain(buffer: &Buffer) -> String {
+    let lines = rows(buffer)
+        .map(|row| {
+            let line: String = visible_cells(&buffer.content[row])
+                .map(|cell| cell.symbol())
+                .collect();
+            line.trim_end().to_string()
+        })
+        .collect::<Vec<_>>();
+    trim_trailing_blank_lines(lines).join("\n")
+}
+
+/// Like `to_plain`, with an SGR sequence wherever the style changes and a reset at
+/// the end of every line.
+fn to_ansi(buffer: &Buffer) -> String {
+    let lines = rows(buffer)
+        .map(|row| {
+            let cells = &buffer.content[row];
+            // Trailing blank cells are dropped unless they have a background
+            let used = cells
+                .iter()
+                .rposition(|cell| cell.symbol() != " " || cell.bg != Color::Reset)
+                .map_or(0, |last| last + 1);
+
+            let mut line = String::new();
+            let mut style = Style::default();
+            for cell in visible_cells(&cells[..used]) {
+                if cell.style() != style {
+                    style = cell.style();
+                    line.push_str(&sgr(style));
+                }
+                line.push_str(cell.symbol());
+            }
+            if style != Style::default() {
+                line.push_str("\x1b[0m");
+            }
+            line
+        })
+        .collect::<Vec<_>>();
+    trim_trailing_blank_lines(lines).join("\n")
+}
+
+fn sgr(style: Style) -> String {
+    let mut sequence = String::from("\x1b[0m");
+    if let Some(fg) = style.fg.filter(|fg| *fg != Color::Reset) {
+        let _ = write!(sequence, "{}", SetForegroundColor(fg.into()));
+    }
+    if let Some(bg) = style.bg.filter(|bg| *bg != Color::Reset) {
+        let _ = write!(sequence, "{}", SetBackgroundColor(bg.into()));
+    }
+    for (modifier, attribute) in MODIFIERS {
+        if style.add_modifier.contains(modifier) {
+            let _ = write!(sequence, "{}", SetAttribute(attribute));
+        }
+    }
+    sequence
+}
+
+fn css(style: Style) -> String {
+    let fg = style.fg.and_then(color_support::rgb);
+    let bg = style.bg.and_then(color_support::rgb);
+    // Reversed text swaps the colors, defaults included
+    let (fg, bg) = match style.add_modifier.contains(Modifier::REVERSED) {
+        true => (
+            Some(bg.unwrap_or(HTML_BACKGROUND)),
+            Some(fg.unwrap_or(HTML_FOREGROUND)),
+        ),
+        false => (fg, bg),
+    };
+
+    let mut declarations = Vec::new();
+    if let Some((r, g, b)) = fg {
+        declarations.push(format!("color:#{:02x}{:02x}{:02x}", r, g, b));
+    }
+    if let Some((r, g, b)) = bg {
+        declarations.push(format!("background:#{:02x}{:02x}{:02x}", r, g, b));
+    }
+    let modifiers = [
+        (Modifier::BOLD, "font-weight:bold"),
+        (Modifier::DIM, "opacity:0.6"),
+        (Modifier::ITALIC, "font-style:italic"),
+        (Modifier::UNDERLINED, "text-decoration:underline"),
+        (Modifier::CROSSED_OUT, "text-decoration:line-through"),
+    ];
+    for (modifier, declaration) in modifiers {
+        if style.add_modifier.contains(modifier) {
+            declarations.push(declaration.to_string());
+        }
+    }
+    declarations.join(";")
+}
+
+/// Skips the cells covered by the wide character before them, which the buffer
+/// keeps as blanks.
+fn visible_cells(cells: &[Cell]) -> impl Iterator<Item = &Cell> {
+    let mut hidden = 0;
+    cells.iter().filter(move |cell| {
+        if hidden > 0 {
+            hidden -= 1;
+            return false;
+        }
+        hidden = cell.symbol().width().saturating_sub(1);
+        true
+    })
+}
+
+fn rows(buffer: &Buffer) -> impl Iterator<Item = std::ops::Range<usize>> {
+    let width = buffer.area.width as usize;
+    (0..buffer.area.height as usize).map(move |y| y * width..(y + 1) * width)
+}
+
+fn trim_trailing_blank_lines(mut lines: Vec<String>) -> Vec<String> {
+    while lines.last().is_some_and(|line| line.trim().is_empty()) {
+        lines.pop();
+    }
+    lines
+}
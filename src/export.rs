@@ -0,0 +1,75 @@
+use std::fmt::Write;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::exec::{self, HTML_BACKGROUND, HTML_FOREGROUND, escape_html};
+use crate::services::Services;
+
+/// Writes the pages `ssh host cat` prints as a static site in `dir`, colors and
+/// all: the about page as `index.html` and every other one as `<title>.html`,
+/// with links between them. Returns the files written.
+pub fn export_html(services: &Services, dir: &Path) -> Result<Vec<PathBuf>, anyhow::Error> {
+    fs::create_dir_all(dir)?;
+    let pages = exec::pages(services);
+    let files = pages
+        .iter()
+        .enumerate()
+        .map(|(i, page)| match i {
+            0 => String::from("index.html"),
+            _ => format!("{}.html", page.title().replace(' ', "-")),
+        })
+        .collect::<Vec<_>>();
+
+    let mut written = Vec::new();
+    for (i, page) in pages.iter().enumerate() {
+        let mut nav = String::new();
+        for (j, (other, file)) in pages.iter().zip(&files).enumerate() {
+            let current = match i == j {
+                true => " aria-current=\"page\"",
+                false => "",
+            };
+            let title = escape_html(other.title());
+            let _ = write!(nav, "<a href=\"{}\"{}>{}</a> ", file, current, title);
+        }
+
+        let (r, g, b) = HTML_BACKGROUND;
+        let background = format!("#{:02x}{:02x}{:02x}", r, g, b);
+        let (r, g, b) = HTML_FOREGROUND;
+        let foreground = format!("#{:02x}{:02x}{:02x}", r, g, b);
+        // The browser drops the newline right after <pre>, not the page's first
+        // line, which is often blank
+        let html = format!(
+            "<!doctype html>
+<html lang=\"en\">
+<head>
+  <meta charset=\"utf-8\">
+  <meta name=\"viewport\" content=\"width=device-width, initial-scale=1\">
+  <title>{title} · krayon.dev</title>
+  <style>
+    body {{ margin: 0; padding: 1em; background: {background}; color: {foreground}; }}
+    nav a {{ color: inherit; margin-right: 1em; }}
+    nav a[aria-current] {{ font-weight: bold; }}
+    pre {{ font-family: monospace; line-height: 1.2; }}
+  </style>
+</head>
+<body>
+  <nav>{nav}</nav>
+  <main>
+    <pre>
+{body}</pre>
+    <p>the full site is at <code>ssh krayon.dev</code></p>
+  </main>
+</body>
+</html>
+",
+            title = escape_html(page.title()),
+            nav = nav.trim_end(),
+            body = exec::render_html(page.as_ref()),
+        );
+
+        let path = dir.join(&files[i]);
+        fs::write(&path, html)?;
+        written.push(path);
+    }
+    Ok(written)
+}
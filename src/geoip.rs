@@ -0,0 +1,53 @@
+use std::net::IpAddr;
+use std::path::Path;
+use std::sync::Arc;
+
+use maxminddb::{Reader, geoip2};
+use tracing::debug;
+
+/// Roughly where an address is, as far as the database knows.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Location {
+    pub country: Option<String>,
+    pub city: Option<String>,
+}
+
+/// Looks visitors up in an offline MaxMind database, GeoLite2 City or Country.
+/// Without one nobody is, and nothing about where they are is logged or kept.
+pub struct GeoIp {
+    reader: Option<Reader<Vec<u8>>>,
+}
+
+impl GeoIp {
+    pub fn open(path: Option<&Path>) -> Result<Arc<Self>, anyhow::Error> {
+        let reader = path
+            .map(|path| {
+                Reader::open_readfile(path).map_err(|e| {
+                    anyhow::anyhow!("Failed to open GeoIP database at {}: {}", path.display(), e)
+                })
+            })
+            .transpose()?;
+        Ok(Arc::new(Self { reader }))
+    }
+
+    pub fn locate(&self, ip: IpAddr) -> Location {
+        let Some(reader) = &self.reader else {
+            return Location::default();
+        };
+        // A Country database decodes as a City one without the city
+        let found = reader
+            .lookup(ip.to_canonical())
+            .and_then(|result| result.decode::<geoip2::City>());
+        match found {
+            Ok(Some(found)) => Location {
+                country: found.country.names.english.map(String::from),
+                city: found.city.names.english.map(String::from),
+            },
+            Ok(None) => Location::default(),
+            Err(e) => {
+                debug!("GeoIP lookup of {} failed: {}", ip, e);
+                Location::default()
+            }
+        }
+    }
+}
@@ -0,0 +1,104 @@
+use std::sync::Arc;
+
+use serde::Deserialize;
+use tracing::info;
+
+use crate::config::{Config, SharedConfig};
+use crate::poller::{self, Poller};
+
+const API: &str = "https://api.github.com";
+
+#[derive(Deserialize, PartialEq)]
+pub struct Repo {
+    pub name: String,
+    pub description: Option<String>,
+    pub language: Option<String>,
+    #[serde(rename = "stargazers_count")]
+    pub stars: u32,
+    #[serde(rename = "forks_count")]
+    pub forks: u32,
+    #[serde(rename = "html_url")]
+    pub url: String,
+    /// As GitHub sends it, e.g. `2025-03-01T12:00:00Z`.
+    pub pushed_at: String,
+    fork: bool,
+    archived: bool,
+}
+
+impl Repo {
+    pub fn pushed_on(&self) -> &str {
+        self.pushed_at.split('T').next().unwrap_or_default()
+    }
+}
+
+/// The configured user's public repos, fetched in the background and shared by
+/// every session. A failed fetch keeps the repos from the last one.
+pub struct GitHub {
+    config: SharedConfig,
+    agent: ureq::Agent,
+    // None until the first fetch went through
+    repos: Arc<Poller<Option<Arc<Vec<Repo>>>>>,
+}
+
+impl GitHub {
+    pub fn new(config: SharedConfig) -> Arc<Self> {
+        Arc::new(Self {
+            config,
+            agent: poller::agent(),
+            repos: Poller::new(None),
+        })
+    }
+
+    /// Whether a user is configured, and so whether sessions show the page.
+    pub fn enabled(&self) -> bool {
+        self.config.read().unwrap().github_user.is_some()
+    }
+
+    /// Most starred first, without forks and archived repos.
+    pub fn repos(&self) -> Arc<Vec<Repo>> {
+        self.repos.get().unwrap_or_default()
+    }
+
+    /// Zero until the first fetch went through, and bumped whenever the repos
+    /// change, so pages know to pick up `repos` again.
+    pub fn generation(&self) -> u64 {
+        self.repos.generation()
+    }
+
+    /// Fetches the repos now and then every `github_refresh_secs`.
+    pub fn refresh_periodically(self: &Arc<Self>) {
+        let github = self.clone();
+        self.repos.start(
+            "the GitHub repos",
+            self.config.clone(),
+            Config::github_refresh,
+            move || {
+                // Set up no longer after a reload
+                let Some(user) = github.config.read().unwrap().github_user.clone() else {
+                    return Ok(None);
+                };
+                let repos = github.fetch(&user)?;
+                info!("Fetched {} GitHub repos of {}", repos.len(), user);
+                Ok(Some(Arc::new(repos)))
+            },
+        );
+    }
+
+    fn fetch(&self, user: &str) -> Result<Vec<Repo>, anyhow::Error> {
+        let url = format!("{}/users/{}/repos?type=owner&per_page=100", API, user);
+        let mut repos: Vec<Repo> = self
+            .agent
+            .get(&url)
+            .header("Accept", "application/vnd.github+json")
+            .call()?
+            .body_mut()
+            .read_json()?;
+        repos.retain(|repo| !repo.fork && !repo.archived);
+        repos.sort_by(|a, b| {
+            b.stars
+                .cmp(&a.stars)
+                .then_with(|| b.pushed_at.cmp(&a.pushed_at))
+        });
+        Ok(repos)
+    }
+}
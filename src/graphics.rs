@@ -0,0 +1,272 @@
+use std::io::{self, Cursor, Write};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU32, Ordering};
+
+use base64::{Engine as _, engine::general_purpose};
+use image::{ImageFormat, RgbImage, imageops::FilterType};
+use ratatui::crossterm::{cursor::MoveTo, queue};
+use ratatui::layout::Rect;
+
+/// Primary device attributes. The reply lists what the terminal supports, sixel
+/// graphics being `4`.
+pub const DEVICE_ATTRIBUTES_QUERY: &[u8] = b"\x1b[c";
+// Pixels per cell when the client doesn't say, about what most fonts come to
+const DEFAULT_CELL_SIZE: (u32, u32) = (10, 20);
+// The kitty protocol takes at most this much base64 per escape sequence
+const KITTY_CHUNK_BYTES: usize = 4096;
+const KITTY_DELETE_ALL: &[u8] = b"\x1b_Ga=d,q=2\x1b\\";
+static NEXT_IMAGE_ID: AtomicU32 = AtomicU32::new(1);
+
+/// How a visitor's terminal can show pictures. In the buffer every image is
+/// drawn in half blocks, which is all a terminal without graphics gets; the
+/// others have the picture drawn over those cells after the frame.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Graphics {
+    HalfBlocks,
+    Sixel,
+    Kitty,
+    Iterm2,
+}
+
+impl Graphics {
+    /// A best guess from `TERM` and `TERM_PROGRAM`. Sixel terminals not named
+    /// here are found by `DEVICE_ATTRIBUTES_QUERY`.
+    pub fn detect(term: &str, term_program: Option<&str>) -> Self {
+        if matches!(term_program, Some("iTerm.app" | "WezTerm")) || term.contains("wezterm") {
+            Self::Iterm2
+        } else if term.contains("kitty") || term.contains("ghostty") {
+            Self::Kitty
+        } else if ["foot", "mlterm", "contour"]
+            .iter()
+            .any(|name| term.contains(name))
+        {
+            Self::Sixel
+        } else {
+            Self::HalfBlocks
+        }
+    }
+
+    /// Whether a status report is a reply to `DEVICE_ATTRIBUTES_QUERY` at all.
+    pub fn is_device_attributes_reply(report: &[u8]) -> bool {
+        report.starts_with(b"?") && report.ends_with(b"c")
+    }
+
+    /// Whether a reply to `DEVICE_ATTRIBUTES_QUERY`, like `?62;4;22c`, lists sixel.
+    pub fn is_sixel_reply(reply: &[u8]) -> bool {
+        let reply = String::from_utf8_lossy(reply);
+        reply
+            .strip_prefix('?')
+            .and_then(|reply| reply.strip_suffix('c'))
+            // The first parameter is the terminal's class, not a feature
+            .is_some_and(|params| params.split(';').skip(1).any(|param| param == "4"))
+    }
+}
+
+/// A picture for pages to show, like the avatar on the about page.
+pub struct Image {
+    id: u32,
+    pixels: RgbImage,
+}
+
+impl Image {
+    pub fn new(pixels: RgbImage) -> Self {
+        Self {
+            id: NEXT_IMAGE_ID.fetch_add(1, Ordering::Relaxed),
+            pixels,
+        }
+    }
+
+    pub fn pixels(&self) -> &RgbImage {
+        &self.pixels
+    }
+
+    fn scaled(&self, width: u32, height: u32) -> RgbImage {
+        image::imageops::resize(&self.pixels, width, height, FilterType::Triangle)
+    }
+
+    fn png(&self, width: u32, height: u32) -> io::Result<Vec<u8>> {
+        let mut png = Vec::new();
+        self.scaled(width, height)
+            .write_to(&mut Cursor::new(&mut png), ImageFormat::Png)
+            .map_err(io::Error::other)?;
+        Ok(png)
+    }
+}
+
+/// Where an image was drawn in the last frame.
+#[derive(Clone)]
+pub struct Placement {
+    pub area: Rect,
+    pub image: Arc<Image>,
+}
+
+impl PartialEq for Placement {
+    fn eq(&self, other: &Self) -> bool {
+        self.area == other.area && self.image.id == other.image.id
+    }
+}
+
+/// Draws a session's images with its terminal's graphics protocol, whenever they
+/// moved since the last frame.
+pub struct Images {
+    graphics: Graphics,
+    cell_size: (u32, u32),
+    drawn: Vec<Placement>,
+}
+
+impl Default for Images {
+    fn default() -> Self {
+        Self::new(Graphics::HalfBlocks, None)
+    }
+}
+
+impl Images {
+    /// `cell_size` is how many pixels wide and high a cell is, if the client said.
+    pub fn new(graphics: Graphics, cell_size: Option<(u32, u32)>) -> Self {
+        Self {
+            graphics,
+            cell_size: cell_size.unwrap_or(DEFAULT_CELL_SIZE),
+            drawn: Vec::new(),
+        }
+    }
+
+    /// The terminal answered that it knows sixel; a protocol found by name is kept.
+    pub fn enable_sixel(&mut self) {
+        if self.graphics == Graphics::HalfBlocks {
+            self.graphics = Graphics::Sixel;
+        }
+    }
+
+    /// The screen was cleared, which takes the images with it.
+    pub fn forget(&mut self) {
+        self.drawn.clear();
+    }
+
+    /// What to send after a frame with `placements` in it.
+    pub fn frame(&mut self, placements: Vec<Placement>) -> io::Result<Vec<u8>> {
+        let mut output = Vec::new();
+        if self.graphics == Graphics::HalfBlocks || placements == self.drawn {
+            return Ok(output);
+        }
+
+        // Kitty images sit above the text, so ones that moved are taken down. The
+        // others are pictures in the cells, which ratatui writes over
+        if self.graphics == Graphics::Kitty && !self.drawn.is_empty() {
+            output.extend_from_slice(KITTY_DELETE_ALL);
+        }
+        for placement in &placements {
+            if self.graphics != Graphics::Kitty && self.drawn.contains(placement) {
+                continue;
+            }
+            let Placement { area, image } = placement;
+            let (width, height) = (
+                area.width as u32 * self.cell_size.0,
+                area.height as u32 * self.cell_size.1,
+            );
+            queue!(output, MoveTo(area.x, area.y))?;
+            match self.graphics {
+                Graphics::HalfBlocks => {}
+                Graphics::Kitty => write_kitty(&mut output, &image.png(width, height)?, *area)?,
+                Graphics::Iterm2 => write_iterm2(&mut output, &image.png(width, height)?, *area)?,
+                Graphics::Sixel => write_sixel(&mut output, &image.scaled(width, height))?,
+            }
+        }
+        self.drawn = placements;
+        Ok(output)
+    }
+}
+
+fn write_kitty(output: &mut Vec<u8>, png: &[u8], area: Rect) -> io::Result<()> {
+    let encoded = general_purpose::STANDARD.encode(png);
+    let chunks = encoded
+        .as_bytes()
+        .chunks(KITTY_CHUNK_BYTES)
+        .collect::<Vec<_>>();
+    for (i, chunk) in chunks.iter().enumerate() {
+        let more = (i + 1 < chunks.len()) as u8;
+        match i {
+            // Scaled to the area, without moving the cursor or answering
+            0 => write!(
+                output,
+                "\x1b_Ga=T,f=100,c={},r={},C=1,q=2,m={};",
+                area.width, area.height, more
+            )?,
+            _ => write!(output, "\x1b_Gm={};", more)?,
+        }
+        output.extend_from_slice(chunk);
+        output.extend_from_slice(b"\x1b\\");
+    }
+    Ok(())
+}
+
+fn write_iterm2(output: &mut Vec<u8>, png: &[u8], area: Rect) -> io::Result<()> {
+    write!(
+        output,
+        "\x1b]1337;File=inline=1;size={};width={};height={};preserveAspectRatio=0:{}\x07",
+        png.len(),
+        area.width,
+        area.height,
+        general_purpose::STANDARD.encode(png)
+    )
+}
+
+/// Sixel with the 216 colors of the 6x6x6 cube, which every sixel terminal has
+/// registers for.
+fn write_sixel(output: &mut Vec<u8>, pixels: &RgbImage) -> io::Result<()> {
+    let level = |value: u8| (value as u32 * 5 + 127) / 255;
+    let colors = pixels
+        .pixels()
+        .map(|pixel| {
+            let [r, g, b] = pixel.0.map(level);
+            (r * 36 + g * 6 + b) as usize
+        })
+        .collect::<Vec<_>>();
+    let (width, height) = (pixels.width() as usize, pixels.height() as usize);
+
+    // Square pixels, and the size up front so the background isn't cleared
+    write!(output, "\x1bP0;1q\"1;1;{};{}", width, height)?;
+    for color in 0..216 {
+        let (r, g, b) = (color / 36, color / 6 % 6, color % 6);
+        write!(output, "#{};2;{};{};{}", color, r * 20, g * 20, b * 20)?;
+    }
+    // Six rows at a time, one pass over them per color they use
+    for band in (0..height).step_by(6) {
+        let rows = band..(band + 6).min(height);
+        let mut used = rows
+            .clone()
+            .flat_map(|y| colors[y * width..(y + 1) * width].iter().copied())
+            .collect::<Vec<_>>();
+        used.sort_unstable();
+        used.dedup();
+        for color in used {
+            write!(output, "#{}", color)?;
+            let sixels = (0..width).map(|x| {
+                let bits = rows
+                    .clone()
+                    .filter(|&y| colors[y * width + x] == color)
+                    .fold(0u8, |bits, y| bits | 1 << (y - band));
+                b'?' + bits
+            });
+            write_run_lengths(output, sixels)?;
+            output.push(b'$');
+        }
+        output.push(b'-');
+    }
+    output.extend_from_slice(b"\x1b\\");
+    Ok(())
+}
+
+fn write_run_lengths(output: &mut Vec<u8>, sixels: impl Iterator<Item = u8>) -> io::Result<()> {
+    let mut sixels = sixels.peekable();
+    while let Some(sixel) = sixels.next() {
+        let mut count = 1;
+        while sixels.next_if_eq(&sixel).is_some() {
+            count += 1;
+        }
+        match count {
+            1..=3 => output.extend(std::iter::repeat_n(sixel, count)),
+            _ => write!(output, "!{}{}", count, sixel as char)?,
+        }
+    }
+    Ok(())
+}
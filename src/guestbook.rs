@@ -0,0 +1,139 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, RwLock};
+use std::time::{Duration, Instant};
+
+use tokio::sync::oneshot;
+use tracing::error;
+
+use crate::config::SharedConfig;
+use crate::store::{GuestbookEntry, Store};
+
+pub const MAX_NAME_CHARS: usize = 24;
+pub const MAX_MESSAGE_CHARS: usize = 200;
+// Older entries stay in the database but aren't shown
+const SHOWN_ENTRIES: usize = 200;
+
+#[derive(Debug)]
+pub enum SignError {
+    MissingName,
+    MissingMessage,
+    TooLong,
+    TooSoon(Duration),
+}
+
+impl fmt::Display for SignError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::MissingName => write!(f, "please enter a name"),
+            Self::MissingMessage => write!(f, "please enter a message"),
+            Self::TooLong => write!(
+                f,
+                "names are up to {} characters, messages up to {}",
+                MAX_NAME_CHARS, MAX_MESSAGE_CHARS
+            ),
+            Self::TooSoon(wait) => write!(
+                f,
+                "you just signed, try again in {}m",
+                wait.as_secs().div_ceil(60).max(1)
+            ),
+        }
+    }
+}
+
+/// Messages visitors leave for the ones after them. Shared by every session, so a
+/// new entry shows up for everyone who has the page open.
+pub struct Guestbook {
+    store: Arc<Store>,
+    config: SharedConfig,
+    entries: RwLock<Arc<Vec<GuestbookEntry>>>,
+    generation: AtomicU64,
+    // When each visitor last signed, keyed by key fingerprint or IP
+    last_signed: Mutex<HashMap<String, Instant>>,
+}
+
+impl Guestbook {
+    pub fn new(store: Arc<Store>, config: SharedConfig) -> Arc<Self> {
+        let guestbook = Arc::new(Self {
+            store,
+            config,
+            entries: RwLock::new(Arc::new(Vec::new())),
+            generation: AtomicU64::new(0),
+            last_signed: Mutex::new(HashMap::new()),
+        });
+        guestbook.reload();
+        guestbook
+    }
+
+    pub fn entries(&self) -> Arc<Vec<GuestbookEntry>> {
+        self.entries.read().unwrap().clone()
+    }
+
+    /// Bumped whenever an entry is added, so pages know to pick up `entries` again.
+    pub fn generation(&self) -> u64 {
+        self.generation.load(Ordering::Acquire)
+    }
+
+    /// Adds an entry for `visitor` after checking its length and that they didn't
+    /// sign within the configured interval. It's saved on a blocking thread, and
+    /// the receiver says whether that worked.
+    pub fn sign(
+        self: &Arc<Self>,
+        visitor: &str,
+        name: &str,
+        message: &str,
+    ) -> Result<oneshot::Receiver<bool>, SignError> {
+        let name = name.trim();
+        let message = message.trim();
+        if name.is_empty() {
+            return Err(SignError::MissingName);
+        }
+        if message.is_empty() {
+            return Err(SignError::MissingMessage);
+        }
+        if name.chars().count() > MAX_NAME_CHARS || message.chars().count() > MAX_MESSAGE_CHARS {
+            return Err(SignError::TooLong);
+        }
+
+        let interval = self.config.read().unwrap().guestbook_interval();
+        {
+            let mut last_signed = self.last_signed.lock().unwrap();
+            last_signed.retain(|_, signed_at| signed_at.elapsed() < interval);
+            if let Some(signed_at) = last_signed.get(visitor) {
+                return Err(SignError::TooSoon(interval - signed_at.elapsed()));
+            }
+            last_signed.insert(visitor.to_string(), Instant::now());
+        }
+
+        let (saved, receiver) = oneshot::channel();
+        let guestbook = self.clone();
+        let (visitor, name, message) = (visitor.to_string(), name.to_string(), message.to_string());
+        tokio::task::spawn_blocking(move || {
+            let ok = match guestbook.store.add_guestbook_entry(&name, &message) {
+                Ok(()) => {
+                    guestbook.reload();
+                    true
+                }
+                Err(e) => {
+                    error!("Failed to save guestbook entry: {}", e);
+                    // Nothing was saved, so they may try again right away
+                    guestbook.last_signed.lock().unwrap().remove(&visitor);
+                    false
+                }
+            };
+            let _ = saved.send(ok);
+        });
+        Ok(receiver)
+    }
+
+    fn reload(&self) {
+        match self.store.guestbook_entries(SHOWN_ENTRIES) {
+            Ok(entries) => {
+                *self.entries.write().unwrap() = Arc::new(entries);
+                self.generation.fetch_add(1, Ordering::AcqRel);
+            }
+            Err(e) => error!("Failed to load the guestbook: {}", e),
+        }
+    }
+}
@@ -0,0 +1,144 @@
+use std::sync::Arc;
+
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use tracing::error;
+
+use crate::pages::text_input::TextInput;
+use crate::store::{HighScore, Store};
+
+/// Places shown on a game's table.
+pub const TABLE_SIZE: usize = 10;
+/// Arcade style, so the table fits next to the board.
+pub const MAX_INITIALS: usize = 3;
+
+/// The best scores of every game, shared by everyone who plays them.
+pub struct HighScores {
+    store: Arc<Store>,
+}
+
+impl HighScores {
+    pub fn new(store: Arc<Store>) -> Arc<Self> {
+        Arc::new(Self { store })
+    }
+
+    /// The table for `game`, best first. Empty if it couldn't be read.
+    pub fn top(&self, game: &str) -> Vec<HighScore> {
+        self.store
+            .high_scores(game, TABLE_SIZE)
+            .inspect_err(|e| error!("Failed to load the {} high scores: {}", game, e))
+            .unwrap_or_default()
+    }
+
+    /// Whether `score` would make it onto the table.
+    pub fn qualifies(&self, game: &str, score: u32) -> bool {
+        let top = self.top(game);
+        score > 0 && (top.len() < TABLE_SIZE || top.iter().any(|entry| score > entry.score))
+    }
+
+    /// Initials are trimmed and upper cased, and fall back to `???`.
+    pub fn submit(&self, game: &str, initials: &str, score: u32) {
+        let initials = initials
+            .trim()
+            .chars()
+            .take(MAX_INITIALS)
+            .collect::<String>()
+            .to_uppercase();
+        let initials = match initials.is_empty() {
+            true => String::from("???"),
+            false => initials,
+        };
+        if let Err(e) = self.store.add_high_score(game, &initials, score) {
+            error!("Failed to save a {} high score: {}", game, e);
+        }
+    }
+
+    /// What `owner` did best at `game` on earlier visits, if anything.
+    pub fn personal_best(&self, game: &str, owner: &str) -> Option<u32> {
+        self.store
+            .personal_best(owner, game)
+            .inspect_err(|e| error!("Failed to load a {} personal best: {}", game, e))
+            .ok()
+            .flatten()
+    }
+
+    pub fn record_personal_best(&self, game: &str, owner: &str, best: u32) {
+        if let Err(e) = self.store.set_personal_best(owner, game, best) {
+            error!("Failed to save a {} personal best: {}", game, e);
+        }
+    }
+
+    /// For games that don't ask for initials, whose table only has the scores.
+    pub fn submit_anonymous(&self, game: &str, score: u32) {
+        if let Err(e) = self.store.add_high_score(game, "", score) {
+            error!("Failed to save a {} high score: {}", game, e);
+        }
+    }
+}
+
+/// A game's table, and the initials of a score good enough for it while the
+/// player types them in. Every game with a table asks for them through it.
+pub struct Leaderboard {
+    high_scores: Arc<HighScores>,
+    game: &'static str,
+    table: Vec<HighScore>,
+    // The score waiting for the player's initials
+    entry: Option<(u32, TextInput)>,
+}
+
+impl Leaderboard {
+    pub fn new(high_scores: Arc<HighScores>, game: &'static str) -> Self {
+        Self {
+            table: high_scores.top(game),
+            high_scores,
+            game,
+            entry: None,
+        }
+    }
+
+    pub fn table(&self) -> &[HighScore] {
+        &self.table
+    }
+
+    /// At the end of a game, asks for initials if `score` makes the table.
+    pub fn finish(&mut self, score: u32) {
+        if self.high_scores.qualifies(self.game, score) {
+            self.entry = Some((score, TextInput::new(MAX_INITIALS)));
+        }
+    }
+
+    /// What the player typed so far, while asking for their initials.
+    pub fn initials(&self) -> Option<&TextInput> {
+        self.entry.as_ref().map(|(_, initials)| initials)
+    }
+
+    /// Whether the key is used up, so the game leaves it be. While asking for
+    /// initials every key is: Enter submits them, Esc leaves the score off the
+    /// table and the rest edit them. Otherwise only shortcuts with Ctrl or Alt
+    /// are, which no game has a use for.
+    pub fn handle_key_event(&mut self, key_event: KeyEvent) -> bool {
+        let Some((score, initials)) = &mut self.entry else {
+            return key_event
+                .modifiers
+                .intersects(KeyModifiers::CONTROL | KeyModifiers::ALT);
+        };
+        match key_event.code {
+            KeyCode::Enter => {
+                self.high_scores
+                    .submit(self.game, &initials.value(), *score);
+                self.table = self.high_scores.top(self.game);
+                self.entry = None;
+            }
+            KeyCode::Esc => self.entry = None,
+            _ => {
+                initials.handle_key_event(key_event);
+            }
+        }
+        true
+    }
+
+    pub fn handle_paste(&mut self, text: &str) {
+        if let Some((_, initials)) = &mut self.entry {
+            initials.insert_str(text);
+        }
+    }
+}
@@ -0,0 +1,201 @@
+use std::io::{self, Write};
+
+use ratatui::buffer::{Buffer, Cell};
+// ratatui converts its colors for the crossterm it depends on, not the app's
+use ratatui::crossterm::cursor::MoveTo;
+use ratatui::crossterm::queue;
+use ratatui::crossterm::style::{Color as CColor, SetBackgroundColor, SetForegroundColor};
+use ratatui::style::Modifier;
+
+use crate::color_support::ColorSupport;
+
+// Terminals that understand OSC 8 without announcing truecolor
+const KNOWN_TERMINALS: [&str; 7] = [
+    "kitty",
+    "wezterm",
+    "foot",
+    "alacritty",
+    "ghostty",
+    "contour",
+    "rio",
+];
+// Top-level domains a bare name like `krayon.dev` is linked for. Any other needs
+// a scheme or a path, so file names like `resume.pdf` stay text
+const BARE_DOMAINS: [&str; 8] = ["com", "dev", "io", "org", "net", "me", "app", "sh"];
+const SGR_MODIFIERS: [(Modifier, u8); 9] = [
+    (Modifier::BOLD, 1),
+    (Modifier::DIM, 2),
+    (Modifier::ITALIC, 3),
+    (Modifier::UNDERLINED, 4),
+    (Modifier::SLOW_BLINK, 5),
+    (Modifier::RAPID_BLINK, 6),
+    (Modifier::REVERSED, 7),
+    (Modifier::HIDDEN, 8),
+    (Modifier::CROSSED_OUT, 9),
+];
+
+/// A guess, like `ColorSupport::detect`: there is no way to ask a terminal about
+/// OSC 8. Terminals recent enough for truecolor support it or at least ignore it,
+/// older ones may print the URL.
+pub fn detect(term: &str, colorterm: Option<&str>) -> bool {
+    KNOWN_TERMINALS.iter().any(|name| term.contains(name))
+        || ColorSupport::detect(term, colorterm) == ColorSupport::TrueColor
+}
+
+#[derive(PartialEq)]
+struct Link {
+    x: u16,
+    y: u16,
+    url: String,
+    cells: Vec<Cell>,
+}
+
+/// Makes URLs and email addresses on screen clickable in terminals that support
+/// OSC 8. Pages draw links as underlined text, which is all other terminals get;
+/// after a frame, the links it changed are written again wrapped in the escape
+/// sequence.
+#[derive(Default)]
+pub struct Hyperlinks {
+    enabled: bool,
+    // The links on the client's screen
+    drawn: Vec<Link>,
+}
+
+impl Hyperlinks {
+    pub fn new(enabled: bool) -> Self {
+        Self {
+            enabled,
+            drawn: Vec::new(),
+        }
+    }
+
+    pub fn enable(&mut self) {
+        self.enabled = true;
+    }
+
+    /// The screen was cleared, so every link is written again with the next frame.
+    pub fn forget(&mut self) {
+        self.drawn.clear();
+    }
+
+    /// What to send after the frame `buffer` holds.
+    pub fn frame(&mut self, buffer: &Buffer) -> io::Result<Vec<u8>> {
+        let mut output = Vec::new();
+        if !self.enabled {
+            return Ok(output);
+        }
+
+        let links = find_links(buffer);
+        // Cells that were a link keep opening it until written without one, even
+        // after ratatui skipped them for looking the same
+        for link in self.drawn.iter().filter(|link| !links.contains(link)) {
+            let cells = (link.x..link.x + link.cells.len() as u16)
+                .filter_map(|x| buffer.cell((x, link.y)).cloned())
+                .collect::<Vec<_>>();
+            write_cells(&mut output, link.x, link.y, &cells)?;
+        }
+        for link in links.iter().filter(|link| !self.drawn.contains(link)) {
+            write!(output, "\x1b]8;;{}\x1b\\", link.url)?;
+            write_cells(&mut output, link.x, link.y, &link.cells)?;
+            output.extend_from_slice(b"\x1b]8;;\x1b\\");
+        }
+        if !output.is_empty() {
+            output.extend_from_slice(b"\x1b[0m");
+        }
+        self.drawn = links;
+        Ok(output)
+    }
+}
+
+fn write_cells(output: &mut Vec<u8>, x: u16, y: u16, cells: &[Cell]) -> io::Result<()> {
+    queue!(output, MoveTo(x, y))?;
+    for cell in cells {
+        output.extend_from_slice(b"\x1b[0");
+        for (modifier, code) in SGR_MODIFIERS {
+            if cell.modifier.contains(modifier) {
+                write!(output, ";{code}")?;
+            }
+        }
+        output.push(b'm');
+        queue!(
+            output,
+            SetForegroundColor(CColor::from(cell.fg)),
+            SetBackgroundColor(CColor::from(cell.bg))
+        )?;
+        output.extend_from_slice(cell.symbol().as_bytes());
+    }
+    Ok(())
+}
+
+fn find_links(buffer: &Buffer) -> Vec<Link> {
+    let area = buffer.area;
+    let mut links = Vec::new();
+    for y in area.top()..area.bottom() {
+        // One character per cell; anything wider or combined can't be in a URL
+        let row = (area.left()..area.right())
+            .map(|x| {
+                let symbol = buffer[(x, y)].symbol();
+                match symbol.len() {
+                    1 => symbol.chars().next().unwrap_or(' '),
+                    _ => ' ',
+                }
+            })
+            .collect::<String>();
+
+        let mut start = 0;
+        for token in row.split(|c: char| !is_url_char(c)) {
+            let trimmed = token.trim_end_matches(['.', ',', ';', ':', '!', '?', '\'']);
+            if let Some(url) = url_for(trimmed) {
+                let x = area.left() + start as u16;
+                links.push(Link {
+                    x,
+                    y,
+                    url,
+                    cells: (x..x + trimmed.len() as u16)
+                        .map(|x| buffer[(x, y)].clone())
+                        .collect(),
+                });
+            }
+            start += token.len() + 1;
+        }
+    }
+    links
+}
+
+fn is_url_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || "-._~:/?#[]@!$&'*+,;=%".contains(c)
+}
+
+/// The URL `text` points to, if it looks like a web address or an email address.
+pub fn url_for(text: &str) -> Option<String> {
+    if let Some(rest) = text
+        .strip_prefix("https://")
+        .or_else(|| text.strip_prefix("http://"))
+    {
+        return is_host(rest.split('/').next()?).then(|| text.to_string());
+    }
+    if let Some((user, domain)) = text.split_once('@') {
+        let valid = !user.is_empty() && !user.contains('/') && is_host(domain);
+        return valid.then(|| format!("mailto:{text}"));
+    }
+
+    let (host, path) = match text.split_once('/') {
+        Some((host, path)) => (host, Some(path)),
+        None => (text, None),
+    };
+    let tld = host.rsplit('.').next()?;
+    let linked = is_host(host) && (path.is_some() || BARE_DOMAINS.contains(&tld));
+    linked.then(|| format!("https://{text}"))
+}
+
+/// A dotted name ending in a top-level domain of letters.
+fn is_host(host: &str) -> bool {
+    let labels = host.split('.').collect::<Vec<_>>();
+    let tld = labels[labels.len() - 1];
+    labels.len() > 1
+        && labels.iter().all(|label| {
+            !label.is_empty() && label.chars().all(|c| c.is_ascii_alphanumeric() || c == '-')
+        })
+        && tld.len() >= 2
+        && tld.chars().all(|c| c.is_ascii_alphabetic())
+}
@@ -0,0 +1,110 @@
+use ratatui::{
+    Frame,
+    layout::{Alignment, Constraint, Flex, Layout},
+    style::Style,
+    text::Line,
+    widgets::Paragraph,
+};
+
+use crate::pages::style::{GRAY, WHITE, gray_span};
+
+const LOGO: [&str; 4] = [
+    r" _    _                   ",
+    r"| |__(_)___ _ _ __ _ _ _  ",
+    r"| / /| / -_) '_/ _` | ' \ ",
+    r"|_\_\|_\___|_| \__,_|_||_|",
+];
+const TAGLINE: &str = "welcome to my terminal portfolio";
+// Logo columns revealed per tick
+const WIPE_SPEED: usize = 2;
+// Ticks the finished intro stays up before the home page
+const HOLD_TICKS: u64 = 30;
+
+#[derive(Clone, Copy, PartialEq)]
+enum Stage {
+    // The logo wiped in from the left
+    Logo,
+    // The tagline typed out a character at a time
+    Typing,
+    Holding,
+    Done,
+}
+
+/// The logo and tagline shown when a session starts, before the home page. Each
+/// stage runs for a number of ticks from when it started.
+pub struct Intro {
+    stage: Stage,
+    // The tick the current stage started on, set by the first tick it sees
+    started: Option<u64>,
+    progress: usize,
+}
+
+impl Intro {
+    pub fn new() -> Self {
+        Self {
+            stage: Stage::Logo,
+            started: None,
+            progress: 0,
+        }
+    }
+
+    /// Returns whether the intro is still playing.
+    pub fn on_tick(&mut self, tick: u64) -> bool {
+        let elapsed = tick.wrapping_sub(*self.started.get_or_insert(tick));
+        let (length, next) = match self.stage {
+            Stage::Logo => (LOGO[0].len() / WIPE_SPEED, Stage::Typing),
+            Stage::Typing => (TAGLINE.len(), Stage::Holding),
+            Stage::Holding => (HOLD_TICKS as usize, Stage::Done),
+            Stage::Done => return false,
+        };
+        self.progress = elapsed as usize;
+        if self.progress >= length {
+            self.stage = next;
+            self.started = Some(tick);
+            self.progress = 0;
+        }
+        self.stage != Stage::Done
+    }
+
+    pub fn render(&self, frame: &mut Frame) {
+        let logo_columns = match self.stage {
+            Stage::Logo => (self.progress * WIPE_SPEED).min(LOGO[0].len()),
+            _ => LOGO[0].len(),
+        };
+        let typed = match self.stage {
+            Stage::Logo => 0,
+            Stage::Typing => self.progress,
+            _ => TAGLINE.len(),
+        };
+
+        let mut lines = LOGO
+            .iter()
+            .map(|row| {
+                // Padded back to full width, so the centered logo doesn't shift
+                // while it's revealed
+                let shown = format!("{:<width$}", &row[..logo_columns], width = row.len());
+                Line::styled(shown, Style::new().fg(WHITE))
+            })
+            .collect::<Vec<_>>();
+        lines.push(Line::from(""));
+        let cursor = match self.stage {
+            Stage::Typing => "█",
+            _ => " ",
+        };
+        lines.push(Line::styled(
+            format!(
+                "{:<width$}",
+                format!("{}{}", &TAGLINE[..typed], cursor),
+                width = TAGLINE.len() + 1
+            ),
+            Style::new().fg(GRAY),
+        ));
+        lines.push(Line::from(""));
+        lines.push(Line::from(gray_span("press any key to skip")));
+
+        let [area] = Layout::vertical([Constraint::Length(lines.len() as u16)])
+            .flex(Flex::Center)
+            .areas(frame.area());
+        frame.render_widget(Paragraph::new(lines).alignment(Alignment::Center), area);
+    }
+}
@@ -0,0 +1,519 @@
+use std::fmt;
+use std::str::FromStr;
+use std::sync::Arc;
+
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use serde::Deserialize;
+
+use crate::error::ConfigError;
+
+/// The key bindings a visitor picked on the settings page.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Keys {
+    /// Whatever the config binds, by default the arrows, Tab and Page Up/Down
+    /// with hjkl as aliases.
+    Standard,
+    /// Standard plus gg/G, Ctrl+D/U/F/B and `/` to search.
+    Vim,
+}
+
+pub const KEYS: [Keys; 2] = [Keys::Standard, Keys::Vim];
+
+impl Keys {
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Standard => "standard",
+            Self::Vim => "vim",
+        }
+    }
+
+    pub fn by_name(name: &str) -> Option<Self> {
+        KEYS.into_iter().find(|keys| keys.name() == name)
+    }
+}
+
+/// What the app can be asked to do from the keyboard, outside of what pages
+/// handle themselves.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Action {
+    Quit,
+    Back,
+    NextPage,
+    PreviousPage,
+    FocusMenu,
+    FocusContent,
+    /// Moves up the menu while it has focus, and scrolls the page otherwise.
+    ScrollUp,
+    ScrollDown,
+    PageUp,
+    PageDown,
+    Top,
+    Bottom,
+    /// Opens the search prompt.
+    Search,
+    /// Finds the last search again further down.
+    SearchNext,
+}
+
+impl Action {
+    pub fn description(self) -> &'static str {
+        match self {
+            Self::Quit => "quit, or close an opened page",
+            Self::Back => "close an opened page",
+            Self::NextPage => "next page",
+            Self::PreviousPage => "previous page",
+            Self::FocusMenu => "focus the menu",
+            Self::FocusContent => "focus the page",
+            Self::ScrollUp => "up",
+            Self::ScrollDown => "down",
+            Self::PageUp => "scroll up a page",
+            Self::PageDown => "scroll down a page",
+            Self::Top => "scroll to the top",
+            Self::Bottom => "scroll to the bottom",
+            Self::Search => "search the page",
+            Self::SearchNext => "next match",
+        }
+    }
+}
+
+/// What the vim keys add on top of the configured bindings, as shown on the help
+/// page.
+pub const VIM_BINDINGS: [(&str, Action); 6] = [
+    ("gg", Action::Top),
+    ("G", Action::Bottom),
+    ("ctrl+u/b", Action::PageUp),
+    ("ctrl+d/f", Action::PageDown),
+    ("/", Action::Search),
+    ("n", Action::SearchNext),
+];
+
+/// Keys the app always answers to, whatever the config binds.
+pub const FIXED_BINDINGS: [(&str, &str); 4] = [
+    ("enter", "focus the page"),
+    (": ctrl+k", "go to or do anything"),
+    ("?", "keys for this page"),
+    ("ctrl+c", "quit"),
+];
+
+/// One key with the modifiers it needs, written like `q`, `ctrl+d`, `shift+tab`
+/// or `pagedown` in the config.
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(try_from = "String")]
+pub struct KeyBinding {
+    code: KeyCode,
+    ctrl: bool,
+    alt: bool,
+}
+
+impl KeyBinding {
+    const fn key(code: KeyCode) -> Self {
+        Self {
+            code,
+            ctrl: false,
+            alt: false,
+        }
+    }
+
+    /// Shift is only told apart through the character it produces, `G` vs `g`.
+    fn matches(&self, key_event: &KeyEvent) -> bool {
+        self.code == key_event.code
+            && self.ctrl == key_event.modifiers.contains(KeyModifiers::CONTROL)
+            && self.alt == key_event.modifiers.contains(KeyModifiers::ALT)
+    }
+}
+
+impl FromStr for KeyBinding {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let mut binding = Self::key(KeyCode::Null);
+        let mut shift = false;
+        let mut rest = value;
+        // A lone `+` is the key itself, not a separator
+        while let Some((modifier, key)) = rest.split_once('+').filter(|(_, key)| !key.is_empty()) {
+            match modifier.to_lowercase().as_str() {
+                "ctrl" => binding.ctrl = true,
+                "alt" => binding.alt = true,
+                "shift" => shift = true,
+                _ => return Err(format!("unknown modifier '{}' in '{}'", modifier, value)),
+            }
+            rest = key;
+        }
+
+        let mut chars = rest.chars();
+        binding.code = match (chars.next(), chars.next()) {
+            (Some(c), None) => KeyCode::Char(c),
+            _ => match rest.to_lowercase().as_str() {
+                "tab" if shift => KeyCode::BackTab,
+                "tab" => KeyCode::Tab,
+                "backtab" => KeyCode::BackTab,
+                "enter" => KeyCode::Enter,
+                "esc" => KeyCode::Esc,
+                "space" => KeyCode::Char(' '),
+                "backspace" => KeyCode::Backspace,
+                "up" => KeyCode::Up,
+                "down" => KeyCode::Down,
+                "left" => KeyCode::Left,
+                "right" => KeyCode::Right,
+                "pageup" => KeyCode::PageUp,
+                "pagedown" => KeyCode::PageDown,
+                "home" => KeyCode::Home,
+                "end" => KeyCode::End,
+                _ => return Err(format!("unknown key '{}'", value)),
+            },
+        };
+        if shift && let KeyCode::Char(c) = binding.code {
+            binding.code = KeyCode::Char(c.to_ascii_uppercase());
+        }
+        // Ctrl+C always quits, wherever a visitor is
+        if binding.ctrl && binding.code == KeyCode::Char('c') {
+            return Err(String::from("ctrl+c is reserved for quitting"));
+        }
+        Ok(binding)
+    }
+}
+
+impl TryFrom<String> for KeyBinding {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        value.parse()
+    }
+}
+
+impl fmt::Display for KeyBinding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.ctrl {
+            write!(f, "ctrl+")?;
+        }
+        if self.alt {
+            write!(f, "alt+")?;
+        }
+        match self.code {
+            KeyCode::Char(' ') => write!(f, "space"),
+            KeyCode::Char(c) => write!(f, "{}", c),
+            KeyCode::BackTab => write!(f, "shift+tab"),
+            KeyCode::Up => write!(f, "↑"),
+            KeyCode::Down => write!(f, "↓"),
+            KeyCode::Left => write!(f, "←"),
+            KeyCode::Right => write!(f, "→"),
+            KeyCode::Tab => write!(f, "tab"),
+            KeyCode::Enter => write!(f, "enter"),
+            KeyCode::Esc => write!(f, "esc"),
+            KeyCode::Backspace => write!(f, "backspace"),
+            KeyCode::PageUp => write!(f, "pageup"),
+            KeyCode::PageDown => write!(f, "pagedown"),
+            KeyCode::Home => write!(f, "home"),
+            KeyCode::End => write!(f, "end"),
+            code => write!(f, "{:?}", code),
+        }
+    }
+}
+
+/// The `[keys]` table of the config: the keys for each action, replacing the
+/// defaults for any action that is listed. An empty list unbinds it.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct KeyBindings {
+    quit: Vec<KeyBinding>,
+    back: Vec<KeyBinding>,
+    next_page: Vec<KeyBinding>,
+    previous_page: Vec<KeyBinding>,
+    focus_menu: Vec<KeyBinding>,
+    focus_content: Vec<KeyBinding>,
+    scroll_up: Vec<KeyBinding>,
+    scroll_down: Vec<KeyBinding>,
+    page_up: Vec<KeyBinding>,
+    page_down: Vec<KeyBinding>,
+    top: Vec<KeyBinding>,
+    bottom: Vec<KeyBinding>,
+}
+
+impl Default for KeyBindings {
+    fn default() -> Self {
+        let keys = |codes: &[KeyCode]| codes.iter().copied().map(KeyBinding::key).collect();
+        Self {
+            quit: keys(&[KeyCode::Char('q')]),
+            back: keys(&[KeyCode::Esc]),
+            next_page: keys(&[KeyCode::Tab]),
+            previous_page: keys(&[KeyCode::BackTab]),
+            focus_menu: keys(&[KeyCode::Left, KeyCode::Char('h')]),
+            focus_content: keys(&[KeyCode::Right, KeyCode::Char('l')]),
+            scroll_up: keys(&[KeyCode::Up, KeyCode::Char('k')]),
+            scroll_down: keys(&[KeyCode::Down, KeyCode::Char('j')]),
+            page_up: keys(&[KeyCode::PageUp]),
+            page_down: keys(&[KeyCode::PageDown]),
+            top: keys(&[KeyCode::Home]),
+            bottom: keys(&[KeyCode::End]),
+        }
+    }
+}
+
+impl KeyBindings {
+    /// Every action with its keys, in the order the help page lists them.
+    pub fn actions(&self) -> [(Action, &[KeyBinding]); 12] {
+        [
+            (Action::ScrollUp, &self.scroll_up),
+            (Action::ScrollDown, &self.scroll_down),
+            (Action::FocusMenu, &self.focus_menu),
+            (Action::FocusContent, &self.focus_content),
+            (Action::NextPage, &self.next_page),
+            (Action::PreviousPage, &self.previous_page),
+            (Action::PageUp, &self.page_up),
+            (Action::PageDown, &self.page_down),
+            (Action::Top, &self.top),
+            (Action::Bottom, &self.bottom),
+            (Action::Back, &self.back),
+            (Action::Quit, &self.quit),
+        ]
+    }
+
+    /// Each bound action with all of its keys, then the fixed ones.
+    pub fn describe(&self) -> Vec<(String, &'static str)> {
+        let mut keys = self
+            .actions()
+            .into_iter()
+            .filter(|(_, bindings)| !bindings.is_empty())
+            .map(|(action, bindings)| {
+                let keys = bindings
+                    .iter()
+                    .map(ToString::to_string)
+                    .collect::<Vec<_>>()
+                    .join(" ");
+                (keys, action.description())
+            })
+            .collect::<Vec<_>>();
+        keys.extend(
+            FIXED_BINDINGS
+                .iter()
+                .map(|(keys, description)| (keys.to_string(), *description)),
+        );
+        keys
+    }
+
+    /// The first key bound to `action`, for hints like the ones under the menu.
+    pub fn label(&self, action: Action) -> Option<String> {
+        let (_, bindings) = self
+            .actions()
+            .into_iter()
+            .find(|(bound, _)| *bound == action)?;
+        bindings.first().map(ToString::to_string)
+    }
+
+    fn action(&self, key_event: &KeyEvent) -> Option<Action> {
+        self.actions()
+            .into_iter()
+            .find(|(_, bindings)| bindings.iter().any(|binding| binding.matches(key_event)))
+            .map(|(action, _)| action)
+    }
+
+    /// Refuses a key bound to two actions, since only one of them could ever run.
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        let actions = self.actions();
+        for (i, (first, bindings)) in actions.iter().enumerate() {
+            for binding in bindings.iter() {
+                let conflict = actions[i + 1..]
+                    .iter()
+                    .find(|(_, others)| others.contains(binding));
+                if let Some((second, _)) = conflict {
+                    return Err(ConfigError::KeyConflict {
+                        key: binding.to_string(),
+                        first: action_name(*first),
+                        second: action_name(*second),
+                    });
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// As written in the config.
+fn action_name(action: Action) -> &'static str {
+    match action {
+        Action::Quit => "quit",
+        Action::Back => "back",
+        Action::NextPage => "next_page",
+        Action::PreviousPage => "previous_page",
+        Action::FocusMenu => "focus_menu",
+        Action::FocusContent => "focus_content",
+        Action::ScrollUp => "scroll_up",
+        Action::ScrollDown => "scroll_down",
+        Action::PageUp => "page_up",
+        Action::PageDown => "page_down",
+        Action::Top => "top",
+        Action::Bottom => "bottom",
+        Action::Search => "search",
+        Action::SearchNext => "search_next",
+    }
+}
+
+/// What a key press means once the bindings are applied.
+pub enum Input {
+    Action(Action),
+    /// Not bound to anything, so it's up to the page.
+    Key(KeyEvent),
+}
+
+/// Sits between the decoded keys and the app, turning them into actions with the
+/// configured bindings and, for visitors who picked them, the vim keys on top.
+/// Only sees keys that aren't typed into a page.
+pub struct Keymap {
+    bindings: Arc<KeyBindings>,
+    // The first `g` of `gg`
+    pending_g: bool,
+}
+
+impl Keymap {
+    pub fn new(bindings: Arc<KeyBindings>) -> Self {
+        Self {
+            bindings,
+            pending_g: false,
+        }
+    }
+
+    pub fn bindings(&self) -> &KeyBindings {
+        &self.bindings
+    }
+
+    /// None while a key waits for the rest of its sequence.
+    pub fn translate(&mut self, keys: Keys, key_event: KeyEvent) -> Option<Input> {
+        let pending_g = std::mem::take(&mut self.pending_g);
+        if keys == Keys::Vim {
+            let ctrl = key_event.modifiers.contains(KeyModifiers::CONTROL);
+            if key_event.code == KeyCode::Char('g') && !pending_g && !ctrl {
+                self.pending_g = true;
+                return None;
+            }
+            if let Some(action) = vim_action(&key_event, pending_g) {
+                return Some(Input::Action(action));
+            }
+        }
+
+        match self.bindings.action(&key_event) {
+            Some(action) => Some(Input::Action(action)),
+            None => Some(Input::Key(key_event)),
+        }
+    }
+
+    /// Whether `key_event` means something with `keys`, rather than going to the
+    /// page. A `g` does, as the start of `gg`.
+    pub fn binds(&self, keys: Keys, key_event: &KeyEvent) -> bool {
+        (keys == Keys::Vim && vim_action(key_event, true).is_some())
+            || self.bindings.action(key_event).is_some()
+    }
+}
+
+/// What `key_event` does in the vim keys alone. `pending_g` is whether it comes
+/// right after a `g`.
+fn vim_action(key_event: &KeyEvent, pending_g: bool) -> Option<Action> {
+    let ctrl = key_event.modifiers.contains(KeyModifiers::CONTROL);
+    match key_event.code {
+        KeyCode::Char('g') if pending_g => Some(Action::Top),
+        KeyCode::Char('G') => Some(Action::Bottom),
+        KeyCode::Char('d' | 'f') if ctrl => Some(Action::PageDown),
+        KeyCode::Char('u' | 'b') if ctrl => Some(Action::PageUp),
+        KeyCode::Char('/') => Some(Action::Search),
+        KeyCode::Char('n') if !ctrl => Some(Action::SearchNext),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bindings(toml: &str) -> Result<KeyBindings, toml::de::Error> {
+        toml::from_str(toml)
+    }
+
+    fn key(code: KeyCode, modifiers: KeyModifiers) -> KeyEvent {
+        KeyEvent::new(code, modifiers)
+    }
+
+    #[test]
+    fn the_defaults_dont_conflict() {
+        assert!(KeyBindings::default().validate().is_ok());
+    }
+
+    #[test]
+    fn a_key_bound_twice_is_refused() {
+        let bindings = bindings(r#"back = ["q"]"#).unwrap();
+        match bindings.validate() {
+            Err(ConfigError::KeyConflict { key, first, second }) => {
+                assert_eq!(key, "q");
+                assert_eq!([first, second], ["back", "quit"]);
+            }
+            other => panic!("expected a conflict, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn rebinding_replaces_the_defaults() {
+        let rebound = bindings(r#"quit = ["x"]"#).unwrap();
+        assert!(rebound.validate().is_ok());
+        assert_eq!(rebound.label(Action::Quit).as_deref(), Some("x"));
+        let unbound = bindings("quit = []").unwrap();
+        assert_eq!(unbound.label(Action::Quit), None);
+    }
+
+    #[test]
+    fn unknown_actions_are_refused() {
+        let error = bindings(r#"jump = ["x"]"#).unwrap_err();
+        assert!(error.to_string().contains("jump"), "{}", error);
+    }
+
+    #[test]
+    fn unknown_keys_and_modifiers_are_refused() {
+        assert_eq!(
+            "nope".parse::<KeyBinding>(),
+            Err(String::from("unknown key 'nope'"))
+        );
+        assert_eq!(
+            "meta+x".parse::<KeyBinding>(),
+            Err(String::from("unknown modifier 'meta' in 'meta+x'"))
+        );
+        assert!(bindings(r#"quit = ["hyper+q"]"#).is_err());
+    }
+
+    #[test]
+    fn modifiers_are_parsed() {
+        let binding = "ctrl+alt+d".parse::<KeyBinding>().unwrap();
+        assert!(binding.matches(&key(
+            KeyCode::Char('d'),
+            KeyModifiers::CONTROL | KeyModifiers::ALT
+        )));
+        assert!(!binding.matches(&key(KeyCode::Char('d'), KeyModifiers::CONTROL)));
+        assert_eq!(binding.to_string(), "ctrl+alt+d");
+
+        assert_eq!("CTRL+d".parse::<KeyBinding>(), "ctrl+d".parse());
+        assert_eq!("shift+g".parse::<KeyBinding>(), "G".parse());
+        assert_eq!(
+            "shift+tab".parse::<KeyBinding>(),
+            Ok(KeyBinding::key(KeyCode::BackTab))
+        );
+        assert_eq!(
+            "+".parse::<KeyBinding>(),
+            Ok(KeyBinding::key(KeyCode::Char('+')))
+        );
+        assert_eq!(
+            "ctrl++".parse::<KeyBinding>().map(|binding| binding.ctrl),
+            Ok(true)
+        );
+    }
+
+    #[test]
+    fn shift_shows_only_in_the_character() {
+        let binding = "G".parse::<KeyBinding>().unwrap();
+        assert!(binding.matches(&key(KeyCode::Char('G'), KeyModifiers::SHIFT)));
+        assert!(!binding.matches(&key(KeyCode::Char('g'), KeyModifiers::NONE)));
+    }
+
+    #[test]
+    fn ctrl_c_cant_be_rebound() {
+        assert_eq!(
+            "ctrl+c".parse::<KeyBinding>(),
+            Err(String::from("ctrl+c is reserved for quitting"))
+        );
+    }
+}
@@ -0,0 +1,48 @@
+//! Serves a ratatui app over SSH, one instance per visitor, and the portfolio
+//! that runs on it. `SshTuiServer::builder()` is the way in; the `portfolio-v2`
+//! binary is a thin command line around it.
+
+pub mod app;
+pub mod bench;
+pub mod charset;
+pub mod chat;
+pub mod color_support;
+pub mod config;
+pub mod contact;
+pub mod content;
+pub mod easter_eggs;
+pub mod error;
+pub mod event_bus;
+pub mod exec;
+pub mod export;
+pub mod geoip;
+pub mod github;
+pub mod graphics;
+pub mod guestbook;
+pub mod high_scores;
+pub mod hyperlinks;
+mod intro;
+pub mod keymap;
+pub mod local_tui;
+pub mod logging;
+pub mod notifier;
+pub mod now_playing;
+pub mod pages;
+pub mod palette;
+pub mod poll;
+pub mod poller;
+pub mod portfolio;
+pub mod preferences;
+pub mod server;
+pub mod services;
+pub mod stats;
+pub mod store;
+pub mod theme;
+pub mod tic_tac_toe;
+pub mod weather;
+
+pub use server::AppServer as SshTuiServer;
+pub use server::{
+    AppFactory, AppServerBuilder, ConnectionHook, ConnectionInfo, FrameRate, FromSession, NewApp,
+    SessionInfo, TuiApp, UserRouter,
+};
@@ -1,31 +1,60 @@
-use crossterm::event::{self, Event};
+use crossterm::event::{
+    self, DisableBracketedPaste, DisableMouseCapture, EnableBracketedPaste, EnableMouseCapture,
+    Event, KeyEventKind,
+};
 
-use crate::app::App;
+use std::sync::{Arc, RwLock};
 
-pub struct LocalTuiRunner;
+use crate::app::{App, KeyOutcome};
+use crate::color_support::ColorSupport;
+use crate::config::Config;
+use crate::preferences::SessionPreferences;
+use crate::services::Services;
+
+pub struct LocalTuiRunner {
+    services: Services,
+}
 
 impl LocalTuiRunner {
-    pub fn new() -> Self {
-        Self
+    pub fn new(config: Config) -> Result<Self, anyhow::Error> {
+        let services = Services::new(Arc::new(RwLock::new(config)), false)?;
+        Ok(Self { services })
     }
 
     pub async fn run(&self) -> Result<(), anyhow::Error> {
         let mut terminal = ratatui::init();
+        crossterm::execute!(std::io::stdout(), EnableMouseCapture, EnableBracketedPaste)?;
 
-        let mut app = App::new();
+        let _content_watcher = self.services.start();
+        let mut app = App::new(
+            &self.services,
+            SessionPreferences::unsaved(self.services.config.read().unwrap().theme()),
+            String::from("local"),
+        );
         let mut tick: u64 = 0;
+        let color_support = ColorSupport::detect(
+            &std::env::var("TERM").unwrap_or_default(),
+            std::env::var("COLORTERM").ok().as_deref(),
+        );
 
         loop {
             terminal.draw(|f| {
                 app.draw(f);
+                color_support.degrade(f.buffer_mut());
             })?;
 
             if event::poll(tokio::time::Duration::from_millis(1000 / 30))? {
-                if let Event::Key(key) = event::read()? {
-                    match app.handle_key_event(key.code) {
-                        Ok(_) => {}
-                        Err(_) => break,
+                match event::read()? {
+                    Event::Key(key) if key.kind == KeyEventKind::Press => {
+                        match app.handle_key_event(key) {
+                            KeyOutcome::Continue => {}
+                            KeyOutcome::Quit => break,
+                        }
                     }
+                    Event::Mouse(mouse) => app.handle_mouse_event(mouse),
+                    Event::Paste(text) => app.handle_paste(&text),
+                    Event::Resize(width, height) => app.handle_resize(width, height),
+                    _ => {}
                 }
             }
 
@@ -33,6 +62,11 @@ impl LocalTuiRunner {
             tick = tick.wrapping_add(1);
         }
 
+        crossterm::execute!(
+            std::io::stdout(),
+            DisableBracketedPaste,
+            DisableMouseCapture
+        )?;
         ratatui::restore();
         Ok(())
     }
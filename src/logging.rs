@@ -0,0 +1,39 @@
+use std::any::Any;
+
+use tracing::error;
+use tracing_subscriber::EnvFilter;
+
+use crate::config::LogFormat;
+
+/// Sends `tracing` events to stdout, panics included, so a session's panic is
+/// reported inside its span. The local TUI never calls this, since anything
+/// printed would end up on top of the interface.
+pub fn init(level: &str, format: LogFormat) -> Result<(), anyhow::Error> {
+    std::panic::set_hook(Box::new(|info| {
+        let location = info
+            .location()
+            .map(|location| location.to_string())
+            .unwrap_or_default();
+        error!(%location, "Panicked: {}", panic_message(info.payload()));
+    }));
+
+    let filter = EnvFilter::try_new(level)
+        .map_err(|e| anyhow::anyhow!("Invalid log_level '{}': {}", level, e))?;
+    let subscriber = tracing_subscriber::fmt().with_env_filter(filter);
+    match format {
+        LogFormat::Text => subscriber.try_init(),
+        LogFormat::Json => subscriber.json().try_init(),
+    }
+    .map_err(|e| anyhow::anyhow!("Failed to set up logging: {}", e))
+}
+
+/// What was passed to `panic!`, when it's text.
+pub fn panic_message(payload: &(dyn Any + Send)) -> &str {
+    match payload.downcast_ref::<&str>() {
+        Some(message) => message,
+        None => payload
+            .downcast_ref::<String>()
+            .map(String::as_str)
+            .unwrap_or("unknown cause"),
+    }
+}
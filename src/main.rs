@@ -1,12 +1,130 @@
-use clap::{Arg, Command};
+use std::net::SocketAddr;
+use std::path::PathBuf;
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
 
-mod app;
-mod local_tui;
-mod pages;
-mod server;
+use clap::{Arg, ArgMatches, Command};
 
-use local_tui::LocalTuiRunner;
-use server::AppServer;
+use portfolio_v2::bench::{self, BenchOptions};
+use portfolio_v2::config::{Config, LogFormat};
+use portfolio_v2::export;
+use portfolio_v2::local_tui::LocalTuiRunner;
+use portfolio_v2::portfolio::Portfolio;
+use portfolio_v2::services::Services;
+use portfolio_v2::stats::Stats;
+use portfolio_v2::store::Store;
+use portfolio_v2::{SshTuiServer, logging, server};
+
+fn config_arg() -> Arg {
+    Arg::new("config")
+        .short('c')
+        .long("config")
+        .env("CONFIG_LOCATION")
+        .value_name("PATH")
+        .help("TOML file with server settings, see config.example.toml")
+        .value_parser(clap::value_parser!(PathBuf))
+}
+
+fn server_args() -> [Arg; 3] {
+    [
+        Arg::new("listen")
+            .short('l')
+            .long("listen")
+            .value_name("ADDR:PORT")
+            .help("Address and port for the SSH server to bind to [default: 0.0.0.0:22]")
+            .value_parser(clap::value_parser!(SocketAddr)),
+        config_arg(),
+        Arg::new("auto-generate-hostkey")
+            .long("auto-generate-hostkey")
+            .help("Create an ed25519 host key if the configured one does not exist")
+            .action(clap::ArgAction::SetTrue),
+    ]
+}
+
+fn print_stats(matches: &ArgMatches) -> Result<(), anyhow::Error> {
+    let config = Config::load(matches.get_one::<PathBuf>("config").map(PathBuf::as_path))?;
+    let store = Arc::new(Store::open(&config.database)?);
+    let summary = Stats::new(store, false)?.summary()?;
+
+    println!("visits           {}", summary.visits);
+    println!("unique visitors  {}", summary.unique_visitors);
+    println!();
+    println!("page views");
+    for (page, views) in summary.page_views {
+        println!("  {:<15}{}", page, views);
+    }
+    for (heading, places) in [("countries", summary.countries), ("cities", summary.cities)] {
+        if places.is_empty() {
+            continue;
+        }
+        println!();
+        println!("{}", heading);
+        for (place, visits) in places {
+            println!("  {:<30}{}", place, visits);
+        }
+    }
+    Ok(())
+}
+
+async fn send_wall(matches: &ArgMatches) -> Result<(), anyhow::Error> {
+    let config = Config::load(matches.get_one::<PathBuf>("config").map(PathBuf::as_path))?;
+    let socket = config.control_socket.ok_or_else(|| {
+        anyhow::anyhow!("No control socket configured, set control_socket or CONTROL_SOCKET")
+    })?;
+    let message = matches
+        .get_one::<String>("message")
+        .expect("message is required");
+    let reply = server::control::send(&socket, &format!("wall {}", message)).await?;
+    if let Some(error) = reply.strip_prefix("error: ") {
+        return Err(anyhow::anyhow!("{}", error));
+    }
+    println!("{}", reply);
+    Ok(())
+}
+
+fn export_html(matches: &ArgMatches) -> Result<(), anyhow::Error> {
+    let config = Config::load(matches.get_one::<PathBuf>("config").map(PathBuf::as_path))?;
+    let dir = matches.get_one::<PathBuf>("dir").expect("dir is required");
+    let services = Services::new(Arc::new(RwLock::new(config)), false)?;
+    for path in export::export_html(&services, dir)? {
+        println!("{}", path.display());
+    }
+    Ok(())
+}
+
+fn load_config(matches: &ArgMatches) -> Result<Config, anyhow::Error> {
+    let mut config = Config::load(matches.get_one::<PathBuf>("config").map(PathBuf::as_path))?;
+    if let Some(listen) = matches.get_one::<SocketAddr>("listen") {
+        config.listen = *listen;
+    }
+    if matches.get_flag("auto-generate-hostkey") {
+        config.auto_generate_host_key = true;
+    }
+    Ok(config)
+}
+
+fn bench_options(matches: &ArgMatches) -> BenchOptions {
+    BenchOptions {
+        target: matches
+            .get_one::<String>("target")
+            .expect("target has a default value")
+            .clone(),
+        clients: *matches
+            .get_one::<usize>("clients")
+            .expect("clients has a default value"),
+        duration: Duration::from_secs(
+            *matches
+                .get_one::<u64>("duration")
+                .expect("duration has a default value"),
+        ),
+        input_interval: Duration::from_millis(
+            *matches
+                .get_one::<u64>("input-interval")
+                .expect("input-interval has a default value"),
+        ),
+        server_pid: matches.get_one::<u32>("pid").copied(),
+    }
+}
 
 #[tokio::main]
 async fn main() -> Result<(), anyhow::Error> {
@@ -17,18 +135,148 @@ async fn main() -> Result<(), anyhow::Error> {
             Arg::new("server")
                 .short('s')
                 .long("server")
-                .help("Run in server mode (SSH server on port 22)")
+                .help("Run in server mode (same as the serve subcommand)")
                 .action(clap::ArgAction::SetTrue),
         )
+        .args(server_args())
+        .subcommand(
+            Command::new("serve")
+                .about("Run the SSH server")
+                .args(server_args()),
+        )
+        .subcommand(
+            Command::new("stats")
+                .about("Print visit and page view counts from the database")
+                .arg(config_arg()),
+        )
+        .subcommand(
+            Command::new("wall")
+                .about("Show a message to every connected visitor")
+                .arg(
+                    Arg::new("message")
+                        .required(true)
+                        .help("The message, e.g. \"restarting in 5 minutes\""),
+                )
+                .arg(config_arg()),
+        )
+        .subcommand(
+            Command::new("export-html")
+                .about("Write the pages as a static website, colors included")
+                .arg(
+                    Arg::new("dir")
+                        .required(true)
+                        .help("Where to write the HTML files")
+                        .value_parser(clap::value_parser!(PathBuf)),
+                )
+                .arg(config_arg()),
+        )
+        .subcommand(
+            Command::new("bench")
+                .about("Load test a server with many concurrent visitors")
+                .long_about(
+                    "Load test a server with many concurrent visitors. Every client \
+                     connects like a visitor without a key, so the server needs \
+                     auth_mode none, and a rate limit and max_clients that let \
+                     this many in from one address",
+                )
+                .arg(
+                    Arg::new("target")
+                        .value_name("HOST:PORT")
+                        .default_value("127.0.0.1:22"),
+                )
+                .arg(
+                    Arg::new("clients")
+                        .short('n')
+                        .long("clients")
+                        .default_value("100")
+                        .value_parser(clap::value_parser!(usize)),
+                )
+                .arg(
+                    Arg::new("duration")
+                        .short('d')
+                        .long("duration")
+                        .value_name("SECS")
+                        .default_value("30")
+                        .value_parser(clap::value_parser!(u64)),
+                )
+                .arg(
+                    Arg::new("input-interval")
+                        .long("input-interval")
+                        .value_name("MILLIS")
+                        .help("Average time between keystrokes, per client")
+                        .default_value("500")
+                        .value_parser(clap::value_parser!(u64).range(1..)),
+                )
+                .arg(
+                    Arg::new("pid")
+                        .long("pid")
+                        .help("The server's pid, to report its memory use")
+                        .value_parser(clap::value_parser!(u32)),
+                ),
+        )
+        .subcommand(
+            Command::new("keygen")
+                .about("Generate a host key")
+                .arg(
+                    Arg::new("path")
+                        .env("SECRETS_LOCATION")
+                        .required(true)
+                        .help("Where to write the private key, or a host key directory")
+                        .value_parser(clap::value_parser!(PathBuf)),
+                )
+                .arg(
+                    Arg::new("algorithm")
+                        .short('t')
+                        .long("algorithm")
+                        .value_parser(["ed25519", "rsa"])
+                        .default_value("ed25519"),
+                ),
+        )
         .get_matches();
 
-    let server_mode = matches.get_flag("server");
+    if let Some(("keygen", keygen_matches)) = matches.subcommand() {
+        let path = keygen_matches
+            .get_one::<PathBuf>("path")
+            .expect("path is required");
+        let algorithm = keygen_matches
+            .get_one::<String>("algorithm")
+            .expect("algorithm has a default value");
+        logging::init("info", LogFormat::Text)?;
+        server::host_key::generate(path, server::host_key::parse_algorithm(algorithm)?)?;
+        return Ok(());
+    }
+
+    if let Some(("stats", stats_matches)) = matches.subcommand() {
+        return print_stats(stats_matches);
+    }
+    if let Some(("wall", wall_matches)) = matches.subcommand() {
+        return send_wall(wall_matches).await;
+    }
+    if let Some(("export-html", export_matches)) = matches.subcommand() {
+        return export_html(export_matches);
+    }
+    if let Some(("bench", bench_matches)) = matches.subcommand() {
+        return bench::run(bench_options(bench_matches)).await;
+    }
+
+    let server_matches = match matches.subcommand() {
+        Some(("serve", serve_matches)) => Some(serve_matches),
+        _ if matches.get_flag("server") => Some(&matches),
+        _ => None,
+    };
 
-    if server_mode {
-        let mut server = AppServer::new();
+    if let Some(server_matches) = server_matches {
+        let config = load_config(server_matches)?;
+        logging::init(&config.log_level, config.log_format)?;
+        let config = Arc::new(RwLock::new(config));
+        let portfolio = Portfolio::start(config.clone())?;
+        let mut server = SshTuiServer::builder()
+            .shared_config(config)
+            .app_factory(portfolio)
+            .build()?;
         server.run().await
     } else {
-        let local_tui = LocalTuiRunner::new();
+        let local_tui = LocalTuiRunner::new(load_config(&matches)?)?;
         local_tui.run().await
     }
 }
@@ -0,0 +1,129 @@
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use tracing::warn;
+
+use crate::config::{Config, SharedConfig};
+use crate::contact::post_webhook;
+use crate::geoip::Location;
+use crate::pages::admin::format_duration;
+
+const DELIVERY_TIMEOUT: Duration = Duration::from_secs(15);
+
+/// Posts to `notify_webhook` when visitors arrive and leave, so the owner hears
+/// about them as they happen. At most one goes out per `notify_interval_secs`.
+pub struct Notifier {
+    config: SharedConfig,
+    agent: ureq::Agent,
+    sent: Mutex<Sent>,
+}
+
+#[derive(Default)]
+struct Sent {
+    last: Option<Instant>,
+    // Held back since `last`
+    skipped: usize,
+}
+
+/// A visitor's session as the notifier sees it. Dropping it along with the
+/// session sends the notification about them leaving.
+pub struct Visit {
+    notifier: Arc<Notifier>,
+    via: &'static str,
+    user: String,
+    country: Option<String>,
+    connected_at: Instant,
+    pages: Mutex<Vec<String>>,
+}
+
+impl Notifier {
+    pub fn new(config: SharedConfig) -> Arc<Self> {
+        let agent = ureq::Agent::config_builder()
+            .timeout_global(Some(DELIVERY_TIMEOUT))
+            .build()
+            .into();
+        Arc::new(Self {
+            config,
+            agent,
+            sent: Mutex::new(Sent::default()),
+        })
+    }
+
+    /// Announces a visitor who came in `via` a listener like `ssh`.
+    pub fn visit(self: &Arc<Self>, via: &'static str, user: &str, location: &Location) -> Visit {
+        let visit = Visit {
+            notifier: self.clone(),
+            via,
+            user: user.to_string(),
+            country: location.country.clone(),
+            connected_at: Instant::now(),
+            pages: Mutex::new(Vec::new()),
+        };
+        self.notify(&visit, |config| &config.notify_connect_template);
+        visit
+    }
+
+    fn notify(&self, visit: &Visit, template: fn(&Config) -> &String) {
+        let (url, text, interval) = {
+            let config = self.config.read().unwrap();
+            let Some(url) = config.notify_webhook.clone() else {
+                return;
+            };
+            let template = template(&config);
+            if template.is_empty() {
+                return;
+            }
+            (url, visit.fill(template), config.notify_interval())
+        };
+
+        let text = {
+            let mut sent = self.sent.lock().unwrap();
+            if sent.last.is_some_and(|last| last.elapsed() < interval) {
+                sent.skipped += 1;
+                return;
+            }
+            sent.last = Some(Instant::now());
+            match std::mem::take(&mut sent.skipped) {
+                0 => text,
+                skipped => format!("{} (+{} more since the last one)", text, skipped),
+            }
+        };
+        let agent = self.agent.clone();
+        tokio::task::spawn_blocking(move || {
+            if let Err(e) = post_webhook(&agent, &url, &text) {
+                warn!("Failed to post a visit notification: {}", e);
+            }
+        });
+    }
+}
+
+impl Visit {
+    /// Counts a page for `{pages}`, once however often it's seen.
+    pub fn viewed(&self, page: &str) {
+        let mut pages = self.pages.lock().unwrap();
+        if !pages.iter().any(|seen| seen == page) {
+            pages.push(page.to_string());
+        }
+    }
+
+    fn fill(&self, template: &str) -> String {
+        let pages = self.pages.lock().unwrap();
+        let pages = match pages.is_empty() {
+            true => String::from("nothing"),
+            false => pages.join(", "),
+        };
+        template
+            .replace("{via}", self.via)
+            .replace("{user}", &self.user)
+            .replace("{country}", self.country.as_deref().unwrap_or("somewhere"))
+            .replace("{duration}", &format_duration(self.connected_at.elapsed()))
+            .replace("{pages}", &pages)
+    }
+}
+
+impl Drop for Visit {
+    fn drop(&mut self) {
+        self.notifier
+            .notify(self, |config| &config.notify_disconnect_template);
+    }
+}
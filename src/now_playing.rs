@@ -0,0 +1,254 @@
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD;
+use serde::Deserialize;
+
+use crate::config::{Config, SharedConfig};
+use crate::poller::{self, Poller};
+
+const LASTFM_API: &str = "https://ws.audioscrobbler.com/2.0/";
+const SPOTIFY_TOKEN: &str = "https://accounts.spotify.com/api/token";
+const SPOTIFY_CURRENTLY_PLAYING: &str = "https://api.spotify.com/v1/me/player/currently-playing";
+// How long a track is kept through failed fetches
+const STALE_AFTER: Duration = Duration::from_secs(300);
+// Spotify's tokens are renewed this long before they run out
+const TOKEN_MARGIN: Duration = Duration::from_secs(60);
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Track {
+    pub title: String,
+    pub artist: String,
+}
+
+enum Source {
+    LastFm {
+        user: String,
+        api_key: String,
+    },
+    Spotify {
+        client_id: String,
+        client_secret: String,
+        refresh_token: String,
+    },
+}
+
+impl Source {
+    /// Spotify when it is set up, since it knows about pauses, else Last.fm.
+    fn from_config(config: &Config) -> Option<Self> {
+        if let (Some(client_id), Some(client_secret), Some(refresh_token)) = (
+            &config.spotify_client_id,
+            &config.spotify_client_secret,
+            &config.spotify_refresh_token,
+        ) {
+            return Some(Self::Spotify {
+                client_id: client_id.clone(),
+                client_secret: client_secret.clone(),
+                refresh_token: refresh_token.clone(),
+            });
+        }
+        Some(Self::LastFm {
+            user: config.lastfm_user.clone()?,
+            api_key: config.lastfm_api_key.clone()?,
+        })
+    }
+}
+
+#[derive(Deserialize)]
+struct RecentTracks {
+    #[serde(rename = "recenttracks")]
+    recent: TrackList,
+}
+
+#[derive(Deserialize)]
+struct TrackList {
+    #[serde(default)]
+    track: Vec<Scrobble>,
+}
+
+#[derive(Deserialize)]
+struct Scrobble {
+    name: String,
+    artist: Text,
+    #[serde(rename = "@attr")]
+    attr: Option<ScrobbleAttr>,
+}
+
+#[derive(Deserialize)]
+struct Text {
+    #[serde(rename = "#text")]
+    text: String,
+}
+
+#[derive(Deserialize)]
+struct ScrobbleAttr {
+    #[serde(rename = "nowplaying")]
+    now_playing: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct Token {
+    access_token: String,
+    expires_in: u64,
+}
+
+#[derive(Deserialize)]
+struct CurrentlyPlaying {
+    is_playing: bool,
+    item: Option<Item>,
+}
+
+#[derive(Deserialize)]
+struct Item {
+    name: String,
+    // Podcast episodes have a show instead
+    #[serde(default)]
+    artists: Vec<Artist>,
+}
+
+#[derive(Deserialize)]
+struct Artist {
+    name: String,
+}
+
+/// The song the owner is listening to, checked in the background and shared by
+/// every session, for the footer.
+pub struct NowPlaying {
+    config: SharedConfig,
+    agent: ureq::Agent,
+    track: Arc<Poller<Option<Track>>>,
+    // Spotify's access token and when it runs out, reused until then
+    token: Mutex<Option<(String, Instant)>>,
+}
+
+impl NowPlaying {
+    pub fn new(config: SharedConfig) -> Arc<Self> {
+        Arc::new(Self {
+            config,
+            agent: poller::agent(),
+            track: Poller::new(Some(STALE_AFTER)),
+            token: Mutex::new(None),
+        })
+    }
+
+    /// None while nothing is playing, or nothing is set up to ask.
+    pub fn track(&self) -> Option<Track> {
+        self.track.get()
+    }
+
+    pub fn generation(&self) -> u64 {
+        self.track.generation()
+    }
+
+    /// Checks now and then every `now_playing_refresh_secs`.
+    pub fn refresh_periodically(self: &Arc<Self>) {
+        let now_playing = self.clone();
+        self.track.start(
+            "the song playing",
+            self.config.clone(),
+            Config::now_playing_refresh,
+            move || {
+                let source = Source::from_config(&now_playing.config.read().unwrap());
+                match &source {
+                    Some(source) => now_playing.fetch(source),
+                    // Set up no longer after a reload
+                    None => Ok(None),
+                }
+            },
+        );
+    }
+
+    fn fetch(&self, source: &Source) -> Result<Option<Track>, anyhow::Error> {
+        match source {
+            Source::LastFm { user, api_key } => self.fetch_lastfm(user, api_key),
+            Source::Spotify {
+                client_id,
+                client_secret,
+                refresh_token,
+            } => {
+                let token = self.spotify_token(client_id, client_secret, refresh_token)?;
+                self.fetch_spotify(&token)
+            }
+        }
+    }
+
+    /// The newest scrobble, if Last.fm says it's still playing.
+    fn fetch_lastfm(&self, user: &str, api_key: &str) -> Result<Option<Track>, anyhow::Error> {
+        let recent: RecentTracks = self
+            .agent
+            .get(LASTFM_API)
+            .query("method", "user.getrecenttracks")
+            .query("user", user)
+            .query("api_key", api_key)
+            .query("format", "json")
+            .query("limit", "1")
+            .call()?
+            .body_mut()
+            .read_json()?;
+        let track = recent.recent.track.into_iter().find(|scrobble| {
+            scrobble
+                .attr
+                .as_ref()
+                .and_then(|attr| attr.now_playing.as_deref())
+                == Some("true")
+        });
+        Ok(track.map(|scrobble| Track {
+            title: scrobble.name,
+            artist: scrobble.artist.text,
+        }))
+    }
+
+    fn spotify_token(
+        &self,
+        client_id: &str,
+        client_secret: &str,
+        refresh_token: &str,
+    ) -> Result<String, anyhow::Error> {
+        if let Some((token, expires_at)) = &*self.token.lock().unwrap()
+            && Instant::now() < *expires_at
+        {
+            return Ok(token.clone());
+        }
+        let credentials = STANDARD.encode(format!("{}:{}", client_id, client_secret));
+        let token: Token = self
+            .agent
+            .post(SPOTIFY_TOKEN)
+            .header("Authorization", &format!("Basic {}", credentials))
+            .send_form([
+                ("grant_type", "refresh_token"),
+                ("refresh_token", refresh_token),
+            ])?
+            .body_mut()
+            .read_json()?;
+        let lifetime = Duration::from_secs(token.expires_in).saturating_sub(TOKEN_MARGIN);
+        *self.token.lock().unwrap() = Some((token.access_token.clone(), Instant::now() + lifetime));
+        Ok(token.access_token)
+    }
+
+    fn fetch_spotify(&self, token: &str) -> Result<Option<Track>, anyhow::Error> {
+        let mut response = self
+            .agent
+            .get(SPOTIFY_CURRENTLY_PLAYING)
+            .header("Authorization", &format!("Bearer {}", token))
+            .call()?;
+        // No content while nothing has played for a while
+        if response.status() == 204 {
+            return Ok(None);
+        }
+        let playing: CurrentlyPlaying = response.body_mut().read_json()?;
+        let track = playing
+            .item
+            .filter(|_| playing.is_playing)
+            .map(|item| Track {
+                title: item.name,
+                artist: item
+                    .artists
+                    .into_iter()
+                    .map(|artist| artist.name)
+                    .collect::<Vec<_>>()
+                    .join(", "),
+            });
+        Ok(track)
+    }
+}
@@ -1,11 +1,20 @@
-use crate::pages::page::Page;
-use crate::pages::style::{dimmed_link_style, gray_span, line_from_spans, link_span, white_span};
+use crate::graphics::{Image, Placement};
+use crate::pages::image::ImageView;
+use crate::pages::page::{Navigation, Page};
+use crate::pages::qr::QrPage;
+use crate::pages::style::{
+    LINK_COLOR, dimmed_link_style, gray_span, gray_span_owned, line_from_spans, link_span,
+    white_span, white_span_owned,
+};
+use crate::preferences::SessionPreferences;
+use crate::weather::{Conditions, Weather};
 use bincode::{Decode, Encode};
 use crossterm::event::KeyCode;
 use image::ImageReader;
 use ratatui::{
     Frame,
     layout::{Constraint, Layout, Rect},
+    style::Style,
     text::{Line, Span},
     widgets::canvas::{Canvas, Points},
     widgets::{Block, Padding, Paragraph, Wrap},
@@ -14,6 +23,12 @@ use rayon::iter::{IntoParallelIterator, IntoParallelRefIterator, ParallelIterato
 use std::fs;
 use std::io::{self, Read, Write};
 use std::path::Path;
+use std::sync::Arc;
+use tracing::{info, warn};
+
+// Cells the avatar gets beside the text, when there's room for both
+const AVATAR_WIDTH: u16 = 20;
+const MIN_TEXT_WIDTH: u16 = 40;
 
 fn osc52(text: &str) {
     use base64::{Engine as _, engine::general_purpose};
@@ -39,6 +54,9 @@ pub struct About<'a> {
     tick: u64,
     show_tooltip: bool,
     tooltip_end_tick: u64,
+    avatar: Option<ImageView>,
+    weather: Arc<Weather>,
+    preferences: Arc<SessionPreferences>,
 }
 
 impl<'a> Page for About<'a> {
@@ -139,8 +157,12 @@ impl<'a> Page for About<'a> {
             Line::from(""),
             line_6,
             Line::from(""),
-            Line::from(white_span("contact:")),
         ];
+        if let Some(conditions) = self.weather.conditions() {
+            lines.push(weather_line(&conditions));
+            lines.push(Line::from(""));
+        }
+        lines.push(Line::from(white_span("contact:")));
 
         lines.append(&mut links);
 
@@ -153,7 +175,16 @@ impl<'a> Page for About<'a> {
             }))
             .wrap(Wrap { trim: true });
 
-        frame.render_widget(paragraph, content_area);
+        match &self.avatar {
+            Some(avatar) if content_area.width >= AVATAR_WIDTH + MIN_TEXT_WIDTH => {
+                let [text_area, avatar_area] =
+                    Layout::horizontal([Constraint::Fill(1), Constraint::Length(AVATAR_WIDTH)])
+                        .areas(content_area);
+                frame.render_widget(paragraph, text_area);
+                avatar.render(frame, avatar_area);
+            }
+            _ => frame.render_widget(paragraph, content_area),
+        }
     }
 
     fn render_additional(&self, frame: &mut Frame, area: Rect, _is_focused: bool) {
@@ -161,7 +192,11 @@ impl<'a> Page for About<'a> {
             return;
         }
 
-        let idx = (self.tick as usize) % self.max_frames;
+        // With animations off she holds still on the first frame
+        let idx = match self.preferences.get().animations {
+            true => (self.tick as usize) % self.max_frames,
+            false => 0,
+        };
         let current_frame = &self.all_frames[idx];
         let frame_height = current_frame.len() as f64;
         if frame_height == 0.0 {
@@ -190,7 +225,7 @@ impl<'a> Page for About<'a> {
         frame.render_widget(canvas, area);
     }
 
-    fn keyboard_event_handler(&mut self, key_code: KeyCode) {
+    fn keyboard_event_handler(&mut self, key_code: KeyCode) -> Option<Navigation> {
         match key_code {
             KeyCode::Up => {
                 if self.state > 0 {
@@ -204,27 +239,40 @@ impl<'a> Page for About<'a> {
                 }
                 self.update_current_link();
             }
-            KeyCode::Enter => {
-                if !self.current_link.is_empty() {
-                    osc52(&self.current_link);
-                    self.show_tooltip = true;
-                    self.tooltip_end_tick = self.tick + 38;
-                }
+            KeyCode::Enter if !self.current_link.is_empty() => {
+                osc52(&self.current_link);
+                self.show_tooltip = true;
+                self.tooltip_end_tick = self.tick + 38;
+            }
+            KeyCode::Char('s') if !self.current_link.is_empty() => {
+                return Some(Navigation::Push(Box::new(QrPage::new(&self.current_link))));
             }
             _ => {}
         }
+        None
     }
 
     fn nav_items(&self) -> Vec<Line<'static>> {
-        vec![line_from_spans(vec![white_span(" ↵  "), gray_span("copy")])]
+        vec![
+            line_from_spans(vec![white_span(" ↵  "), gray_span("copy")]),
+            line_from_spans(vec![white_span(" s  "), gray_span("scan")]),
+        ]
+    }
+
+    fn images(&self) -> Vec<Placement> {
+        self.avatar
+            .iter()
+            .filter_map(|avatar| avatar.take_placement())
+            .collect()
     }
 
     fn on_tick(&mut self, tick: u64) -> bool {
         self.tick = tick;
         if self.show_tooltip && tick >= self.tooltip_end_tick {
             self.show_tooltip = false;
+            return true;
         }
-        true
+        self.show_tooltip || self.preferences.get().animations
     }
 }
 
@@ -235,7 +283,12 @@ impl<'a> About<'a> {
         }
     }
 
-    pub fn new(show_debug_frames: bool) -> Self {
+    pub fn new(
+        show_debug_frames: bool,
+        avatar: Option<Arc<Image>>,
+        weather: Arc<Weather>,
+        preferences: Arc<SessionPreferences>,
+    ) -> Self {
         let links: Vec<ContactLink> = vec![
             ContactLink {
                 display_text: "twitter",
@@ -272,13 +325,35 @@ impl<'a> About<'a> {
             tick: 0,
             show_tooltip: false,
             tooltip_end_tick: 0,
+            avatar: avatar.map(ImageView::new),
+            weather,
+            preferences,
         }
     }
 }
 
+fn weather_line(conditions: &Conditions) -> Line<'static> {
+    let mut spans = vec![
+        gray_span("right now it's "),
+        Span::styled(
+            conditions.sky.glyph(conditions.day).to_string(),
+            Style::new().fg(LINK_COLOR),
+        ),
+        white_span_owned(format!(" {}°{}", conditions.temperature, conditions.unit)),
+        gray_span_owned(format!(" and {}", conditions.sky.description())),
+    ];
+    if let Some(place) = &conditions.place {
+        spans.push(gray_span(" in "));
+        spans.push(white_span_owned(place.clone()));
+    }
+    line_from_spans(spans)
+}
+
+type RgbFrames = Vec<Vec<Vec<[u8; 3]>>>;
+
 #[derive(Encode, Decode)]
 struct FrameCache {
-    frames: Vec<Vec<Vec<[u8; 3]>>>,
+    frames: RgbFrames,
 }
 
 fn get_all_frames_rgb_vals(show_debug_frames: bool) -> Vec<Vec<Vec<[u8; 3]>>> {
@@ -287,11 +362,11 @@ fn get_all_frames_rgb_vals(show_debug_frames: bool) -> Vec<Vec<Vec<[u8; 3]>>> {
     // Try to load from cache first
     if Path::new(CACHE_FILE).exists() {
         if show_debug_frames {
-            println!("Loading frames from cache...");
+            info!("Loading frames from cache...");
         }
         if let Ok(cached_frames) = load_frames_from_cache(CACHE_FILE) {
             if show_debug_frames {
-                println!(
+                info!(
                     "Successfully loaded {} frames from cache",
                     cached_frames.len()
                 );
@@ -299,27 +374,24 @@ fn get_all_frames_rgb_vals(show_debug_frames: bool) -> Vec<Vec<Vec<[u8; 3]>>> {
             return cached_frames;
         }
         if show_debug_frames {
-            println!("Cache load failed, recalculating frames...");
+            info!("Cache load failed, recalculating frames...");
         }
     }
 
     if show_debug_frames {
-        println!("Cache not found, processing frames...");
+        info!("Cache not found, processing frames...");
     }
 
     // Read all frame files from hikari directory
     let mut frame_files = Vec::new();
     if let Ok(entries) = fs::read_dir("./hikari-dance") {
-        for entry in entries {
-            if let Ok(entry) = entry {
-                let path = entry.path();
-                if let Some(extension) = path.extension() {
-                    if extension == "png" || extension == "jpg" || extension == "jpeg" {
-                        if let Some(_file_name) = path.file_name() {
-                            frame_files.push(path.clone());
-                        }
-                    }
-                }
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if let Some(extension) = path.extension()
+                && (extension == "png" || extension == "jpg" || extension == "jpeg")
+                && path.file_name().is_some()
+            {
+                frame_files.push(path.clone());
             }
         }
     }
@@ -341,9 +413,9 @@ fn get_all_frames_rgb_vals(show_debug_frames: bool) -> Vec<Vec<Vec<[u8; 3]>>> {
 
     // Debug: Print first few frame names to verify ordering
     if show_debug_frames {
-        println!("Frame loading order (first 10):");
+        info!("Frame loading order (first 10):");
         for (i, path) in frame_files.iter().take(10).enumerate() {
-            println!("{}: {}", i, path.file_name().unwrap().to_string_lossy());
+            info!("{}: {}", i, path.file_name().unwrap().to_string_lossy());
         }
     }
 
@@ -381,10 +453,10 @@ fn get_all_frames_rgb_vals(show_debug_frames: bool) -> Vec<Vec<Vec<[u8; 3]>>> {
 
     // Save to cache for future use
     if let Err(e) = save_frames_to_cache(&all_frames, CACHE_FILE) {
-        eprintln!("Warning: Failed to save frames to cache: {}", e);
+        warn!("Failed to save frames to cache: {}", e);
     } else {
         if show_debug_frames {
-            println!("Successfully cached {} frames", all_frames.len());
+            info!("Successfully cached {} frames", all_frames.len());
         }
     }
 
@@ -405,9 +477,7 @@ fn save_frames_to_cache(
     Ok(())
 }
 
-fn load_frames_from_cache(
-    path: &str,
-) -> Result<Vec<Vec<Vec<[u8; 3]>>>, Box<dyn std::error::Error>> {
+fn load_frames_from_cache(path: &str) -> Result<RgbFrames, Box<dyn std::error::Error>> {
     let mut file = fs::File::open(path)?;
     let mut buffer = Vec::new();
     file.read_to_end(&mut buffer)?;
@@ -0,0 +1,306 @@
+use std::time::Duration;
+
+use crossterm::event::{KeyCode, KeyEvent};
+use ratatui::{
+    Frame,
+    layout::{Constraint, Layout, Rect},
+    text::Line,
+    widgets::{Block, Cell, Padding, Paragraph, Row, Table, Wrap},
+};
+use tracing::info;
+
+use crate::pages::{
+    page::{Navigation, Page},
+    style::{
+        dimmed_selected_style, gray_span, gray_span_owned, gray_style, line_from_spans,
+        selected_style, white_span, white_style,
+    },
+    text_input::TextInput,
+};
+use crate::server::session_registry::{SessionId, SessionRegistry};
+
+const MAX_MESSAGE_CHARS: usize = 200;
+const KICK_MESSAGE: &[u8] = b"disconnected by the operator\r\n";
+
+/// One line of the session list, formatted when the list is refreshed so a tick
+/// can tell whether anything visible changed.
+#[derive(PartialEq)]
+struct SessionRow {
+    id: SessionId,
+    address: String,
+    connected: String,
+    page: String,
+    idle: String,
+    sent: String,
+    draw: String,
+    rtt: String,
+}
+
+/// The live sessions, for admin keys. Each can be sent a message of its own or
+/// kicked.
+pub struct AdminPage {
+    sessions: SessionRegistry,
+    own_session: SessionId,
+    rows: Vec<SessionRow>,
+    selected: usize,
+    input: TextInput,
+    typing: bool,
+    status: Option<String>,
+}
+
+impl AdminPage {
+    pub fn new(sessions: SessionRegistry, own_session: SessionId) -> Self {
+        let mut page = Self {
+            sessions,
+            own_session,
+            rows: Vec::new(),
+            selected: 0,
+            input: TextInput::new(MAX_MESSAGE_CHARS),
+            typing: false,
+            status: None,
+        };
+        page.refresh();
+        page
+    }
+
+    fn refresh(&mut self) -> bool {
+        let rows = self
+            .sessions
+            .sessions()
+            .into_iter()
+            .map(|(id, session)| SessionRow {
+                id,
+                address: session
+                    .peer_addr
+                    .map(|addr| addr.ip().to_canonical().to_string())
+                    .unwrap_or_else(|| String::from("unknown")),
+                connected: format_duration(session.connected_at.elapsed()),
+                page: match id == self.own_session {
+                    true => String::from("(you)"),
+                    false => session.current_page(),
+                },
+                idle: format_duration(session.idle_for()),
+                sent: format_bytes(session.bytes_sent()),
+                draw: format_latency(session.latency().draw),
+                rtt: format_latency(session.latency().rtt),
+            })
+            .collect::<Vec<_>>();
+        let changed = rows != self.rows;
+        self.rows = rows;
+        self.selected = self.selected.min(self.rows.len().saturating_sub(1));
+        changed
+    }
+
+    fn selected_session(&self) -> Option<SessionId> {
+        self.rows.get(self.selected).map(|row| row.id)
+    }
+
+    fn send_message(&mut self) {
+        let message = self.input.value().trim().to_string();
+        self.input = TextInput::new(MAX_MESSAGE_CHARS);
+        self.typing = false;
+        let Some(id) = self.selected_session() else {
+            return;
+        };
+        let Some(session) = self.sessions.get(id) else {
+            self.status = Some(format!("#{} is gone", id));
+            return;
+        };
+        if message.is_empty() {
+            return;
+        }
+
+        tokio::spawn(async move { session.show_wall(message).await });
+        self.status = Some(format!("sent to #{}", id));
+    }
+
+    fn kick(&mut self) {
+        let Some(id) = self.selected_session() else {
+            return;
+        };
+        if id == self.own_session {
+            self.status = Some(String::from("that's you, press q to leave"));
+            return;
+        }
+        let Some(session) = self.sessions.remove(id) else {
+            self.status = Some(format!("#{} is gone", id));
+            return;
+        };
+
+        info!(parent: &session.span, "Kicked from the admin view");
+        tokio::spawn(session.close_with(KICK_MESSAGE));
+        self.status = Some(format!("kicked #{}", id));
+    }
+}
+
+impl Page for AdminPage {
+    fn title(&self) -> &str {
+        "sessions"
+    }
+
+    fn render(&self, frame: &mut Frame, area: Rect, is_focused: bool) {
+        let [table_area, status_area, input_area] = Layout::vertical([
+            Constraint::Fill(1),
+            Constraint::Length(1),
+            Constraint::Length(1),
+        ])
+        .areas(area);
+
+        let header = [
+            "#",
+            "address",
+            "connected",
+            "page",
+            "idle",
+            "sent",
+            "draw",
+            "rtt",
+        ]
+        .into_iter()
+        .map(Cell::from)
+        .collect::<Row>()
+        .style(white_style());
+        let rows = self.rows.iter().enumerate().map(|(index, row)| {
+            let style = match (index == self.selected, is_focused) {
+                (true, true) => selected_style(),
+                (true, false) => dimmed_selected_style(),
+                (false, _) => gray_style(),
+            };
+            [
+                row.id.to_string(),
+                row.address.clone(),
+                row.connected.clone(),
+                row.page.clone(),
+                row.idle.clone(),
+                row.sent.clone(),
+                row.draw.clone(),
+                row.rtt.clone(),
+            ]
+            .into_iter()
+            .map(Cell::from)
+            .collect::<Row>()
+            .style(style)
+        });
+        let table = Table::new(
+            rows,
+            [
+                Constraint::Length(5),
+                Constraint::Fill(1),
+                Constraint::Length(10),
+                Constraint::Length(16),
+                Constraint::Length(8),
+                Constraint::Length(9),
+                Constraint::Length(7),
+                Constraint::Length(7),
+            ],
+        )
+        .header(header)
+        .block(Block::new().padding(Padding {
+            left: 1,
+            right: 2,
+            top: 1,
+            bottom: 0,
+        }));
+        frame.render_widget(table, table_area);
+
+        if let Some(status) = &self.status {
+            frame.render_widget(Paragraph::new(gray_span_owned(status.clone())), status_area);
+        }
+        if self.typing {
+            let mut input = self.input.line("", is_focused);
+            input.spans.insert(0, white_span("message> "));
+            frame.render_widget(Paragraph::new(input), input_area);
+        }
+    }
+
+    fn render_additional(&self, frame: &mut Frame, area: Rect, _is_focused: bool) {
+        let description = vec![
+            line_from_spans(vec![white_span("desc")]),
+            line_from_spans(vec![gray_span(
+                "everyone connected right now. idle is the time since their last key press, sent how much their screen took to draw. draw is how long a frame takes on the server, rtt how long their terminal takes to answer",
+            )]),
+        ];
+        frame.render_widget(Paragraph::new(description).wrap(Wrap { trim: true }), area);
+    }
+
+    fn keyboard_event_handler(&mut self, key_code: KeyCode) -> Option<Navigation> {
+        match key_code {
+            KeyCode::Enter if self.typing => self.send_message(),
+            KeyCode::Esc => self.typing = false,
+            key_code if self.typing => {
+                self.input.handle_key(key_code);
+            }
+            KeyCode::Up | KeyCode::Char('k') => self.selected = self.selected.saturating_sub(1),
+            KeyCode::Down | KeyCode::Char('j') => {
+                self.selected = (self.selected + 1).min(self.rows.len().saturating_sub(1))
+            }
+            KeyCode::Char('m') if !self.rows.is_empty() => self.typing = true,
+            KeyCode::Char('x') => self.kick(),
+            _ => {}
+        }
+        None
+    }
+
+    fn paste_event_handler(&mut self, text: &str) {
+        if self.typing {
+            self.input.insert_str(text);
+        }
+    }
+
+    fn key_event_handler(&mut self, key_event: KeyEvent) -> Option<Navigation> {
+        if self.typing && self.input.handle_modified_key(key_event) {
+            return None;
+        }
+        self.keyboard_event_handler(key_event.code)
+    }
+
+    fn captures_input(&self) -> bool {
+        self.typing
+    }
+
+    fn on_tick(&mut self, _tick: u64) -> bool {
+        self.refresh()
+    }
+
+    fn nav_items(&self) -> Vec<Line<'static>> {
+        match self.typing {
+            true => vec![
+                line_from_spans(vec![white_span(" ↵  "), gray_span("send")]),
+                line_from_spans(vec![white_span("esc "), gray_span("cancel")]),
+            ],
+            false => vec![
+                line_from_spans(vec![white_span(" m  "), gray_span("message")]),
+                line_from_spans(vec![white_span(" x  "), gray_span("kick")]),
+            ],
+        }
+    }
+}
+
+pub(crate) fn format_duration(duration: Duration) -> String {
+    let seconds = duration.as_secs();
+    match seconds {
+        0..60 => format!("{}s", seconds),
+        60..3600 => format!("{}m {:02}s", seconds / 60, seconds % 60),
+        3600..86400 => format!("{}h {:02}m", seconds / 3600, seconds % 3600 / 60),
+        _ => format!("{}d {:02}h", seconds / 86400, seconds % 86400 / 3600),
+    }
+}
+
+fn format_bytes(bytes: u64) -> String {
+    match bytes {
+        0..1024 => format!("{} B", bytes),
+        1024..1_048_576 => format!("{:.1} KiB", bytes as f64 / 1024.0),
+        _ => format!("{:.1} MiB", bytes as f64 / 1_048_576.0),
+    }
+}
+
+// Not known until the first frame, or the terminal's first answer
+fn format_latency(latency: Option<Duration>) -> String {
+    match latency {
+        Some(latency) if latency < Duration::from_millis(10) => {
+            format!("{:.1}ms", latency.as_secs_f64() * 1000.0)
+        }
+        Some(latency) => format!("{}ms", latency.as_millis()),
+        None => String::from("-"),
+    }
+}
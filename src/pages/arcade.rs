@@ -0,0 +1,191 @@
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use crossterm::event::KeyCode;
+use ratatui::{
+    Frame,
+    layout::{Constraint, Rect},
+    text::Line,
+    widgets::{Block, Cell, Padding, Paragraph, Row, Table},
+};
+
+use crate::high_scores::HighScores;
+use crate::pages::{
+    game2048::Game2048Page,
+    page::{Navigation, Page},
+    pong::PongPage,
+    snake::SnakePage,
+    style::{
+        dimmed_selected_style, gray_span, gray_span_owned, gray_style, line_from_spans,
+        selected_style, white_span, white_span_owned,
+    },
+    tetris::TetrisPage,
+    typing::TypingPage,
+};
+use crate::preferences::SessionPreferences;
+use crate::store::HighScore;
+
+// The tables come from the database, so they are only re-read this often
+const REFRESH_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Who's playing, for the games that keep more than the shared tables.
+#[derive(Clone)]
+pub struct Player {
+    pub high_scores: Arc<HighScores>,
+    pub preferences: Arc<SessionPreferences>,
+}
+
+/// A game the arcade lists. `name` is also what its scores are kept under.
+struct Game {
+    name: &'static str,
+    description: &'static str,
+    open: fn(Player) -> Box<dyn Page>,
+}
+
+const GAMES: &[Game] = &[
+    Game {
+        name: "snake",
+        description: "eat, grow, and don't run into yourself",
+        open: |player| Box::new(SnakePage::new(player.high_scores)),
+    },
+    Game {
+        name: "tetris",
+        description: "clear lines before the stack reaches the top",
+        open: |player| Box::new(TetrisPage::new(player.high_scores)),
+    },
+    Game {
+        name: "typing",
+        description: "how many words a minute you type",
+        open: |player| Box::new(TypingPage::new(player.high_scores)),
+    },
+    Game {
+        name: "2048",
+        description: "slide the tiles and merge them up to 2048",
+        open: |player| Box::new(Game2048Page::new(player)),
+    },
+    Game {
+        name: "pong",
+        description: "keep the ball out of your side, against the server",
+        open: |player| Box::new(PongPage::new(player.high_scores)),
+    },
+];
+
+/// The high scores with the initials they were entered under, as every game
+/// shows them next to its board.
+pub fn leaderboard(table: &[HighScore]) -> Vec<Line<'static>> {
+    let mut lines = vec![
+        line_from_spans(vec![white_span("high scores")]),
+        Line::from(""),
+    ];
+    if table.is_empty() {
+        lines.push(line_from_spans(vec![gray_span("nobody yet")]));
+    }
+    for (place, entry) in table.iter().enumerate() {
+        lines.push(line_from_spans(vec![
+            gray_span_owned(format!("{:>2}. ", place + 1)),
+            white_span_owned(format!("{:<4}", entry.name)),
+            white_span_owned(format!("{:>6}", entry.score)),
+            gray_span_owned(format!("  {}", entry.date)),
+        ]));
+    }
+    lines
+}
+
+/// The games, with the best players of the selected one beside them.
+pub struct ArcadePage {
+    player: Player,
+    selected: usize,
+    // One per entry of `GAMES`
+    tables: Vec<Vec<HighScore>>,
+    refreshed_at: Instant,
+}
+
+impl ArcadePage {
+    pub fn new(player: Player) -> Self {
+        let mut page = Self {
+            player,
+            selected: 0,
+            tables: Vec::new(),
+            refreshed_at: Instant::now(),
+        };
+        page.refresh();
+        page
+    }
+
+    fn refresh(&mut self) {
+        self.refreshed_at = Instant::now();
+        self.tables = GAMES
+            .iter()
+            .map(|game| self.player.high_scores.top(game.name))
+            .collect();
+    }
+}
+
+impl Page for ArcadePage {
+    fn title(&self) -> &str {
+        "arcade"
+    }
+
+    fn render(&self, frame: &mut Frame, area: Rect, is_focused: bool) {
+        let rows = GAMES.iter().enumerate().map(|(i, game)| {
+            let style = match (i == self.selected, is_focused) {
+                (true, true) => selected_style(),
+                (true, false) => dimmed_selected_style(),
+                (false, _) => gray_style(),
+            };
+            Row::new([Cell::from(game.name), Cell::from(game.description)]).style(style)
+        });
+        let table = Table::new(rows, [Constraint::Length(10), Constraint::Fill(1)])
+            .header(Row::new(["game", ""]))
+            .block(Block::new().padding(Padding {
+                left: 1,
+                right: 2,
+                top: 0,
+                bottom: 0,
+            }));
+        frame.render_widget(table, area);
+    }
+
+    fn render_additional(&self, frame: &mut Frame, area: Rect, _is_focused: bool) {
+        let table = self
+            .tables
+            .get(self.selected)
+            .map_or(&[][..], Vec::as_slice);
+        frame.render_widget(Paragraph::new(leaderboard(table)), area);
+    }
+
+    fn keyboard_event_handler(&mut self, key_code: KeyCode) -> Option<Navigation> {
+        match key_code {
+            KeyCode::Char('k') | KeyCode::Up => self.selected = self.selected.saturating_sub(1),
+            KeyCode::Char('j') | KeyCode::Down => {
+                self.selected = (self.selected + 1).min(GAMES.len() - 1);
+            }
+            KeyCode::Enter => {
+                let game = (GAMES[self.selected].open)(self.player.clone());
+                return Some(Navigation::Push(game));
+            }
+            _ => {}
+        }
+        None
+    }
+
+    fn position(&self) -> usize {
+        self.selected
+    }
+
+    fn set_position(&mut self, position: usize) {
+        self.selected = position.min(GAMES.len() - 1);
+    }
+
+    fn on_tick(&mut self, _tick: u64) -> bool {
+        if self.refreshed_at.elapsed() < REFRESH_INTERVAL {
+            return false;
+        }
+        self.refresh();
+        true
+    }
+
+    fn nav_items(&self) -> Vec<Line<'static>> {
+        vec![line_from_spans(vec![white_span(" ↵  "), gray_span("play")])]
+    }
+}
@@ -0,0 +1,185 @@
+use std::sync::Arc;
+
+use crossterm::event::KeyCode;
+use ratatui::{
+    Frame,
+    layout::Rect,
+    text::Line,
+    widgets::{Block, Padding, Paragraph, Wrap},
+};
+
+use crate::content::Post;
+use crate::pages::{
+    page::{Navigation, Page},
+    scroll_view::ScrollView,
+    style::{
+        dimmed_selected_style, gray_span, gray_span_owned, gray_style, line_from_spans,
+        selected_style, white_span, white_span_owned,
+    },
+};
+
+// Title, date and tags, and a blank line
+const LINES_PER_POST: usize = 3;
+
+/// The posts in the content directory's `posts` folder, newest first unless
+/// flipped with `s`. Enter opens the selected one.
+pub struct BlogPage {
+    posts: Arc<Vec<Post>>,
+    selected: usize,
+    oldest_first: bool,
+}
+
+impl BlogPage {
+    pub fn new(posts: Arc<Vec<Post>>) -> Self {
+        Self {
+            posts,
+            selected: 0,
+            oldest_first: false,
+        }
+    }
+
+    /// Where the `position`th post in the shown order is in `posts`.
+    fn post_index(&self, position: usize) -> usize {
+        match self.oldest_first {
+            true => self.posts.len() - 1 - position,
+            false => position,
+        }
+    }
+}
+
+impl Page for BlogPage {
+    fn title(&self) -> &str {
+        "blog"
+    }
+
+    fn render(&self, frame: &mut Frame, area: Rect, is_focused: bool) {
+        let lines = (0..self.posts.len())
+            .flat_map(|position| {
+                let post = &self.posts[self.post_index(position)];
+                let style = match (position == self.selected, is_focused) {
+                    (true, true) => selected_style(),
+                    (true, false) => dimmed_selected_style(),
+                    (false, _) => gray_style(),
+                };
+                [
+                    Line::styled(post.title.clone(), style),
+                    line_from_spans(vec![gray_span_owned(post_details(post))]),
+                    Line::from(""),
+                ]
+            })
+            .collect::<Vec<_>>();
+        // Scrolled just far enough to keep the selected post in view
+        let offset = ((self.selected + 1) * LINES_PER_POST).saturating_sub(area.height as usize);
+        frame.render_widget(
+            Paragraph::new(lines)
+                .scroll((offset as u16, 0))
+                .block(Block::new().padding(Padding::new(1, 2, 0, 0))),
+            area,
+        );
+    }
+
+    fn render_additional(&self, frame: &mut Frame, area: Rect, _is_focused: bool) {
+        let count = match self.posts.len() {
+            1 => String::from("1 post"),
+            count => format!("{} posts", count),
+        };
+        let order = match self.oldest_first {
+            true => "oldest first",
+            false => "newest first",
+        };
+        let lines = vec![
+            line_from_spans(vec![white_span("desc")]),
+            line_from_spans(vec![gray_span_owned(format!(
+                "{}, {}. press enter to read one",
+                count, order
+            ))]),
+        ];
+        frame.render_widget(Paragraph::new(lines).wrap(Wrap { trim: true }), area);
+    }
+
+    fn keyboard_event_handler(&mut self, key_code: KeyCode) -> Option<Navigation> {
+        match key_code {
+            KeyCode::Up | KeyCode::Char('k') => self.selected = self.selected.saturating_sub(1),
+            KeyCode::Down | KeyCode::Char('j') => {
+                self.selected = (self.selected + 1).min(self.posts.len().saturating_sub(1));
+            }
+            KeyCode::Char('s') => {
+                self.oldest_first = !self.oldest_first;
+                self.selected = self.posts.len().saturating_sub(1) - self.selected;
+            }
+            KeyCode::Enter if !self.posts.is_empty() => {
+                return Some(Navigation::Push(Box::new(PostPage {
+                    posts: self.posts.clone(),
+                    index: self.post_index(self.selected),
+                    scroll: ScrollView::default(),
+                })));
+            }
+            _ => {}
+        }
+        None
+    }
+
+    fn nav_items(&self) -> Vec<Line<'static>> {
+        vec![
+            line_from_spans(vec![white_span(" ↵  "), gray_span("read")]),
+            line_from_spans(vec![white_span(" s  "), gray_span("sort")]),
+        ]
+    }
+}
+
+fn post_details(post: &Post) -> String {
+    match post.tags.is_empty() {
+        true => post.date.clone(),
+        false => format!("{} · {}", post.date, post.tags.join(", ")),
+    }
+}
+
+/// One post, scrolled like the content pages.
+struct PostPage {
+    posts: Arc<Vec<Post>>,
+    index: usize,
+    scroll: ScrollView,
+}
+
+impl PostPage {
+    fn lines(&self) -> Vec<Line<'static>> {
+        let post = &self.posts[self.index];
+        let mut lines = vec![
+            line_from_spans(vec![white_span_owned(post.title.clone())]),
+            line_from_spans(vec![gray_span_owned(post_details(post))]),
+            Line::from(""),
+        ];
+        lines.extend(post.markdown.text().lines.iter().cloned());
+        lines
+    }
+}
+
+impl Page for PostPage {
+    fn title(&self) -> &str {
+        &self.posts[self.index].title
+    }
+
+    fn render(&self, frame: &mut Frame, area: Rect, is_focused: bool) {
+        let paragraph = Paragraph::new(self.lines()).wrap(Wrap { trim: false });
+        self.scroll.render(frame, area, paragraph, is_focused);
+    }
+
+    fn render_additional(&self, _frame: &mut Frame, _area: Rect, _is_focused: bool) {}
+
+    fn keyboard_event_handler(&mut self, key_code: KeyCode) -> Option<Navigation> {
+        self.scroll.handle_key(key_code);
+        None
+    }
+
+    fn search(&mut self, query: &str) -> bool {
+        let lines = self.lines();
+        self.scroll.search(&lines, query)
+    }
+
+    fn nav_items(&self) -> Vec<Line<'static>> {
+        vec![line_from_spans(vec![
+            white_span("⇞/⇟ "),
+            gray_span("scroll"),
+        ])]
+    }
+}
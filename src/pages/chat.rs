@@ -0,0 +1,222 @@
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU16, Ordering};
+
+use crossterm::event::{KeyCode, KeyEvent};
+use ratatui::{
+    Frame,
+    layout::{Constraint, Layout, Rect},
+    style::{Color, Style},
+    text::{Line, Span},
+    widgets::{Paragraph, Wrap},
+};
+use tokio::sync::broadcast::{self, error::TryRecvError};
+
+use crate::chat::{ChatLine, ChatMember, ChatRoom, MAX_MESSAGE_CHARS, SCROLLBACK};
+use crate::event_bus::{Event, EventBus};
+use crate::pages::{
+    page::{Navigation, Page},
+    style::{gray_span, gray_span_owned, line_from_spans, white_span, white_span_owned},
+    text_input::TextInput,
+};
+
+/// A room shared by everyone connected. Enter starts typing; while typing the
+/// page takes every key, and Esc goes back to browsing.
+pub struct ChatPage {
+    room: Arc<ChatRoom>,
+    member: ChatMember,
+    events: broadcast::Receiver<Event>,
+    lines: Vec<Arc<ChatLine>>,
+    input: TextInput,
+    typing: bool,
+    // Lines scrolled up from the newest one
+    scroll_back: u16,
+    // Measured while rendering, like `ScrollView` does
+    max_scroll_back: AtomicU16,
+    error: Option<String>,
+}
+
+impl ChatPage {
+    pub fn new(room: Arc<ChatRoom>, events: &EventBus) -> Self {
+        // Subscribe first so nothing said in between is missed
+        let receiver = events.subscribe();
+        Self {
+            member: room.join(),
+            lines: room.scrollback(),
+            room,
+            events: receiver,
+            input: TextInput::new(MAX_MESSAGE_CHARS),
+            typing: false,
+            scroll_back: 0,
+            max_scroll_back: AtomicU16::new(0),
+            error: None,
+        }
+    }
+
+    fn submit(&mut self) {
+        let text = self.input.value();
+        let result = match text.trim().strip_prefix("/nick ") {
+            Some(nick) => self.member.rename(nick),
+            None => self.member.send(&text),
+        };
+        match result {
+            Ok(()) => {
+                self.input = TextInput::new(MAX_MESSAGE_CHARS);
+                self.error = None;
+                self.scroll_back = 0;
+            }
+            Err(e) => self.error = Some(e.to_string()),
+        }
+    }
+
+    fn render_line(line: &ChatLine) -> Line<'static> {
+        match line {
+            ChatLine::Message { nick, text } => line_from_spans(vec![
+                white_span_owned(format!("{}: ", nick)),
+                gray_span_owned(text.clone()),
+            ]),
+            ChatLine::Notice(text) => line_from_spans(vec![gray_span_owned(format!("* {}", text))]),
+        }
+    }
+}
+
+impl Page for ChatPage {
+    fn title(&self) -> &str {
+        "chat"
+    }
+
+    fn render(&self, frame: &mut Frame, area: Rect, is_focused: bool) {
+        let [messages_area, status_area, input_area] = Layout::vertical([
+            Constraint::Fill(1),
+            Constraint::Length(1),
+            Constraint::Length(1),
+        ])
+        .areas(area);
+
+        let mut lines: Vec<Line> = self
+            .lines
+            .iter()
+            .map(|line| Self::render_line(line))
+            .collect();
+        if lines.is_empty() {
+            lines.push(line_from_spans(vec![gray_span(
+                "nobody has said anything yet",
+            )]));
+        }
+        let paragraph = Paragraph::new(lines).wrap(Wrap { trim: false });
+        // Stick to the newest lines unless the visitor scrolled up
+        let line_count = paragraph.line_count(messages_area.width) as u16;
+        let max_offset = line_count.saturating_sub(messages_area.height);
+        self.max_scroll_back.store(max_offset, Ordering::Relaxed);
+        let offset = max_offset.saturating_sub(self.scroll_back);
+        frame.render_widget(paragraph.scroll((offset, 0)), messages_area);
+
+        if let Some(error) = &self.error {
+            frame.render_widget(
+                Paragraph::new(Span::styled(error.clone(), Style::new().fg(Color::Red))),
+                status_area,
+            );
+        }
+
+        let prompt = white_span_owned(format!("{}> ", self.member.nick()));
+        let mut input = self.input.line(
+            "press enter to chat, /nick to rename",
+            is_focused && self.typing,
+        );
+        input.spans.insert(0, prompt);
+        frame.render_widget(Paragraph::new(input), input_area);
+    }
+
+    fn render_additional(&self, frame: &mut Frame, area: Rect, _is_focused: bool) {
+        let description = vec![
+            line_from_spans(vec![white_span("desc")]),
+            line_from_spans(vec![gray_span(
+                "talk with whoever else is here right now. messages aren't saved, only the last few are shown to people who join later",
+            )]),
+        ];
+        frame.render_widget(Paragraph::new(description).wrap(Wrap { trim: true }), area);
+    }
+
+    fn keyboard_event_handler(&mut self, key_code: KeyCode) -> Option<Navigation> {
+        let scroll_back = match key_code {
+            KeyCode::PageUp => self.scroll_back.saturating_add(5),
+            KeyCode::PageDown => self.scroll_back.saturating_sub(5),
+            KeyCode::Enter if !self.typing => {
+                self.typing = true;
+                return None;
+            }
+            KeyCode::Enter => {
+                self.submit();
+                return None;
+            }
+            KeyCode::Esc => {
+                self.typing = false;
+                return None;
+            }
+            key_code if self.typing => {
+                self.input.handle_key(key_code);
+                return None;
+            }
+            KeyCode::Up | KeyCode::Char('k') => self.scroll_back.saturating_add(1),
+            KeyCode::Down | KeyCode::Char('j') => self.scroll_back.saturating_sub(1),
+            _ => return None,
+        };
+        self.scroll_back = scroll_back.min(self.max_scroll_back.load(Ordering::Relaxed));
+        None
+    }
+
+    fn paste_event_handler(&mut self, text: &str) {
+        self.typing = true;
+        self.input.insert_str(text);
+    }
+
+    fn key_event_handler(&mut self, key_event: KeyEvent) -> Option<Navigation> {
+        if self.typing && self.input.handle_modified_key(key_event) {
+            return None;
+        }
+        self.keyboard_event_handler(key_event.code)
+    }
+
+    fn captures_input(&self) -> bool {
+        self.typing
+    }
+
+    /// Picks up what others said since the last tick.
+    fn on_tick(&mut self, _tick: u64) -> bool {
+        let mut changed = false;
+        loop {
+            match self.events.try_recv() {
+                Ok(Event::Chat(line)) => {
+                    self.lines.push(line);
+                    changed = true;
+                }
+                Ok(_) => {}
+                Err(TryRecvError::Lagged(_)) => {
+                    self.lines = self.room.scrollback();
+                    changed = true;
+                }
+                Err(TryRecvError::Empty | TryRecvError::Closed) => break,
+            }
+        }
+        if self.lines.len() > SCROLLBACK {
+            self.lines.drain(..self.lines.len() - SCROLLBACK);
+        }
+        changed
+    }
+
+    /// Lines scrolled back are counted after wrapping, which just changed, so the
+    /// same count would land somewhere else. Back to the newest instead.
+    fn handle_resize(&mut self, _width: u16, _height: u16) {
+        self.scroll_back = 0;
+    }
+
+    fn nav_items(&self) -> Vec<Line<'static>> {
+        let (key, action) = match self.typing {
+            true => ("esc ", "stop typing"),
+            false => (" ↵  ", "chat"),
+        };
+        vec![
+            line_from_spans(vec![white_span(key), gray_span(action)]),
+            line_from_spans(vec![white_span("⇞/⇟ "), gray_span("scroll")]),
+        ]
+    }
+}
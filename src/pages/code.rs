@@ -0,0 +1,102 @@
+use std::sync::LazyLock;
+
+use ratatui::{
+    buffer::Buffer,
+    layout::Rect,
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::{Paragraph, Widget},
+};
+use syntect::easy::HighlightLines;
+use syntect::highlighting::{FontStyle, Theme, ThemeSet};
+use syntect::parsing::{SyntaxReference, SyntaxSet};
+use syntect::util::LinesWithEndings;
+
+use crate::pages::style::code_style;
+
+// Parsing the bundled definitions takes a while, so it's done once, for the first
+// code block
+static SYNTAXES: LazyLock<SyntaxSet> = LazyLock::new(SyntaxSet::load_defaults_newlines);
+static THEME: LazyLock<Theme> = LazyLock::new(|| {
+    ThemeSet::load_defaults()
+        .themes
+        .remove("base16-ocean.dark")
+        .expect("syntect bundles base16-ocean.dark")
+});
+
+/// A block of code, highlighted for the language its fence names. The colors are
+/// 24-bit like everything else drawn, and come down to what the visitor's
+/// terminal shows with the rest of the frame. Code in a language syntect doesn't
+/// know stays plain.
+pub struct CodeSnippet {
+    lines: Vec<Line<'static>>,
+}
+
+impl CodeSnippet {
+    pub fn new(code: &str, language: &str) -> Self {
+        let lines = match SYNTAXES.find_syntax_by_token(language) {
+            Some(syntax) => highlight(code, syntax),
+            None => code
+                .lines()
+                .map(|line| padded(vec![Span::styled(line.to_string(), code_style())]))
+                .collect(),
+        };
+        Self { lines }
+    }
+
+    pub fn into_lines(self) -> Vec<Line<'static>> {
+        self.lines
+    }
+}
+
+impl Widget for &CodeSnippet {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        Paragraph::new(self.lines.clone()).render(area, buf);
+    }
+}
+
+fn highlight(code: &str, syntax: &SyntaxReference) -> Vec<Line<'static>> {
+    let mut highlighter = HighlightLines::new(syntax, &THEME);
+    LinesWithEndings::from(code)
+        .map(|line| {
+            let spans = match highlighter.highlight_line(line, &SYNTAXES) {
+                Ok(pieces) => pieces
+                    .into_iter()
+                    .map(|(style, text)| {
+                        Span::styled(text.trim_end_matches('\n').to_string(), to_style(style))
+                    })
+                    .collect(),
+                Err(_) => vec![Span::styled(
+                    line.trim_end_matches('\n').to_string(),
+                    code_style(),
+                )],
+            };
+            padded(spans)
+        })
+        .collect()
+}
+
+fn to_style(style: syntect::highlighting::Style) -> Style {
+    let color = style.foreground;
+    let mut modifier = Modifier::empty();
+    if style.font_style.contains(FontStyle::BOLD) {
+        modifier |= Modifier::BOLD;
+    }
+    if style.font_style.contains(FontStyle::ITALIC) {
+        modifier |= Modifier::ITALIC;
+    }
+    if style.font_style.contains(FontStyle::UNDERLINE) {
+        modifier |= Modifier::UNDERLINED;
+    }
+    // The theme's own background would clash with the page's code blocks
+    code_style()
+        .fg(Color::Rgb(color.r, color.g, color.b))
+        .add_modifier(modifier)
+}
+
+/// A line of code with the room on both sides the block's background shows in.
+fn padded(mut spans: Vec<Span<'static>>) -> Line<'static> {
+    spans.insert(0, Span::styled("  ", code_style()));
+    spans.push(Span::styled("  ", code_style()));
+    Line::from(spans)
+}
@@ -0,0 +1,67 @@
+use std::sync::Arc;
+
+use crossterm::event::KeyCode;
+use ratatui::{Frame, layout::Rect, text::Line};
+
+use crate::content::ContentEntry;
+use crate::pages::{
+    markdown::Markdown,
+    page::{Navigation, Page},
+    scroll_view::ScrollView,
+    style::{gray_span, line_from_spans, white_span},
+};
+
+/// A page loaded from a Markdown file in the content directory.
+pub struct ContentPage {
+    title: String,
+    markdown: Arc<Markdown>,
+    scroll: ScrollView,
+}
+
+impl ContentPage {
+    pub fn new(entry: &ContentEntry) -> Self {
+        Self {
+            title: entry.title.clone(),
+            markdown: entry.markdown.clone(),
+            scroll: ScrollView::default(),
+        }
+    }
+}
+
+impl Page for ContentPage {
+    fn title(&self) -> &str {
+        &self.title
+    }
+
+    fn render(&self, frame: &mut Frame, area: Rect, is_focused: bool) {
+        self.scroll
+            .render(frame, area, self.markdown.paragraph(), is_focused);
+    }
+
+    fn render_additional(&self, _frame: &mut Frame, _area: Rect, _is_focused: bool) {}
+
+    fn keyboard_event_handler(&mut self, key_code: KeyCode) -> Option<Navigation> {
+        self.scroll.handle_key(key_code);
+        None
+    }
+
+    fn search(&mut self, query: &str) -> bool {
+        self.scroll.search(&self.markdown.text().lines, query)
+    }
+
+    fn position(&self) -> usize {
+        self.scroll.offset() as usize
+    }
+
+    fn set_position(&mut self, position: usize) {
+        self.scroll
+            .set_offset(position.min(u16::MAX as usize) as u16);
+    }
+
+    fn nav_items(&self) -> Vec<Line<'static>> {
+        vec![line_from_spans(vec![
+            white_span("⇞/⇟ "),
+            gray_span("scroll"),
+        ])]
+    }
+}
@@ -0,0 +1,61 @@
+use crossterm::event::KeyCode;
+use ratatui::{
+    Frame,
+    layout::Rect,
+    text::Line,
+    widgets::{Block, Padding, Paragraph, Wrap},
+};
+
+use crate::pages::{
+    page::{Navigation, Page},
+    style::{gray_span, line_from_spans, white_span},
+};
+
+/// The last menu entry, for leaving without knowing about `q`.
+pub struct ExitPage;
+
+impl Page for ExitPage {
+    fn title(&self) -> &str {
+        "exit"
+    }
+
+    fn render(&self, frame: &mut Frame, area: Rect, is_focused: bool) {
+        let prompt = match is_focused {
+            true => line_from_spans(vec![white_span("↵ "), gray_span("disconnect")]),
+            false => line_from_spans(vec![gray_span("press enter to leave")]),
+        };
+        let text = vec![
+            line_from_spans(vec![white_span("thanks for stopping by!")]),
+            line_from_spans(vec![]),
+            prompt,
+        ];
+        frame.render_widget(
+            Paragraph::new(text).block(Block::new().padding(Padding::new(1, 2, 1, 0))),
+            area,
+        );
+    }
+
+    fn render_additional(&self, frame: &mut Frame, area: Rect, _is_focused: bool) {
+        let description = vec![
+            line_from_spans(vec![white_span("desc")]),
+            line_from_spans(vec![gray_span(
+                "closes the connection and leaves your terminal the way it was. q and ctrl+c do the same from anywhere",
+            )]),
+        ];
+        frame.render_widget(Paragraph::new(description).wrap(Wrap { trim: true }), area);
+    }
+
+    fn keyboard_event_handler(&mut self, key_code: KeyCode) -> Option<Navigation> {
+        match key_code {
+            KeyCode::Enter => Some(Navigation::Quit),
+            _ => None,
+        }
+    }
+
+    fn nav_items(&self) -> Vec<Line<'static>> {
+        vec![line_from_spans(vec![
+            white_span(" ↵  "),
+            gray_span("disconnect"),
+        ])]
+    }
+}
@@ -28,7 +28,7 @@ use crate::pages::{
 };
 use crate::pages::{
     labels::{container::LabelContainer, expo::Expo, react::react_native::ReactNative},
-    page::Page,
+    page::{Navigation, Page},
 };
 
 struct ExperienceItem {
@@ -51,6 +51,12 @@ pub struct Experience {
     show_tech_block: bool,
 }
 
+impl Default for Experience {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl Experience {
     pub fn new() -> Self {
         let show_widgets = env::var("SHOW_WIDGETS").unwrap_or_default();
@@ -189,7 +195,7 @@ impl Experience {
         let experience_item = &self.experiences[experience_index];
 
         for desc_part in &experience_item.description {
-            final_vec.push(line_from_spans(vec![gray_span(&desc_part)]));
+            final_vec.push(line_from_spans(vec![gray_span(desc_part)]));
         }
 
         final_vec
@@ -223,7 +229,7 @@ impl Page for Experience {
             };
 
             item.into_iter()
-                .map(|content| Cell::from(content))
+                .map(Cell::from)
                 .collect::<Row>()
                 .style(style_config)
                 .height(1)
@@ -287,7 +293,7 @@ impl Page for Experience {
         container.render(frame, tech_area);
     }
 
-    fn keyboard_event_handler(&mut self, key_code: KeyCode) {
+    fn keyboard_event_handler(&mut self, key_code: KeyCode) -> Option<Navigation> {
         match key_code {
             KeyCode::Char('k') | KeyCode::Up => {
                 self.previous_experience();
@@ -297,5 +303,14 @@ impl Page for Experience {
             }
             _ => {}
         }
+        None
+    }
+
+    fn position(&self) -> usize {
+        self.state
+    }
+
+    fn set_position(&mut self, position: usize) {
+        self.state = position.min(self.experiences.len() - 1);
     }
 }
@@ -0,0 +1,329 @@
+use std::sync::Arc;
+
+use crossterm::event::{KeyCode, KeyEvent};
+use rand::Rng;
+use ratatui::{
+    Frame,
+    layout::{Constraint, Layout, Rect},
+    style::{Color, Style},
+    text::{Line, Span},
+    widgets::{Block, Padding, Paragraph},
+};
+
+use crate::high_scores::{HighScores, Leaderboard};
+use crate::pages::{
+    arcade::{Player, leaderboard},
+    page::{Navigation, Page},
+    style::{BLACK, GRAY, WHITE, gray_span, gray_span_owned, line_from_spans, white_span},
+};
+use crate::preferences::SessionPreferences;
+
+const GAME: &str = "2048";
+const SIZE: usize = 4;
+// Each tile is this many cells, with the number in the middle row
+const TILE_WIDTH: usize = 7;
+const TILE_HEIGHT: usize = 3;
+// One new tile in ten is a 4
+const FOUR_ODDS: u32 = 10;
+const GOAL: u32 = 2048;
+
+type Board = [[u32; SIZE]; SIZE];
+
+#[derive(Clone, Copy)]
+enum Direction {
+    Up,
+    Down,
+    Left,
+    Right,
+}
+
+enum State {
+    Playing,
+    Over,
+}
+
+/// Slides one row towards its start, merging equal neighbours once each. Returns
+/// what the merges scored.
+fn slide(line: [u32; SIZE]) -> ([u32; SIZE], u32) {
+    let mut slid = [0; SIZE];
+    let mut score = 0;
+    let mut next = 0;
+    let mut merged = false;
+    for tile in line.into_iter().filter(|&tile| tile != 0) {
+        if next > 0 && slid[next - 1] == tile && !merged {
+            slid[next - 1] *= 2;
+            score += slid[next - 1];
+            merged = true;
+        } else {
+            slid[next] = tile;
+            next += 1;
+            merged = false;
+        }
+    }
+    (slid, score)
+}
+
+fn tile_style(tile: u32) -> Style {
+    let (background, foreground) = match tile {
+        2 => (Color::Rgb(238, 228, 218), BLACK),
+        4 => (Color::Rgb(237, 224, 200), BLACK),
+        8 => (Color::Rgb(242, 177, 121), WHITE),
+        16 => (Color::Rgb(245, 149, 99), WHITE),
+        32 => (Color::Rgb(246, 124, 95), WHITE),
+        64 => (Color::Rgb(246, 94, 59), WHITE),
+        128 => (Color::Rgb(237, 207, 114), WHITE),
+        256 => (Color::Rgb(237, 204, 97), WHITE),
+        512 => (Color::Rgb(237, 200, 80), WHITE),
+        1024 => (Color::Rgb(237, 197, 63), WHITE),
+        2048 => (Color::Rgb(237, 194, 46), WHITE),
+        _ => (Color::Rgb(60, 58, 50), WHITE),
+    };
+    Style::new().fg(foreground).bg(background)
+}
+
+/// 2048, from the arcade. Visitors who come back see the best tile they ever
+/// reached, kept like their preferences are.
+pub struct Game2048Page {
+    high_scores: Arc<HighScores>,
+    preferences: Arc<SessionPreferences>,
+    leaderboard: Leaderboard,
+    state: State,
+    board: Board,
+    score: u32,
+    best_tile: Option<u32>,
+}
+
+impl Game2048Page {
+    pub fn new(player: Player) -> Self {
+        let best_tile = player
+            .preferences
+            .owner()
+            .and_then(|owner| player.high_scores.personal_best(GAME, &owner));
+        let mut page = Self {
+            leaderboard: Leaderboard::new(player.high_scores.clone(), GAME),
+            high_scores: player.high_scores,
+            preferences: player.preferences,
+            state: State::Playing,
+            board: [[0; SIZE]; SIZE],
+            score: 0,
+            best_tile,
+        };
+        page.reset();
+        page
+    }
+
+    fn reset(&mut self) {
+        self.board = [[0; SIZE]; SIZE];
+        self.score = 0;
+        self.state = State::Playing;
+        self.spawn();
+        self.spawn();
+    }
+
+    fn spawn(&mut self) {
+        let empty = (0..SIZE)
+            .flat_map(|y| (0..SIZE).map(move |x| (x, y)))
+            .filter(|&(x, y)| self.board[y][x] == 0)
+            .collect::<Vec<_>>();
+        if empty.is_empty() {
+            return;
+        }
+        let (x, y) = empty[rand::rng().next_u32() as usize % empty.len()];
+        self.board[y][x] = match rand::rng().next_u32() % FOUR_ODDS {
+            0 => 4,
+            _ => 2,
+        };
+    }
+
+    /// The board after a move, and what it scored. Every direction is a slide
+    /// towards the start of the rows or columns, read in the right order.
+    fn moved(board: &Board, direction: Direction) -> (Board, u32) {
+        let mut moved = [[0; SIZE]; SIZE];
+        let mut score = 0;
+        for i in 0..SIZE {
+            let cell = |j: usize| match direction {
+                Direction::Left => (j, i),
+                Direction::Right => (SIZE - 1 - j, i),
+                Direction::Up => (i, j),
+                Direction::Down => (i, SIZE - 1 - j),
+            };
+            let line = std::array::from_fn(|j| {
+                let (x, y) = cell(j);
+                board[y][x]
+            });
+            let (slid, scored) = slide(line);
+            score += scored;
+            for (j, tile) in slid.into_iter().enumerate() {
+                let (x, y) = cell(j);
+                moved[y][x] = tile;
+            }
+        }
+        (moved, score)
+    }
+
+    fn play(&mut self, direction: Direction) {
+        let (moved, scored) = Self::moved(&self.board, direction);
+        if moved == self.board {
+            return;
+        }
+        self.board = moved;
+        self.score += scored;
+        self.spawn();
+        self.note_best_tile();
+
+        let stuck = [
+            Direction::Up,
+            Direction::Down,
+            Direction::Left,
+            Direction::Right,
+        ]
+        .into_iter()
+        .all(|direction| Self::moved(&self.board, direction).0 == self.board);
+        if stuck {
+            self.leaderboard.finish(self.score);
+            self.state = State::Over;
+        }
+    }
+
+    fn largest_tile(&self) -> u32 {
+        self.board.iter().flatten().copied().max().unwrap_or(0)
+    }
+
+    fn note_best_tile(&mut self) {
+        let tile = self.largest_tile();
+        if self.best_tile.is_some_and(|best| best >= tile) {
+            return;
+        }
+        self.best_tile = Some(tile);
+        if let Some(owner) = self.preferences.owner() {
+            self.high_scores.record_personal_best(GAME, &owner, tile);
+        }
+    }
+
+    fn board_lines(&self) -> Vec<Line<'static>> {
+        let border = Style::new().fg(GRAY);
+        let horizontal = "─".repeat(SIZE * TILE_WIDTH);
+        let mut lines = vec![Line::styled(format!("┌{}┐", horizontal), border)];
+        for row in &self.board {
+            for line in 0..TILE_HEIGHT {
+                let mut spans = vec![Span::styled("│", border)];
+                spans.extend(row.iter().map(|&tile| {
+                    let text = match (tile, line == TILE_HEIGHT / 2) {
+                        (0, true) => format!("{:^width$}", "·", width = TILE_WIDTH),
+                        (tile, true) => format!("{:^width$}", tile, width = TILE_WIDTH),
+                        (_, false) => " ".repeat(TILE_WIDTH),
+                    };
+                    match tile {
+                        0 => Span::styled(text, border),
+                        tile => Span::styled(text, tile_style(tile)),
+                    }
+                }));
+                spans.push(Span::styled("│", border));
+                lines.push(Line::from(spans));
+            }
+        }
+        lines.push(Line::styled(format!("└{}┘", horizontal), border));
+        lines
+    }
+
+    fn status(&self) -> Vec<Line<'static>> {
+        let mut lines = vec![
+            line_from_spans(vec![gray_span_owned(format!("score  {}", self.score))]),
+            line_from_spans(vec![gray_span_owned(format!(
+                "tile   {}",
+                self.largest_tile()
+            ))]),
+        ];
+        if let Some(best) = self.best_tile {
+            lines.push(line_from_spans(vec![gray_span_owned(format!(
+                "best   {}",
+                best
+            ))]));
+        }
+        lines.push(Line::from(""));
+        match &self.state {
+            State::Playing if self.largest_tile() >= GOAL => {
+                lines.push(line_from_spans(vec![white_span("2048! keep going")]));
+            }
+            State::Playing => {}
+            State::Over if let Some(initials) = self.leaderboard.initials() => {
+                lines.push(line_from_spans(vec![gray_span("a high score!")]));
+                let mut line = line_from_spans(vec![gray_span("your initials: ")]);
+                line.spans.extend(initials.line("", true).spans);
+                lines.push(line);
+            }
+            State::Over => {
+                lines.push(line_from_spans(vec![gray_span("no moves left")]));
+                lines.push(line_from_spans(vec![
+                    white_span("↵ "),
+                    gray_span("play again"),
+                ]));
+            }
+        }
+        lines
+    }
+}
+
+impl Page for Game2048Page {
+    fn title(&self) -> &str {
+        "2048"
+    }
+
+    fn render(&self, frame: &mut Frame, area: Rect, _is_focused: bool) {
+        let area = Block::new().padding(Padding::new(1, 2, 1, 0)).inner(area);
+        let [board_area, status_area] = Layout::horizontal([
+            Constraint::Length((SIZE * TILE_WIDTH) as u16 + 2),
+            Constraint::Fill(1),
+        ])
+        .spacing(3)
+        .areas(area);
+        frame.render_widget(Paragraph::new(self.board_lines()), board_area);
+
+        let status_area = Rect {
+            y: status_area.y + 1,
+            height: status_area.height.saturating_sub(1),
+            ..status_area
+        };
+        frame.render_widget(Paragraph::new(self.status()), status_area);
+    }
+
+    fn render_additional(&self, frame: &mut Frame, area: Rect, _is_focused: bool) {
+        frame.render_widget(Paragraph::new(leaderboard(self.leaderboard.table())), area);
+    }
+
+    fn keyboard_event_handler(&mut self, key_code: KeyCode) -> Option<Navigation> {
+        match key_code {
+            KeyCode::Esc | KeyCode::Char('q') => return Some(Navigation::Pop),
+            KeyCode::Enter if matches!(self.state, State::Over) => self.reset(),
+            _ if !matches!(self.state, State::Playing) => {}
+            KeyCode::Up | KeyCode::Char('k' | 'w') => self.play(Direction::Up),
+            KeyCode::Down | KeyCode::Char('j' | 's') => self.play(Direction::Down),
+            KeyCode::Left | KeyCode::Char('h' | 'a') => self.play(Direction::Left),
+            KeyCode::Right | KeyCode::Char('l' | 'd') => self.play(Direction::Right),
+            _ => {}
+        }
+        None
+    }
+
+    fn paste_event_handler(&mut self, text: &str) {
+        self.leaderboard.handle_paste(text);
+    }
+
+    fn key_event_handler(&mut self, key_event: KeyEvent) -> Option<Navigation> {
+        if self.leaderboard.handle_key_event(key_event) {
+            return None;
+        }
+        self.keyboard_event_handler(key_event.code)
+    }
+
+    fn captures_input(&self) -> bool {
+        true
+    }
+
+    fn nav_items(&self) -> Vec<Line<'static>> {
+        vec![line_from_spans(vec![
+            white_span("←↑↓→ "),
+            gray_span("slide"),
+        ])]
+    }
+}
@@ -0,0 +1,207 @@
+use std::sync::Arc;
+
+use crossterm::event::KeyCode;
+use ratatui::{
+    Frame,
+    layout::{Constraint, Rect},
+    text::Line,
+    widgets::{Block, Cell, Padding, Paragraph, Row, Table, Wrap},
+};
+
+use crate::github::{GitHub, Repo};
+use crate::pages::{
+    page::{Navigation, Page},
+    qr::QrPage,
+    style::{
+        dimmed_selected_style, gray_span, gray_span_owned, gray_style, line_from_spans, link_span,
+        selected_style, white_span, white_span_owned,
+    },
+};
+
+/// The public repos on GitHub, as last fetched by the server. Enter opens the
+/// selected one's details.
+pub struct GitHubPage {
+    github: Arc<GitHub>,
+    generation: u64,
+    repos: Arc<Vec<Repo>>,
+    selected: usize,
+}
+
+impl GitHubPage {
+    pub fn new(github: Arc<GitHub>) -> Self {
+        Self {
+            generation: github.generation(),
+            repos: github.repos(),
+            github,
+            selected: 0,
+        }
+    }
+}
+
+impl Page for GitHubPage {
+    fn title(&self) -> &str {
+        "github"
+    }
+
+    fn render(&self, frame: &mut Frame, area: Rect, is_focused: bool) {
+        let block = Block::new().padding(Padding::new(1, 2, 1, 0));
+        if self.repos.is_empty() {
+            let message = match self.generation {
+                0 => "fetching repos from github...",
+                _ => "no public repos yet",
+            };
+            frame.render_widget(
+                Paragraph::new(line_from_spans(vec![gray_span(message)])).block(block),
+                area,
+            );
+            return;
+        }
+
+        let header = ["name", "stars", "language"]
+            .into_iter()
+            .map(Cell::from)
+            .collect::<Row>()
+            .height(1);
+        let rows = self.repos.iter().enumerate().map(|(i, repo)| {
+            let style = match (i == self.selected, is_focused) {
+                (true, true) => selected_style(),
+                (true, false) => dimmed_selected_style(),
+                (false, _) => gray_style(),
+            };
+            Row::new([
+                Cell::from(repo.name.clone()),
+                Cell::from(format!("★ {}", repo.stars)),
+                Cell::from(repo.language.clone().unwrap_or_default()),
+            ])
+            .style(style)
+        });
+        let table = Table::new(
+            rows,
+            [
+                Constraint::Fill(1),
+                Constraint::Length(8),
+                Constraint::Length(12),
+            ],
+        )
+        .header(header)
+        .block(block);
+        frame.render_widget(table, area);
+    }
+
+    fn render_additional(&self, frame: &mut Frame, area: Rect, _is_focused: bool) {
+        let mut lines = vec![line_from_spans(vec![white_span("desc")])];
+        match self.repos.get(self.selected) {
+            Some(repo) => lines.push(line_from_spans(vec![gray_span(
+                repo.description.as_deref().unwrap_or("no description"),
+            )])),
+            None => lines.push(line_from_spans(vec![gray_span(
+                "my public repos, straight from github",
+            )])),
+        }
+        frame.render_widget(Paragraph::new(lines).wrap(Wrap { trim: true }), area);
+    }
+
+    fn keyboard_event_handler(&mut self, key_code: KeyCode) -> Option<Navigation> {
+        match key_code {
+            KeyCode::Up | KeyCode::Char('k') => self.selected = self.selected.saturating_sub(1),
+            KeyCode::Down | KeyCode::Char('j') => {
+                self.selected = (self.selected + 1).min(self.repos.len().saturating_sub(1));
+            }
+            KeyCode::Enter if self.selected < self.repos.len() => {
+                return Some(Navigation::Push(Box::new(RepoDetails {
+                    repos: self.repos.clone(),
+                    index: self.selected,
+                })));
+            }
+            _ => {}
+        }
+        None
+    }
+
+    fn on_tick(&mut self, _tick: u64) -> bool {
+        if self.github.generation() == self.generation {
+            return false;
+        }
+        self.generation = self.github.generation();
+        self.repos = self.github.repos();
+        self.selected = self.selected.min(self.repos.len().saturating_sub(1));
+        true
+    }
+
+    fn nav_items(&self) -> Vec<Line<'static>> {
+        vec![line_from_spans(vec![
+            white_span(" ↵  "),
+            gray_span("details"),
+        ])]
+    }
+}
+
+/// One repo, from the list it was opened from so a refresh doesn't swap it out
+/// underneath.
+struct RepoDetails {
+    repos: Arc<Vec<Repo>>,
+    index: usize,
+}
+
+impl RepoDetails {
+    fn repo(&self) -> &Repo {
+        &self.repos[self.index]
+    }
+}
+
+impl Page for RepoDetails {
+    fn title(&self) -> &str {
+        &self.repo().name
+    }
+
+    fn render(&self, frame: &mut Frame, area: Rect, _is_focused: bool) {
+        let repo = self.repo();
+        let detail = |label: &'static str, value: String| {
+            line_from_spans(vec![
+                gray_span_owned(format!("{:<10}", label)),
+                white_span_owned(value),
+            ])
+        };
+        let lines = vec![
+            line_from_spans(vec![white_span(&repo.name)]),
+            line_from_spans(vec![gray_span(
+                repo.description.as_deref().unwrap_or("no description"),
+            )]),
+            Line::from(""),
+            detail(
+                "language",
+                repo.language.clone().unwrap_or_else(|| String::from("-")),
+            ),
+            detail("stars", repo.stars.to_string()),
+            detail("forks", repo.forks.to_string()),
+            detail("updated", repo.pushed_on().to_string()),
+            Line::from(""),
+            line_from_spans(vec![link_span(&repo.url)]),
+        ];
+        frame.render_widget(
+            Paragraph::new(lines)
+                .wrap(Wrap { trim: true })
+                .block(Block::new().padding(Padding::new(1, 2, 1, 0))),
+            area,
+        );
+    }
+
+    fn render_additional(&self, frame: &mut Frame, area: Rect, _is_focused: bool) {
+        let lines = vec![
+            line_from_spans(vec![white_span("desc")]),
+            line_from_spans(vec![gray_span("press esc to go back to the list of repos")]),
+        ];
+        frame.render_widget(Paragraph::new(lines).wrap(Wrap { trim: true }), area);
+    }
+
+    fn keyboard_event_handler(&mut self, key_code: KeyCode) -> Option<Navigation> {
+        match key_code {
+            KeyCode::Char('s') => Some(Navigation::Push(Box::new(QrPage::new(&self.repo().url)))),
+            _ => None,
+        }
+    }
+
+    fn nav_items(&self) -> Vec<Line<'static>> {
+        vec![line_from_spans(vec![white_span(" s  "), gray_span("scan")])]
+    }
+}
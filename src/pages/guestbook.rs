@@ -0,0 +1,309 @@
+use std::sync::Arc;
+
+use crossterm::event::{KeyCode, KeyEvent};
+use ratatui::{
+    Frame,
+    layout::Rect,
+    style::{Color, Style},
+    text::{Line, Span},
+    widgets::{Paragraph, Wrap},
+};
+use tokio::sync::oneshot::{self, error::TryRecvError};
+
+use crate::guestbook::{Guestbook, MAX_MESSAGE_CHARS, MAX_NAME_CHARS};
+use crate::pages::{
+    page::{Navigation, Page},
+    scroll_view::ScrollView,
+    style::{gray_span, gray_span_owned, line_from_spans, white_span, white_span_owned},
+    text_input::TextInput,
+};
+
+/// Lists what earlier visitors wrote, newest first. Enter opens the form for
+/// signing it.
+pub struct GuestbookPage {
+    guestbook: Arc<Guestbook>,
+    // Who signs from this session, for the guestbook's rate limit
+    visitor: String,
+    generation: u64,
+    lines: Vec<Line<'static>>,
+    scroll: ScrollView,
+}
+
+impl GuestbookPage {
+    pub fn new(guestbook: Arc<Guestbook>, visitor: String) -> Self {
+        let mut page = Self {
+            guestbook,
+            visitor,
+            generation: 0,
+            lines: Vec::new(),
+            scroll: ScrollView::default(),
+        };
+        page.refresh();
+        page
+    }
+
+    fn refresh(&mut self) {
+        self.generation = self.guestbook.generation();
+        let entries = self.guestbook.entries();
+        self.lines = entries
+            .iter()
+            .flat_map(|entry| {
+                [
+                    line_from_spans(vec![
+                        white_span_owned(entry.name.clone()),
+                        gray_span_owned(format!(" · {}", entry.date)),
+                    ]),
+                    line_from_spans(vec![gray_span_owned(entry.message.clone())]),
+                    Line::from(""),
+                ]
+            })
+            .collect();
+        if self.lines.is_empty() {
+            self.lines.push(line_from_spans(vec![gray_span(
+                "no entries yet, be the first to sign!",
+            )]));
+        }
+    }
+}
+
+impl Page for GuestbookPage {
+    fn title(&self) -> &str {
+        "guestbook"
+    }
+
+    fn render(&self, frame: &mut Frame, area: Rect, is_focused: bool) {
+        let paragraph = Paragraph::new(self.lines.clone()).wrap(Wrap { trim: false });
+        self.scroll.render(frame, area, paragraph, is_focused);
+    }
+
+    fn render_additional(&self, frame: &mut Frame, area: Rect, _is_focused: bool) {
+        let description = vec![
+            line_from_spans(vec![white_span("desc")]),
+            line_from_spans(vec![gray_span(
+                "notes left by visitors before you. press enter to leave one of your own",
+            )]),
+        ];
+        frame.render_widget(Paragraph::new(description).wrap(Wrap { trim: true }), area);
+    }
+
+    fn keyboard_event_handler(&mut self, key_code: KeyCode) -> Option<Navigation> {
+        match key_code {
+            KeyCode::Enter => Some(Navigation::Push(Box::new(SignGuestbook::new(
+                self.guestbook.clone(),
+                self.visitor.clone(),
+            )))),
+            _ => {
+                self.scroll.handle_key(key_code);
+                None
+            }
+        }
+    }
+
+    fn position(&self) -> usize {
+        self.scroll.offset() as usize
+    }
+
+    fn set_position(&mut self, position: usize) {
+        self.scroll
+            .set_offset(position.min(u16::MAX as usize) as u16);
+    }
+
+    fn on_tick(&mut self, _tick: u64) -> bool {
+        if self.guestbook.generation() == self.generation {
+            return false;
+        }
+        self.refresh();
+        true
+    }
+
+    fn nav_items(&self) -> Vec<Line<'static>> {
+        vec![
+            line_from_spans(vec![white_span(" ↵  "), gray_span("sign")]),
+            line_from_spans(vec![white_span("⇞/⇟ "), gray_span("scroll")]),
+        ]
+    }
+}
+
+#[derive(PartialEq)]
+enum Field {
+    Name,
+    Message,
+}
+
+enum Status {
+    Editing,
+    Signing(oneshot::Receiver<bool>),
+    Signed,
+}
+
+/// The form for a new guestbook entry. It takes every key while open, so
+/// typing doesn't switch pages; Esc leaves without signing.
+struct SignGuestbook {
+    guestbook: Arc<Guestbook>,
+    visitor: String,
+    name: TextInput,
+    message: TextInput,
+    field: Field,
+    status: Status,
+    error: Option<String>,
+}
+
+impl SignGuestbook {
+    fn new(guestbook: Arc<Guestbook>, visitor: String) -> Self {
+        Self {
+            guestbook,
+            visitor,
+            name: TextInput::new(MAX_NAME_CHARS),
+            message: TextInput::new(MAX_MESSAGE_CHARS),
+            field: Field::Name,
+            status: Status::Editing,
+            error: None,
+        }
+    }
+
+    fn focused_input(&mut self) -> &mut TextInput {
+        match self.field {
+            Field::Name => &mut self.name,
+            Field::Message => &mut self.message,
+        }
+    }
+
+    fn switch_field(&mut self) {
+        self.field = match self.field {
+            Field::Name => Field::Message,
+            Field::Message => Field::Name,
+        };
+    }
+
+    fn submit(&mut self) {
+        match self
+            .guestbook
+            .sign(&self.visitor, &self.name.value(), &self.message.value())
+        {
+            Ok(saved) => {
+                self.status = Status::Signing(saved);
+                self.error = None;
+            }
+            Err(e) => self.error = Some(e.to_string()),
+        }
+    }
+}
+
+impl Page for SignGuestbook {
+    fn title(&self) -> &str {
+        "sign the guestbook"
+    }
+
+    fn render(&self, frame: &mut Frame, area: Rect, is_focused: bool) {
+        let editing = is_focused && matches!(self.status, Status::Editing);
+        let label = |text: &'static str, count: usize, max: usize| {
+            line_from_spans(vec![
+                white_span(text),
+                gray_span_owned(format!(" {}/{}", count, max)),
+            ])
+        };
+
+        let mut lines = vec![
+            label("name", self.name.char_count(), MAX_NAME_CHARS),
+            self.name
+                .line("who are you?", editing && self.field == Field::Name),
+            Line::from(""),
+            label("message", self.message.char_count(), MAX_MESSAGE_CHARS),
+            self.message
+                .line("say hi!", editing && self.field == Field::Message),
+        ];
+        match &self.status {
+            Status::Editing => {}
+            Status::Signing(_) => {
+                lines.push(Line::from(""));
+                lines.push(line_from_spans(vec![gray_span("signing...")]));
+            }
+            Status::Signed => {
+                lines.push(Line::from(""));
+                lines.push(line_from_spans(vec![
+                    white_span("signed, thank you! "),
+                    gray_span("press esc to go back"),
+                ]));
+            }
+        }
+        if let Some(error) = &self.error {
+            lines.push(Line::from(""));
+            lines.push(Line::from(Span::styled(
+                error.clone(),
+                Style::new().fg(Color::Red),
+            )));
+        }
+
+        frame.render_widget(Paragraph::new(lines).wrap(Wrap { trim: false }), area);
+    }
+
+    fn render_additional(&self, frame: &mut Frame, area: Rect, _is_focused: bool) {
+        let description = vec![
+            line_from_spans(vec![white_span("desc")]),
+            line_from_spans(vec![gray_span(
+                "your entry is shown to everyone who visits after you. be nice!",
+            )]),
+        ];
+        frame.render_widget(Paragraph::new(description).wrap(Wrap { trim: true }), area);
+    }
+
+    fn keyboard_event_handler(&mut self, key_code: KeyCode) -> Option<Navigation> {
+        if key_code == KeyCode::Esc {
+            return Some(Navigation::Pop);
+        }
+        if !matches!(self.status, Status::Editing) {
+            return None;
+        }
+        match key_code {
+            KeyCode::Enter if self.field == Field::Name => self.field = Field::Message,
+            KeyCode::Enter => self.submit(),
+            KeyCode::Tab | KeyCode::BackTab | KeyCode::Up | KeyCode::Down => self.switch_field(),
+            key_code => {
+                self.focused_input().handle_key(key_code);
+            }
+        }
+        None
+    }
+
+    fn paste_event_handler(&mut self, text: &str) {
+        if matches!(self.status, Status::Editing) {
+            self.focused_input().insert_str(text);
+        }
+    }
+
+    fn key_event_handler(&mut self, key_event: KeyEvent) -> Option<Navigation> {
+        if matches!(self.status, Status::Editing)
+            && self.focused_input().handle_modified_key(key_event)
+        {
+            return None;
+        }
+        self.keyboard_event_handler(key_event.code)
+    }
+
+    fn captures_input(&self) -> bool {
+        true
+    }
+
+    fn on_tick(&mut self, _tick: u64) -> bool {
+        let Status::Signing(saved) = &mut self.status else {
+            return false;
+        };
+        match saved.try_recv() {
+            Ok(true) => self.status = Status::Signed,
+            Ok(false) | Err(TryRecvError::Closed) => {
+                self.status = Status::Editing;
+                self.error = Some(String::from("couldn't save your entry, try again later"));
+            }
+            Err(TryRecvError::Empty) => {}
+        }
+        // Keeps ticking at full rate until the entry is saved
+        true
+    }
+
+    fn nav_items(&self) -> Vec<Line<'static>> {
+        vec![
+            line_from_spans(vec![white_span(" ↵  "), gray_span("sign")]),
+            line_from_spans(vec![white_span("tab "), gray_span("next field")]),
+        ]
+    }
+}
@@ -0,0 +1,88 @@
+use std::sync::{Arc, Mutex};
+
+use image::RgbImage;
+use ratatui::{Frame, layout::Rect, style::Color};
+
+use crate::graphics::{Image, Placement};
+
+/// An image scaled to fit an area, keeping its shape, in its top left corner. It
+/// is drawn in half blocks, two pixels to a cell, and notes where it went so the
+/// session can draw the real picture over it in terminals with graphics.
+pub struct ImageView {
+    image: Arc<Image>,
+    // Scaled for the half blocks at the size last drawn
+    scaled: Mutex<Option<RgbImage>>,
+    placement: Mutex<Option<Rect>>,
+}
+
+impl ImageView {
+    pub fn new(image: Arc<Image>) -> Self {
+        Self {
+            image,
+            scaled: Mutex::new(None),
+            placement: Mutex::new(None),
+        }
+    }
+
+    pub fn render(&self, frame: &mut Frame, area: Rect) {
+        if area.is_empty() {
+            return;
+        }
+        let pixels = self.image.pixels();
+        let (width, height) = (pixels.width().max(1), pixels.height().max(1));
+        // Half blocks come out about square, so the area is twice as tall in pixels
+        let scale = f64::min(
+            area.width as f64 / width as f64,
+            area.height as f64 * 2.0 / height as f64,
+        );
+        let scaled_width = ((width as f64 * scale) as u32).max(1);
+        let scaled_height = ((height as f64 * scale) as u32).max(1);
+
+        let mut scaled = self.scaled.lock().unwrap();
+        let scaled = match scaled.take() {
+            Some(image) if image.dimensions() == (scaled_width, scaled_height) => {
+                scaled.insert(image)
+            }
+            _ => scaled.insert(image::imageops::resize(
+                pixels,
+                scaled_width,
+                scaled_height,
+                image::imageops::FilterType::Triangle,
+            )),
+        };
+
+        let color = |x: u32, y: u32| {
+            let [r, g, b] = scaled.get_pixel(x, y).0;
+            Color::Rgb(r, g, b)
+        };
+        let buffer = frame.buffer_mut();
+        for y in (0..scaled_height).step_by(2) {
+            for x in 0..scaled_width {
+                let bottom = match y + 1 < scaled_height {
+                    true => color(x, y + 1),
+                    false => Color::Reset,
+                };
+                buffer[(area.x + x as u16, area.y + (y / 2) as u16)]
+                    .set_symbol("▀")
+                    .set_fg(color(x, y))
+                    .set_bg(bottom);
+            }
+        }
+        *self.placement.lock().unwrap() = Some(Rect::new(
+            area.x,
+            area.y,
+            scaled_width as u16,
+            scaled_height.div_ceil(2) as u16,
+        ));
+    }
+
+    /// Where the last frame drew it. Taken, so a frame that didn't draw it has
+    /// none.
+    pub fn take_placement(&self) -> Option<Placement> {
+        let area = self.placement.lock().unwrap().take()?;
+        Some(Placement {
+            area,
+            image: self.image.clone(),
+        })
+    }
+}
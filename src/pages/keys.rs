@@ -0,0 +1,86 @@
+use std::sync::Arc;
+
+use crossterm::event::KeyCode;
+use ratatui::{
+    Frame,
+    layout::{Constraint, Rect},
+    widgets::{Block, Cell, Padding, Paragraph, Row, Table, Wrap},
+};
+
+use crate::keymap::{KeyBindings, Keys, VIM_BINDINGS};
+use crate::pages::{
+    page::{Navigation, Page},
+    style::{gray_span, gray_style, line_from_spans, white_span, white_style},
+};
+use crate::preferences::SessionPreferences;
+
+/// Every key the app itself answers to, from the bindings in use, so it stays
+/// right however the config remaps them.
+pub struct KeysPage {
+    bindings: Arc<KeyBindings>,
+    preferences: Arc<SessionPreferences>,
+}
+
+impl KeysPage {
+    pub fn new(bindings: Arc<KeyBindings>, preferences: Arc<SessionPreferences>) -> Self {
+        Self {
+            bindings,
+            preferences,
+        }
+    }
+}
+
+impl Page for KeysPage {
+    fn title(&self) -> &str {
+        "keys"
+    }
+
+    fn render(&self, frame: &mut Frame, area: Rect, _is_focused: bool) {
+        let row = |keys: String, description: &'static str| {
+            Row::new([Cell::from(keys), Cell::from(description)]).style(gray_style())
+        };
+        let heading = |text: &'static str| Row::new([text]).style(white_style());
+
+        let mut rows = vec![heading("everywhere")];
+        rows.extend(
+            self.bindings
+                .describe()
+                .into_iter()
+                .map(|(keys, description)| row(keys, description)),
+        );
+
+        if self.preferences.get().keys == Keys::Vim {
+            rows.push(Row::new([""]));
+            rows.push(heading("vim"));
+            rows.extend(
+                VIM_BINDINGS
+                    .iter()
+                    .map(|(keys, action)| row(keys.to_string(), action.description())),
+            );
+        }
+
+        let table = Table::new(rows, [Constraint::Length(20), Constraint::Fill(1)]).block(
+            Block::new().padding(Padding {
+                left: 1,
+                right: 2,
+                top: 1,
+                bottom: 0,
+            }),
+        );
+        frame.render_widget(table, area);
+    }
+
+    fn render_additional(&self, frame: &mut Frame, area: Rect, _is_focused: bool) {
+        let description = vec![
+            line_from_spans(vec![white_span("desc")]),
+            line_from_spans(vec![gray_span(
+                "the keys that work on every page. pages list their own under the menu, and the vim keys can be turned on in settings",
+            )]),
+        ];
+        frame.render_widget(Paragraph::new(description).wrap(Wrap { trim: true }), area);
+    }
+
+    fn keyboard_event_handler(&mut self, _key_code: KeyCode) -> Option<Navigation> {
+        None
+    }
+}
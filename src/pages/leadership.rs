@@ -7,7 +7,7 @@ use ratatui::{
     widgets::{Block, Cell, Padding, Paragraph, Row, Table, Wrap},
 };
 
-use crate::pages::page::Page;
+use crate::pages::page::{Navigation, Page};
 use crate::pages::style::{
     dimmed_selected_style, gray_span, gray_style, line_from_spans, selected_style, white_span,
 };
@@ -30,6 +30,12 @@ pub struct Leadership {
     experiences: Vec<ExperienceItem>,
 }
 
+impl Default for Leadership {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl Leadership {
     pub fn new() -> Self {
         let experiences = vec![
@@ -82,7 +88,7 @@ impl Leadership {
         let experience_item = &self.experiences[experience_index];
 
         for desc_part in &experience_item.description {
-            final_vec.push(line_from_spans(vec![gray_span(&desc_part)]));
+            final_vec.push(line_from_spans(vec![gray_span(desc_part)]));
         }
 
         final_vec
@@ -116,7 +122,7 @@ impl Page for Leadership {
             };
 
             item.into_iter()
-                .map(|content| Cell::from(content))
+                .map(Cell::from)
                 .collect::<Row>()
                 .style(style_config)
                 .height(1)
@@ -149,7 +155,7 @@ impl Page for Leadership {
         frame.render_widget(paragraph, area);
     }
 
-    fn keyboard_event_handler(&mut self, key_code: KeyCode) {
+    fn keyboard_event_handler(&mut self, key_code: KeyCode) -> Option<Navigation> {
         match key_code {
             KeyCode::Char('k') | KeyCode::Up => {
                 self.previous_experience();
@@ -159,5 +165,14 @@ impl Page for Leadership {
             }
             _ => {}
         }
+        None
+    }
+
+    fn position(&self) -> usize {
+        self.state
+    }
+
+    fn set_position(&mut self, position: usize) {
+        self.state = position.min(self.experiences.len() - 1);
     }
 }
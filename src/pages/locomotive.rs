@@ -0,0 +1,144 @@
+use std::sync::atomic::{AtomicU16, Ordering};
+
+use crossterm::event::KeyCode;
+use ratatui::{
+    Frame,
+    layout::Rect,
+    style::Style,
+    text::Line,
+    widgets::{Paragraph, Wrap},
+};
+
+use crate::pages::{
+    page::{Navigation, Page},
+    style::{GRAY, WHITE, gray_span, line_from_spans, white_span},
+};
+
+const SMOKE: [[&str; 2]; 2] = [
+    ["      (@@)  (  )   (@)", "    (   )  (@@@)  ( )"],
+    ["     (  )  (@@@)  (  )", "   (@@)  (   )  (@@)"],
+];
+const BODY: [&str; 4] = [
+    "      ++      +------",
+    "      ||      |+-+ |",
+    "    /---------|| | |",
+    "   + ========  +-+ |",
+];
+const WHEELS: [[&str; 2]; 2] = [
+    ["  _|--O========O~\\-+", " //// \\_/      \\_/"],
+    ["  _|--/~\\------/~\\-+", " //// \\O/      \\O/"],
+];
+// Wide enough for every line above, so the last of the train leaves the screen
+const TRAIN_WIDTH: u16 = 23;
+// Ticks between changes of smoke and wheels
+const FRAME_TICKS: u64 = 3;
+
+/// A steam locomotive crossing the page, for typing `sl` where `ls` was meant.
+pub struct LocomotivePage {
+    // Columns the train has come in from the right edge
+    travelled: u16,
+    tick: u64,
+    // How wide the page was when last drawn, to know when the train is gone
+    width: AtomicU16,
+}
+
+impl LocomotivePage {
+    pub fn new() -> Self {
+        Self {
+            travelled: 0,
+            tick: 0,
+            width: AtomicU16::new(u16::MAX - TRAIN_WIDTH),
+        }
+    }
+
+    fn gone(&self) -> bool {
+        self.travelled >= self.width.load(Ordering::Relaxed) + TRAIN_WIDTH
+    }
+
+    fn train(&self) -> Vec<&'static str> {
+        let frame = (self.tick / FRAME_TICKS % 2) as usize;
+        SMOKE[frame]
+            .into_iter()
+            .chain(BODY)
+            .chain(WHEELS[frame])
+            .collect()
+    }
+}
+
+impl Default for LocomotivePage {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Page for LocomotivePage {
+    fn title(&self) -> &str {
+        "sl"
+    }
+
+    fn render(&self, frame: &mut Frame, area: Rect, _is_focused: bool) {
+        self.width.store(area.width, Ordering::Relaxed);
+        if self.gone() {
+            let lines = vec![
+                line_from_spans(vec![gray_span("the train has left the station")]),
+                line_from_spans(vec![]),
+                line_from_spans(vec![white_span("↵ "), gray_span("another one")]),
+            ];
+            frame.render_widget(Paragraph::new(lines), area);
+            return;
+        }
+
+        // Enters at the right edge, then slides off the left one
+        let column = area.width.saturating_sub(self.travelled) as usize;
+        let hidden = self.travelled.saturating_sub(area.width) as usize;
+        let train = self.train();
+        let top = area.height.saturating_sub(train.len() as u16) / 2;
+        let lines = train
+            .iter()
+            .enumerate()
+            .map(|(row, line)| {
+                let style = match row < SMOKE[0].len() {
+                    true => Style::new().fg(GRAY),
+                    false => Style::new().fg(WHITE),
+                };
+                let visible = line.get(hidden..).unwrap_or("");
+                Line::styled(format!("{}{}", " ".repeat(column), visible), style)
+            })
+            .collect::<Vec<_>>();
+        let area = Rect {
+            y: area.y + top,
+            height: area.height - top,
+            ..area
+        };
+        frame.render_widget(Paragraph::new(lines), area);
+    }
+
+    fn render_additional(&self, frame: &mut Frame, area: Rect, _is_focused: bool) {
+        let description = vec![
+            line_from_spans(vec![white_span("desc")]),
+            line_from_spans(vec![gray_span(
+                "you typed sl. it happens to the best of us",
+            )]),
+        ];
+        frame.render_widget(Paragraph::new(description).wrap(Wrap { trim: true }), area);
+    }
+
+    fn keyboard_event_handler(&mut self, key_code: KeyCode) -> Option<Navigation> {
+        match key_code {
+            KeyCode::Enter if self.gone() => {
+                self.travelled = 0;
+                None
+            }
+            _ => None,
+        }
+    }
+
+    fn on_tick(&mut self, tick: u64) -> bool {
+        if self.gone() {
+            return false;
+        }
+        self.tick = tick;
+        self.travelled += 1;
+        true
+    }
+}
@@ -0,0 +1,240 @@
+use pulldown_cmark::{CodeBlockKind, Event, HeadingLevel, Options, Parser, Tag, TagEnd};
+use ratatui::{
+    style::{Modifier, Style},
+    text::{Line, Span, Text},
+    widgets::{Paragraph, Wrap},
+};
+
+use crate::pages::code::CodeSnippet;
+use crate::pages::style::{code_style, dimmed_white_style, gray_style, link_style, white_style};
+
+/// Markdown source rendered once into styled text. Supports headings, emphasis,
+/// lists, block quotes, code with syntax highlighting and links.
+pub struct Markdown {
+    text: Text<'static>,
+}
+
+impl Markdown {
+    pub fn new(source: &str) -> Self {
+        Self {
+            text: to_text(source),
+        }
+    }
+
+    pub fn text(&self) -> &Text<'static> {
+        &self.text
+    }
+
+    /// The text wrapped to whatever area it is drawn in.
+    pub fn paragraph(&self) -> Paragraph<'static> {
+        Paragraph::new(self.text.clone()).wrap(Wrap { trim: false })
+    }
+}
+
+pub fn to_text(source: &str) -> Text<'static> {
+    let mut renderer = Renderer::default();
+    for event in Parser::new_ext(source, Options::ENABLE_STRIKETHROUGH) {
+        renderer.event(event);
+    }
+    renderer.finish()
+}
+
+#[derive(Default)]
+struct Renderer {
+    lines: Vec<Line<'static>>,
+    line: Vec<Span<'static>>,
+    styles: Vec<Style>,
+    // One entry per open list, holding the next number for ordered ones
+    lists: Vec<Option<u64>>,
+    quote_depth: usize,
+    // Language and source of the fenced code being read
+    code_block: Option<(String, String)>,
+    // Destination and text of the link being rendered
+    link: Option<(String, String)>,
+}
+
+impl Renderer {
+    fn event(&mut self, event: Event) {
+        match event {
+            Event::Start(tag) => self.start(tag),
+            Event::End(tag) => self.end(tag),
+            Event::Text(text) if self.code_block.is_some() => {
+                if let Some((_, code)) = &mut self.code_block {
+                    code.push_str(&text);
+                }
+            }
+            Event::Text(text) => self.push_text(&text),
+            Event::Code(code) => self.push(Span::styled(format!(" {} ", code), code_style())),
+            Event::SoftBreak => self.push_text(" "),
+            Event::HardBreak => self.flush_line(),
+            Event::Rule => {
+                self.flush_line();
+                self.lines.push(Line::styled("─".repeat(40), gray_style()));
+                self.blank_line();
+            }
+            _ => {}
+        }
+    }
+
+    fn start(&mut self, tag: Tag) {
+        match tag {
+            Tag::Heading { level, .. } => {
+                self.flush_line();
+                let style = match level {
+                    HeadingLevel::H1 => {
+                        white_style().add_modifier(Modifier::BOLD | Modifier::UNDERLINED)
+                    }
+                    HeadingLevel::H2 => white_style().add_modifier(Modifier::BOLD),
+                    _ => dimmed_white_style().add_modifier(Modifier::BOLD),
+                };
+                self.styles.push(style);
+            }
+            Tag::BlockQuote(_) => {
+                self.flush_line();
+                self.quote_depth += 1;
+            }
+            Tag::CodeBlock(kind) => {
+                self.flush_line();
+                let language = match kind {
+                    CodeBlockKind::Fenced(info) => info
+                        .split_whitespace()
+                        .next()
+                        .unwrap_or_default()
+                        .to_string(),
+                    CodeBlockKind::Indented => String::new(),
+                };
+                self.code_block = Some((language, String::new()));
+            }
+            Tag::List(start) => {
+                self.flush_line();
+                self.lists.push(start);
+            }
+            Tag::Item => {
+                self.flush_line();
+                self.start_line(self.lists.len().saturating_sub(1));
+                let bullet = match self.lists.last_mut() {
+                    Some(Some(number)) => {
+                        *number += 1;
+                        format!("{}. ", *number - 1)
+                    }
+                    _ => String::from("• "),
+                };
+                self.line.push(Span::styled(bullet, gray_style()));
+            }
+            Tag::Emphasis => self.push_style(Style::new().add_modifier(Modifier::ITALIC)),
+            Tag::Strong => self.push_style(Style::new().add_modifier(Modifier::BOLD)),
+            Tag::Strikethrough => self.push_style(Style::new().add_modifier(Modifier::CROSSED_OUT)),
+            Tag::Link { dest_url, .. } => {
+                self.link = Some((dest_url.into_string(), String::new()));
+                self.push_style(link_style());
+            }
+            _ => {}
+        }
+    }
+
+    fn end(&mut self, tag: TagEnd) {
+        match tag {
+            // Paragraphs inside list items would otherwise spread the list out
+            TagEnd::Paragraph if !self.lists.is_empty() => self.flush_line(),
+            TagEnd::Paragraph => self.blank_line(),
+            TagEnd::Heading(_) => {
+                self.styles.pop();
+                self.blank_line();
+            }
+            TagEnd::BlockQuote(_) => {
+                self.flush_line();
+                self.quote_depth -= 1;
+                if self.quote_depth == 0 {
+                    self.blank_line();
+                }
+            }
+            TagEnd::CodeBlock => {
+                if let Some((language, code)) = self.code_block.take() {
+                    for line in CodeSnippet::new(&code, &language).into_lines() {
+                        for span in line.spans {
+                            self.push(span);
+                        }
+                        self.flush_line();
+                    }
+                }
+                self.blank_line();
+            }
+            TagEnd::List(_) => {
+                self.lists.pop();
+                match self.lists.is_empty() {
+                    true => self.blank_line(),
+                    false => self.flush_line(),
+                }
+            }
+            TagEnd::Item => self.flush_line(),
+            TagEnd::Emphasis | TagEnd::Strong | TagEnd::Strikethrough => {
+                self.styles.pop();
+            }
+            TagEnd::Link => {
+                self.styles.pop();
+                // Terminals can't follow the link, so show where it goes unless the
+                // text already says it
+                if let Some((url, text)) = self.link.take()
+                    && url != text
+                {
+                    self.push(Span::styled(format!(" ({})", url), gray_style()));
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn current_style(&self) -> Style {
+        self.styles.last().copied().unwrap_or_else(white_style)
+    }
+
+    fn push_style(&mut self, style: Style) {
+        self.styles.push(self.current_style().patch(style));
+    }
+
+    fn push_text(&mut self, text: &str) {
+        if let Some((_, link_text)) = &mut self.link {
+            link_text.push_str(text);
+        }
+        self.push(Span::styled(text.to_string(), self.current_style()));
+    }
+
+    /// Adds a span, starting the line with the quote bars and list indentation it
+    /// sits under.
+    fn push(&mut self, span: Span<'static>) {
+        if self.line.is_empty() {
+            self.start_line(self.lists.len());
+        }
+        self.line.push(span);
+    }
+
+    fn start_line(&mut self, indent: usize) {
+        for _ in 0..self.quote_depth {
+            self.line.push(Span::styled("│ ", gray_style()));
+        }
+        if indent > 0 {
+            self.line.push(Span::raw("  ".repeat(indent)));
+        }
+    }
+
+    fn flush_line(&mut self) {
+        if !self.line.is_empty() {
+            self.lines.push(Line::from(std::mem::take(&mut self.line)));
+        }
+    }
+
+    fn blank_line(&mut self) {
+        self.flush_line();
+        if self.lines.last().is_some_and(|line| !line.spans.is_empty()) {
+            self.lines.push(Line::default());
+        }
+    }
+
+    fn finish(mut self) -> Text<'static> {
+        self.flush_line();
+        while self.lines.last().is_some_and(|line| line.spans.is_empty()) {
+            self.lines.pop();
+        }
+        Text::from(self.lines)
+    }
+}
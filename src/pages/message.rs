@@ -0,0 +1,281 @@
+use std::sync::Arc;
+
+use crossterm::event::{KeyCode, KeyEvent};
+use ratatui::{
+    Frame,
+    layout::Rect,
+    style::{Color, Style},
+    text::{Line, Span},
+    widgets::{Paragraph, Wrap},
+};
+use tokio::sync::oneshot::{self, error::TryRecvError};
+
+use crate::contact::{Contact, MAX_MESSAGE_CHARS, MAX_NAME_CHARS, MAX_REPLY_TO_CHARS, Submission};
+use crate::pages::{
+    page::{Navigation, Page},
+    style::{gray_span, gray_span_owned, line_from_spans, white_span},
+    text_input::TextInput,
+};
+
+/// Explains the contact form, which Enter opens.
+pub struct MessagePage {
+    contact: Arc<Contact>,
+    // Who writes from this session, for the form's rate limit
+    visitor: String,
+}
+
+impl MessagePage {
+    pub fn new(contact: Arc<Contact>, visitor: String) -> Self {
+        Self { contact, visitor }
+    }
+}
+
+impl Page for MessagePage {
+    fn title(&self) -> &str {
+        "message me"
+    }
+
+    fn render(&self, frame: &mut Frame, area: Rect, _is_focused: bool) {
+        let lines = vec![
+            line_from_spans(vec![gray_span(
+                "got a question, an opportunity or just want to say hi?",
+            )]),
+            Line::from(""),
+            line_from_spans(vec![
+                gray_span("press "),
+                white_span("enter"),
+                gray_span(" to write me a message. it goes straight to me, not to other visitors"),
+            ]),
+        ];
+        frame.render_widget(Paragraph::new(lines).wrap(Wrap { trim: false }), area);
+    }
+
+    fn render_additional(&self, frame: &mut Frame, area: Rect, _is_focused: bool) {
+        let description = vec![
+            line_from_spans(vec![white_span("desc")]),
+            line_from_spans(vec![gray_span(
+                "leave a way to reach you if you'd like an answer",
+            )]),
+        ];
+        frame.render_widget(Paragraph::new(description).wrap(Wrap { trim: true }), area);
+    }
+
+    fn keyboard_event_handler(&mut self, key_code: KeyCode) -> Option<Navigation> {
+        match key_code {
+            KeyCode::Enter => Some(Navigation::Push(Box::new(MessageForm::new(
+                self.contact.clone(),
+                self.visitor.clone(),
+            )))),
+            _ => None,
+        }
+    }
+
+    fn nav_items(&self) -> Vec<Line<'static>> {
+        vec![line_from_spans(vec![
+            white_span(" ↵  "),
+            gray_span("write"),
+        ])]
+    }
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Field {
+    Name,
+    ReplyTo,
+    Message,
+}
+
+impl Field {
+    fn next(self) -> Self {
+        match self {
+            Self::Name => Self::ReplyTo,
+            Self::ReplyTo => Self::Message,
+            Self::Message => Self::Name,
+        }
+    }
+
+    fn previous(self) -> Self {
+        match self {
+            Self::Name => Self::Message,
+            Self::ReplyTo => Self::Name,
+            Self::Message => Self::ReplyTo,
+        }
+    }
+}
+
+enum Status {
+    Editing,
+    Sending(oneshot::Receiver<bool>),
+    Sent,
+}
+
+/// The form itself. It takes every key while open; Esc leaves without sending.
+struct MessageForm {
+    contact: Arc<Contact>,
+    visitor: String,
+    name: TextInput,
+    reply_to: TextInput,
+    message: TextInput,
+    field: Field,
+    status: Status,
+    error: Option<String>,
+}
+
+impl MessageForm {
+    fn new(contact: Arc<Contact>, visitor: String) -> Self {
+        Self {
+            contact,
+            visitor,
+            name: TextInput::new(MAX_NAME_CHARS),
+            reply_to: TextInput::new(MAX_REPLY_TO_CHARS),
+            message: TextInput::new(MAX_MESSAGE_CHARS),
+            field: Field::Name,
+            status: Status::Editing,
+            error: None,
+        }
+    }
+
+    fn focused_input(&mut self) -> &mut TextInput {
+        match self.field {
+            Field::Name => &mut self.name,
+            Field::ReplyTo => &mut self.reply_to,
+            Field::Message => &mut self.message,
+        }
+    }
+
+    fn submit(&mut self) {
+        let submission = Submission {
+            name: self.name.value(),
+            reply_to: self.reply_to.value(),
+            message: self.message.value(),
+        };
+        match self.contact.send(&self.visitor, submission) {
+            Ok(delivered) => {
+                self.status = Status::Sending(delivered);
+                self.error = None;
+            }
+            Err(e) => self.error = Some(e.to_string()),
+        }
+    }
+}
+
+impl Page for MessageForm {
+    fn title(&self) -> &str {
+        "write me a message"
+    }
+
+    fn render(&self, frame: &mut Frame, area: Rect, is_focused: bool) {
+        let editing = is_focused && matches!(self.status, Status::Editing);
+        let label = |text: &'static str, count: usize, max: usize| {
+            line_from_spans(vec![
+                white_span(text),
+                gray_span_owned(format!(" {}/{}", count, max)),
+            ])
+        };
+
+        let mut lines = vec![
+            label("name", self.name.char_count(), MAX_NAME_CHARS),
+            self.name
+                .line("who are you?", editing && self.field == Field::Name),
+            Line::from(""),
+            label("reply to", self.reply_to.char_count(), MAX_REPLY_TO_CHARS),
+            self.reply_to.line(
+                "an email or handle, optional",
+                editing && self.field == Field::ReplyTo,
+            ),
+            Line::from(""),
+            label("message", self.message.char_count(), MAX_MESSAGE_CHARS),
+            self.message.line(
+                "what's on your mind?",
+                editing && self.field == Field::Message,
+            ),
+            Line::from(""),
+        ];
+        match &self.status {
+            Status::Editing => {}
+            Status::Sending(_) => lines.push(line_from_spans(vec![gray_span("sending...")])),
+            Status::Sent => lines.push(line_from_spans(vec![
+                white_span("sent, thank you! "),
+                gray_span("press esc to go back"),
+            ])),
+        }
+        if let Some(error) = &self.error {
+            lines.push(Line::from(Span::styled(
+                error.clone(),
+                Style::new().fg(Color::Red),
+            )));
+        }
+
+        frame.render_widget(Paragraph::new(lines).wrap(Wrap { trim: false }), area);
+    }
+
+    fn render_additional(&self, frame: &mut Frame, area: Rect, _is_focused: bool) {
+        let description = vec![
+            line_from_spans(vec![white_span("desc")]),
+            line_from_spans(vec![gray_span(
+                "only i get to read this, it isn't shown to anyone else",
+            )]),
+        ];
+        frame.render_widget(Paragraph::new(description).wrap(Wrap { trim: true }), area);
+    }
+
+    fn keyboard_event_handler(&mut self, key_code: KeyCode) -> Option<Navigation> {
+        if key_code == KeyCode::Esc {
+            return Some(Navigation::Pop);
+        }
+        if !matches!(self.status, Status::Editing) {
+            return None;
+        }
+        match key_code {
+            KeyCode::Enter if self.field == Field::Message => self.submit(),
+            KeyCode::Enter | KeyCode::Tab | KeyCode::Down => self.field = self.field.next(),
+            KeyCode::BackTab | KeyCode::Up => self.field = self.field.previous(),
+            key_code => {
+                self.focused_input().handle_key(key_code);
+            }
+        }
+        None
+    }
+
+    fn paste_event_handler(&mut self, text: &str) {
+        if matches!(self.status, Status::Editing) {
+            self.focused_input().insert_str(text);
+        }
+    }
+
+    fn key_event_handler(&mut self, key_event: KeyEvent) -> Option<Navigation> {
+        if matches!(self.status, Status::Editing)
+            && self.focused_input().handle_modified_key(key_event)
+        {
+            return None;
+        }
+        self.keyboard_event_handler(key_event.code)
+    }
+
+    fn captures_input(&self) -> bool {
+        true
+    }
+
+    fn on_tick(&mut self, _tick: u64) -> bool {
+        let Status::Sending(delivered) = &mut self.status else {
+            return false;
+        };
+        match delivered.try_recv() {
+            Ok(true) => self.status = Status::Sent,
+            Ok(false) | Err(TryRecvError::Closed) => {
+                self.status = Status::Editing;
+                self.error = Some(String::from("couldn't send your message, try again later"));
+            }
+            Err(TryRecvError::Empty) => {}
+        }
+        // Keeps ticking at full rate until the delivery is done
+        true
+    }
+
+    fn nav_items(&self) -> Vec<Line<'static>> {
+        vec![
+            line_from_spans(vec![white_span(" ↵  "), gray_span("next / send")]),
+            line_from_spans(vec![white_span("tab "), gray_span("next field")]),
+        ]
+    }
+}
@@ -1,7 +1,34 @@
 pub mod about;
+pub mod admin;
+pub mod arcade;
+pub mod blog;
+pub mod chat;
+pub mod code;
+pub mod content_page;
+pub mod exit;
 pub mod experience;
+pub mod game2048;
+pub mod github;
+pub mod guestbook;
+pub mod image;
+pub mod keys;
 pub mod labels;
 pub mod leadership;
+pub mod locomotive;
+pub mod markdown;
+pub mod message;
 pub mod page;
+pub mod poll;
+pub mod pong;
 pub mod projects;
+pub mod qr;
+pub mod scroll_view;
+pub mod settings;
+pub mod shell;
+pub mod snake;
+pub mod stats;
 pub mod style;
+pub mod tetris;
+pub mod text_input;
+pub mod tic_tac_toe;
+pub mod typing;
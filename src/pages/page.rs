@@ -1,14 +1,59 @@
-use crossterm::event::KeyCode;
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
 use ratatui::{Frame, layout::Rect, text::Line};
 
+use crate::graphics::Placement;
+
+/// What a page asks the app to do after handling a key.
+pub enum Navigation {
+    /// Opens a sub-page on top of the current one, e.g. the details of a list item.
+    Push(Box<dyn Page>),
+    /// Goes back to the page underneath.
+    Pop,
+    /// Ends the session, like `q`.
+    Quit,
+}
+
 pub trait Page: Send + Sync {
     fn title(&self) -> &str;
     fn render(&self, frame: &mut Frame, area: Rect, is_focused: bool);
     fn render_additional(&self, frame: &mut Frame, area: Rect, is_focused: bool);
-    fn keyboard_event_handler(&mut self, key_code: KeyCode);
+    fn keyboard_event_handler(&mut self, key_code: KeyCode) -> Option<Navigation>;
+    /// The key with its modifiers, for pages with a `TextInput` and its shortcuts.
+    /// Keys with Ctrl held only arrive while the page captures input.
+    fn key_event_handler(&mut self, key_event: KeyEvent) -> Option<Navigation> {
+        if key_event.modifiers.contains(KeyModifiers::CONTROL) {
+            return None;
+        }
+        self.keyboard_event_handler(key_event.code)
+    }
+    fn paste_event_handler(&mut self, _text: &str) {}
+    /// Whether every key but Ctrl+C goes to the page, e.g. while typing into a
+    /// form. The page then handles Esc itself.
+    fn captures_input(&self) -> bool {
+        false
+    }
+    /// Scrolls to the next place `query` appears, for the vim keys' `/`. False if
+    /// it's nowhere on the page.
+    fn search(&mut self, _query: &str) -> bool {
+        false
+    }
     fn on_tick(&mut self, _tick: u64) -> bool {
         false
     }
+    /// The terminal's new size, for pages that keep anything measured at the old
+    /// one. Every page hears about it, shown or not.
+    fn handle_resize(&mut self, _width: u16, _height: u16) {}
+    /// The images drawn in the last frame, for terminals that show them as
+    /// pictures instead of the half blocks in their cells.
+    fn images(&self) -> Vec<Placement> {
+        vec![]
+    }
+    /// How far into the page the visitor is, the line scrolled to or the item
+    /// selected, for picking up there when they reconnect.
+    fn position(&self) -> usize {
+        0
+    }
+    fn set_position(&mut self, _position: usize) {}
     fn nav_items(&self) -> Vec<Line<'static>> {
         vec![]
     }
@@ -0,0 +1,174 @@
+use std::sync::Arc;
+
+use crossterm::event::KeyCode;
+use ratatui::{
+    Frame,
+    layout::Rect,
+    style::{Color, Style},
+    text::{Line, Span},
+    widgets::{Block, Padding, Paragraph, Wrap},
+};
+
+use crate::pages::{
+    page::{Navigation, Page},
+    style::{
+        GRAY, LINK_COLOR, dimmed_selected_style, gray_span, gray_span_owned, line_from_spans,
+        selected_style, white_span, white_span_owned,
+    },
+};
+use crate::poll::{CHOICES, Poll, QUESTION};
+
+// Cells a bar takes at 100%
+const BAR_WIDTH: usize = 30;
+
+/// The poll with a bar per choice, which fill in as anyone votes.
+pub struct PollPage {
+    poll: Arc<Poll>,
+    visitor: String,
+    selected: usize,
+    voted: Option<usize>,
+    tallies: Vec<u64>,
+    generation: u64,
+    error: Option<String>,
+}
+
+impl PollPage {
+    /// `visitor` is whoever gets the vote, their key fingerprint or IP.
+    pub fn new(poll: Arc<Poll>, visitor: String) -> Self {
+        Self {
+            voted: poll.choice_of(&visitor),
+            tallies: poll.tallies(),
+            generation: poll.generation(),
+            selected: 0,
+            error: None,
+            visitor,
+            poll,
+        }
+    }
+
+    fn bar(&self, choice: usize, is_focused: bool) -> Line<'static> {
+        let votes = self.tallies.get(choice).copied().unwrap_or(0);
+        let total = self.tallies.iter().sum::<u64>();
+        let share = match total {
+            0 => 0.0,
+            _ => votes as f64 / total as f64,
+        };
+        let filled = (share * BAR_WIDTH as f64).round() as usize;
+
+        let name = format!(" {:<8}", CHOICES[choice]);
+        let name = match (self.voted, choice == self.selected, is_focused) {
+            (None, true, true) => Span::styled(name, selected_style()),
+            (None, true, false) => Span::styled(name, dimmed_selected_style()),
+            (Some(voted), _, _) if voted == choice => white_span_owned(name),
+            _ => gray_span_owned(name),
+        };
+        let mut spans = vec![
+            name,
+            Span::raw(" "),
+            Span::styled("█".repeat(filled), Style::new().fg(LINK_COLOR)),
+            Span::styled("░".repeat(BAR_WIDTH - filled), Style::new().fg(GRAY)),
+            gray_span_owned(format!(
+                " {:>3.0}% · {} {}",
+                share * 100.0,
+                votes,
+                match votes {
+                    1 => "vote",
+                    _ => "votes",
+                }
+            )),
+        ];
+        if self.voted == Some(choice) {
+            spans.push(white_span("  ✔ yours"));
+        }
+        line_from_spans(spans)
+    }
+}
+
+impl Page for PollPage {
+    fn title(&self) -> &str {
+        "poll"
+    }
+
+    fn render(&self, frame: &mut Frame, area: Rect, is_focused: bool) {
+        let mut lines = vec![line_from_spans(vec![white_span(QUESTION)]), Line::from("")];
+        lines.extend((0..CHOICES.len()).map(|choice| self.bar(choice, is_focused)));
+        lines.push(Line::from(""));
+        let total = self.tallies.iter().sum::<u64>();
+        lines.push(line_from_spans(vec![gray_span_owned(match self.voted {
+            Some(_) => format!("thanks for voting · {} so far", total),
+            None => String::from("one vote each, and it's for keeps"),
+        })]));
+        if let Some(error) = &self.error {
+            lines.push(Line::from(Span::styled(
+                error.clone(),
+                Style::new().fg(Color::Red),
+            )));
+        }
+
+        frame.render_widget(
+            Paragraph::new(lines).block(Block::new().padding(Padding::new(1, 2, 1, 0))),
+            area,
+        );
+    }
+
+    fn render_additional(&self, frame: &mut Frame, area: Rect, _is_focused: bool) {
+        let description = vec![
+            line_from_spans(vec![white_span("desc")]),
+            line_from_spans(vec![gray_span(
+                "settle it once and for all. votes are counted by a hash of your key or ip, so everyone gets one",
+            )]),
+        ];
+        frame.render_widget(Paragraph::new(description).wrap(Wrap { trim: true }), area);
+    }
+
+    fn keyboard_event_handler(&mut self, key_code: KeyCode) -> Option<Navigation> {
+        if self.voted.is_some() {
+            return None;
+        }
+        match key_code {
+            KeyCode::Char('k') | KeyCode::Up => self.selected = self.selected.saturating_sub(1),
+            KeyCode::Char('j') | KeyCode::Down => {
+                self.selected = (self.selected + 1).min(CHOICES.len() - 1);
+            }
+            KeyCode::Enter => match self.poll.vote(&self.visitor, self.selected) {
+                Ok(()) => {
+                    self.voted = Some(self.selected);
+                    self.error = None;
+                }
+                Err(e) => {
+                    // Voted in another session meanwhile
+                    self.voted = self.poll.choice_of(&self.visitor);
+                    self.error = Some(e.to_string());
+                }
+            },
+            _ => {}
+        }
+        None
+    }
+
+    fn position(&self) -> usize {
+        self.selected
+    }
+
+    fn set_position(&mut self, position: usize) {
+        self.selected = position.min(CHOICES.len() - 1);
+    }
+
+    /// Picks up votes counted since the last tick.
+    fn on_tick(&mut self, _tick: u64) -> bool {
+        let generation = self.poll.generation();
+        if generation == self.generation {
+            return false;
+        }
+        self.generation = generation;
+        self.tallies = self.poll.tallies();
+        true
+    }
+
+    fn nav_items(&self) -> Vec<Line<'static>> {
+        match self.voted {
+            Some(_) => Vec::new(),
+            None => vec![line_from_spans(vec![white_span(" ↵  "), gray_span("vote")])],
+        }
+    }
+}
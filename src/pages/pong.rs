@@ -0,0 +1,350 @@
+use std::collections::VecDeque;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use crossterm::event::{KeyCode, KeyEvent};
+use rand::Rng;
+use ratatui::{
+    Frame,
+    layout::Rect,
+    style::Style,
+    text::{Line, Span},
+    widgets::{Block, Padding, Paragraph},
+};
+
+use crate::high_scores::{HighScores, Leaderboard};
+use crate::pages::{
+    arcade::leaderboard,
+    page::{Navigation, Page},
+    style::{GRAY, WHITE, gray_span, gray_span_owned, line_from_spans, white_span},
+};
+
+const GAME: &str = "pong";
+const WIDTH: i16 = 56;
+// In rows. The ball moves in half rows, drawn with half blocks
+const HEIGHT: i16 = 14;
+const PADDLE: i16 = 4;
+const PLAYER_X: f32 = 1.0;
+const SERVER_X: f32 = (WIDTH - 2) as f32;
+const POINTS_TO_WIN: u32 = 5;
+// Speeds are per tick, so in columns and half rows
+const SERVE_SPEED: f32 = 0.5;
+const MAX_SPEED: f32 = 0.95;
+const SPEEDUP: f32 = 1.06;
+// How much steeper the ball leaves for each row off the paddle's middle
+const ANGLE: f32 = 0.22;
+// The server's paddle, in rows a tick, and how far off it may aim, in rows
+const SERVER_SPEED: f32 = 0.3;
+const SERVER_MISS: f32 = 2.5;
+const SERVE_DELAY: u64 = 30;
+// Ticks made up for at once after a slow frame, past it the game slows instead
+const MAX_CATCH_UP: u64 = 10;
+const FPS_WINDOW: Duration = Duration::from_secs(1);
+
+enum State {
+    Ready,
+    Playing,
+    Over,
+}
+
+/// A number between -1 and 1.
+fn random_unit() -> f32 {
+    rand::rng().next_u32() as f32 / u32::MAX as f32 * 2.0 - 1.0
+}
+
+/// Pong against the server, from the arcade. Everything moves a little each tick,
+/// so the ball is only as smooth as the session's frame rate, which the page shows.
+pub struct PongPage {
+    leaderboard: Leaderboard,
+    state: State,
+    // In columns and half rows
+    ball: (f32, f32),
+    velocity: (f32, f32),
+    // The top rows of the paddles
+    player: i16,
+    server: f32,
+    // Where on its paddle the server means to take the ball, chosen each return
+    aim: f32,
+    points: (u32, u32),
+    // The balls the player sent back, which is what the table keeps
+    returns: u32,
+    serve_in: u64,
+    last_tick: Option<u64>,
+    // When the frames of the last second were drawn, while playing
+    frames: VecDeque<Instant>,
+}
+
+impl PongPage {
+    pub fn new(high_scores: Arc<HighScores>) -> Self {
+        let mut page = Self {
+            leaderboard: Leaderboard::new(high_scores, GAME),
+            state: State::Ready,
+            ball: (0.0, 0.0),
+            velocity: (0.0, 0.0),
+            player: 0,
+            server: 0.0,
+            aim: 0.0,
+            points: (0, 0),
+            returns: 0,
+            serve_in: 0,
+            last_tick: None,
+            frames: VecDeque::new(),
+        };
+        page.reset();
+        page
+    }
+
+    fn reset(&mut self) {
+        self.player = (HEIGHT - PADDLE) / 2;
+        self.server = ((HEIGHT - PADDLE) / 2) as f32;
+        self.points = (0, 0);
+        self.returns = 0;
+        self.last_tick = None;
+        self.frames.clear();
+        self.serve(-1.0);
+    }
+
+    /// Puts the ball in the middle, to leave towards `direction` after a moment.
+    fn serve(&mut self, direction: f32) {
+        self.ball = ((WIDTH / 2) as f32, HEIGHT as f32);
+        self.velocity = (SERVE_SPEED * direction, random_unit() * ANGLE);
+        self.aim = random_unit() * SERVER_MISS;
+        self.serve_in = SERVE_DELAY;
+    }
+
+    fn move_player(&mut self, rows: i16) {
+        self.player = (self.player + rows).clamp(0, HEIGHT - PADDLE);
+    }
+
+    /// The server follows the ball when it's coming, and goes back to the
+    /// middle when it isn't, never faster than a person could.
+    fn move_server(&mut self) {
+        let middle = (PADDLE as f32) / 2.0;
+        let target = match self.velocity.0 > 0.0 {
+            true => self.ball.1 / 2.0 + self.aim - middle,
+            false => ((HEIGHT - PADDLE) / 2) as f32,
+        };
+        let step = (target - self.server).clamp(-SERVER_SPEED, SERVER_SPEED);
+        self.server = (self.server + step).clamp(0.0, (HEIGHT - PADDLE) as f32);
+    }
+
+    /// Sends the ball back off a paddle, steeper the further from its middle.
+    fn bounce(&mut self, top: i16, row: i16) {
+        let offset = (row - top) as f32 + 0.5 - (PADDLE as f32) / 2.0;
+        let speed = (self.velocity.0.abs() * SPEEDUP).min(MAX_SPEED);
+        self.velocity = (-self.velocity.0.signum() * speed, offset * ANGLE);
+    }
+
+    fn step(&mut self) {
+        self.move_server();
+        if self.serve_in > 0 {
+            self.serve_in -= 1;
+            return;
+        }
+
+        let (x, y) = self.ball;
+        let (mut next_x, mut next_y) = (x + self.velocity.0, y + self.velocity.1);
+        let bottom = (HEIGHT * 2) as f32;
+        if next_y < 0.0 || next_y >= bottom {
+            next_y = match next_y < 0.0 {
+                true => -next_y,
+                false => 2.0 * bottom - next_y,
+            }
+            .clamp(0.0, bottom - 0.01);
+            self.velocity.1 = -self.velocity.1;
+        }
+        let row = (next_y / 2.0) as i16;
+
+        // The ball only ever crosses a paddle's column from the inside
+        let player_face = PLAYER_X + 1.0;
+        if x >= player_face
+            && next_x < player_face
+            && (self.player..self.player + PADDLE).contains(&row)
+        {
+            next_x = 2.0 * player_face - next_x;
+            self.bounce(self.player, row);
+            self.returns += 1;
+            self.aim = random_unit() * SERVER_MISS;
+        }
+        if x < SERVER_X && next_x >= SERVER_X {
+            let server = self.server.round() as i16;
+            if (server..server + PADDLE).contains(&row) {
+                next_x = 2.0 * SERVER_X - next_x - 0.01;
+                self.bounce(server, row);
+            }
+        }
+        self.ball = (next_x, next_y);
+
+        if next_x < 0.0 {
+            self.points.1 += 1;
+            self.scored(-1.0);
+        } else if next_x >= WIDTH as f32 {
+            self.points.0 += 1;
+            self.scored(1.0);
+        }
+    }
+
+    /// After a point, the ball goes to whoever missed it, unless the match is over.
+    fn scored(&mut self, towards: f32) {
+        if self.points.0 < POINTS_TO_WIN && self.points.1 < POINTS_TO_WIN {
+            self.serve(towards);
+            return;
+        }
+        self.leaderboard.finish(self.returns);
+        self.state = State::Over;
+    }
+
+    fn cell(&self, cell: (i16, i16)) -> Span<'static> {
+        let (x, row) = cell;
+        let server = self.server.round() as i16;
+        let paddle = |top: i16| (top..top + PADDLE).contains(&row);
+        let (ball_x, ball_y) = (self.ball.0 as i16, self.ball.1 as i16);
+        if x == PLAYER_X as i16 && paddle(self.player) {
+            Span::styled("█", Style::new().fg(WHITE))
+        } else if x == SERVER_X as i16 && paddle(server) {
+            Span::styled("█", Style::new().fg(GRAY))
+        } else if x == ball_x && row == ball_y / 2 {
+            let half = match ball_y % 2 {
+                0 => "▀",
+                _ => "▄",
+            };
+            Span::styled(half, Style::new().fg(WHITE))
+        } else if x == WIDTH / 2 && row % 2 == 0 {
+            Span::styled("┊", Style::new().fg(GRAY))
+        } else {
+            Span::raw(" ")
+        }
+    }
+
+    fn status(&self) -> Line<'static> {
+        let points = format!("you {} · server {}", self.points.0, self.points.1);
+        match &self.state {
+            State::Ready => line_from_spans(vec![
+                white_span("↵ "),
+                gray_span_owned(format!("start, first to {} wins", POINTS_TO_WIN)),
+            ]),
+            State::Playing => line_from_spans(vec![gray_span_owned(format!(
+                "{} · {} returned · {} fps",
+                points,
+                self.returns,
+                self.frames.len()
+            ))]),
+            State::Over if let Some(initials) = self.leaderboard.initials() => {
+                let mut line = line_from_spans(vec![gray_span_owned(format!(
+                    "{} returned, a high score! your initials: ",
+                    self.returns
+                ))]);
+                line.spans.extend(initials.line("", true).spans);
+                line
+            }
+            State::Over => {
+                let winner = match self.points.0 >= POINTS_TO_WIN {
+                    true => "you won",
+                    false => "the server won",
+                };
+                line_from_spans(vec![
+                    gray_span_owned(format!("{}, {} · ", winner, points)),
+                    white_span("↵ "),
+                    gray_span("play again"),
+                ])
+            }
+        }
+    }
+}
+
+impl Page for PongPage {
+    fn title(&self) -> &str {
+        "pong"
+    }
+
+    fn render(&self, frame: &mut Frame, area: Rect, _is_focused: bool) {
+        let mut lines = vec![self.status(), Line::from("")];
+        let border = Style::new().fg(GRAY);
+        let horizontal = "─".repeat(WIDTH as usize);
+        lines.push(Line::styled(format!("┌{}┐", horizontal), border));
+        for row in 0..HEIGHT {
+            let mut spans = vec![Span::styled("│", border)];
+            spans.extend((0..WIDTH).map(|x| self.cell((x, row))));
+            spans.push(Span::styled("│", border));
+            lines.push(Line::from(spans));
+        }
+        lines.push(Line::styled(format!("└{}┘", horizontal), border));
+
+        frame.render_widget(
+            Paragraph::new(lines).block(Block::new().padding(Padding::new(1, 2, 1, 0))),
+            area,
+        );
+    }
+
+    fn render_additional(&self, frame: &mut Frame, area: Rect, _is_focused: bool) {
+        frame.render_widget(Paragraph::new(leaderboard(self.leaderboard.table())), area);
+    }
+
+    fn keyboard_event_handler(&mut self, key_code: KeyCode) -> Option<Navigation> {
+        match key_code {
+            KeyCode::Esc | KeyCode::Char('q') => return Some(Navigation::Pop),
+            KeyCode::Up | KeyCode::Char('k' | 'w') => self.move_player(-1),
+            KeyCode::Down | KeyCode::Char('j' | 's') => self.move_player(1),
+            KeyCode::Enter | KeyCode::Char(' ') => match self.state {
+                State::Ready => self.state = State::Playing,
+                State::Over => {
+                    self.reset();
+                    self.state = State::Playing;
+                }
+                _ => {}
+            },
+            _ => {}
+        }
+        None
+    }
+
+    fn paste_event_handler(&mut self, text: &str) {
+        self.leaderboard.handle_paste(text);
+    }
+
+    fn key_event_handler(&mut self, key_event: KeyEvent) -> Option<Navigation> {
+        if self.leaderboard.handle_key_event(key_event) {
+            return None;
+        }
+        self.keyboard_event_handler(key_event.code)
+    }
+
+    fn captures_input(&self) -> bool {
+        true
+    }
+
+    // Ticks can come two at a time at a lower frame rate, so the game steps once
+    // for each and moves as fast whatever the rate
+    fn on_tick(&mut self, tick: u64) -> bool {
+        if !matches!(self.state, State::Playing) {
+            self.last_tick = None;
+            return false;
+        }
+        let now = Instant::now();
+        self.frames.push_back(now);
+        while self
+            .frames
+            .front()
+            .is_some_and(|drawn| now.duration_since(*drawn) > FPS_WINDOW)
+        {
+            self.frames.pop_front();
+        }
+
+        let elapsed = match self.last_tick {
+            Some(last) => tick.wrapping_sub(last).min(MAX_CATCH_UP),
+            None => 1,
+        };
+        self.last_tick = Some(tick);
+        for _ in 0..elapsed {
+            self.step();
+            if !matches!(self.state, State::Playing) {
+                break;
+            }
+        }
+        true
+    }
+
+    fn nav_items(&self) -> Vec<Line<'static>> {
+        vec![line_from_spans(vec![white_span("↑/↓ "), gray_span("move")])]
+    }
+}
@@ -29,7 +29,7 @@ use crate::pages::{
         typescript::TypeScript,
         websocket::WebSocket,
     },
-    page::Page,
+    page::{Navigation, Page},
     style::{
         dimmed_selected_style, gray_span, gray_style, line_from_spans, selected_style, white_span,
     },
@@ -108,7 +108,7 @@ impl Page for Projects {
             };
 
             item.into_iter()
-                .map(|content| Cell::from(content))
+                .map(Cell::from)
                 .collect::<Row>()
                 .style(style_config)
                 .height(1)
@@ -164,7 +164,7 @@ impl Page for Projects {
         container.render(frame, tech_area);
     }
 
-    fn keyboard_event_handler(&mut self, key_code: KeyCode) {
+    fn keyboard_event_handler(&mut self, key_code: KeyCode) -> Option<Navigation> {
         match key_code {
             KeyCode::Char('k') | KeyCode::Up => {
                 self.previous_project();
@@ -179,12 +179,22 @@ impl Page for Projects {
             }
             _ => {}
         }
+        None
     }
 
     fn nav_items(&self) -> Vec<Line<'static>> {
         vec![line_from_spans(vec![white_span(" ↵  "), gray_span("copy")])]
     }
 
+    fn position(&self) -> usize {
+        self.state
+    }
+
+    fn set_position(&mut self, position: usize) {
+        self.state = position.min(self.projects.len() - 1);
+        self.change_current_link();
+    }
+
     fn on_tick(&mut self, tick: u64) -> bool {
         self.current_tick = tick;
         if self.show_tooltip && tick >= self.tooltip_end_tick {
@@ -194,6 +204,12 @@ impl Page for Projects {
     }
 }
 
+impl Default for Projects {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl Projects {
     pub fn new() -> Self {
         let projects = vec![
@@ -353,15 +369,15 @@ impl Projects {
         let project_item = &self.projects[project_index];
 
         for prize in &project_item.prizes {
-            final_vec.push(line_from_spans(vec![gray_span(&prize)]));
+            final_vec.push(line_from_spans(vec![gray_span(prize)]));
         }
 
-        if project_item.prizes.len() > 0 {
+        if !project_item.prizes.is_empty() {
             final_vec.push(Line::from(""));
         }
 
         for desc_part in &project_item.description {
-            final_vec.push(line_from_spans(vec![gray_span(&desc_part)]));
+            final_vec.push(line_from_spans(vec![gray_span(desc_part)]));
         }
 
         final_vec
@@ -0,0 +1,145 @@
+use crossterm::event::KeyCode;
+use qrcode::EcLevel;
+use ratatui::{
+    Frame,
+    buffer::Buffer,
+    layout::{Constraint, Flex, Layout, Rect},
+    style::Color,
+    widgets::{Block, Padding, Paragraph, Widget, Wrap},
+};
+
+use crate::hyperlinks::url_for;
+use crate::pages::{
+    page::{Navigation, Page},
+    style::{gray_span, line_from_spans, link_span, white_span},
+};
+
+// Light modules around the code, which scanners need to find its edges
+const QUIET_ZONE: usize = 2;
+// Just off the palette's white and black, so themes leave the code readable
+const LIGHT: Color = Color::Rgb(254, 254, 254);
+const DARK: Color = Color::Rgb(1, 1, 1);
+
+/// A QR code drawn with half blocks, two modules to a cell, centered in its area.
+pub struct QrCode {
+    // Whether each module is dark, rows first, quiet zone included
+    modules: Vec<Vec<bool>>,
+}
+
+impl QrCode {
+    /// None if `data` is too long for any QR code.
+    pub fn new(data: &str) -> Option<Self> {
+        let code = qrcode::QrCode::with_error_correction_level(data, EcLevel::L).ok()?;
+        let width = code.width();
+        let size = width + QUIET_ZONE * 2;
+        let modules = (0..size)
+            .map(|y| {
+                (0..size)
+                    .map(|x| {
+                        let (Some(x), Some(y)) =
+                            (x.checked_sub(QUIET_ZONE), y.checked_sub(QUIET_ZONE))
+                        else {
+                            return false;
+                        };
+                        x < width && y < width && code[(x, y)] == qrcode::Color::Dark
+                    })
+                    .collect()
+            })
+            .collect();
+        Some(Self { modules })
+    }
+
+    pub fn width(&self) -> u16 {
+        self.modules.len() as u16
+    }
+
+    pub fn height(&self) -> u16 {
+        self.modules.len().div_ceil(2) as u16
+    }
+}
+
+impl Widget for &QrCode {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        // Half a code scans as nothing, so too small an area says so instead
+        if area.width < self.width() || area.height < self.height() {
+            Paragraph::new(line_from_spans(vec![gray_span(
+                "make the window bigger to see the code",
+            )]))
+            .wrap(Wrap { trim: true })
+            .render(area, buf);
+            return;
+        }
+
+        let [area] = Layout::horizontal([Constraint::Length(self.width())])
+            .flex(Flex::Center)
+            .areas(area);
+        let color = |dark: bool| if dark { DARK } else { LIGHT };
+        for (row, pair) in self.modules.chunks(2).enumerate() {
+            for (column, &top) in pair[0].iter().enumerate() {
+                let bottom = pair.get(1).is_some_and(|modules| modules[column]);
+                buf[(area.x + column as u16, area.y + row as u16)]
+                    .set_symbol("▀")
+                    .set_fg(color(top))
+                    .set_bg(color(bottom));
+            }
+        }
+    }
+}
+
+/// A link as a QR code, opened from a page showing it, so a visitor reading on a
+/// desktop can follow it on their phone.
+pub struct QrPage {
+    link: String,
+    code: Option<QrCode>,
+}
+
+impl QrPage {
+    /// `link` as shown on the page, which may leave out the scheme.
+    pub fn new(link: &str) -> Self {
+        let url = url_for(link).unwrap_or_else(|| link.to_string());
+        Self {
+            link: link.to_string(),
+            code: QrCode::new(&url),
+        }
+    }
+}
+
+impl Page for QrPage {
+    fn title(&self) -> &str {
+        "scan"
+    }
+
+    fn render(&self, frame: &mut Frame, area: Rect, _is_focused: bool) {
+        let area = Block::new().padding(Padding::new(1, 2, 1, 0)).inner(area);
+        let [text_area, code_area] =
+            Layout::vertical([Constraint::Length(2), Constraint::Fill(1)]).areas(area);
+
+        let text = line_from_spans(vec![
+            gray_span("scan to open "),
+            link_span(&self.link),
+            gray_span(" on your phone"),
+        ]);
+        frame.render_widget(Paragraph::new(text).wrap(Wrap { trim: true }), text_area);
+        match &self.code {
+            Some(code) => frame.render_widget(code, code_area),
+            None => frame.render_widget(
+                Paragraph::new(line_from_spans(vec![gray_span(
+                    "this link is too long for a code",
+                )])),
+                code_area,
+            ),
+        }
+    }
+
+    fn render_additional(&self, frame: &mut Frame, area: Rect, _is_focused: bool) {
+        let description = vec![
+            line_from_spans(vec![white_span("desc")]),
+            line_from_spans(vec![gray_span("press esc to go back")]),
+        ];
+        frame.render_widget(Paragraph::new(description).wrap(Wrap { trim: true }), area);
+    }
+
+    fn keyboard_event_handler(&mut self, _key_code: KeyCode) -> Option<Navigation> {
+        None
+    }
+}
@@ -0,0 +1,139 @@
+use std::sync::atomic::{AtomicU16, Ordering};
+
+use crossterm::event::KeyCode;
+use ratatui::{
+    Frame,
+    layout::Rect,
+    symbols,
+    text::Line,
+    widgets::{Paragraph, Scrollbar, ScrollbarOrientation, ScrollbarState, Wrap},
+};
+
+use crate::pages::style::{GRAY, WHITE};
+
+/// Scroll position for a paragraph that can be taller than the area it is drawn
+/// in. Every page keeps its own, so switching pages and back keeps the position.
+#[derive(Default)]
+pub struct ScrollView {
+    offset: u16,
+    // Measured while rendering, since that is the only place the area is known
+    max_offset: AtomicU16,
+    page_height: AtomicU16,
+    text_width: AtomicU16,
+    // The line the last search scrolled to, so the next one carries on after it
+    last_match: Option<usize>,
+}
+
+impl ScrollView {
+    /// Moves the view for the arrow, page and Home/End keys. Returns false for any
+    /// other key.
+    pub fn handle_key(&mut self, key_code: KeyCode) -> bool {
+        let max_offset = self.max_offset.load(Ordering::Relaxed);
+        let page = self
+            .page_height
+            .load(Ordering::Relaxed)
+            .saturating_sub(1)
+            .max(1);
+        self.offset = match key_code {
+            KeyCode::Up | KeyCode::Char('k') => self.offset.saturating_sub(1),
+            KeyCode::Down | KeyCode::Char('j') => self.offset.saturating_add(1),
+            KeyCode::PageUp => self.offset.saturating_sub(page),
+            KeyCode::PageDown => self.offset.saturating_add(page),
+            KeyCode::Home => 0,
+            KeyCode::End => max_offset,
+            _ => return false,
+        }
+        .min(max_offset);
+        true
+    }
+
+    pub fn offset(&self) -> u16 {
+        self.offset
+    }
+
+    /// Scrolls to `offset`, or as far as the text goes once it's drawn.
+    pub fn set_offset(&mut self, offset: u16) {
+        self.offset = offset;
+    }
+
+    /// Scrolls to the next of `lines` containing `query`, ignoring case, after the
+    /// last match or the top of the view, and wrapping around to the start.
+    /// `lines` have to be the ones rendered, wrapped without trimming.
+    pub fn search(&mut self, lines: &[Line], query: &str) -> bool {
+        let query = query.to_lowercase();
+        let width = self.text_width.load(Ordering::Relaxed);
+        // The row each line starts on once wrapped
+        let mut rows = Vec::with_capacity(lines.len());
+        let mut row = 0u16;
+        for line in lines {
+            rows.push(row);
+            let height = match width {
+                0 => 1,
+                width => Paragraph::new(line.clone())
+                    .wrap(Wrap { trim: false })
+                    .line_count(width) as u16,
+            };
+            row = row.saturating_add(height);
+        }
+
+        let top = rows
+            .iter()
+            .rposition(|&row| row <= self.offset)
+            .unwrap_or(0);
+        let start = match self.last_match {
+            Some(last) if last >= top => last + 1,
+            _ => top + 1,
+        };
+        let found = (start..lines.len())
+            .chain(0..start.min(lines.len()))
+            .find(|&i| {
+                let text: String = lines[i]
+                    .spans
+                    .iter()
+                    .map(|span| span.content.as_ref())
+                    .collect();
+                text.to_lowercase().contains(&query)
+            });
+        let Some(found) = found else {
+            return false;
+        };
+        self.last_match = Some(found);
+        self.offset = rows[found].min(self.max_offset.load(Ordering::Relaxed));
+        true
+    }
+
+    /// Draws the visible part of `paragraph`, with a scrollbar on the right edge
+    /// when it doesn't fit.
+    pub fn render(&self, frame: &mut Frame, area: Rect, paragraph: Paragraph, is_focused: bool) {
+        // Leave a column free for the scrollbar so it never covers text
+        let text_area = Rect {
+            width: area.width.saturating_sub(2),
+            ..area
+        };
+        let line_count = paragraph.line_count(text_area.width) as u16;
+        let max_offset = line_count.saturating_sub(area.height);
+        self.max_offset.store(max_offset, Ordering::Relaxed);
+        self.page_height.store(area.height, Ordering::Relaxed);
+        self.text_width.store(text_area.width, Ordering::Relaxed);
+
+        // The content may have shrunk since the last key press, e.g. after a resize
+        let offset = self.offset.min(max_offset);
+        frame.render_widget(paragraph.scroll((offset, 0)), text_area);
+
+        if max_offset == 0 {
+            return;
+        }
+        let thumb_color = if is_focused { WHITE } else { GRAY };
+        let mut state = ScrollbarState::new(max_offset as usize).position(offset as usize);
+        frame.render_stateful_widget(
+            Scrollbar::new(ScrollbarOrientation::VerticalRight)
+                .symbols(symbols::scrollbar::VERTICAL)
+                .begin_symbol(None)
+                .end_symbol(None)
+                .track_style(GRAY)
+                .thumb_style(thumb_color),
+            area,
+            &mut state,
+        );
+    }
+}
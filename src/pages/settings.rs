@@ -0,0 +1,290 @@
+use std::sync::Arc;
+
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use ratatui::{
+    Frame,
+    layout::{Constraint, Layout, Rect},
+    text::Line,
+    widgets::{Block, Cell, Padding, Paragraph, Row, Table, Wrap},
+};
+
+use crate::keymap::{KEYS, Keys};
+use crate::pages::page::{Navigation, Page};
+use crate::pages::style::{
+    dimmed_selected_style, gray_span, gray_style, line_from_spans, selected_style, white_span,
+    white_span_owned, white_style,
+};
+use crate::pages::text_input::TextInput;
+use crate::preferences::{FRAME_RATE_LIMITS, FrameRateLimit, Preferences, SessionPreferences};
+use crate::theme::{THEMES, Theme};
+
+// Room for a code typed with spaces or a dash anywhere
+const MAX_CODE_CHARS: usize = 12;
+
+/// One selectable row.
+#[derive(Clone, Copy)]
+enum Choice {
+    Theme(&'static Theme),
+    Keys(Keys),
+    Animations(bool),
+    FrameRate(FrameRateLimit),
+    /// Opens the prompt for a code from an earlier visit.
+    EnterCode,
+}
+
+impl Choice {
+    fn name(self) -> &'static str {
+        match self {
+            Self::Theme(theme) => theme.name,
+            Self::Keys(keys) => keys.name(),
+            Self::Animations(true) => "on",
+            Self::Animations(false) => "off",
+            Self::FrameRate(limit) => limit.name(),
+            Self::EnterCode => "enter a code",
+        }
+    }
+
+    fn in_use(self, current: &Preferences) -> bool {
+        match self {
+            Self::Theme(theme) => theme == current.theme,
+            Self::Keys(keys) => keys == current.keys,
+            Self::Animations(animations) => animations == current.animations,
+            Self::FrameRate(limit) => limit == current.frame_rate,
+            Self::EnterCode => false,
+        }
+    }
+}
+
+/// The visitor's theme, key bindings, animations and frame rate, each listed
+/// under its own heading. `state` runs over every choice, down the left column
+/// and then the right. Visitors
+/// without a key also get their code, and can enter one from an earlier visit.
+pub struct Settings {
+    state: usize,
+    preferences: Arc<SessionPreferences>,
+    // Set while a code is being typed
+    code: Option<TextInput>,
+    // How the last code entered went
+    outcome: Option<&'static str>,
+}
+
+impl Settings {
+    pub fn new(preferences: Arc<SessionPreferences>) -> Self {
+        let current = preferences.get().theme;
+        Self {
+            state: THEMES
+                .iter()
+                .position(|theme| *theme == current)
+                .unwrap_or(0),
+            preferences,
+            code: None,
+            outcome: None,
+        }
+    }
+
+    /// The headings with their choices, in two columns so they fit the page.
+    fn columns(&self) -> [Vec<(&'static str, Vec<Choice>)>; 2] {
+        let mut right = vec![
+            ("keys", KEYS.into_iter().map(Choice::Keys).collect()),
+            (
+                "frame rate",
+                FRAME_RATE_LIMITS
+                    .into_iter()
+                    .map(Choice::FrameRate)
+                    .collect(),
+            ),
+        ];
+        if self.preferences.code().is_some() {
+            right.push(("code", vec![Choice::EnterCode]));
+        }
+        [
+            vec![
+                ("theme", THEMES.into_iter().map(Choice::Theme).collect()),
+                (
+                    "animations",
+                    vec![Choice::Animations(true), Choice::Animations(false)],
+                ),
+            ],
+            right,
+        ]
+    }
+
+    fn choices(&self) -> Vec<Choice> {
+        self.columns()
+            .into_iter()
+            .flatten()
+            .flat_map(|(_, choices)| choices)
+            .collect()
+    }
+
+    fn apply_selected(&mut self) {
+        let Some(choice) = self.choices().get(self.state).copied() else {
+            return;
+        };
+        match choice {
+            Choice::Theme(theme) => self.preferences.set_theme(theme),
+            Choice::Keys(keys) => self.preferences.set_keys(keys),
+            Choice::Animations(animations) => self.preferences.set_animations(animations),
+            Choice::FrameRate(limit) => self.preferences.set_frame_rate(limit),
+            Choice::EnterCode => {
+                self.code = Some(TextInput::new(MAX_CODE_CHARS));
+                self.outcome = None;
+            }
+        }
+    }
+
+    fn submit_code(&mut self) {
+        let Some(code) = self.code.take() else {
+            return;
+        };
+        self.outcome = Some(match self.preferences.restore(&code.value()) {
+            true => "settings restored",
+            false => "no settings under that code",
+        });
+    }
+}
+
+impl Page for Settings {
+    fn title(&self) -> &str {
+        "settings"
+    }
+
+    fn render(&self, frame: &mut Frame, area: Rect, is_focused: bool) {
+        let current = self.preferences.get();
+        let option = |i: usize, choice: Choice| {
+            let style_config = match i == self.state {
+                true => {
+                    if is_focused {
+                        selected_style()
+                    } else {
+                        dimmed_selected_style()
+                    }
+                }
+                false => gray_style(),
+            };
+            let name = match (&self.code, choice) {
+                (Some(code), Choice::EnterCode) => code.line("", is_focused),
+                _ => Line::from(choice.name()),
+            };
+            let marker = if choice.in_use(&current) {
+                "in use"
+            } else {
+                ""
+            };
+
+            Row::new([Cell::from(name), Cell::from(marker)])
+                .style(style_config)
+                .height(1)
+        };
+
+        let area = Block::new()
+            .padding(Padding {
+                left: 1,
+                right: 2,
+                top: 1,
+                bottom: 0,
+            })
+            .inner(area);
+        let column_areas = Layout::horizontal([Constraint::Fill(1), Constraint::Fill(1)])
+            .spacing(2)
+            .split(area);
+        let mut i = 0;
+        for (sections, column_area) in self.columns().into_iter().zip(column_areas.iter()) {
+            let mut rows = Vec::new();
+            for (heading, choices) in sections {
+                if !rows.is_empty() {
+                    rows.push(Row::new([""]));
+                }
+                rows.push(Row::new([heading]).style(white_style()));
+                for choice in choices {
+                    rows.push(option(i, choice));
+                    i += 1;
+                }
+            }
+            let table = Table::new(rows, [Constraint::Fill(1), Constraint::Length(8)]);
+            frame.render_widget(table, *column_area);
+        }
+    }
+
+    fn render_additional(&self, frame: &mut Frame, area: Rect, _is_focused: bool) {
+        let mut description = vec![
+            line_from_spans(vec![white_span("desc")]),
+            line_from_spans(vec![gray_span(
+                "colors used across every page, and how much moves. with animations off there's no intro and the dance holds still; a lower frame rate is kinder to slow connections. the vim keys add gg/G, ctrl+d/u and / to search",
+            )]),
+            Line::from(""),
+        ];
+        match (self.preferences.is_saved(), self.preferences.code()) {
+            (false, _) => {}
+            (true, None) => description.push(line_from_spans(vec![gray_span(
+                "you connected with an ssh key, so your picks are remembered for next time",
+            )])),
+            (true, Some(code)) => description.push(line_from_spans(vec![
+                gray_span("your picks are saved under the code "),
+                white_span_owned(code),
+                gray_span(". enter it here next time to get them back"),
+            ])),
+        }
+        if let Some(outcome) = self.outcome {
+            description.push(line_from_spans(vec![white_span(outcome)]));
+        }
+        frame.render_widget(Paragraph::new(description).wrap(Wrap { trim: true }), area);
+    }
+
+    fn keyboard_event_handler(&mut self, key_code: KeyCode) -> Option<Navigation> {
+        if let Some(code) = &mut self.code {
+            match key_code {
+                KeyCode::Esc => self.code = None,
+                KeyCode::Enter => self.submit_code(),
+                key_code => {
+                    code.handle_key(key_code);
+                }
+            }
+            return None;
+        }
+        match key_code {
+            KeyCode::Char('k') | KeyCode::Up => {
+                self.state = self.state.saturating_sub(1);
+            }
+            KeyCode::Char('j') | KeyCode::Down => {
+                self.state = (self.state + 1).min(self.choices().len() - 1);
+            }
+            KeyCode::Enter => self.apply_selected(),
+            _ => {}
+        }
+        None
+    }
+
+    fn key_event_handler(&mut self, key_event: KeyEvent) -> Option<Navigation> {
+        if key_event.modifiers.contains(KeyModifiers::CONTROL) {
+            if let Some(code) = &mut self.code {
+                code.handle_key_event(key_event);
+            }
+            return None;
+        }
+        self.keyboard_event_handler(key_event.code)
+    }
+
+    fn paste_event_handler(&mut self, text: &str) {
+        if let Some(code) = &mut self.code {
+            code.insert_str(text);
+        }
+    }
+
+    fn captures_input(&self) -> bool {
+        self.code.is_some()
+    }
+
+    fn nav_items(&self) -> Vec<Line<'static>> {
+        match self.code {
+            Some(_) => vec![
+                line_from_spans(vec![white_span(" ↵  "), gray_span("restore")]),
+                line_from_spans(vec![white_span("esc "), gray_span("cancel")]),
+            ],
+            None => vec![line_from_spans(vec![
+                white_span(" ↵  "),
+                gray_span("apply"),
+            ])],
+        }
+    }
+}
@@ -0,0 +1,281 @@
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use ratatui::{
+    Frame,
+    layout::Rect,
+    text::Line,
+    widgets::{Paragraph, Wrap},
+};
+
+use crate::exec;
+use crate::pages::{
+    page::{Navigation, Page},
+    style::{gray_span, gray_span_owned, line_from_spans, white_span, white_span_owned},
+    text_input::TextInput,
+};
+use crate::services::Services;
+
+const PROMPT: &str = "visitor@krayon.dev:~$ ";
+const MAX_COMMAND_CHARS: usize = 200;
+// Output lines kept, the oldest are dropped past it
+const SCROLLBACK: usize = 1000;
+const MAX_HISTORY: usize = 50;
+
+/// What a command leaves behind.
+enum Output {
+    Lines(Vec<String>),
+    Clear,
+    Exit,
+}
+
+type Run = fn(&ShellPage, &[&str]) -> Output;
+
+/// The commands the shell knows, with what `help` says about them.
+const COMMANDS: &[(&str, &str, Run)] = &[
+    ("help", "list the commands", help),
+    ("ls", "list the files", ls),
+    ("cat", "print a file, e.g. `cat about.txt`", cat),
+    ("whoami", "who you are", whoami),
+    ("pwd", "where you are", pwd),
+    ("cd", "go somewhere else", cd),
+    ("echo", "say something back", echo),
+    ("uname", "what this is running on", uname),
+    ("sudo", "do something as root", sudo),
+    ("rm", "remove files", rm),
+    ("clear", "clear the screen", clear),
+    ("exit", "leave the shell", exit),
+];
+
+/// Not a real shell: what looks like one, opened by typing `sh`, with a handful
+/// of commands that answer as if it were.
+pub struct ShellPage {
+    // A page's title as a file name, and the page as text
+    files: Vec<(String, String)>,
+    lines: Vec<Line<'static>>,
+    input: TextInput,
+    history: Vec<String>,
+    // Which of `history` Up went back to, None while typing a new command
+    recalled: Option<usize>,
+}
+
+impl ShellPage {
+    /// The files are the pages `ssh host cat` prints, as it prints them.
+    pub fn new(services: &Services) -> Self {
+        let files = exec::pages(services)
+            .iter()
+            .map(|page| {
+                let name = format!("{}.txt", page.title().replace(' ', "-"));
+                (name, exec::render(page.as_ref(), None))
+            })
+            .collect();
+        let mut page = Self {
+            files,
+            lines: Vec::new(),
+            input: TextInput::new(MAX_COMMAND_CHARS),
+            history: Vec::new(),
+            recalled: None,
+        };
+        page.print(vec![String::from(
+            "welcome to krayon.dev! type `help` to see what's here, esc to leave",
+        )]);
+        page
+    }
+
+    fn print(&mut self, lines: Vec<String>) {
+        self.lines
+            .extend(lines.into_iter().map(gray_span_owned).map(Line::from));
+        if self.lines.len() > SCROLLBACK {
+            self.lines.drain(..self.lines.len() - SCROLLBACK);
+        }
+    }
+
+    fn submit(&mut self) -> Option<Navigation> {
+        let command = self.input.value();
+        self.input = TextInput::new(MAX_COMMAND_CHARS);
+        self.recalled = None;
+        self.lines.push(line_from_spans(vec![
+            white_span(PROMPT),
+            white_span_owned(command.clone()),
+        ]));
+
+        let words = command.split_whitespace().collect::<Vec<_>>();
+        let (name, args) = words.split_first()?;
+        if self.history.last() != Some(&command) {
+            self.history.push(command.clone());
+            if self.history.len() > MAX_HISTORY {
+                self.history.remove(0);
+            }
+        }
+        let output = match COMMANDS.iter().find(|(command, _, _)| command == name) {
+            Some((_, _, run)) => run(self, args),
+            None => Output::Lines(vec![format!("sh: {}: command not found", name)]),
+        };
+        match output {
+            Output::Lines(lines) => self.print(lines),
+            Output::Clear => self.lines.clear(),
+            Output::Exit => return Some(Navigation::Pop),
+        }
+        None
+    }
+
+    /// Up and Down go through the commands run so far, like in a real shell.
+    fn recall(&mut self, older: bool) {
+        let recalled = match (self.recalled, older) {
+            (None, true) => self.history.len().checked_sub(1),
+            (None, false) => return,
+            (Some(i), true) => Some(i.saturating_sub(1)),
+            (Some(i), false) => Some(i + 1).filter(|&i| i < self.history.len()),
+        };
+        self.recalled = recalled;
+        self.input = TextInput::new(MAX_COMMAND_CHARS);
+        if let Some(i) = recalled {
+            self.input.insert_str(&self.history[i]);
+        }
+    }
+}
+
+fn help(_: &ShellPage, _: &[&str]) -> Output {
+    Output::Lines(
+        COMMANDS
+            .iter()
+            .map(|(name, description, _)| format!("{:<8}{}", name, description))
+            .collect(),
+    )
+}
+
+fn ls(shell: &ShellPage, _: &[&str]) -> Output {
+    Output::Lines(vec![
+        shell
+            .files
+            .iter()
+            .map(|(name, _)| name.as_str())
+            .collect::<Vec<_>>()
+            .join("  "),
+    ])
+}
+
+fn cat(shell: &ShellPage, args: &[&str]) -> Output {
+    let Some(name) = args.first() else {
+        return Output::Lines(vec![String::from("cat: which file? try `ls`")]);
+    };
+    let lines = match shell.files.iter().find(|(file, _)| file == name) {
+        Some((_, text)) => text.lines().map(String::from).collect(),
+        None => vec![format!("cat: {}: no such file or directory", name)],
+    };
+    Output::Lines(lines)
+}
+
+fn whoami(_: &ShellPage, _: &[&str]) -> Output {
+    Output::Lines(vec![String::from("visitor, and a welcome one")])
+}
+
+fn pwd(_: &ShellPage, _: &[&str]) -> Output {
+    Output::Lines(vec![String::from("/home/visitor")])
+}
+
+fn cd(_: &ShellPage, _: &[&str]) -> Output {
+    Output::Lines(vec![String::from(
+        "cd: there's only the one directory, but it's a nice one",
+    )])
+}
+
+fn echo(_: &ShellPage, args: &[&str]) -> Output {
+    Output::Lines(vec![args.join(" ")])
+}
+
+fn uname(_: &ShellPage, _: &[&str]) -> Output {
+    Output::Lines(vec![String::from(
+        "krayon.dev, a ratatui app pretending to be a shell",
+    )])
+}
+
+fn sudo(_: &ShellPage, args: &[&str]) -> Output {
+    let line = match args {
+        [] => "usage: sudo <command>",
+        ["make-me-a-sandwich" | "make", ..] => "okay.",
+        _ => "visitor is not in the sudoers file. this incident will be reported.",
+    };
+    Output::Lines(vec![String::from(line)])
+}
+
+fn rm(_: &ShellPage, args: &[&str]) -> Output {
+    let line = match args {
+        [] => "rm: missing operand",
+        _ => "rm: nice try, everything here is read only",
+    };
+    Output::Lines(vec![String::from(line)])
+}
+
+fn clear(_: &ShellPage, _: &[&str]) -> Output {
+    Output::Clear
+}
+
+fn exit(_: &ShellPage, _: &[&str]) -> Output {
+    Output::Exit
+}
+
+impl Page for ShellPage {
+    fn title(&self) -> &str {
+        "sh"
+    }
+
+    fn render(&self, frame: &mut Frame, area: Rect, _is_focused: bool) {
+        let mut lines = self.lines.clone();
+        // The page takes every key, so the cursor is always there
+        let mut prompt = line_from_spans(vec![white_span(PROMPT)]);
+        prompt.spans.extend(self.input.line("", true).spans);
+        lines.push(prompt);
+
+        let paragraph = Paragraph::new(lines).wrap(Wrap { trim: false });
+        // Like a terminal, the prompt stays at the bottom once the screen is full
+        let line_count = paragraph.line_count(area.width) as u16;
+        let offset = line_count.saturating_sub(area.height);
+        frame.render_widget(paragraph.scroll((offset, 0)), area);
+    }
+
+    fn render_additional(&self, frame: &mut Frame, area: Rect, _is_focused: bool) {
+        let description = vec![
+            line_from_spans(vec![white_span("desc")]),
+            line_from_spans(vec![gray_span(
+                "a shell, or close enough. nothing typed here runs anywhere",
+            )]),
+        ];
+        frame.render_widget(Paragraph::new(description).wrap(Wrap { trim: true }), area);
+    }
+
+    fn keyboard_event_handler(&mut self, key_code: KeyCode) -> Option<Navigation> {
+        self.key_event_handler(KeyEvent::from(key_code))
+    }
+
+    fn key_event_handler(&mut self, key_event: KeyEvent) -> Option<Navigation> {
+        let ctrl = key_event.modifiers.contains(KeyModifiers::CONTROL);
+        match key_event.code {
+            KeyCode::Esc => return Some(Navigation::Pop),
+            KeyCode::Enter => return self.submit(),
+            KeyCode::Up => self.recall(true),
+            KeyCode::Down => self.recall(false),
+            KeyCode::Char('l') if ctrl => self.lines.clear(),
+            KeyCode::Char('d') if ctrl && self.input.char_count() == 0 => {
+                return Some(Navigation::Pop);
+            }
+            _ => {
+                self.input.handle_key_event(key_event);
+            }
+        }
+        None
+    }
+
+    fn paste_event_handler(&mut self, text: &str) {
+        self.input.insert_str(text);
+    }
+
+    fn captures_input(&self) -> bool {
+        true
+    }
+
+    fn nav_items(&self) -> Vec<Line<'static>> {
+        vec![
+            line_from_spans(vec![white_span(" ↵  "), gray_span("run")]),
+            line_from_spans(vec![white_span("↑/↓ "), gray_span("history")]),
+        ]
+    }
+}
@@ -0,0 +1,274 @@
+use std::collections::VecDeque;
+use std::sync::Arc;
+
+use crossterm::event::{KeyCode, KeyEvent};
+use rand::Rng;
+use ratatui::{
+    Frame,
+    layout::Rect,
+    style::Style,
+    text::{Line, Span},
+    widgets::{Block, Padding, Paragraph},
+};
+
+use crate::high_scores::{HighScores, Leaderboard};
+use crate::pages::{
+    arcade::leaderboard,
+    page::{Navigation, Page},
+    style::{GRAY, LINK_COLOR, WHITE, gray_span, gray_span_owned, line_from_spans, white_span},
+};
+
+const GAME: &str = "snake";
+// In cells, each drawn two columns wide so they come out roughly square
+const BOARD_WIDTH: i16 = 28;
+const BOARD_HEIGHT: i16 = 14;
+// Ticks between moves at the start, and at most
+const SLOWEST_STEP: u64 = 5;
+const FASTEST_STEP: u64 = 2;
+// Turns pressed faster than the snake moves are kept for the next moves
+const MAX_QUEUED_TURNS: usize = 2;
+
+#[derive(Clone, Copy, PartialEq)]
+enum Direction {
+    Up,
+    Down,
+    Left,
+    Right,
+}
+
+impl Direction {
+    fn offset(self) -> (i16, i16) {
+        match self {
+            Self::Up => (0, -1),
+            Self::Down => (0, 1),
+            Self::Left => (-1, 0),
+            Self::Right => (1, 0),
+        }
+    }
+
+    fn opposite(self) -> Self {
+        match self {
+            Self::Up => Self::Down,
+            Self::Down => Self::Up,
+            Self::Left => Self::Right,
+            Self::Right => Self::Left,
+        }
+    }
+}
+
+enum State {
+    Ready,
+    Playing,
+    Over,
+}
+
+/// Snake, from the arcade or the Konami code. The page keeps every key while it's
+/// open, so the arrows steer instead of moving through the menu.
+pub struct SnakePage {
+    leaderboard: Leaderboard,
+    state: State,
+    // Head first
+    snake: VecDeque<(i16, i16)>,
+    direction: Direction,
+    turns: VecDeque<Direction>,
+    food: (i16, i16),
+    score: u32,
+    next_step: Option<u64>,
+}
+
+impl SnakePage {
+    pub fn new(high_scores: Arc<HighScores>) -> Self {
+        let mut page = Self {
+            leaderboard: Leaderboard::new(high_scores, GAME),
+            state: State::Ready,
+            snake: VecDeque::new(),
+            direction: Direction::Right,
+            turns: VecDeque::new(),
+            food: (0, 0),
+            score: 0,
+            next_step: None,
+        };
+        page.reset();
+        page
+    }
+
+    fn reset(&mut self) {
+        let (x, y) = (BOARD_WIDTH / 4, BOARD_HEIGHT / 2);
+        self.snake = VecDeque::from([(x, y), (x - 1, y), (x - 2, y)]);
+        self.direction = Direction::Right;
+        self.turns.clear();
+        self.score = 0;
+        self.next_step = None;
+        self.place_food();
+    }
+
+    fn place_food(&mut self) {
+        let free = (0..BOARD_HEIGHT)
+            .flat_map(|y| (0..BOARD_WIDTH).map(move |x| (x, y)))
+            .filter(|cell| !self.snake.contains(cell))
+            .collect::<Vec<_>>();
+        match free.is_empty() {
+            true => self.game_over(),
+            false => self.food = free[rand::rng().next_u32() as usize % free.len()],
+        }
+    }
+
+    fn turn(&mut self, direction: Direction) {
+        let last = self.turns.back().copied().unwrap_or(self.direction);
+        if direction != last && direction != last.opposite() && self.turns.len() < MAX_QUEUED_TURNS
+        {
+            self.turns.push_back(direction);
+        }
+    }
+
+    fn step(&mut self) {
+        if let Some(direction) = self.turns.pop_front() {
+            self.direction = direction;
+        }
+        let (dx, dy) = self.direction.offset();
+        let (x, y) = self.snake[0];
+        let head = (x + dx, y + dy);
+
+        let eating = head == self.food;
+        if !eating {
+            self.snake.pop_back();
+        }
+        let outside = !(0..BOARD_WIDTH).contains(&head.0) || !(0..BOARD_HEIGHT).contains(&head.1);
+        if outside || self.snake.contains(&head) {
+            self.game_over();
+            return;
+        }
+        self.snake.push_front(head);
+        if eating {
+            self.score += 1;
+            self.place_food();
+        }
+    }
+
+    fn step_interval(&self) -> u64 {
+        SLOWEST_STEP
+            .saturating_sub(self.score as u64 / 5)
+            .max(FASTEST_STEP)
+    }
+
+    fn game_over(&mut self) {
+        self.leaderboard.finish(self.score);
+        self.state = State::Over;
+    }
+
+    fn cell(&self, cell: (i16, i16)) -> Span<'static> {
+        if cell == self.snake[0] {
+            Span::styled("██", Style::new().fg(WHITE))
+        } else if self.snake.contains(&cell) {
+            Span::styled("██", Style::new().fg(GRAY))
+        } else if cell == self.food {
+            Span::styled("()", Style::new().fg(LINK_COLOR))
+        } else {
+            Span::raw("  ")
+        }
+    }
+
+    fn status(&self) -> Line<'static> {
+        match &self.state {
+            State::Ready => line_from_spans(vec![white_span("↵ "), gray_span("start")]),
+            State::Playing => {
+                line_from_spans(vec![gray_span_owned(format!("score {}", self.score))])
+            }
+            State::Over if let Some(initials) = self.leaderboard.initials() => {
+                let mut line = line_from_spans(vec![gray_span_owned(format!(
+                    "score {}, a high score! your initials: ",
+                    self.score
+                ))]);
+                line.spans.extend(initials.line("", true).spans);
+                line
+            }
+            State::Over => line_from_spans(vec![
+                gray_span_owned(format!("game over, score {} · ", self.score)),
+                white_span("↵ "),
+                gray_span("play again"),
+            ]),
+        }
+    }
+}
+
+impl Page for SnakePage {
+    fn title(&self) -> &str {
+        "snake"
+    }
+
+    fn render(&self, frame: &mut Frame, area: Rect, _is_focused: bool) {
+        let mut lines = vec![self.status(), Line::from("")];
+        let border = Style::new().fg(GRAY);
+        let horizontal = "─".repeat(BOARD_WIDTH as usize * 2);
+        lines.push(Line::styled(format!("┌{}┐", horizontal), border));
+        for y in 0..BOARD_HEIGHT {
+            let mut spans = vec![Span::styled("│", border)];
+            spans.extend((0..BOARD_WIDTH).map(|x| self.cell((x, y))));
+            spans.push(Span::styled("│", border));
+            lines.push(Line::from(spans));
+        }
+        lines.push(Line::styled(format!("└{}┘", horizontal), border));
+
+        frame.render_widget(
+            Paragraph::new(lines).block(Block::new().padding(Padding::new(1, 2, 1, 0))),
+            area,
+        );
+    }
+
+    fn render_additional(&self, frame: &mut Frame, area: Rect, _is_focused: bool) {
+        frame.render_widget(Paragraph::new(leaderboard(self.leaderboard.table())), area);
+    }
+
+    fn keyboard_event_handler(&mut self, key_code: KeyCode) -> Option<Navigation> {
+        match key_code {
+            KeyCode::Esc | KeyCode::Char('q') => return Some(Navigation::Pop),
+            KeyCode::Up | KeyCode::Char('k' | 'w') => self.turn(Direction::Up),
+            KeyCode::Down | KeyCode::Char('j' | 's') => self.turn(Direction::Down),
+            KeyCode::Left | KeyCode::Char('h' | 'a') => self.turn(Direction::Left),
+            KeyCode::Right | KeyCode::Char('l' | 'd') => self.turn(Direction::Right),
+            KeyCode::Enter | KeyCode::Char(' ') => match self.state {
+                State::Ready => self.state = State::Playing,
+                State::Over => {
+                    self.reset();
+                    self.state = State::Playing;
+                }
+                _ => {}
+            },
+            _ => {}
+        }
+        None
+    }
+
+    fn paste_event_handler(&mut self, text: &str) {
+        self.leaderboard.handle_paste(text);
+    }
+
+    fn key_event_handler(&mut self, key_event: KeyEvent) -> Option<Navigation> {
+        if self.leaderboard.handle_key_event(key_event) {
+            return None;
+        }
+        self.keyboard_event_handler(key_event.code)
+    }
+
+    fn captures_input(&self) -> bool {
+        true
+    }
+
+    fn on_tick(&mut self, tick: u64) -> bool {
+        if !matches!(self.state, State::Playing) {
+            return false;
+        }
+        if tick >= *self.next_step.get_or_insert(tick) {
+            self.step();
+            self.next_step = Some(tick.wrapping_add(self.step_interval()));
+        }
+        true
+    }
+
+    fn nav_items(&self) -> Vec<Line<'static>> {
+        vec![line_from_spans(vec![
+            white_span("←↑↓→ "),
+            gray_span("steer"),
+        ])]
+    }
+}
@@ -0,0 +1,201 @@
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use crossterm::event::KeyCode;
+use ratatui::{
+    Frame,
+    layout::{Constraint, Layout, Rect},
+    style::Style,
+    widgets::{Block, Cell, Padding, Paragraph, Row, Sparkline, Table, Wrap},
+};
+use tracing::warn;
+
+use crate::pages::{
+    admin::format_duration,
+    page::{Navigation, Page},
+    style::{
+        LINK_COLOR, gray_span, gray_span_owned, gray_style, line_from_spans, white_span,
+        white_style,
+    },
+};
+use crate::stats::{Stats, Summary};
+
+// The counts come from the database, so they are only re-read this often
+const REFRESH_INTERVAL: Duration = Duration::from_secs(5);
+// Rows the visits per hour are drawn in, and columns per hour
+const CHART_HEIGHT: u16 = 4;
+const HOUR_WIDTH: u16 = 2;
+
+pub struct StatsPage {
+    stats: Arc<Stats>,
+    summary: Option<Summary>,
+    refreshed_at: Instant,
+}
+
+impl StatsPage {
+    pub fn new(stats: Arc<Stats>) -> Self {
+        let mut page = Self {
+            stats,
+            summary: None,
+            refreshed_at: Instant::now(),
+        };
+        page.refresh();
+        page
+    }
+
+    fn refresh(&mut self) {
+        self.refreshed_at = Instant::now();
+        match self.stats.summary() {
+            Ok(summary) => self.summary = Some(summary),
+            Err(e) => warn!("Failed to load stats: {}", e),
+        }
+    }
+
+    /// Visits per hour as a sparkline, over the hours they're from.
+    fn render_chart(&self, frame: &mut Frame, area: Rect, hourly_visits: &[u64]) {
+        let width = hourly_visits.len() as u16 * HOUR_WIDTH;
+        let [heading_area, sparkline_area, axis_area] = Layout::vertical([
+            Constraint::Length(1),
+            Constraint::Length(CHART_HEIGHT),
+            Constraint::Length(1),
+        ])
+        .areas(area);
+        let busiest = hourly_visits.iter().max().copied().unwrap_or(0);
+        frame.render_widget(
+            Paragraph::new(line_from_spans(vec![
+                white_span("visits per hour"),
+                gray_span_owned(format!("  busiest {}", busiest)),
+            ])),
+            heading_area,
+        );
+
+        let bars = hourly_visits
+            .iter()
+            .flat_map(|&visits| std::iter::repeat_n(visits, HOUR_WIDTH as usize))
+            .collect::<Vec<_>>();
+        let sparkline = Sparkline::default()
+            .data(&bars)
+            // An empty day stays flat rather than drawn at full height
+            .max(busiest.max(1))
+            .style(Style::new().fg(LINK_COLOR));
+        frame.render_widget(
+            sparkline,
+            Rect {
+                width: width.min(sparkline_area.width),
+                ..sparkline_area
+            },
+        );
+
+        let start = format!("{}h ago", hourly_visits.len());
+        let axis = format!(
+            "{}{:>pad$}",
+            start,
+            "now",
+            pad = (width as usize).saturating_sub(start.len())
+        );
+        frame.render_widget(Paragraph::new(gray_span_owned(axis)), axis_area);
+    }
+}
+
+impl Page for StatsPage {
+    fn title(&self) -> &str {
+        "stats"
+    }
+
+    fn render(&self, frame: &mut Frame, area: Rect, _is_focused: bool) {
+        let Some(summary) = &self.summary else {
+            frame.render_widget(Paragraph::new(gray_span("no stats yet")), area);
+            return;
+        };
+
+        let area = Block::new().padding(Padding::new(1, 2, 1, 0)).inner(area);
+        let row = |label: String, value: String, style| {
+            [label, value]
+                .into_iter()
+                .map(Cell::from)
+                .collect::<Row>()
+                .style(style)
+        };
+        let mut counts = vec![row(
+            String::from("up"),
+            format_duration(summary.uptime),
+            white_style(),
+        )];
+        if let Some(online) = summary.online {
+            counts.push(row(
+                String::from("online now"),
+                online.to_string(),
+                white_style(),
+            ));
+        }
+        counts.push(row(
+            String::from("visits"),
+            summary.visits.to_string(),
+            white_style(),
+        ));
+        counts.push(row(
+            String::from("unique visitors"),
+            summary.unique_visitors.to_string(),
+            white_style(),
+        ));
+
+        let [counts_area, chart_area, rows_area] = Layout::vertical([
+            Constraint::Length(counts.len() as u16 + 1),
+            Constraint::Length(CHART_HEIGHT + 3),
+            Constraint::Fill(1),
+        ])
+        .areas(area);
+        let widths = [Constraint::Fill(1), Constraint::Length(10)];
+        frame.render_widget(Table::new(counts, widths), counts_area);
+        self.render_chart(frame, chart_area, &summary.hourly_visits);
+
+        let row = |label: String, value: u64, style| row(label, value.to_string(), style);
+        let mut rows = vec![Row::new(["most viewed"]).style(white_style())];
+        rows.extend(
+            summary
+                .page_views
+                .iter()
+                .map(|(page, views)| row(page.clone(), *views, gray_style())),
+        );
+        for (heading, counts) in [
+            ("countries", &summary.countries),
+            ("cities", &summary.cities),
+            ("terminals", &summary.terminals),
+        ] {
+            if counts.is_empty() {
+                continue;
+            }
+            rows.push(Row::new([""]));
+            rows.push(Row::new([heading]).style(white_style()));
+            rows.extend(
+                counts
+                    .iter()
+                    .map(|(name, visits)| row(name.clone(), *visits, gray_style())),
+            );
+        }
+
+        frame.render_widget(Table::new(rows, widths), rows_area);
+    }
+
+    fn render_additional(&self, frame: &mut Frame, area: Rect, _is_focused: bool) {
+        let description = vec![
+            line_from_spans(vec![white_span("desc")]),
+            line_from_spans(vec![gray_span(
+                "how long the server has been up, how many people stopped by and when, what they looked at and on which terminals. visitors are counted by a hash of their ip, never the ip itself, and where they came from only as a number per city",
+            )]),
+        ];
+        frame.render_widget(Paragraph::new(description).wrap(Wrap { trim: true }), area);
+    }
+
+    fn keyboard_event_handler(&mut self, _key_code: KeyCode) -> Option<Navigation> {
+        None
+    }
+
+    fn on_tick(&mut self, _tick: u64) -> bool {
+        if self.refreshed_at.elapsed() < REFRESH_INTERVAL {
+            return false;
+        }
+        self.refresh();
+        true
+    }
+}
@@ -9,6 +9,7 @@ pub const DIMMED_WHITE_BG: Color = Color::Rgb(215, 215, 215);
 pub const DIMMED_WHITE: Color = Color::Rgb(215, 215, 215);
 pub const LINK_COLOR: Color = Color::Rgb(0, 255, 251);
 pub const DIMMED_LINK_COLOR: Color = Color::Rgb(0, 205, 201);
+pub const CODE_BG: Color = Color::Rgb(40, 40, 40);
 
 // Style presets
 pub fn gray_style() -> Style {
@@ -31,6 +32,10 @@ pub fn dimmed_white_style() -> Style {
     Style::default().fg(DIMMED_WHITE)
 }
 
+pub fn code_style() -> Style {
+    Style::new().fg(DIMMED_WHITE).bg(CODE_BG)
+}
+
 pub fn link_style() -> Style {
     Style::default()
         .fg(LINK_COLOR)
@@ -48,6 +53,10 @@ pub fn gray_span(text: &str) -> Span<'_> {
     Span::styled(text, gray_style())
 }
 
+pub fn gray_span_owned(text: String) -> Span<'static> {
+    Span::styled(text, gray_style())
+}
+
 pub fn white_span(text: &str) -> Span<'_> {
     Span::styled(text, white_style())
 }
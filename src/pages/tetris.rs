@@ -0,0 +1,554 @@
+use std::sync::Arc;
+
+use crossterm::event::{KeyCode, KeyEvent};
+use rand::Rng;
+use ratatui::{
+    Frame,
+    layout::{Constraint, Layout, Rect},
+    style::{Color, Style},
+    text::{Line, Span},
+    widgets::{Block, Padding, Paragraph},
+};
+
+use crate::high_scores::{HighScores, Leaderboard};
+use crate::pages::{
+    arcade::leaderboard,
+    page::{Navigation, Page},
+    style::{GRAY, gray_span, gray_span_owned, line_from_spans, white_span},
+};
+
+const GAME: &str = "tetris";
+// In cells, each drawn two columns wide. Shorter than the usual 20 rows so the
+// board fits the content area of a 40 row terminal
+const BOARD_WIDTH: i16 = 10;
+const BOARD_HEIGHT: i16 = 16;
+// Ticks a piece takes to fall a row at the first level, and at most
+const SLOWEST_FALL: u64 = 24;
+const FASTEST_FALL: u64 = 3;
+const LINES_PER_LEVEL: u32 = 10;
+// Per level, for clearing one to four lines at once
+const LINE_SCORES: [u32; 5] = [0, 100, 300, 500, 800];
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Shape {
+    I,
+    O,
+    T,
+    S,
+    Z,
+    J,
+    L,
+}
+
+const SHAPES: [Shape; 7] = [
+    Shape::I,
+    Shape::O,
+    Shape::T,
+    Shape::S,
+    Shape::Z,
+    Shape::J,
+    Shape::L,
+];
+
+impl Shape {
+    /// The cells at spawn, inside a square of `size()` they rotate in.
+    fn cells(self) -> [(i16, i16); 4] {
+        match self {
+            Self::I => [(0, 1), (1, 1), (2, 1), (3, 1)],
+            Self::O => [(0, 0), (1, 0), (0, 1), (1, 1)],
+            Self::T => [(1, 0), (0, 1), (1, 1), (2, 1)],
+            Self::S => [(1, 0), (2, 0), (0, 1), (1, 1)],
+            Self::Z => [(0, 0), (1, 0), (1, 1), (2, 1)],
+            Self::J => [(0, 0), (0, 1), (1, 1), (2, 1)],
+            Self::L => [(2, 0), (0, 1), (1, 1), (2, 1)],
+        }
+    }
+
+    fn size(self) -> i16 {
+        match self {
+            Self::I => 4,
+            Self::O => 2,
+            _ => 3,
+        }
+    }
+
+    fn color(self) -> Color {
+        match self {
+            Self::I => Color::Cyan,
+            Self::O => Color::Yellow,
+            Self::T => Color::Magenta,
+            Self::S => Color::Green,
+            Self::Z => Color::Red,
+            Self::J => Color::Blue,
+            Self::L => Color::LightRed,
+        }
+    }
+}
+
+#[derive(Clone, Copy)]
+struct Piece {
+    shape: Shape,
+    // Relative to `origin`, the square's top left corner on the board
+    cells: [(i16, i16); 4],
+    origin: (i16, i16),
+}
+
+impl Piece {
+    fn new(shape: Shape) -> Self {
+        Self {
+            shape,
+            cells: shape.cells(),
+            origin: ((BOARD_WIDTH - shape.size()) / 2, 0),
+        }
+    }
+
+    fn board_cells(&self) -> impl Iterator<Item = (i16, i16)> + '_ {
+        self.cells
+            .iter()
+            .map(|(x, y)| (self.origin.0 + x, self.origin.1 + y))
+    }
+
+    fn moved(&self, dx: i16, dy: i16) -> Self {
+        Self {
+            origin: (self.origin.0 + dx, self.origin.1 + dy),
+            ..*self
+        }
+    }
+
+    /// Turned a quarter clockwise within its square.
+    fn rotated(&self) -> Self {
+        let size = self.shape.size();
+        Self {
+            cells: self.cells.map(|(x, y)| (size - 1 - y, x)),
+            ..*self
+        }
+    }
+}
+
+enum State {
+    Ready,
+    Playing,
+    Over,
+}
+
+/// Tetris, from the arcade. Like snake, the page keeps every key while it's
+/// open.
+pub struct TetrisPage {
+    leaderboard: Leaderboard,
+    state: State,
+    // Row by row from the top, with the shape that left each filled cell
+    board: Vec<[Option<Shape>; BOARD_WIDTH as usize]>,
+    piece: Piece,
+    next: Shape,
+    // What's left of the current set of all seven, so none is missing for long
+    bag: Vec<Shape>,
+    score: u32,
+    lines: u32,
+    next_fall: Option<u64>,
+}
+
+impl TetrisPage {
+    pub fn new(high_scores: Arc<HighScores>) -> Self {
+        let mut page = Self {
+            leaderboard: Leaderboard::new(high_scores, GAME),
+            state: State::Ready,
+            board: Vec::new(),
+            piece: Piece::new(Shape::T),
+            next: Shape::T,
+            bag: Vec::new(),
+            score: 0,
+            lines: 0,
+            next_fall: None,
+        };
+        page.reset();
+        page
+    }
+
+    fn reset(&mut self) {
+        self.board = vec![[None; BOARD_WIDTH as usize]; BOARD_HEIGHT as usize];
+        self.bag.clear();
+        self.piece = Piece::new(self.draw_shape());
+        self.next = self.draw_shape();
+        self.score = 0;
+        self.lines = 0;
+        self.next_fall = None;
+    }
+
+    fn draw_shape(&mut self) -> Shape {
+        if self.bag.is_empty() {
+            self.bag = SHAPES.to_vec();
+        }
+        let i = rand::rng().next_u32() as usize % self.bag.len();
+        self.bag.swap_remove(i)
+    }
+
+    fn level(&self) -> u32 {
+        self.lines / LINES_PER_LEVEL + 1
+    }
+
+    fn fall_interval(&self) -> u64 {
+        SLOWEST_FALL
+            .saturating_sub(2 * (self.level() as u64 - 1))
+            .max(FASTEST_FALL)
+    }
+
+    fn fits(&self, piece: &Piece) -> bool {
+        piece.board_cells().all(|(x, y)| {
+            (0..BOARD_WIDTH).contains(&x)
+                && (0..BOARD_HEIGHT).contains(&y)
+                && self.board[y as usize][x as usize].is_none()
+        })
+    }
+
+    /// Moves the piece if there's room, and says whether there was.
+    fn shift(&mut self, dx: i16, dy: i16) -> bool {
+        let moved = self.piece.moved(dx, dy);
+        let fits = self.fits(&moved);
+        if fits {
+            self.piece = moved;
+        }
+        fits
+    }
+
+    /// Against a wall or another piece, a rotation that doesn't fit is tried a
+    /// column or two to either side.
+    fn rotate(&mut self) {
+        let rotated = self.piece.rotated();
+        if let Some(kicked) = [0, -1, 1, -2, 2]
+            .into_iter()
+            .map(|dx| rotated.moved(dx, 0))
+            .find(|piece| self.fits(piece))
+        {
+            self.piece = kicked;
+        }
+    }
+
+    fn fall(&mut self) {
+        if !self.shift(0, 1) {
+            self.lock();
+        }
+    }
+
+    fn soft_drop(&mut self) {
+        if self.shift(0, 1) {
+            self.score += 1;
+        }
+    }
+
+    fn hard_drop(&mut self) {
+        while self.shift(0, 1) {
+            self.score += 2;
+        }
+        self.lock();
+    }
+
+    fn lock(&mut self) {
+        for (x, y) in self.piece.board_cells() {
+            self.board[y as usize][x as usize] = Some(self.piece.shape);
+        }
+
+        self.board.retain(|row| row.iter().any(Option::is_none));
+        let cleared = BOARD_HEIGHT as usize - self.board.len();
+        for _ in 0..cleared {
+            self.board.insert(0, [None; BOARD_WIDTH as usize]);
+        }
+        self.score += LINE_SCORES[cleared] * self.level();
+        self.lines += cleared as u32;
+
+        self.piece = Piece::new(self.next);
+        self.next = self.draw_shape();
+        if !self.fits(&self.piece) {
+            self.game_over();
+        }
+    }
+
+    fn game_over(&mut self) {
+        self.leaderboard.finish(self.score);
+        self.state = State::Over;
+    }
+
+    fn cell(&self, cell: (i16, i16)) -> Span<'static> {
+        let falling = !matches!(self.state, State::Ready)
+            && self.piece.board_cells().any(|piece| piece == cell);
+        let shape = match falling {
+            true => Some(self.piece.shape),
+            false => self.board[cell.1 as usize][cell.0 as usize],
+        };
+        match shape {
+            Some(shape) => Span::styled("██", Style::new().fg(shape.color())),
+            None => Span::raw("  "),
+        }
+    }
+
+    fn status(&self) -> Vec<Line<'static>> {
+        let mut lines = vec![
+            line_from_spans(vec![gray_span_owned(format!("score  {}", self.score))]),
+            line_from_spans(vec![gray_span_owned(format!("lines  {}", self.lines))]),
+            line_from_spans(vec![gray_span_owned(format!("level  {}", self.level()))]),
+            Line::from(""),
+            line_from_spans(vec![gray_span("next")]),
+        ];
+        let next = Piece::new(self.next);
+        for y in 0..2 {
+            let row = (0..4)
+                .map(|x| match next.cells.contains(&(x, y)) {
+                    true => Span::styled("██", Style::new().fg(self.next.color())),
+                    false => Span::raw("  "),
+                })
+                .collect::<Vec<_>>();
+            lines.push(Line::from(row));
+        }
+        lines.push(Line::from(""));
+
+        match &self.state {
+            State::Ready => {
+                lines.push(line_from_spans(vec![white_span("↵ "), gray_span("start")]));
+            }
+            State::Playing => {}
+            State::Over if let Some(initials) = self.leaderboard.initials() => {
+                lines.push(line_from_spans(vec![gray_span("a high score!")]));
+                let mut line = line_from_spans(vec![gray_span("your initials: ")]);
+                line.spans.extend(initials.line("", true).spans);
+                lines.push(line);
+            }
+            State::Over => {
+                lines.push(line_from_spans(vec![gray_span("game over")]));
+                lines.push(line_from_spans(vec![
+                    white_span("↵ "),
+                    gray_span("play again"),
+                ]));
+            }
+        }
+        lines
+    }
+}
+
+impl Page for TetrisPage {
+    fn title(&self) -> &str {
+        "tetris"
+    }
+
+    fn render(&self, frame: &mut Frame, area: Rect, _is_focused: bool) {
+        let area = Block::new().padding(Padding::new(1, 2, 1, 0)).inner(area);
+        let [board_area, status_area] = Layout::horizontal([
+            Constraint::Length(BOARD_WIDTH as u16 * 2 + 2),
+            Constraint::Fill(1),
+        ])
+        .spacing(3)
+        .areas(area);
+
+        let border = Style::new().fg(GRAY);
+        let horizontal = "─".repeat(BOARD_WIDTH as usize * 2);
+        let mut lines = vec![Line::styled(format!("┌{}┐", horizontal), border)];
+        for y in 0..BOARD_HEIGHT {
+            let mut spans = vec![Span::styled("│", border)];
+            spans.extend((0..BOARD_WIDTH).map(|x| self.cell((x, y))));
+            spans.push(Span::styled("│", border));
+            lines.push(Line::from(spans));
+        }
+        lines.push(Line::styled(format!("└{}┘", horizontal), border));
+        frame.render_widget(Paragraph::new(lines), board_area);
+
+        let status_area = Rect {
+            y: status_area.y + 1,
+            height: status_area.height.saturating_sub(1),
+            ..status_area
+        };
+        frame.render_widget(Paragraph::new(self.status()), status_area);
+    }
+
+    fn render_additional(&self, frame: &mut Frame, area: Rect, _is_focused: bool) {
+        frame.render_widget(Paragraph::new(leaderboard(self.leaderboard.table())), area);
+    }
+
+    fn keyboard_event_handler(&mut self, key_code: KeyCode) -> Option<Navigation> {
+        match key_code {
+            KeyCode::Esc | KeyCode::Char('q') => return Some(Navigation::Pop),
+            KeyCode::Enter => match self.state {
+                State::Ready => self.state = State::Playing,
+                State::Over => {
+                    self.reset();
+                    self.state = State::Playing;
+                }
+                _ => {}
+            },
+            _ if !matches!(self.state, State::Playing) => {}
+            KeyCode::Left | KeyCode::Char('h' | 'a') => {
+                self.shift(-1, 0);
+            }
+            KeyCode::Right | KeyCode::Char('l' | 'd') => {
+                self.shift(1, 0);
+            }
+            KeyCode::Up | KeyCode::Char('k' | 'w' | 'x') => self.rotate(),
+            KeyCode::Down | KeyCode::Char('j' | 's') => self.soft_drop(),
+            KeyCode::Char(' ') => self.hard_drop(),
+            _ => {}
+        }
+        None
+    }
+
+    fn paste_event_handler(&mut self, text: &str) {
+        self.leaderboard.handle_paste(text);
+    }
+
+    fn key_event_handler(&mut self, key_event: KeyEvent) -> Option<Navigation> {
+        if self.leaderboard.handle_key_event(key_event) {
+            return None;
+        }
+        self.keyboard_event_handler(key_event.code)
+    }
+
+    fn captures_input(&self) -> bool {
+        true
+    }
+
+    fn on_tick(&mut self, tick: u64) -> bool {
+        if !matches!(self.state, State::Playing) {
+            return false;
+        }
+        if tick >= *self.next_fall.get_or_insert(tick + self.fall_interval()) {
+            self.fall();
+            self.next_fall = Some(tick.wrapping_add(self.fall_interval()));
+        }
+        true
+    }
+
+    fn nav_items(&self) -> Vec<Line<'static>> {
+        vec![
+            line_from_spans(vec![white_span("←/→ "), gray_span("move")]),
+            line_from_spans(vec![white_span(" ↑  "), gray_span("rotate")]),
+            line_from_spans(vec![white_span(" ↓  "), gray_span("drop faster")]),
+            line_from_spans(vec![white_span("spc "), gray_span("drop")]),
+        ]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use super::*;
+    use crate::store::Store;
+
+    fn page() -> TetrisPage {
+        let store = Arc::new(Store::open(Path::new(":memory:")).unwrap());
+        let mut page = TetrisPage::new(HighScores::new(store));
+        page.state = State::Playing;
+        page
+    }
+
+    fn cells(piece: &Piece) -> Vec<(i16, i16)> {
+        let mut cells = piece.board_cells().collect::<Vec<_>>();
+        cells.sort();
+        cells
+    }
+
+    #[test]
+    fn four_quarter_turns_come_back_around() {
+        for shape in SHAPES {
+            let piece = Piece::new(shape);
+            let turned = (0..4).fold(piece, |piece, _| piece.rotated());
+            assert_eq!(cells(&turned), cells(&piece));
+        }
+    }
+
+    #[test]
+    fn a_turn_stands_the_i_piece_up() {
+        let piece = Piece::new(Shape::I).rotated();
+        let columns = piece.board_cells().map(|(x, _)| x).collect::<Vec<_>>();
+        assert!(columns.iter().all(|&x| x == columns[0]));
+    }
+
+    #[test]
+    fn pieces_stop_at_the_walls_and_floor() {
+        let mut page = page();
+        page.piece = Piece::new(Shape::O);
+        while page.shift(-1, 0) {}
+        assert_eq!(page.piece.origin.0, 0);
+        while page.shift(1, 0) {}
+        assert_eq!(page.piece.origin.0, BOARD_WIDTH - 2);
+        while page.shift(0, 1) {}
+        assert_eq!(page.piece.origin.1, BOARD_HEIGHT - 2);
+    }
+
+    #[test]
+    fn pieces_dont_overlap_filled_cells() {
+        let mut page = page();
+        page.piece = Piece::new(Shape::O);
+        let (x, y) = page.piece.origin;
+        page.board[y as usize + 2][x as usize] = Some(Shape::I);
+        assert!(!page.shift(0, 1));
+        assert!(page.shift(1, 0));
+    }
+
+    #[test]
+    fn a_turn_against_the_wall_is_kicked_back_in() {
+        let mut page = page();
+        // Standing up against the left wall
+        let mut piece = Piece::new(Shape::I).rotated();
+        let column = piece.board_cells().next().unwrap().0;
+        piece = piece.moved(-column, 4);
+        assert!(page.fits(&piece));
+        page.piece = piece;
+
+        page.rotate();
+        assert!(page.fits(&page.piece));
+        let rows = page.piece.board_cells().map(|(_, y)| y).collect::<Vec<_>>();
+        assert!(rows.iter().all(|&y| y == rows[0]), "still standing up");
+    }
+
+    #[test]
+    fn full_rows_are_cleared_and_the_rest_fall() {
+        let mut page = page();
+        let bottom = BOARD_HEIGHT as usize - 1;
+        for x in 4..BOARD_WIDTH as usize {
+            page.board[bottom][x] = Some(Shape::O);
+        }
+        page.board[bottom - 1][9] = Some(Shape::T);
+        // Lying flat in the gap on the left
+        page.piece = Piece::new(Shape::I);
+        page.piece.origin = (0, BOARD_HEIGHT - 2);
+
+        page.lock();
+        assert_eq!(page.lines, 1);
+        assert_eq!(page.score, LINE_SCORES[1]);
+        assert_eq!(page.board.len(), BOARD_HEIGHT as usize);
+        assert_eq!(page.board[bottom][9], Some(Shape::T));
+        assert_eq!(page.board[bottom].iter().flatten().count(), 1);
+        assert!(matches!(page.state, State::Playing));
+    }
+
+    #[test]
+    fn several_rows_at_once_score_more() {
+        let mut page = page();
+        for row in BOARD_HEIGHT as usize - 4..BOARD_HEIGHT as usize {
+            for x in 1..BOARD_WIDTH as usize {
+                page.board[row][x] = Some(Shape::O);
+            }
+        }
+        page.piece = Piece::new(Shape::I).rotated();
+        let column = page.piece.board_cells().next().unwrap().0;
+        page.piece = page.piece.moved(-column, 0);
+
+        page.hard_drop();
+        assert_eq!(page.lines, 4);
+        assert!(page.score >= LINE_SCORES[4]);
+        assert!(page.board.iter().flatten().all(Option::is_none));
+    }
+
+    #[test]
+    fn the_game_is_over_once_a_new_piece_cant_fit() {
+        let mut page = page();
+        // Rows that aren't full, so they stay, across the top where pieces start
+        for row in 0..2 {
+            for x in 1..BOARD_WIDTH as usize {
+                page.board[row][x] = Some(Shape::O);
+            }
+        }
+        page.piece = Piece::new(Shape::O);
+        page.piece.origin = (0, BOARD_HEIGHT - 2);
+
+        page.lock();
+        assert!(matches!(page.state, State::Over));
+    }
+}
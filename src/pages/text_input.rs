@@ -0,0 +1,173 @@
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use ratatui::{
+    style::Modifier,
+    text::{Line, Span},
+};
+
+use crate::pages::style::{gray_style, selected_style, white_style};
+
+/// A line of editable text with a cursor, for pages that ask visitors to type
+/// something. Input beyond `max_chars` is ignored.
+pub struct TextInput {
+    chars: Vec<char>,
+    cursor: usize,
+    max_chars: usize,
+    // Toggled with Insert, typing then replaces the character under the cursor
+    overwrite: bool,
+}
+
+impl TextInput {
+    pub fn new(max_chars: usize) -> Self {
+        Self {
+            chars: Vec::new(),
+            cursor: 0,
+            max_chars,
+            overwrite: false,
+        }
+    }
+
+    pub fn value(&self) -> String {
+        self.chars.iter().collect()
+    }
+
+    pub fn char_count(&self) -> usize {
+        self.chars.len()
+    }
+
+    /// Edits the text for printable characters, Backspace/Delete, Insert and the
+    /// cursor keys. Returns false for any other key.
+    pub fn handle_key(&mut self, key_code: KeyCode) -> bool {
+        self.handle_key_event(KeyEvent::from(key_code))
+    }
+
+    /// Like `handle_key`, plus the readline shortcuts: Ctrl or Alt with the arrows,
+    /// Alt+B/F and Alt+Backspace work on whole words, Ctrl+A/E jump to either end,
+    /// and Ctrl+W/U/K delete the word before the cursor, everything before it or
+    /// everything after it.
+    pub fn handle_key_event(&mut self, key_event: KeyEvent) -> bool {
+        let ctrl = key_event.modifiers.contains(KeyModifiers::CONTROL);
+        let alt = key_event.modifiers.contains(KeyModifiers::ALT);
+        let word = ctrl || alt;
+        match key_event.code {
+            KeyCode::Char('a') if ctrl => self.cursor = 0,
+            KeyCode::Char('e') if ctrl => self.cursor = self.chars.len(),
+            KeyCode::Char('w') if ctrl => self.delete_to(self.previous_word()),
+            KeyCode::Char('u') if ctrl => self.delete_to(0),
+            KeyCode::Char('k') if ctrl => self.delete_to(self.chars.len()),
+            KeyCode::Char('b') if alt => self.cursor = self.previous_word(),
+            KeyCode::Char('f') if alt => self.cursor = self.next_word(),
+            KeyCode::Backspace if word => self.delete_to(self.previous_word()),
+            KeyCode::Left if word => self.cursor = self.previous_word(),
+            KeyCode::Right if word => self.cursor = self.next_word(),
+            // Any other shortcut isn't text
+            KeyCode::Char(_) if word => return false,
+            KeyCode::Char(c) => self.insert(c),
+            KeyCode::Backspace => self.delete_to(self.cursor.saturating_sub(1)),
+            KeyCode::Delete => self.delete_to((self.cursor + 1).min(self.chars.len())),
+            KeyCode::Insert => self.overwrite = !self.overwrite,
+            KeyCode::Left => self.cursor = self.cursor.saturating_sub(1),
+            KeyCode::Right => self.cursor = (self.cursor + 1).min(self.chars.len()),
+            KeyCode::Home => self.cursor = 0,
+            KeyCode::End => self.cursor = self.chars.len(),
+            _ => return false,
+        }
+        true
+    }
+
+    /// What pages' `key_event_handler` starts with: shortcuts with Ctrl or Alt only
+    /// ever edit the text, so they are handled here and true is returned even when
+    /// they do nothing. Anything else is left to the page.
+    pub fn handle_modified_key(&mut self, key_event: KeyEvent) -> bool {
+        let modified = key_event
+            .modifiers
+            .intersects(KeyModifiers::CONTROL | KeyModifiers::ALT);
+        if modified {
+            self.handle_key_event(key_event);
+        }
+        modified
+    }
+
+    /// Pasted text is kept on one line, with line breaks and tabs turned into spaces.
+    pub fn insert_str(&mut self, text: &str) {
+        for c in text.chars() {
+            self.insert(if c.is_whitespace() { ' ' } else { c });
+        }
+    }
+
+    fn insert(&mut self, c: char) {
+        if c.is_control() {
+            return;
+        }
+        if self.overwrite && self.cursor < self.chars.len() {
+            self.chars[self.cursor] = c;
+        } else if self.chars.len() < self.max_chars {
+            self.chars.insert(self.cursor, c);
+        } else {
+            return;
+        }
+        self.cursor += 1;
+    }
+
+    /// Removes the characters between the cursor and `position`, on either side
+    /// of it, leaving the cursor where they started.
+    fn delete_to(&mut self, position: usize) {
+        let range = self.cursor.min(position)..self.cursor.max(position);
+        self.cursor = range.start;
+        self.chars.drain(range);
+    }
+
+    /// The start of the word before the cursor, like readline skipping any spaces
+    /// first.
+    fn previous_word(&self) -> usize {
+        let mut position = self.cursor;
+        while position > 0 && self.chars[position - 1].is_whitespace() {
+            position -= 1;
+        }
+        while position > 0 && !self.chars[position - 1].is_whitespace() {
+            position -= 1;
+        }
+        position
+    }
+
+    /// The end of the word after the cursor.
+    fn next_word(&self) -> usize {
+        let mut position = self.cursor;
+        while position < self.chars.len() && self.chars[position].is_whitespace() {
+            position += 1;
+        }
+        while position < self.chars.len() && !self.chars[position].is_whitespace() {
+            position += 1;
+        }
+        position
+    }
+
+    /// The text with the cursor drawn as a highlighted cell while focused, or
+    /// `placeholder` while it's empty and unfocused. The cell is underlined too
+    /// while overwriting.
+    pub fn line(&self, placeholder: &'static str, is_focused: bool) -> Line<'static> {
+        if !is_focused {
+            return match self.chars.is_empty() {
+                true => Line::from(Span::styled(placeholder, gray_style())),
+                false => Line::from(Span::styled(self.value(), white_style())),
+            };
+        }
+
+        let cursor_style = match self.overwrite {
+            true => selected_style().add_modifier(Modifier::UNDERLINED),
+            false => selected_style(),
+        };
+        let before: String = self.chars[..self.cursor].iter().collect();
+        let under = self.chars.get(self.cursor).copied().unwrap_or(' ');
+        let after: String = self
+            .chars
+            .get(self.cursor + 1..)
+            .unwrap_or(&[])
+            .iter()
+            .collect();
+        Line::from(vec![
+            Span::styled(before, white_style()),
+            Span::styled(under.to_string(), cursor_style),
+            Span::styled(after, white_style()),
+        ])
+    }
+}
@@ -0,0 +1,219 @@
+use crossterm::event::KeyCode;
+use ratatui::{
+    Frame,
+    layout::Rect,
+    style::{Color, Style},
+    text::{Line, Span},
+    widgets::{Block, Padding, Paragraph, Wrap},
+};
+use tokio::sync::broadcast::{self, error::TryRecvError};
+
+use crate::event_bus::{Event, EventBus};
+use crate::pages::{
+    page::{Navigation, Page},
+    style::{
+        GRAY, LINK_COLOR, WHITE, gray_span, gray_span_owned, line_from_spans, white_span,
+        white_span_owned,
+    },
+};
+use crate::tic_tac_toe::{Board, Mark, MatchView, Outcome, Seat, Status};
+
+pub const TITLE: &str = "tic-tac-toe";
+// Each square is this many columns, with the mark in the middle
+const SQUARE_WIDTH: usize = 7;
+
+/// The lobby and the board for tic-tac-toe against another visitor. It stays put
+/// while the visitor browses, so a game can go on in the background, and the app
+/// tells them when it's their move.
+pub struct TicTacToePage {
+    seat: Seat,
+    events: broadcast::Receiver<Event>,
+    status: Status,
+    // Waiting visitors and games going, as of the last change
+    lobby: (usize, usize),
+    error: Option<String>,
+}
+
+impl TicTacToePage {
+    pub fn new(seat: Seat, events: &EventBus) -> Self {
+        let mut page = Self {
+            events: events.subscribe(),
+            status: Status::Idle,
+            lobby: (0, 0),
+            error: None,
+            seat,
+        };
+        page.refresh();
+        page
+    }
+
+    fn refresh(&mut self) {
+        self.status = self.seat.status();
+        self.lobby = self.seat.lobby();
+    }
+
+    fn board_lines(board: &Board) -> Vec<Line<'static>> {
+        let border = Style::new().fg(GRAY);
+        let divider = vec!["─".repeat(SQUARE_WIDTH); 3].join("┼");
+        let mut lines = Vec::new();
+        for (row, squares) in board.chunks(3).enumerate() {
+            if row > 0 {
+                lines.push(Line::styled(divider.clone(), border));
+            }
+            let mut spans = Vec::new();
+            for (column, square) in squares.iter().enumerate() {
+                if column > 0 {
+                    spans.push(Span::styled("│", border));
+                }
+                // Empty squares show the number that plays them
+                let (text, style) = match square {
+                    Some(Mark::X) => (String::from("x"), Style::new().fg(WHITE)),
+                    Some(Mark::O) => (String::from("o"), Style::new().fg(LINK_COLOR)),
+                    None => ((row * 3 + column + 1).to_string(), border),
+                };
+                spans.push(Span::styled(
+                    format!("{:^width$}", text, width = SQUARE_WIDTH),
+                    style,
+                ));
+            }
+            lines.push(Line::from(spans));
+        }
+        lines
+    }
+
+    fn game_status(game: &MatchView) -> Vec<Line<'static>> {
+        let playing = format!("you're {} against {}", game.mark, game.opponent);
+        let mut lines = vec![line_from_spans(vec![gray_span_owned(playing)])];
+        let result = match game.outcome {
+            None if game.my_move() => {
+                lines.push(line_from_spans(vec![white_span("your move")]));
+                return lines;
+            }
+            None => {
+                lines.push(line_from_spans(vec![gray_span_owned(format!(
+                    "{}'s move",
+                    game.opponent
+                ))]));
+                return lines;
+            }
+            Some(Outcome::Won(mark)) if mark == game.mark => String::from("you won!"),
+            Some(Outcome::Won(_)) => format!("{} won", game.opponent),
+            Some(Outcome::Draw) => String::from("a draw"),
+            Some(Outcome::Forfeited(mark)) if mark == game.mark => String::from("you gave up"),
+            Some(Outcome::Forfeited(_)) => format!("{} gave up, so you win", game.opponent),
+        };
+        lines.push(line_from_spans(vec![white_span_owned(result)]));
+        lines.push(line_from_spans(vec![
+            white_span("↵ "),
+            gray_span("find another opponent"),
+        ]));
+        lines
+    }
+}
+
+impl Page for TicTacToePage {
+    fn title(&self) -> &str {
+        TITLE
+    }
+
+    fn render(&self, frame: &mut Frame, area: Rect, _is_focused: bool) {
+        let mut lines = match &self.status {
+            Status::Idle => vec![
+                line_from_spans(vec![gray_span(
+                    "play tic-tac-toe against someone else who's here right now",
+                )]),
+                line_from_spans(vec![white_span("↵ "), gray_span("find an opponent")]),
+            ],
+            Status::Waiting => vec![
+                line_from_spans(vec![gray_span(
+                    "looking for an opponent, you can browse the site meanwhile",
+                )]),
+                line_from_spans(vec![white_span("↵ "), gray_span("stop looking")]),
+            ],
+            Status::Playing(game) => {
+                let mut lines = Self::game_status(game);
+                lines.push(Line::from(""));
+                lines.extend(Self::board_lines(&game.board));
+                lines
+            }
+        };
+        lines.push(Line::from(""));
+        let (waiting, going) = self.lobby;
+        lines.push(line_from_spans(vec![gray_span_owned(format!(
+            "playing as {} · {} waiting · {} {} going",
+            self.seat.name(),
+            waiting,
+            going,
+            match going {
+                1 => "game",
+                _ => "games",
+            }
+        ))]));
+        if let Some(error) = &self.error {
+            lines.push(Line::from(Span::styled(
+                error.clone(),
+                Style::new().fg(Color::Red),
+            )));
+        }
+
+        frame.render_widget(
+            Paragraph::new(lines).block(Block::new().padding(Padding::new(1, 2, 1, 0))),
+            area,
+        );
+    }
+
+    fn render_additional(&self, frame: &mut Frame, area: Rect, _is_focused: bool) {
+        let description = vec![
+            line_from_spans(vec![white_span("desc")]),
+            line_from_spans(vec![gray_span(
+                "get matched with whoever asked first, x moves first. leaving the site gives the game up",
+            )]),
+        ];
+        frame.render_widget(Paragraph::new(description).wrap(Wrap { trim: true }), area);
+    }
+
+    fn keyboard_event_handler(&mut self, key_code: KeyCode) -> Option<Navigation> {
+        self.error = None;
+        let playing = matches!(&self.status, Status::Playing(game) if game.outcome.is_none());
+        match key_code {
+            KeyCode::Enter if matches!(self.status, Status::Waiting) => self.seat.stop_looking(),
+            KeyCode::Enter if !playing => self.seat.find_opponent(),
+            KeyCode::Char('r') if playing => self.seat.resign(),
+            KeyCode::Char(c @ '1'..='9') if playing => {
+                let square = c as usize - '1' as usize;
+                if let Err(e) = self.seat.play(square) {
+                    self.error = Some(e.to_string());
+                }
+            }
+            _ => {}
+        }
+        self.refresh();
+        None
+    }
+
+    /// Picks up moves and matches made since the last tick.
+    fn on_tick(&mut self, _tick: u64) -> bool {
+        let mut changed = false;
+        loop {
+            match self.events.try_recv() {
+                Ok(Event::TicTacToe(_)) | Err(TryRecvError::Lagged(_)) => changed = true,
+                Ok(_) => {}
+                Err(TryRecvError::Empty | TryRecvError::Closed) => break,
+            }
+        }
+        if changed {
+            self.refresh();
+        }
+        changed
+    }
+
+    fn nav_items(&self) -> Vec<Line<'static>> {
+        match &self.status {
+            Status::Playing(game) if game.outcome.is_none() => vec![
+                line_from_spans(vec![white_span("1-9 "), gray_span("play a square")]),
+                line_from_spans(vec![white_span(" r  "), gray_span("give up")]),
+            ],
+            _ => vec![line_from_spans(vec![white_span(" ↵  "), gray_span("play")])],
+        }
+    }
+}
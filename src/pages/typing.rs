@@ -0,0 +1,250 @@
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use rand::Rng;
+use ratatui::{
+    Frame,
+    layout::Rect,
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Padding, Paragraph, Wrap},
+};
+
+use crate::high_scores::HighScores;
+use crate::pages::{
+    arcade::leaderboard,
+    page::{Navigation, Page},
+    style::{
+        gray_span, gray_span_owned, gray_style, line_from_spans, selected_style, white_span,
+        white_span_owned, white_style,
+    },
+};
+use crate::store::HighScore;
+
+const GAME: &str = "typing";
+// Five characters make a word, the usual measure
+const CHARS_PER_WORD: f64 = 5.0;
+const SENTENCES: &[&str] = &[
+    "the quick brown fox jumps over the lazy dog while the cat watches from the fence",
+    "every program is a little story about what a computer should do next and why",
+    "a terminal is just a grid of cells, but it can hold a whole website if you let it",
+    "rust makes you think about who owns what, which turns out to be good advice in general",
+    "ssh was made for servers and shells, and here it is serving a portfolio instead",
+    "the best way to learn a new language is to build something small and finish it",
+];
+
+enum State {
+    // Waiting for the first key, which starts the clock
+    Ready,
+    Typing(Instant),
+    Done { wpm: u32, accuracy: u32, best: bool },
+}
+
+/// Typing speed, from the arcade. The clock starts with the first key, and pasted
+/// text doesn't count.
+pub struct TypingPage {
+    high_scores: Arc<HighScores>,
+    table: Vec<HighScore>,
+    sentence: Vec<char>,
+    typed: Vec<char>,
+    // Every character typed, and the ones that were wrong when they were, even
+    // if fixed since
+    keystrokes: u32,
+    mistakes: u32,
+    state: State,
+}
+
+impl TypingPage {
+    pub fn new(high_scores: Arc<HighScores>) -> Self {
+        let table = high_scores.top(GAME);
+        let mut page = Self {
+            high_scores,
+            table,
+            sentence: Vec::new(),
+            typed: Vec::new(),
+            keystrokes: 0,
+            mistakes: 0,
+            state: State::Ready,
+        };
+        page.reset();
+        page
+    }
+
+    fn reset(&mut self) {
+        let sentence = SENTENCES[rand::rng().next_u32() as usize % SENTENCES.len()];
+        self.sentence = sentence.chars().collect();
+        self.typed.clear();
+        self.keystrokes = 0;
+        self.mistakes = 0;
+        self.state = State::Ready;
+    }
+
+    fn type_char(&mut self, c: char) {
+        let started_at = match self.state {
+            State::Ready => Instant::now(),
+            State::Typing(started_at) => started_at,
+            State::Done { .. } => return,
+        };
+        self.state = State::Typing(started_at);
+        if self.typed.len() == self.sentence.len() {
+            return;
+        }
+        self.keystrokes += 1;
+        if self.sentence[self.typed.len()] != c {
+            self.mistakes += 1;
+        }
+        self.typed.push(c);
+        if self.typed == self.sentence {
+            self.finish(started_at.elapsed());
+        }
+    }
+
+    fn correct_chars(&self) -> usize {
+        self.typed
+            .iter()
+            .zip(&self.sentence)
+            .filter(|(typed, expected)| typed == expected)
+            .count()
+    }
+
+    /// Words a minute from the characters typed right so far.
+    fn wpm(&self, elapsed: Duration) -> u32 {
+        let minutes = elapsed.as_secs_f64() / 60.0;
+        if minutes == 0.0 {
+            return 0;
+        }
+        (self.correct_chars() as f64 / CHARS_PER_WORD / minutes).round() as u32
+    }
+
+    fn accuracy(&self) -> u32 {
+        match self.keystrokes {
+            0 => 100,
+            keystrokes => (keystrokes - self.mistakes) * 100 / keystrokes,
+        }
+    }
+
+    fn finish(&mut self, elapsed: Duration) {
+        let wpm = self.wpm(elapsed);
+        let best = self.high_scores.qualifies(GAME, wpm);
+        if best {
+            self.high_scores.submit_anonymous(GAME, wpm);
+            self.table = self.high_scores.top(GAME);
+        }
+        self.state = State::Done {
+            wpm,
+            accuracy: self.accuracy(),
+            best,
+        };
+    }
+
+    fn sentence_line(&self) -> Line<'static> {
+        let spans = self
+            .sentence
+            .iter()
+            .enumerate()
+            .map(|(i, &expected)| {
+                let style = match self.typed.get(i) {
+                    Some(&typed) if typed == expected => white_style(),
+                    // Spaces typed wrong would be invisible without the underline
+                    Some(_) => Style::new()
+                        .fg(Color::Red)
+                        .add_modifier(Modifier::UNDERLINED),
+                    None if i == self.typed.len() => selected_style(),
+                    None => gray_style(),
+                };
+                Span::styled(expected.to_string(), style)
+            })
+            .collect::<Vec<_>>();
+        Line::from(spans)
+    }
+
+    fn status(&self) -> Vec<Line<'static>> {
+        match self.state {
+            State::Ready => vec![line_from_spans(vec![gray_span(
+                "start typing, the clock starts with the first key",
+            )])],
+            State::Typing(started_at) => {
+                let elapsed = started_at.elapsed();
+                vec![line_from_spans(vec![gray_span_owned(format!(
+                    "{} wpm · {}% accuracy · {}s",
+                    self.wpm(elapsed),
+                    self.accuracy(),
+                    elapsed.as_secs()
+                ))])]
+            }
+            State::Done {
+                wpm,
+                accuracy,
+                best,
+            } => {
+                let mut lines = vec![line_from_spans(vec![white_span_owned(format!(
+                    "{} wpm, {}% accuracy",
+                    wpm, accuracy
+                ))])];
+                if best {
+                    lines.push(line_from_spans(vec![gray_span("one of the best yet!")]));
+                }
+                lines.push(Line::from(""));
+                lines.push(line_from_spans(vec![
+                    white_span("↵ "),
+                    gray_span("another sentence"),
+                ]));
+                lines
+            }
+        }
+    }
+}
+
+impl Page for TypingPage {
+    fn title(&self) -> &str {
+        "typing"
+    }
+
+    fn render(&self, frame: &mut Frame, area: Rect, _is_focused: bool) {
+        let mut lines = vec![self.sentence_line(), Line::from("")];
+        lines.extend(self.status());
+        frame.render_widget(
+            Paragraph::new(lines)
+                .wrap(Wrap { trim: false })
+                .block(Block::new().padding(Padding::new(1, 2, 1, 0))),
+            area,
+        );
+    }
+
+    fn render_additional(&self, frame: &mut Frame, area: Rect, _is_focused: bool) {
+        frame.render_widget(Paragraph::new(leaderboard(&self.table)), area);
+    }
+
+    fn keyboard_event_handler(&mut self, key_code: KeyCode) -> Option<Navigation> {
+        match key_code {
+            KeyCode::Esc => return Some(Navigation::Pop),
+            KeyCode::Enter if matches!(self.state, State::Done { .. }) => self.reset(),
+            KeyCode::Backspace if matches!(self.state, State::Typing(_)) => {
+                self.typed.pop();
+            }
+            KeyCode::Char(c) => self.type_char(c),
+            _ => {}
+        }
+        None
+    }
+
+    fn key_event_handler(&mut self, key_event: KeyEvent) -> Option<Navigation> {
+        let modified = key_event
+            .modifiers
+            .intersects(KeyModifiers::CONTROL | KeyModifiers::ALT);
+        if modified {
+            return None;
+        }
+        self.keyboard_event_handler(key_event.code)
+    }
+
+    fn captures_input(&self) -> bool {
+        true
+    }
+
+    // Redrawn while typing so the numbers keep up with the clock
+    fn on_tick(&mut self, _tick: u64) -> bool {
+        matches!(self.state, State::Typing(_))
+    }
+}
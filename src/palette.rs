@@ -0,0 +1,287 @@
+use crossterm::event::{KeyCode, KeyEvent};
+use ratatui::{
+    Frame,
+    layout::{Constraint, Flex, Layout},
+    style::Style,
+    text::Line,
+    widgets::{Block, Borders, Clear, Padding, Paragraph},
+};
+
+use crate::pages::style::{
+    LINK_COLOR, gray_span, gray_style, line_from_spans, selected_style, white_style,
+};
+use crate::pages::text_input::TextInput;
+use crate::theme::Theme;
+
+const MAX_QUERY_CHARS: usize = 40;
+// Matches listed at once, the best first
+const MAX_SHOWN: usize = 8;
+
+/// What a command does once picked.
+pub enum Action {
+    /// Selects the menu entry at this index.
+    GoTo(usize),
+    SetTheme(&'static Theme),
+    /// Explains how to fetch this public file.
+    Download(String),
+}
+
+pub struct Command {
+    pub label: String,
+    pub action: Action,
+}
+
+impl Command {
+    pub fn new(label: String, action: Action) -> Self {
+        Self { label, action }
+    }
+}
+
+/// What the app does with the palette after a key.
+pub enum PaletteOutcome {
+    Open,
+    Closed,
+    Run(Action),
+}
+
+/// A search box over everything the visitor can jump to or do, opened with Ctrl+K
+/// or `:`. Typing narrows the commands down to those containing the query's
+/// letters in order, best match first.
+pub struct CommandPalette {
+    commands: Vec<Command>,
+    query: TextInput,
+    // Indices into `commands` matching the query, best first
+    matches: Vec<usize>,
+    selected: usize,
+}
+
+impl CommandPalette {
+    pub fn new(commands: Vec<Command>) -> Self {
+        let mut palette = Self {
+            commands,
+            query: TextInput::new(MAX_QUERY_CHARS),
+            matches: Vec::new(),
+            selected: 0,
+        };
+        palette.update_matches();
+        palette
+    }
+
+    pub fn handle_key(&mut self, key_event: KeyEvent) -> PaletteOutcome {
+        match key_event.code {
+            KeyCode::Esc => return PaletteOutcome::Closed,
+            KeyCode::Enter => {
+                return match self.matches.get(self.selected) {
+                    Some(&index) => PaletteOutcome::Run(self.commands.swap_remove(index).action),
+                    None => PaletteOutcome::Closed,
+                };
+            }
+            KeyCode::Up | KeyCode::BackTab => self.selected = self.selected.saturating_sub(1),
+            KeyCode::Down | KeyCode::Tab => {
+                self.selected = (self.selected + 1).min(self.matches.len().saturating_sub(1));
+            }
+            _ => {
+                let before = self.query.value();
+                self.query.handle_key_event(key_event);
+                if self.query.value() != before {
+                    self.update_matches();
+                }
+            }
+        }
+        PaletteOutcome::Open
+    }
+
+    pub fn paste(&mut self, text: &str) {
+        self.query.insert_str(text);
+        self.update_matches();
+    }
+
+    fn update_matches(&mut self) {
+        let query = self.query.value().to_lowercase();
+        let mut scored = self
+            .commands
+            .iter()
+            .enumerate()
+            .filter_map(|(i, command)| Some((fuzzy_score(&query, &command.label)?, i)))
+            .collect::<Vec<_>>();
+        // Stable, so equally good matches keep the menu's order
+        scored.sort_by_key(|(score, _)| std::cmp::Reverse(*score));
+        self.matches = scored.into_iter().map(|(_, i)| i).collect();
+        self.selected = 0;
+    }
+
+    /// A box near the top of the screen, over whatever page is open.
+    pub fn render(&self, frame: &mut Frame) {
+        // The query, a blank line, the matches and the borders
+        let height = self.matches.len().clamp(1, MAX_SHOWN) as u16 + 4;
+        let [_, area] = Layout::vertical([Constraint::Length(3), Constraint::Length(height)])
+            .areas(frame.area());
+        let [area] = Layout::horizontal([Constraint::Max(60)])
+            .flex(Flex::Center)
+            .areas(area);
+
+        let mut lines = vec![self.query.line("", true), Line::from("")];
+        // Scrolled so the selected match stays in the list
+        let skip = (self.selected + 1).saturating_sub(MAX_SHOWN);
+        lines.extend(
+            self.matches
+                .iter()
+                .enumerate()
+                .skip(skip)
+                .take(MAX_SHOWN)
+                .map(|(position, &index)| {
+                    let style = match position == self.selected {
+                        true => selected_style(),
+                        false => gray_style(),
+                    };
+                    Line::styled(self.commands[index].label.clone(), style)
+                }),
+        );
+        if self.matches.is_empty() {
+            lines.push(line_from_spans(vec![gray_span("nothing matches")]));
+        }
+
+        frame.render_widget(Clear, area);
+        frame.render_widget(
+            Paragraph::new(lines).style(white_style()).block(
+                Block::new()
+                    .title(" go to or do anything ")
+                    .title_bottom(" ↵ run · esc close ")
+                    .borders(Borders::ALL)
+                    .border_style(Style::new().fg(LINK_COLOR))
+                    .padding(Padding::horizontal(1)),
+            ),
+            area,
+        );
+    }
+}
+
+/// How well `label` matches `query`, already lowercased, or None if it doesn't
+/// contain the query's characters in order. Runs of consecutive characters and
+/// matches at the start of words count the most.
+fn fuzzy_score(query: &str, label: &str) -> Option<i64> {
+    if query.is_empty() {
+        return Some(0);
+    }
+    let label = label.to_lowercase().chars().collect::<Vec<_>>();
+    let mut score = 0;
+    let mut next = 0;
+    let mut previous: Option<usize> = None;
+    for c in query.chars().filter(|c| !c.is_whitespace()) {
+        let found = next + label[next..].iter().position(|&l| l == c)?;
+        score += 1;
+        if previous.is_some_and(|previous| previous + 1 == found) {
+            score += 5;
+        }
+        if found == 0 || label[found - 1] == ' ' {
+            score += 3;
+        }
+        previous = Some(found);
+        next = found + 1;
+    }
+    // Among equal matches, shorter labels are closer to what was typed
+    Some(score * 100 - label.len() as i64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn palette() -> CommandPalette {
+        let labels = [
+            "Home",
+            "Projects",
+            "Photo report",
+            "Blog",
+            "Theme: solarized",
+        ];
+        CommandPalette::new(
+            labels
+                .iter()
+                .enumerate()
+                .map(|(i, label)| Command::new(label.to_string(), Action::GoTo(i)))
+                .collect(),
+        )
+    }
+
+    fn labels(palette: &CommandPalette) -> Vec<&str> {
+        palette
+            .matches
+            .iter()
+            .map(|&i| palette.commands[i].label.as_str())
+            .collect()
+    }
+
+    fn type_in(palette: &mut CommandPalette, text: &str) {
+        for c in text.chars() {
+            palette.handle_key(KeyEvent::from(KeyCode::Char(c)));
+        }
+    }
+
+    #[test]
+    fn an_empty_query_lists_everything_in_order() {
+        let palette = palette();
+        assert_eq!(
+            labels(&palette),
+            [
+                "Home",
+                "Projects",
+                "Photo report",
+                "Blog",
+                "Theme: solarized"
+            ]
+        );
+    }
+
+    #[test]
+    fn letters_in_a_row_rank_above_scattered_ones() {
+        let mut palette = palette();
+        type_in(&mut palette, "pro");
+        assert_eq!(labels(&palette), ["Projects", "Photo report"]);
+        assert!(fuzzy_score("pro", "Projects") > fuzzy_score("pro", "Photo report"));
+    }
+
+    #[test]
+    fn word_starts_rank_above_the_middle_of_words() {
+        assert!(fuzzy_score("s", "Theme: solarized") > fuzzy_score("s", "Projects"));
+    }
+
+    #[test]
+    fn letters_must_come_in_order() {
+        assert_eq!(fuzzy_score("golb", "Blog"), None);
+        assert!(fuzzy_score("blg", "Blog").is_some());
+    }
+
+    #[test]
+    fn nothing_matching_closes_on_enter() {
+        let mut palette = palette();
+        type_in(&mut palette, "zzz");
+        assert!(palette.matches.is_empty());
+        assert!(matches!(
+            palette.handle_key(KeyEvent::from(KeyCode::Enter)),
+            PaletteOutcome::Closed
+        ));
+    }
+
+    #[test]
+    fn case_doesnt_matter() {
+        let mut palette = palette();
+        type_in(&mut palette, "BLOG");
+        assert_eq!(labels(&palette), ["Blog"]);
+        let mut palette =
+            CommandPalette::new(vec![Command::new(String::from("blog"), Action::GoTo(0))]);
+        type_in(&mut palette, "Blog");
+        assert_eq!(labels(&palette), ["blog"]);
+    }
+
+    #[test]
+    fn enter_runs_the_selected_match() {
+        let mut palette = palette();
+        type_in(&mut palette, "pro");
+        palette.handle_key(KeyEvent::from(KeyCode::Down));
+        assert!(matches!(
+            palette.handle_key(KeyEvent::from(KeyCode::Enter)),
+            PaletteOutcome::Run(Action::GoTo(2))
+        ));
+    }
+}
@@ -0,0 +1,105 @@
+use std::fmt;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, RwLock};
+
+use tracing::error;
+
+use crate::stats::salted_hash;
+use crate::store::Store;
+
+pub const QUESTION: &str = "tabs or spaces?";
+pub const CHOICES: &[&str] = &["tabs", "spaces"];
+// What the votes are kept under, so a new question starts from nothing
+const POLL: &str = "tabs-vs-spaces";
+
+#[derive(Debug)]
+pub enum VoteError {
+    AlreadyVoted,
+    Failed,
+}
+
+impl fmt::Display for VoteError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::AlreadyVoted => write!(f, "you already voted"),
+            Self::Failed => write!(f, "couldn't count your vote, try again later"),
+        }
+    }
+}
+
+/// The poll every visitor gets one vote in, kept in the store. Voters are only
+/// stored as salted hashes of their key fingerprint or IP, like visits are.
+pub struct Poll {
+    store: Arc<Store>,
+    salt: String,
+    // One per entry of `CHOICES`
+    tallies: RwLock<Vec<u64>>,
+    // Bumped whenever a vote is counted, so pages know to redraw the bars
+    generation: AtomicU64,
+}
+
+impl Poll {
+    pub fn new(store: Arc<Store>) -> Result<Arc<Self>, anyhow::Error> {
+        let salt = store.salt()?;
+        let poll = Arc::new(Self {
+            store,
+            salt,
+            tallies: RwLock::new(vec![0; CHOICES.len()]),
+            generation: AtomicU64::new(0),
+        });
+        poll.reload();
+        Ok(poll)
+    }
+
+    pub fn tallies(&self) -> Vec<u64> {
+        self.tallies.read().unwrap().clone()
+    }
+
+    pub fn generation(&self) -> u64 {
+        self.generation.load(Ordering::Acquire)
+    }
+
+    /// What `visitor` voted for, if they did.
+    pub fn choice_of(&self, visitor: &str) -> Option<usize> {
+        self.store
+            .vote(POLL, &self.voter(visitor))
+            .inspect_err(|e| error!("Failed to load a vote: {}", e))
+            .ok()
+            .flatten()
+    }
+
+    /// Counts `visitor`'s vote for the `choice`th of `CHOICES`, once.
+    pub fn vote(&self, visitor: &str, choice: usize) -> Result<(), VoteError> {
+        match self.store.add_vote(POLL, &self.voter(visitor), choice) {
+            Ok(true) => {}
+            Ok(false) => return Err(VoteError::AlreadyVoted),
+            Err(e) => {
+                error!("Failed to save a vote: {}", e);
+                return Err(VoteError::Failed);
+            }
+        }
+        self.reload();
+        Ok(())
+    }
+
+    fn voter(&self, visitor: &str) -> String {
+        salted_hash(&self.salt, visitor)
+    }
+
+    fn reload(&self) {
+        match self.store.tallies(POLL) {
+            Ok(counted) => {
+                let mut tallies = vec![0; CHOICES.len()];
+                // Choices a since shortened list no longer has are left out
+                for (choice, votes) in counted {
+                    if let Some(tally) = tallies.get_mut(choice) {
+                        *tally = votes;
+                    }
+                }
+                *self.tallies.write().unwrap() = tallies;
+                self.generation.fetch_add(1, Ordering::AcqRel);
+            }
+            Err(e) => error!("Failed to load the poll: {}", e),
+        }
+    }
+}
@@ -0,0 +1,105 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, RwLock};
+use std::time::{Duration, Instant};
+
+use tracing::warn;
+
+use crate::config::{Config, SharedConfig};
+
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// An agent for the APIs pollers fetch from.
+pub fn agent() -> ureq::Agent {
+    ureq::Agent::config_builder()
+        .timeout_global(Some(REQUEST_TIMEOUT))
+        .user_agent(concat!("portfolio-v2/", env!("CARGO_PKG_VERSION")))
+        .build()
+        .into()
+}
+
+/// A value fetched in the background every so often and shared by every
+/// session, like the GitHub repos, the song playing or the weather.
+pub struct Poller<T> {
+    value: RwLock<T>,
+    // When a fetch last went through
+    fetched_at: Mutex<Option<Instant>>,
+    // A value kept through failed fetches is reset once it's this old, rather
+    // than shown as current for as long as the API is down
+    stale_after: Option<Duration>,
+    // Bumped whenever `value` changes, so sessions know to redraw
+    generation: AtomicU64,
+}
+
+impl<T: Clone + Default + PartialEq + Send + Sync + 'static> Poller<T> {
+    /// Starts out at `T::default()`, and keeps a value through failed fetches
+    /// for `stale_after`, or until one goes through if None.
+    pub fn new(stale_after: Option<Duration>) -> Arc<Self> {
+        Arc::new(Self {
+            value: RwLock::new(T::default()),
+            fetched_at: Mutex::new(None),
+            stale_after,
+            generation: AtomicU64::new(0),
+        })
+    }
+
+    pub fn get(&self) -> T {
+        self.value.read().unwrap().clone()
+    }
+
+    pub fn generation(&self) -> u64 {
+        self.generation.load(Ordering::Acquire)
+    }
+
+    /// Calls `fetch` now and then every `interval` of the config, with failures
+    /// logged as failing to fetch `what`.
+    pub fn start(
+        self: &Arc<Self>,
+        what: &'static str,
+        config: SharedConfig,
+        interval: fn(&Config) -> Duration,
+        fetch: impl Fn() -> Result<T, anyhow::Error> + Send + Sync + 'static,
+    ) {
+        let poller = self.clone();
+        let fetch = Arc::new(fetch);
+        tokio::spawn(async move {
+            loop {
+                let (fetching, fetch) = (poller.clone(), fetch.clone());
+                // ureq blocks, so it gets a thread of its own
+                let _ = tokio::task::spawn_blocking(move || fetching.refresh(what, &*fetch)).await;
+                let interval = interval(&config.read().unwrap());
+                tokio::time::sleep(interval).await;
+            }
+        });
+    }
+
+    fn refresh(&self, what: &str, fetch: &dyn Fn() -> Result<T, anyhow::Error>) {
+        match fetch() {
+            Ok(value) => {
+                *self.fetched_at.lock().unwrap() = Some(Instant::now());
+                self.set(value);
+            }
+            Err(e) => {
+                warn!("Failed to fetch {}: {}", what, e);
+                let Some(stale_after) = self.stale_after else {
+                    return;
+                };
+                let stale = self
+                    .fetched_at
+                    .lock()
+                    .unwrap()
+                    .is_none_or(|fetched_at| fetched_at.elapsed() >= stale_after);
+                if stale {
+                    self.set(T::default());
+                }
+            }
+        }
+    }
+
+    fn set(&self, value: T) {
+        let mut current = self.value.write().unwrap();
+        if *current != value {
+            *current = value;
+            self.generation.fetch_add(1, Ordering::AcqRel);
+        }
+    }
+}
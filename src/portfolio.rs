@@ -0,0 +1,110 @@
+use notify::RecommendedWatcher;
+use tracing::info;
+
+use crate::app::App;
+use crate::color_support::ColorSupport;
+use crate::config::SharedConfig;
+use crate::exec;
+use crate::preferences::SessionPreferences;
+use crate::server::{AppFactory, SessionInfo, TuiApp};
+use crate::services::Services;
+
+/// Makes the portfolio for every session, or the admin view for admin keys, and
+/// keeps what they share running: the content watcher and the pollers. What the
+/// `portfolio-v2` binary serves.
+pub struct Portfolio {
+    services: Services,
+    _content_watcher: Option<RecommendedWatcher>,
+}
+
+impl Portfolio {
+    /// `config` is the server's too, so both see the same reloads.
+    pub fn start(config: SharedConfig) -> Result<Self, anyhow::Error> {
+        let services = Services::new(config, true)?;
+        info!(
+            "Serving {} content pages from {}",
+            services.content.entries().len(),
+            services.config.read().unwrap().content_dir.display()
+        );
+        let content_watcher = services.start();
+        Ok(Self {
+            services,
+            _content_watcher: content_watcher,
+        })
+    }
+}
+
+impl AppFactory for Portfolio {
+    fn make(&self, session: SessionInfo) -> Box<dyn TuiApp> {
+        let services = &self.services;
+        let store = services.store.clone();
+        let theme = services.config.read().unwrap().theme();
+        let preferences = match &session.fingerprint {
+            Some(fingerprint) => SessionPreferences::load(store, fingerprint.clone(), theme),
+            None => SessionPreferences::anonymous(store, theme),
+        };
+        if session.admin {
+            return Box::new(App::admin(
+                services,
+                preferences,
+                session.sessions,
+                session.id,
+            ));
+        }
+
+        let location = session
+            .peer_addr
+            .map(|addr| services.geoip.locate(addr.ip()))
+            .unwrap_or_default();
+        info!(
+            country = location.country.as_deref(),
+            city = location.city.as_deref(),
+            "Visitor located"
+        );
+        services.stats.record_visit(
+            session.peer_addr.map(|addr| addr.ip()),
+            &location,
+            &session.term,
+        );
+        let visit = services
+            .notifier
+            .visit(session.via, &session.user, &location);
+
+        let mut app = App::new(services, preferences, session.visitor);
+        if let Some(fingerprint) = session.fingerprint {
+            app.resume(services.store.clone(), fingerprint);
+        }
+        app.start_on(&session.user);
+        app.set_visit(visit);
+        Box::new(app)
+    }
+
+    fn exec(&self, command: &str, colors: Option<ColorSupport>) -> Result<String, String> {
+        exec::run(command, &self.services, colors)
+    }
+
+    /// The same pages `ssh host cat` prints: the about page and what else there
+    /// is for an empty query, or the page asked for.
+    fn finger(&self, query: &str) -> String {
+        let pages = exec::pages(&self.services);
+        if query.is_empty() {
+            let titles = pages
+                .iter()
+                .map(|page| page.title().replace(' ', "-"))
+                .collect::<Vec<_>>();
+            let profile = exec::render(pages[0].as_ref(), None);
+            return format!(
+                "{}\n\nfinger <page>@krayon.dev for one of: {}\nssh krayon.dev for the full site",
+                profile,
+                titles.join(", ")
+            );
+        }
+        match pages
+            .iter()
+            .find(|page| page.title().replace(' ', "-") == query.to_lowercase())
+        {
+            Some(page) => exec::render(page.as_ref(), None),
+            None => format!("no page called '{}', try `finger @krayon.dev`", query),
+        }
+    }
+}
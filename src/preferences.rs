@@ -0,0 +1,267 @@
+use std::sync::{Arc, RwLock};
+
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use tracing::warn;
+
+use crate::keymap::Keys;
+use crate::store::Store;
+use crate::theme::Theme;
+
+// Codes are typed back in by hand, so letters and digits that look alike are left out
+const CODE_ALPHABET: &[u8] = b"abcdefghjkmnpqrstuvwxyz23456789";
+const CODE_LENGTH: usize = 8;
+
+/// How fast pages that move are redrawn, for slow links or terminals.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FrameRateLimit {
+    /// The server's frame rate.
+    Full,
+    Half,
+    /// The idle frame rate, even while something moves.
+    Low,
+}
+
+pub const FRAME_RATE_LIMITS: [FrameRateLimit; 3] = [
+    FrameRateLimit::Full,
+    FrameRateLimit::Half,
+    FrameRateLimit::Low,
+];
+
+impl FrameRateLimit {
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Full => "full",
+            Self::Half => "half",
+            Self::Low => "low",
+        }
+    }
+
+    fn by_name(name: &str) -> Option<Self> {
+        FRAME_RATE_LIMITS
+            .into_iter()
+            .find(|limit| limit.name() == name)
+    }
+}
+
+/// What a visitor picked on the settings page.
+#[derive(Clone, Copy)]
+pub struct Preferences {
+    pub theme: &'static Theme,
+    pub keys: Keys,
+    /// The intro and the about page's dance; off leaves them still.
+    pub animations: bool,
+    pub frame_rate: FrameRateLimit,
+}
+
+impl Preferences {
+    /// What a visitor has before picking anything, with the server's `theme`.
+    fn new(theme: &'static Theme) -> Self {
+        Self {
+            theme,
+            keys: Keys::Standard,
+            animations: true,
+            frame_rate: FrameRateLimit::Full,
+        }
+    }
+}
+
+/// Preferences as stored, by name so a renamed theme only loses that choice.
+#[derive(Default, Serialize, Deserialize)]
+#[serde(default)]
+struct Saved {
+    theme: Option<String>,
+    keys: Option<String>,
+    animations: Option<bool>,
+    frame_rate: Option<String>,
+}
+
+impl Saved {
+    fn apply_to(&self, preferences: &mut Preferences) {
+        if let Some(theme) = self.theme.as_deref().and_then(Theme::by_name) {
+            preferences.theme = theme;
+        }
+        if let Some(keys) = self.keys.as_deref().and_then(Keys::by_name) {
+            preferences.keys = keys;
+        }
+        if let Some(animations) = self.animations {
+            preferences.animations = animations;
+        }
+        if let Some(limit) = self.frame_rate.as_deref().and_then(FrameRateLimit::by_name) {
+            preferences.frame_rate = limit;
+        }
+    }
+
+    fn from(preferences: &Preferences) -> Self {
+        Self {
+            theme: Some(preferences.theme.name.to_string()),
+            keys: Some(preferences.keys.name().to_string()),
+            animations: Some(preferences.animations),
+            frame_rate: Some(preferences.frame_rate.name().to_string()),
+        }
+    }
+}
+
+/// Who the preferences are saved for.
+enum Owner {
+    /// The fingerprint of the key the visitor authenticated with.
+    Key(String),
+    /// A visitor without a key, who gets a code to type in on their next visit.
+    Code(String),
+}
+
+impl Owner {
+    fn store_key(&self) -> String {
+        match self {
+            Self::Key(fingerprint) => fingerprint.clone(),
+            Self::Code(code) => format!("code:{}", code),
+        }
+    }
+}
+
+/// The preferences of one session, shared by the app and its settings page.
+/// Visitors who authenticated with a public key are recognized by its fingerprint;
+/// the others can bring their choices back with the code the settings page shows.
+pub struct SessionPreferences {
+    current: RwLock<Preferences>,
+    saved: Option<(Arc<Store>, RwLock<Owner>)>,
+}
+
+impl SessionPreferences {
+    /// Preferences that only last as long as the session.
+    pub fn unsaved(theme: &'static Theme) -> Arc<Self> {
+        Arc::new(Self {
+            current: RwLock::new(Preferences::new(theme)),
+            saved: None,
+        })
+    }
+
+    pub fn load(store: Arc<Store>, fingerprint: String, theme: &'static Theme) -> Arc<Self> {
+        let owner = Owner::Key(fingerprint);
+        let mut preferences = Preferences::new(theme);
+        if let Some(saved) = read(&store, &owner) {
+            saved.apply_to(&mut preferences);
+        }
+        Arc::new(Self {
+            current: RwLock::new(preferences),
+            saved: Some((store, RwLock::new(owner))),
+        })
+    }
+
+    /// For a visitor without a key: nothing is saved until they change something,
+    /// under a fresh code.
+    pub fn anonymous(store: Arc<Store>, theme: &'static Theme) -> Arc<Self> {
+        Arc::new(Self {
+            current: RwLock::new(Preferences::new(theme)),
+            saved: Some((store, RwLock::new(Owner::Code(new_code())))),
+        })
+    }
+
+    pub fn get(&self) -> Preferences {
+        *self.current.read().unwrap()
+    }
+
+    /// The code to type in next time, as `abcd-efgh`, for visitors without a key.
+    pub fn code(&self) -> Option<String> {
+        let (_, owner) = self.saved.as_ref()?;
+        match &*owner.read().unwrap() {
+            Owner::Code(code) => Some(format!("{}-{}", &code[..4], &code[4..])),
+            Owner::Key(_) => None,
+        }
+    }
+
+    /// What the visitor's preferences are saved under, for anything else kept for
+    /// their next visit. It changes when they bring back a code.
+    pub fn owner(&self) -> Option<String> {
+        let (_, owner) = self.saved.as_ref()?;
+        Some(owner.read().unwrap().store_key())
+    }
+
+    /// Whether anything the visitor picks is kept for their next visit.
+    pub fn is_saved(&self) -> bool {
+        self.saved.is_some()
+    }
+
+    /// Switches to the preferences saved under `code`, which later changes are
+    /// saved under too. False if there are none.
+    pub fn restore(&self, code: &str) -> bool {
+        let Some((store, owner)) = &self.saved else {
+            return false;
+        };
+        let code = code
+            .chars()
+            .filter(char::is_ascii_alphanumeric)
+            .map(|c| c.to_ascii_lowercase())
+            .collect::<String>();
+        if code.len() != CODE_LENGTH {
+            return false;
+        }
+        let restored = Owner::Code(code);
+        let Some(saved) = read(store, &restored) else {
+            return false;
+        };
+        saved.apply_to(&mut self.current.write().unwrap());
+        *owner.write().unwrap() = restored;
+        true
+    }
+
+    pub fn set_theme(&self, theme: &'static Theme) {
+        self.update(|preferences| preferences.theme = theme);
+    }
+
+    pub fn set_keys(&self, keys: Keys) {
+        self.update(|preferences| preferences.keys = keys);
+    }
+
+    pub fn set_animations(&self, animations: bool) {
+        self.update(|preferences| preferences.animations = animations);
+    }
+
+    pub fn set_frame_rate(&self, limit: FrameRateLimit) {
+        self.update(|preferences| preferences.frame_rate = limit);
+    }
+
+    fn update(&self, change: impl FnOnce(&mut Preferences)) {
+        let preferences = {
+            let mut current = self.current.write().unwrap();
+            change(&mut current);
+            *current
+        };
+        let Some((store, owner)) = &self.saved else {
+            return;
+        };
+        let key = owner.read().unwrap().store_key();
+        let saved = serde_json::to_string(&Saved::from(&preferences))
+            .expect("preferences always serialize");
+        if let Err(e) = store.set_preferences(&key, &saved) {
+            warn!("Failed to save preferences for {}: {}", key, e);
+        }
+    }
+}
+
+fn read(store: &Store, owner: &Owner) -> Option<Saved> {
+    let key = owner.store_key();
+    match store.preferences(&key) {
+        Ok(Some(saved)) => match serde_json::from_str(&saved) {
+            Ok(saved) => Some(saved),
+            Err(e) => {
+                warn!("Ignoring unreadable preferences for {}: {}", key, e);
+                None
+            }
+        },
+        Ok(None) => None,
+        Err(e) => {
+            warn!("Failed to load preferences for {}: {}", key, e);
+            None
+        }
+    }
+}
+
+fn new_code() -> String {
+    let mut bytes = [0u8; CODE_LENGTH];
+    rand::rng().fill_bytes(&mut bytes);
+    bytes
+        .iter()
+        .map(|b| CODE_ALPHABET[*b as usize % CODE_ALPHABET.len()] as char)
+        .collect()
+}
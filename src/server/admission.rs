@@ -0,0 +1,78 @@
+use std::net::SocketAddr;
+use std::sync::Arc;
+use std::sync::atomic::Ordering;
+use std::time::SystemTime;
+
+use crate::server::audit::{AuditEntry, AuditLog, ByteCounts};
+use crate::server::hooks::{Hooks, SharedConnectionInfo};
+use crate::server::rate_limit::{RateLimiter, Verdict};
+
+/// What a connection that runs sessions has to get past before anything else
+/// happens, SSH or telnet alike: the rate limiter and the connect hooks. Once
+/// it's over, this is also where it goes in the audit log.
+pub struct Admission {
+    rate_limiter: Arc<RateLimiter>,
+    hooks: Arc<Hooks>,
+    audit: Option<Arc<AuditLog>>,
+}
+
+impl Admission {
+    pub fn new(
+        rate_limiter: Arc<RateLimiter>,
+        hooks: Arc<Hooks>,
+        audit: Option<Arc<AuditLog>>,
+    ) -> Self {
+        Self {
+            rate_limiter,
+            hooks,
+            audit,
+        }
+    }
+
+    pub fn hooks(&self) -> &Arc<Hooks> {
+        &self.hooks
+    }
+
+    /// Why the connection from `peer_addr` is turned away, or `None` to let it
+    /// in. Counts as an attempt with the rate limiter either way.
+    pub fn refusal(&self, peer_addr: SocketAddr) -> Option<&'static str> {
+        match self.rate_limiter.check(peer_addr.ip()) {
+            Verdict::Allow if self.hooks.connect(peer_addr) => None,
+            Verdict::Allow => Some("refused by a hook"),
+            Verdict::RateLimited => Some("rate limited"),
+            Verdict::Banned => Some("banned"),
+            Verdict::Denied => Some("denied"),
+        }
+    }
+
+    /// Adds the connection to the audit log, if there is one. `via` is how it
+    /// came in, like `ssh`.
+    pub fn audit(
+        &self,
+        via: &str,
+        connected_at: SystemTime,
+        connection: &SharedConnectionInfo,
+        counts: &ByteCounts,
+        reason: &str,
+    ) {
+        let Some(audit) = &self.audit else {
+            return;
+        };
+        let connection = connection.lock().unwrap();
+        let Some(peer_addr) = connection.peer_addr else {
+            return;
+        };
+        audit.record(&AuditEntry {
+            connected_at,
+            peer_addr,
+            via,
+            user: connection.user.as_deref(),
+            fingerprint: connection.fingerprint.as_deref(),
+            admin: connection.admin,
+            duration: connection.connected_at.elapsed(),
+            bytes_in: counts.read.load(Ordering::Relaxed),
+            bytes_out: counts.written.load(Ordering::Relaxed),
+            reason,
+        });
+    }
+}
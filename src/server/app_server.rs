@@ -1,299 +1,466 @@
-use std::collections::HashMap;
-use std::env;
+use std::borrow::Cow;
+use std::net::SocketAddr;
 use std::path::Path;
-use std::sync::Arc;
-
-use crossterm::event::KeyCode;
-use ratatui::backend::CrosstermBackend;
-use ratatui::layout::Rect;
-use ratatui::{Terminal, TerminalOptions, Viewport};
-use russh::server::Handle;
-use russh::{Channel, ChannelId, Pty};
-use russh::{MethodKind, MethodSet, server::*};
-use tokio::sync::Mutex;
-use tokio::sync::mpsc::unbounded_channel;
-
-use crate::app::App;
-use crate::server::TerminalHandle;
-
-type SshTerminal = Terminal<CrosstermBackend<TerminalHandle>>;
-
-#[derive(Clone)]
-pub struct AppServer {
-    clients: Arc<Mutex<HashMap<usize, (SshTerminal, App, std::time::Instant, Handle, ChannelId)>>>,
-    id: usize,
+use std::sync::{Arc, Mutex, RwLock};
+use std::time::{Duration, SystemTime};
+
+use russh::server::{Config as SshConfig, Server};
+use russh::{Preferred, compression};
+use socket2::SockRef;
+use tokio::net::{TcpListener, TcpStream};
+use tokio::signal::unix::{SignalKind, signal};
+use tokio::task::JoinSet;
+use tracing::{debug, error, info, warn};
+
+use crate::config::{Config, SharedConfig};
+use crate::error::SessionError;
+use crate::server::admission::Admission;
+use crate::server::audit::{AuditLog, ByteCounts, CountingStream};
+use crate::server::builder::AppServerBuilder;
+use crate::server::control::ControlSocket;
+use crate::server::finger::FingerListener;
+use crate::server::honeypot::Honeypot;
+use crate::server::hooks::{ConnectionInfo, Hooks, SharedConnectionInfo};
+use crate::server::privileges;
+use crate::server::proxy_protocol;
+use crate::server::rate_limit::RateLimiter;
+use crate::server::session_registry::GOODBYE_MESSAGE;
+use crate::server::shutdown::Shutdown;
+use crate::server::systemd;
+use crate::server::telnet::TelnetListener;
+use crate::server::tui_app::AppFactory;
+use crate::server::web::WebGateway;
+use crate::server::{Authenticator, ConnectionHandler, SessionRegistry, host_key};
+use crate::store::Store;
+
+// How long a connection has to send its PROXY header before it is dropped
+const PROXY_HEADER_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Cloned into every connection's task, which then makes its handler. `F` makes
+/// the app each session runs, and is whatever `AppServerBuilder` was given.
+pub struct AppServer<F = ()> {
+    config: SharedConfig,
+    registry: SessionRegistry,
+    rate_limiter: Arc<RateLimiter>,
+    // Swapped on reload; connections keep the authenticator they started with
+    authenticator: Arc<RwLock<Arc<Authenticator>>>,
+    app_factory: Arc<F>,
+    admission: Arc<Admission>,
+    honeypot: Option<Arc<Honeypot>>,
+    // Fired before draining, for the web, telnet and finger listeners
+    shutdown: Shutdown,
 }
 
-impl AppServer {
-    pub fn new() -> Self {
+// Derived, it would need `F: Clone` too
+impl<F> Clone for AppServer<F> {
+    fn clone(&self) -> Self {
         Self {
-            clients: Arc::new(Mutex::new(HashMap::new())),
-            id: 0,
+            config: self.config.clone(),
+            registry: self.registry.clone(),
+            rate_limiter: self.rate_limiter.clone(),
+            authenticator: self.authenticator.clone(),
+            app_factory: self.app_factory.clone(),
+            admission: self.admission.clone(),
+            honeypot: self.honeypot.clone(),
+            shutdown: self.shutdown.clone(),
         }
     }
+}
 
-    fn load_host_keys() -> Result<russh::keys::PrivateKey, anyhow::Error> {
-        let secrets_location =
-            env::var("SECRETS_LOCATION").expect("SECRETS_LOCATION was not defined.");
-        let key_path = Path::new(&secrets_location);
+impl AppServer {
+    pub fn builder() -> AppServerBuilder {
+        AppServerBuilder::new()
+    }
+}
 
-        if !key_path.exists() {
-            return Err(anyhow::anyhow!(
-                "Host key not found at {}. Please generate host keys first.",
-                key_path.display()
-            ));
-        }
+impl<F: AppFactory> AppServer<F> {
+    pub(crate) fn new(
+        config: SharedConfig,
+        app_factory: Arc<F>,
+        hooks: Arc<Hooks>,
+    ) -> Result<Self, anyhow::Error> {
+        let (honeypot, audit) = {
+            let config = config.read().unwrap();
+            // The database only keeps the salt addresses are hashed with here
+            let honeypot = config
+                .honeypot_log
+                .as_deref()
+                .map(|path| {
+                    let store = Store::open(&config.database)?;
+                    Honeypot::open(path, config.honeypot_raw, &store)
+                })
+                .transpose()?;
+            let audit = config
+                .audit_log
+                .as_deref()
+                .map(|path| AuditLog::open(path, config.audit_log_max_bytes, config.audit_log_keep))
+                .transpose()?;
+            (honeypot, audit)
+        };
+        let rate_limiter = Arc::new(RateLimiter::new(config.clone()));
+        Ok(Self {
+            registry: SessionRegistry::new(config.clone()),
+            admission: Arc::new(Admission::new(rate_limiter.clone(), hooks, audit)),
+            rate_limiter,
+            config,
+            authenticator: Arc::new(RwLock::new(Arc::new(Authenticator::anonymous()))),
+            app_factory,
+            honeypot,
+            shutdown: Shutdown::new(),
+        })
+    }
 
-        let key = russh::keys::PrivateKey::read_openssh_file(key_path)
-            .map_err(|e| anyhow::anyhow!("Failed to read host key: {}", e))?;
+    fn load_host_keys(config: &Config) -> Result<Vec<russh::keys::PrivateKey>, anyhow::Error> {
+        let key_path: &Path = config.host_key.as_deref().ok_or_else(|| {
+            anyhow::anyhow!("No host key configured, set host_key or SECRETS_LOCATION")
+        })?;
+        host_key::load(key_path, config.auto_generate_host_key)
+    }
+
+    fn reload(
+        config: &SharedConfig,
+        authenticator: &RwLock<Arc<Authenticator>>,
+        honeypot: &Option<Arc<Honeypot>>,
+    ) {
+        let mut config = config.write().unwrap();
+        if let Err(e) = config.reload() {
+            error!("Failed to reload config, keeping the current one: {}", e);
+            return;
+        }
 
-        Ok(key)
+        match Authenticator::from_config(&config, honeypot.clone()) {
+            Ok(reloaded) => *authenticator.write().unwrap() = Arc::new(reloaded),
+            Err(e) => error!("Failed to reload auth, keeping the current keys: {}", e),
+        }
+        info!(
+            max_clients = config.max_clients,
+            auth = %authenticator.read().unwrap().describe(),
+            "Reloaded config"
+        );
     }
 
     pub async fn run(&mut self) -> Result<(), anyhow::Error> {
-        let clients = self.clients.clone();
+        let registry = self.registry.clone();
+        let config = self.config.clone();
         tokio::spawn(async move {
-            let mut tick: u64 = 0;
             loop {
-                tokio::time::sleep(tokio::time::Duration::from_millis(1000 / 30)).await;
-
-                for (_, (terminal, app, _, _, _)) in clients.lock().await.iter_mut() {
-                    app.handle_tick(tick);
-
-                    let _ = terminal.draw(|f| {
-                        app.draw(f);
-                    });
+                tokio::time::sleep(std::time::Duration::from_secs(1)).await;
+                let idle_timeout = config.read().unwrap().idle_timeout();
+                if idle_timeout.is_zero() {
+                    continue;
+                }
+                let expired = registry.idle_longer_than(idle_timeout);
+                for (id, client) in expired {
+                    // Input may have arrived since, or the client hung up
+                    if client.idle_for() <= idle_timeout {
+                        continue;
+                    }
+                    let Some(client) = registry.remove(id) else {
+                        continue;
+                    };
+                    info!(parent: &client.span, "Disconnecting idle session");
+                    // A client that stopped reading mustn't hold up the others
+                    tokio::spawn(client.close_with(b""));
                 }
-                tick = tick.wrapping_add(1);
             }
         });
 
-        let clients_timeout = self.clients.clone();
+        let rate_limiter = self.rate_limiter.clone();
         tokio::spawn(async move {
             loop {
-                tokio::time::sleep(std::time::Duration::from_secs(1)).await;
-                let mut to_remove = Vec::new();
-                {
-                    let clients_lock = clients_timeout.lock().await;
-                    for (&id, (_, _, last_activity, handle, channel_id)) in clients_lock.iter() {
-                        if last_activity.elapsed() > std::time::Duration::from_secs(300) {
-                            to_remove.push((id, handle.clone(), *channel_id));
-                        }
-                    }
-                }
-                for (id, handle, channel_id) in to_remove {
-                    let reset_sequence = b"\x1b[0m\x1b[2J\x1b[H\x1b[r\x1b[?25h";
-                    let _ = handle
-                        .data(channel_id, reset_sequence.as_ref().into())
-                        .await;
-                    let _ = handle.close(channel_id).await;
-                    clients_timeout.lock().await.remove(&id);
-                }
+                tokio::time::sleep(std::time::Duration::from_secs(60)).await;
+                rate_limiter.prune();
             }
         });
 
-        let mut methods = MethodSet::empty();
-        methods.push(MethodKind::None);
+        let mut hangup = signal(SignalKind::hangup())?;
+        let config = self.config.clone();
+        let authenticator = self.authenticator.clone();
+        let honeypot = self.honeypot.clone();
+        tokio::spawn(async move {
+            while hangup.recv().await.is_some() {
+                Self::reload(&config, &authenticator, &honeypot);
+            }
+        });
 
-        println!("Starting SSH server on port 22...");
+        let (ssh_config, listen) = {
+            let config = self.config.read().unwrap();
+            let authenticator = Authenticator::from_config(&config, self.honeypot.clone())?;
+
+            info!(
+                listen = %config.listen,
+                max_clients = config.max_clients,
+                auth = %authenticator.describe(),
+                "Starting SSH server"
+            );
+
+            let host_keys = Self::load_host_keys(&config)
+                .map_err(|e| anyhow::anyhow!("Failed to load host keys: {}", e))?;
+            let algorithms = host_keys
+                .iter()
+                .map(|key| key.algorithm().to_string())
+                .collect::<Vec<_>>();
+            info!("Offering host keys: {}", algorithms.join(", "));
+
+            let ssh_config = SshConfig {
+                inactivity_timeout: None,
+                auth_rejection_time: std::time::Duration::from_secs(3),
+                auth_rejection_time_initial: Some(std::time::Duration::from_secs(0)),
+                methods: authenticator.methods(),
+                keys: host_keys,
+                nodelay: true,
+                // Catches connections that vanished without a FIN; russh then ends
+                // the session and the handler's Drop removes it from the registry
+                keepalive_interval: config.keepalive_interval(),
+                keepalive_max: config.keepalive_max,
+                preferred: preferred_algorithms(config.compression),
+                ..Default::default()
+            };
+
+            *self.authenticator.write().unwrap() = Arc::new(authenticator);
+            (ssh_config, config.listen)
+        };
 
-        let host_key = Self::load_host_keys()
-            .map_err(|e| anyhow::anyhow!("Failed to load host keys: {}", e))?;
+        let control_socket = self.config.read().unwrap().control_socket.clone();
+        if let Some(path) = &control_socket {
+            let control = ControlSocket::bind(path, self.registry.clone())?;
+            info!("Accepting operator commands on {}", path.display());
+            tokio::spawn(control.run());
+        }
 
-        let config = Config {
-            inactivity_timeout: None,
-            auth_rejection_time: std::time::Duration::from_secs(3),
-            auth_rejection_time_initial: Some(std::time::Duration::from_secs(0)),
-            methods,
-            keys: vec![host_key],
-            nodelay: true,
-            ..Default::default()
+        let listener = match systemd::inherited_listener()? {
+            Some(listener) => {
+                info!(listen = %listener.local_addr()?, "Using the socket passed by systemd");
+                TcpListener::from_std(listener)?
+            }
+            None => TcpListener::bind(listen).await?,
         };
-
-        self.run_on_address(Arc::new(config), ("0.0.0.0", 22))
+        // The actual port, when the configured one is 0
+        info!(listen = %listener.local_addr()?, "Accepting connections");
+        let (web_listen, telnet_listen, finger_listen) = {
+            let config = self.config.read().unwrap();
+            (
+                config.web_listen,
+                config.telnet_listen,
+                config.finger_listen,
+            )
+        };
+        if let Some(web_listen) = web_listen {
+            // Bound before privileges are dropped, like the SSH port
+            let web_listener = TcpListener::bind(web_listen).await?;
+            info!(listen = %web_listener.local_addr()?, "Serving the web terminal");
+            let gateway = WebGateway::new(
+                self.registry.clone(),
+                self.config.clone(),
+                self.rate_limiter.clone(),
+                self.app_factory.clone(),
+            );
+            tokio::spawn(gateway.run(web_listener, self.shutdown.clone()));
+        }
+        if let Some(telnet_listen) = telnet_listen {
+            let telnet_listener = TcpListener::bind(telnet_listen).await?;
+            warn!(
+                listen = %telnet_listener.local_addr()?,
+                "Serving unencrypted telnet"
+            );
+            let telnet = TelnetListener::new(
+                self.registry.clone(),
+                self.config.clone(),
+                self.admission.clone(),
+                self.app_factory.clone(),
+            );
+            tokio::spawn(telnet.run(telnet_listener, self.shutdown.clone()));
+        }
+        if let Some(finger_listen) = finger_listen {
+            let finger_listener = TcpListener::bind(finger_listen).await?;
+            info!(listen = %finger_listener.local_addr()?, "Answering finger queries");
+            let finger = FingerListener::new(self.rate_limiter.clone(), self.app_factory.clone());
+            tokio::spawn(finger.run(finger_listener, self.shutdown.clone()));
+        }
+        let (user, group) = {
+            let config = self.config.read().unwrap();
+            (config.user.clone(), config.group.clone())
+        };
+        if let Some(user) = user {
+            privileges::drop_to(&user, group.as_deref())?;
+        }
+        systemd::notify_ready();
+        let connections = self
+            .accept_until_shutdown(Arc::new(ssh_config), listener)
             .await?;
-        Ok(())
-    }
-
-    fn map_key_event(data: &[u8]) -> Option<KeyCode> {
-        match data {
-            b"q" => Some(KeyCode::Char('q')),
-            b"Q" => Some(KeyCode::Char('Q')),
-            b"\x1b[A" | b"\x1bOA" => Some(KeyCode::Up),
-            b"\x1b[B" | b"\x1bOB" => Some(KeyCode::Down),
-            b"\x1b[C" | b"\x1bOC" => Some(KeyCode::Right),
-            b"\x1b[D" | b"\x1bOD" => Some(KeyCode::Left),
-            b"\x1b[5~" => Some(KeyCode::PageUp),
-            b"\x1b[6~" => Some(KeyCode::PageDown),
-            b"\x1b[H" | b"\x1bOH" => Some(KeyCode::Home),
-            b"\x1b[F" | b"\x1bOF" => Some(KeyCode::End),
-            b"\t" => Some(KeyCode::Tab),
-            b"\x7f" => Some(KeyCode::Backspace),
-            b"\x1b[3~" => Some(KeyCode::Delete),
-            b"\r" | b"\n" => Some(KeyCode::Enter),
-            b" " => Some(KeyCode::Char(' ')),
-            [c] if c.is_ascii() && c.is_ascii_graphic() => Some(KeyCode::Char(*c as char)),
-            _ => None,
+        systemd::notify_stopping();
+        self.drain(connections).await;
+        if let Some(path) = control_socket {
+            let _ = std::fs::remove_file(path);
         }
+        Ok(())
     }
-}
-
-impl Server for AppServer {
-    type Handler = Self;
-    fn new_client(&mut self, _: Option<std::net::SocketAddr>) -> Self {
-        let s = self.clone();
-        self.id += 1;
-        s
-    }
-}
-
-impl Handler for AppServer {
-    type Error = anyhow::Error;
 
-    async fn channel_open_session(
+    /// Accepts connections until SIGTERM or SIGINT. The returned set holds every
+    /// connection that is still open.
+    async fn accept_until_shutdown(
         &mut self,
-        channel: Channel<Msg>,
-        session: &mut Session,
-    ) -> Result<bool, Self::Error> {
-        let (sender, mut receiver) = unbounded_channel::<Vec<u8>>();
-        let channel_id = channel.id();
-        let handle = session.handle();
-        let handle_clone = handle.clone();
+        ssh_config: Arc<SshConfig>,
+        listener: TcpListener,
+    ) -> Result<JoinSet<()>, anyhow::Error> {
+        let mut terminate = signal(SignalKind::terminate())?;
+        let mut interrupt = signal(SignalKind::interrupt())?;
+        let mut connections = JoinSet::new();
+
+        loop {
+            tokio::select! {
+                _ = terminate.recv() => break,
+                _ = interrupt.recv() => break,
+                Some(_) = connections.join_next(), if !connections.is_empty() => {}
+                accepted = listener.accept() => {
+                    let (socket, peer_addr) = match accepted {
+                        Ok(accepted) => accepted,
+                        Err(e) => {
+                            warn!("Failed to accept connection: {}", e);
+                            continue;
+                        }
+                    };
+                    let _ = socket.set_nodelay(true);
+                    // A client that stops reading leaves russh blocked on the write,
+                    // where its keepalive timer can't fire. Let the kernel give up on
+                    // unacknowledged output after the same grace period instead
+                    if let Some(interval) = ssh_config.keepalive_interval {
+                        let user_timeout = interval * ssh_config.keepalive_max.max(1) as u32;
+                        let _ = SockRef::from(&socket).set_tcp_user_timeout(Some(user_timeout));
+                    }
 
-        tokio::spawn(async move {
-            while let Some(data) = receiver.recv().await {
-                let result = handle_clone.data(channel_id, data.into()).await;
-                if result.is_err() {
-                    eprintln!("Failed to send data: {result:?}");
-                    break;
+                    let server = self.clone();
+                    let ssh_config = ssh_config.clone();
+                    connections.spawn(server.serve_connection(socket, peer_addr, ssh_config));
                 }
             }
-        });
-
-        let terminal_handle = TerminalHandle::new_with_sender(sender);
-        let backend = CrosstermBackend::new(terminal_handle);
-
-        let options = TerminalOptions {
-            viewport: Viewport::Fixed(Rect::default()),
-        };
-
-        let terminal = Terminal::with_options(backend, options)?;
-        let app = App::new();
-
-        let mut clients = self.clients.lock().await;
-        clients.insert(
-            self.id,
-            (terminal, app, std::time::Instant::now(), handle, channel_id),
-        );
-
-        Ok(true)
-    }
+        }
 
-    async fn auth_none(&mut self, _: &str) -> Result<Auth, Self::Error> {
-        Ok(Auth::Accept)
+        Ok(connections)
     }
 
-    async fn data(
-        &mut self,
-        channel: ChannelId,
-        data: &[u8],
-        session: &mut Session,
-    ) -> Result<(), Self::Error> {
-        if let Some(key_code) = Self::map_key_event(data) {
-            let mut clients = self.clients.lock().await;
-            if let Some((_, app, last_activity, _, _)) = clients.get_mut(&self.id) {
-                *last_activity = std::time::Instant::now();
-                let handle_result = app.handle_key_event(key_code);
-                if handle_result.is_err() {
-                    // Send terminal reset sequence directly through SSH session
-                    let reset_sequence = b"\x1b[0m\x1b[2J\x1b[H\x1b[r\x1b[?25h";
-                    let _ = session.data(channel, reset_sequence.as_ref().into());
-
-                    clients.remove(&self.id);
-                    session.close(channel)?;
+    /// Runs the SSH session, after the PROXY header if `proxy_protocol` is on.
+    async fn serve_connection(
+        self,
+        mut socket: TcpStream,
+        peer_addr: SocketAddr,
+        ssh_config: Arc<SshConfig>,
+    ) {
+        let proxy_protocol = self.config.read().unwrap().proxy_protocol;
+        let peer_addr = match proxy_protocol {
+            false => peer_addr,
+            true => {
+                let header = proxy_protocol::read_header(&mut socket);
+                match tokio::time::timeout(PROXY_HEADER_TIMEOUT, header).await {
+                    Ok(Ok(Some(source))) => source,
+                    // The proxy itself, e.g. a health check
+                    Ok(Ok(None)) => peer_addr,
+                    Ok(Err(e)) => {
+                        warn!(peer = %peer_addr, "Dropping connection: {}", e);
+                        return;
+                    }
+                    Err(_) => {
+                        warn!(peer = %peer_addr, "Dropping connection: no PROXY header");
+                        return;
+                    }
                 }
             }
-        }
-
-        Ok(())
-    }
-
-    async fn window_change_request(
-        &mut self,
-        _: ChannelId,
-        col_width: u32,
-        row_height: u32,
-        _: u32,
-        _: u32,
-        _: &mut Session,
-    ) -> Result<(), Self::Error> {
-        let rect = Rect {
-            x: 0,
-            y: 0,
-            width: col_width as u16,
-            height: row_height as u16,
         };
 
-        let mut clients = self.clients.lock().await;
-        if let Some((terminal, _, _, _, _)) = clients.get_mut(&self.id) {
-            let _ = terminal.resize(rect);
+        // Refused connections are dropped before the SSH handshake
+        let connected_at = SystemTime::now();
+        let refused = self.admission.refusal(peer_addr);
+        let connection = Arc::new(Mutex::new(ConnectionInfo::new(Some(peer_addr))));
+        let counts = Arc::new(ByteCounts::default());
+        if let Some(reason) = refused {
+            self.admission
+                .audit("ssh", connected_at, &connection, &counts, reason);
+            return;
+        }
+        let handler = self.handler_for(connection.clone());
+        let socket = CountingStream::new(socket, counts.clone());
+        let result = match russh::server::run_stream(ssh_config, socket, handler).await {
+            Ok(session) => session.await,
+            Err(e) => Err(e),
+        };
+        if let Err(e) = &result {
+            self.admission.hooks().error(&connection, e);
+        }
+        self.admission.hooks().disconnect(&connection);
+        let reason = match &result {
+            Ok(()) => String::from("closed"),
+            Err(e) => e.to_string(),
+        };
+        self.admission
+            .audit("ssh", connected_at, &connection, &counts, &reason);
+        match result {
+            Ok(()) => {}
+            Err(SessionError::Disconnected(e)) => {
+                debug!(peer = %peer_addr, "Connection ended: {}", e)
+            }
+            Err(SessionError::Render(e)) => warn!(peer = %peer_addr, "Connection closed: {}", e),
         }
-
-        Ok(())
     }
 
-    async fn pty_request(
-        &mut self,
-        channel: ChannelId,
-        _: &str,
-        col_width: u32,
-        row_height: u32,
-        _: u32,
-        _: u32,
-        _: &[(Pty, u32)],
-        session: &mut Session,
-    ) -> Result<(), Self::Error> {
-        let rect = Rect {
-            x: 0,
-            y: 0,
-            width: col_width as u16,
-            height: row_height as u16,
-        };
+    fn handler_for(&self, connection: SharedConnectionInfo) -> ConnectionHandler<F> {
+        ConnectionHandler::new(
+            self.registry.clone(),
+            self.config.clone(),
+            self.authenticator.read().unwrap().clone(),
+            self.app_factory.clone(),
+            self.admission.hooks().clone(),
+            connection,
+        )
+    }
 
-        let mut clients = self.clients.lock().await;
-        if let Some((terminal, _, _, _, _)) = clients.get_mut(&self.id) {
-            let _ = terminal.resize(rect);
+    /// Stops the other listeners and says goodbye to every visitor so their
+    /// terminal is left usable, then waits for their clients to hang up, giving up
+    /// after the drain timeout.
+    async fn drain(&self, mut connections: JoinSet<()>) {
+        self.shutdown.fire();
+        let sessions = self.registry.remove_all();
+        info!(
+            sessions = sessions.len(),
+            connections = connections.len(),
+            "Shutting down, closing every session"
+        );
+        for session in sessions {
+            session.close_with(GOODBYE_MESSAGE).await;
         }
 
-        session.channel_success(channel)?;
-        Ok(())
+        let drain_timeout = self.config.read().unwrap().drain_timeout();
+        let drained = tokio::time::timeout(drain_timeout, async {
+            while connections.join_next().await.is_some() {}
+        })
+        .await;
+        if drained.is_err() {
+            warn!(
+                "{} connections still open after {:?}, exiting anyway",
+                connections.len(),
+                drain_timeout
+            );
+        }
     }
+}
 
-    async fn channel_close(
-        &mut self,
-        channel: ChannelId,
-        session: &mut Session,
-    ) -> Result<(), Self::Error> {
-        let mut clients = self.clients.lock().await;
-
-        // Send terminal reset sequence directly through SSH session
-        let reset_sequence = b"\x1b[0m\x1b[2J\x1b[H\x1b[r\x1b[?25h";
-        let _ = session.data(channel, reset_sequence.as_ref().into());
+impl<F: AppFactory> Server for AppServer<F> {
+    type Handler = ConnectionHandler<F>;
 
-        clients.remove(&self.id);
-        session.close(channel)?;
-        Ok(())
+    fn new_client(&mut self, peer_addr: Option<SocketAddr>) -> ConnectionHandler<F> {
+        self.handler_for(Arc::new(Mutex::new(ConnectionInfo::new(peer_addr))))
     }
 }
 
-impl Drop for AppServer {
-    fn drop(&mut self) {
-        let id = self.id;
-        let clients = self.clients.clone();
-        // Note: Can't send reset sequence here since we don't have session access
-        tokio::spawn(async move {
-            let mut clients = clients.lock().await;
-            clients.remove(&id);
-        });
+/// russh's defaults, offering zlib@openssh.com before none when compression is
+/// on. The client's order decides, and russh only starts compressing once the
+/// visitor is authenticated, so nothing before that goes through zlib.
+fn preferred_algorithms(compression: bool) -> Preferred {
+    let names: &'static [compression::Name] = match compression {
+        true => &[compression::ZLIB_LEGACY, compression::NONE],
+        false => &[compression::NONE],
+    };
+    Preferred {
+        compression: Cow::Borrowed(names),
+        ..Preferred::default()
     }
 }
@@ -0,0 +1,167 @@
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Write};
+use std::net::SocketAddr;
+use std::path::{Path, PathBuf};
+use std::pin::Pin;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use serde_json::json;
+use tokio::io::{AsyncRead, AsyncWrite, ReadBuf};
+use tracing::warn;
+
+/// One connection, from accept to hang up, as it goes in the audit log.
+pub struct AuditEntry<'a> {
+    pub connected_at: SystemTime,
+    pub peer_addr: SocketAddr,
+    /// How it came in, `ssh` or `telnet`.
+    pub via: &'a str,
+    pub user: Option<&'a str>,
+    pub fingerprint: Option<&'a str>,
+    pub admin: bool,
+    pub duration: Duration,
+    pub bytes_in: u64,
+    pub bytes_out: u64,
+    /// Why it ended, like `closed` or `rate limited`.
+    pub reason: &'a str,
+}
+
+/// An append-only record of every SSH and telnet connection, one JSON object per
+/// line, kept apart from the tracing output so it doesn't change with the log
+/// level. Once the file reaches `max_bytes` it becomes `<path>.1`, the one before
+/// `<path>.2` and so on, keeping `keep` of them.
+pub struct AuditLog {
+    path: PathBuf,
+    max_bytes: u64,
+    keep: usize,
+    file: Mutex<(File, u64)>,
+}
+
+impl AuditLog {
+    pub fn open(path: &Path, max_bytes: u64, keep: usize) -> Result<Arc<Self>, anyhow::Error> {
+        let file = Self::open_file(path).map_err(|e| {
+            anyhow::anyhow!("Failed to open audit log at {}: {}", path.display(), e)
+        })?;
+        let size = file.metadata()?.len();
+        Ok(Arc::new(Self {
+            path: path.to_path_buf(),
+            max_bytes,
+            keep,
+            file: Mutex::new((file, size)),
+        }))
+    }
+
+    pub fn record(&self, entry: &AuditEntry) {
+        let connected_at = entry
+            .connected_at
+            .duration_since(UNIX_EPOCH)
+            .map(|since| since.as_secs())
+            .unwrap_or_default();
+        let line = json!({
+            "connected_at": connected_at,
+            "ip": entry.peer_addr.ip().to_canonical().to_string(),
+            "via": entry.via,
+            "user": entry.user,
+            "fingerprint": entry.fingerprint,
+            "admin": entry.admin,
+            "duration_secs": (entry.duration.as_secs_f64() * 1000.0).round() / 1000.0,
+            "bytes_in": entry.bytes_in,
+            "bytes_out": entry.bytes_out,
+            "reason": entry.reason,
+        })
+        .to_string();
+
+        let mut file = self.file.lock().unwrap();
+        if self.max_bytes > 0 && file.1 > 0 && file.1 + line.len() as u64 >= self.max_bytes {
+            match self.rotate() {
+                Ok(fresh) => *file = (fresh, 0),
+                Err(e) => warn!("Failed to rotate the audit log: {}", e),
+            }
+        }
+        match writeln!(file.0, "{}", line) {
+            Ok(()) => file.1 += line.len() as u64 + 1,
+            Err(e) => warn!("Failed to write to the audit log: {}", e),
+        }
+    }
+
+    fn rotate(&self) -> io::Result<File> {
+        let rotated = |n: usize| {
+            let mut name = self.path.clone().into_os_string();
+            name.push(format!(".{}", n));
+            PathBuf::from(name)
+        };
+        if self.keep == 0 {
+            fs::remove_file(&self.path)?;
+        } else {
+            let _ = fs::remove_file(rotated(self.keep));
+            for n in (1..self.keep).rev() {
+                let _ = fs::rename(rotated(n), rotated(n + 1));
+            }
+            fs::rename(&self.path, rotated(1))?;
+        }
+        Self::open_file(&self.path)
+    }
+
+    fn open_file(path: &Path) -> io::Result<File> {
+        OpenOptions::new().create(true).append(true).open(path)
+    }
+}
+
+/// Bytes that went through a `CountingStream`, readable while it's in use.
+#[derive(Default)]
+pub struct ByteCounts {
+    pub read: AtomicU64,
+    pub written: AtomicU64,
+}
+
+/// A stream that counts what goes through it, for the audit log.
+pub struct CountingStream<S> {
+    inner: S,
+    counts: Arc<ByteCounts>,
+}
+
+impl<S> CountingStream<S> {
+    pub fn new(inner: S, counts: Arc<ByteCounts>) -> Self {
+        Self { inner, counts }
+    }
+}
+
+impl<S: AsyncRead + Unpin> AsyncRead for CountingStream<S> {
+    fn poll_read(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+        let before = buf.filled().len();
+        let poll = Pin::new(&mut self.inner).poll_read(cx, buf);
+        let read = (buf.filled().len() - before) as u64;
+        self.counts.read.fetch_add(read, Ordering::Relaxed);
+        poll
+    }
+}
+
+impl<S: AsyncWrite + Unpin> AsyncWrite for CountingStream<S> {
+    fn poll_write(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        let poll = Pin::new(&mut self.inner).poll_write(cx, buf);
+        if let Poll::Ready(Ok(written)) = poll {
+            self.counts
+                .written
+                .fetch_add(written as u64, Ordering::Relaxed);
+        }
+        poll
+    }
+
+    fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.inner).poll_flush(cx)
+    }
+
+    fn poll_shutdown(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.inner).poll_shutdown(cx)
+    }
+}
@@ -0,0 +1,153 @@
+use std::path::Path;
+use std::sync::Arc;
+
+use russh::keys::PublicKey;
+use russh::keys::ssh_key::AuthorizedKeys;
+use russh::keys::ssh_key::public::KeyData;
+use russh::{MethodKind, MethodSet};
+
+use crate::config::{AuthMode, Config};
+use crate::server::challenge::ChallengeKind;
+use crate::server::honeypot::Honeypot;
+
+/// Who gets a visitor session. Anonymous access is the default: any public key is
+/// accepted so returning visitors can be recognized, and clients without one get
+/// in through an empty keyboard-interactive round. The challenge mode asks a
+/// keyboard-interactive question first, and the public key mode restricts the server
+/// to the keys listed in an `authorized_keys` file.
+pub enum Access {
+    Anonymous,
+    Challenge(ChallengeKind),
+    PublicKey(Vec<KeyData>),
+}
+
+/// Decides who may open a session. Admin keys are let in whatever the access mode
+/// and get the admin view instead of the site.
+pub struct Authenticator {
+    access: Access,
+    admin_keys: Vec<KeyData>,
+    // Password auth is offered, and always fails, when set
+    honeypot: Option<Arc<Honeypot>>,
+}
+
+impl Authenticator {
+    pub fn anonymous() -> Self {
+        Self {
+            access: Access::Anonymous,
+            admin_keys: Vec::new(),
+            honeypot: None,
+        }
+    }
+
+    pub fn from_config(
+        config: &Config,
+        honeypot: Option<Arc<Honeypot>>,
+    ) -> Result<Self, anyhow::Error> {
+        let access = match config.auth_mode {
+            AuthMode::None => Access::Anonymous,
+            AuthMode::Challenge => Access::Challenge(config.challenge),
+            AuthMode::PublicKey => {
+                let location = config.authorized_keys.as_deref().ok_or_else(|| {
+                    anyhow::anyhow!("authorized_keys must be set for publickey auth")
+                })?;
+                let keys = read_keys(location)?;
+                if keys.is_empty() {
+                    return Err(anyhow::anyhow!(
+                        "No keys found in {}, nobody would be able to connect",
+                        location.display()
+                    ));
+                }
+                Access::PublicKey(keys)
+            }
+        };
+        let admin_keys = match config.admin_keys.as_deref() {
+            Some(location) => read_keys(location)?,
+            None => Vec::new(),
+        };
+        Ok(Self {
+            access,
+            admin_keys,
+            honeypot,
+        })
+    }
+
+    pub fn methods(&self) -> MethodSet {
+        let mut methods = MethodSet::empty();
+        match self.access {
+            Access::Anonymous => {
+                methods.push(MethodKind::PublicKey);
+                methods.push(MethodKind::KeyboardInteractive);
+            }
+            Access::Challenge(_) => methods.push(MethodKind::KeyboardInteractive),
+            Access::PublicKey(_) => methods.push(MethodKind::PublicKey),
+        }
+        if !self.admin_keys.is_empty() && matches!(self.access, Access::Challenge(_)) {
+            methods.push(MethodKind::PublicKey);
+        }
+        if self.honeypot.is_some() {
+            methods.push(MethodKind::Password);
+        }
+        methods
+    }
+
+    pub fn allows_anonymous(&self) -> bool {
+        matches!(self.access, Access::Anonymous)
+    }
+
+    pub fn challenge_kind(&self) -> Option<ChallengeKind> {
+        match self.access {
+            Access::Challenge(kind) => Some(kind),
+            _ => None,
+        }
+    }
+
+    pub fn allows_key(&self, public_key: &PublicKey) -> bool {
+        if self.is_admin(public_key) {
+            return true;
+        }
+        match &self.access {
+            Access::Anonymous => true,
+            Access::PublicKey(keys) => keys.contains(public_key.key_data()),
+            Access::Challenge(_) => false,
+        }
+    }
+
+    pub fn honeypot(&self) -> Option<&Honeypot> {
+        self.honeypot.as_deref()
+    }
+
+    pub fn is_admin(&self, public_key: &PublicKey) -> bool {
+        self.admin_keys.contains(public_key.key_data())
+    }
+
+    pub fn describe(&self) -> String {
+        let access = match &self.access {
+            Access::Anonymous => String::from("anonymous auth"),
+            Access::Challenge(kind) => format!("{:?} challenge auth", kind).to_lowercase(),
+            Access::PublicKey(keys) => format!("public key auth ({} authorized)", keys.len()),
+        };
+        let access = match self.admin_keys.len() {
+            0 => access,
+            admins => format!("{}, {} admin keys", access, admins),
+        };
+        match self.honeypot {
+            Some(_) => format!("{}, password honeypot", access),
+            None => access,
+        }
+    }
+}
+
+fn read_keys(path: &Path) -> Result<Vec<KeyData>, anyhow::Error> {
+    let entries = AuthorizedKeys::read_file(path).map_err(|e| {
+        anyhow::anyhow!(
+            "Failed to read authorized keys at {}: {}",
+            path.display(),
+            e
+        )
+    })?;
+
+    Ok(entries
+        .iter()
+        .map(|entry| entry.public_key().key_data().clone())
+        .collect())
+}
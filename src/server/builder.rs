@@ -0,0 +1,125 @@
+use std::net::SocketAddr;
+use std::path::PathBuf;
+use std::sync::{Arc, RwLock};
+
+use crate::config::{Config, SharedConfig};
+use crate::server::AppServer;
+use crate::server::hooks::{ConnectionHook, Hooks};
+use crate::server::session_registry::{SessionId, SessionRegistry};
+use crate::server::tui_app::{AppFactory, FromSession, NewApp};
+
+/// Who a session is for, handed to the app factory.
+pub struct SessionInfo {
+    pub id: SessionId,
+    /// Tells visitors apart: their key's fingerprint, or their address without one.
+    pub visitor: String,
+    /// The fingerprint of the key the visitor authenticated with.
+    pub fingerprint: Option<String>,
+    pub peer_addr: Option<SocketAddr>,
+    /// The listener the visitor came in on, like `ssh`.
+    pub via: &'static str,
+    /// The username the client logged in with, e.g. `blog` for `ssh blog@host`.
+    pub user: String,
+    /// `TERM`, or what the listener takes it to be.
+    pub term: String,
+    /// Authenticated with an admin key, see `Authenticator`.
+    pub admin: bool,
+    /// The live sessions, for apps that show them.
+    pub sessions: SessionRegistry,
+}
+
+/// Puts an `AppServer` together. Anything not set comes from `Config::default()`,
+/// and there is no app until `app` or `app_factory` sets one.
+///
+/// ```no_run
+/// # async fn serve() -> Result<(), anyhow::Error> {
+/// # struct MyApp;
+/// # impl portfolio_v2::TuiApp for MyApp {
+/// #     fn draw(&mut self, _: &mut ratatui::Frame) {}
+/// #     fn handle_key(&mut self, _: crossterm::event::KeyEvent) {}
+/// #     fn handle_tick(&mut self, _: u64) -> bool { false }
+/// #     fn wants_close(&self) -> bool { false }
+/// # }
+/// # impl portfolio_v2::FromSession for MyApp {
+/// #     fn new(_: portfolio_v2::SessionInfo) -> Self { MyApp }
+/// # }
+/// use portfolio_v2::SshTuiServer;
+///
+/// let mut server = SshTuiServer::builder()
+///     .addr("0.0.0.0:2222".parse()?)
+///     .host_key("/etc/my-app/host_key")
+///     .app::<MyApp>()
+///     .build()?;
+/// server.run().await
+/// # }
+/// ```
+pub struct AppServerBuilder<F = ()> {
+    config: SharedConfig,
+    app_factory: F,
+    hooks: Hooks,
+}
+
+impl AppServerBuilder {
+    pub(crate) fn new() -> Self {
+        Self {
+            config: Arc::new(RwLock::new(Config::default())),
+            app_factory: (),
+            hooks: Hooks::default(),
+        }
+    }
+}
+
+impl<F> AppServerBuilder<F> {
+    /// Every setting at once, e.g. from `Config::load`. The other methods change
+    /// what it says, so call this first.
+    pub fn config(self, config: Config) -> Self {
+        self.shared_config(Arc::new(RwLock::new(config)))
+    }
+
+    /// Like `config`, for an app factory that reads the settings too and should
+    /// see them change when the server reloads them.
+    pub fn shared_config(mut self, config: SharedConfig) -> Self {
+        self.config = config;
+        self
+    }
+
+    pub fn addr(self, addr: SocketAddr) -> Self {
+        self.config.write().unwrap().listen = addr;
+        self
+    }
+
+    /// A private key file, or a directory of them, as with `host_key` in the config.
+    pub fn host_key(self, path: impl Into<PathBuf>) -> Self {
+        self.config.write().unwrap().host_key = Some(path.into());
+        self
+    }
+
+    /// Serves `A`, made with `FromSession::new`.
+    pub fn app<A: FromSession>(self) -> AppServerBuilder<NewApp<A>> {
+        self.app_factory(NewApp::default())
+    }
+
+    pub fn app_factory<G: AppFactory>(self, app_factory: G) -> AppServerBuilder<G> {
+        AppServerBuilder {
+            config: self.config,
+            app_factory,
+            hooks: self.hooks,
+        }
+    }
+
+    /// Adds callbacks along every connection's life, see `ConnectionHook`.
+    pub fn hook(mut self, hook: impl ConnectionHook) -> Self {
+        self.hooks.push(hook);
+        self
+    }
+}
+
+impl<F: AppFactory> AppServerBuilder<F> {
+    pub fn build(self) -> Result<AppServer<F>, anyhow::Error> {
+        AppServer::new(
+            self.config,
+            Arc::new(self.app_factory),
+            Arc::new(self.hooks),
+        )
+    }
+}
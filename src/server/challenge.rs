@@ -0,0 +1,62 @@
+use std::str::FromStr;
+
+use rand::Rng;
+use serde::Deserialize;
+
+const WORDS: &[&str] = &[
+    "krayon", "ferris", "tunnel", "socket", "cursor", "kernel", "signal", "binary", "packet",
+    "vector",
+];
+
+#[derive(Clone, Copy, Debug, Default, Deserialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum ChallengeKind {
+    #[default]
+    Math,
+    Word,
+}
+
+impl FromStr for ChallengeKind {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "math" => Ok(Self::Math),
+            "word" => Ok(Self::Word),
+            _ => Err(String::from("expected 'math' or 'word'")),
+        }
+    }
+}
+
+/// A question asked over keyboard-interactive auth. Trivial for a person at a
+/// terminal, but it stops scanners that only try `none` and password logins.
+pub struct Challenge {
+    pub prompt: String,
+    answer: String,
+}
+
+impl Challenge {
+    pub fn generate(kind: ChallengeKind) -> Self {
+        match kind {
+            ChallengeKind::Math => {
+                let a = rand::rng().next_u32() % 10 + 1;
+                let b = rand::rng().next_u32() % 10 + 1;
+                Self {
+                    prompt: format!("what is {} + {}? ", a, b),
+                    answer: (a + b).to_string(),
+                }
+            }
+            ChallengeKind::Word => {
+                let word = WORDS[rand::rng().next_u32() as usize % WORDS.len()];
+                Self {
+                    prompt: format!("type '{}' backwards: ", word),
+                    answer: word.chars().rev().collect(),
+                }
+            }
+        }
+    }
+
+    pub fn check(&self, response: &str) -> bool {
+        response.trim().eq_ignore_ascii_case(&self.answer)
+    }
+}
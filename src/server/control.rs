@@ -0,0 +1,90 @@
+use std::os::unix::fs::PermissionsExt;
+use std::path::Path;
+
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::net::{UnixListener, UnixStream};
+use tracing::{info, warn};
+
+use crate::server::SessionRegistry;
+
+/// Commands for the operator, one per line on a unix socket only the server's
+/// user can open. Every command gets a single line back.
+pub struct ControlSocket {
+    listener: UnixListener,
+    registry: SessionRegistry,
+}
+
+impl ControlSocket {
+    pub fn bind(path: &Path, registry: SessionRegistry) -> Result<Self, anyhow::Error> {
+        // Left behind if the server didn't exit cleanly
+        if path.exists() {
+            std::fs::remove_file(path)?;
+        }
+        let listener = UnixListener::bind(path).map_err(|e| {
+            anyhow::anyhow!("Failed to bind control socket {}: {}", path.display(), e)
+        })?;
+        std::fs::set_permissions(path, std::fs::Permissions::from_mode(0o600))?;
+        Ok(Self { listener, registry })
+    }
+
+    pub async fn run(self) {
+        loop {
+            let stream = match self.listener.accept().await {
+                Ok((stream, _)) => stream,
+                Err(e) => {
+                    warn!("Failed to accept control connection: {}", e);
+                    continue;
+                }
+            };
+            let registry = self.registry.clone();
+            tokio::spawn(async move {
+                if let Err(e) = serve(stream, registry).await {
+                    warn!("Control connection failed: {}", e);
+                }
+            });
+        }
+    }
+}
+
+async fn serve(stream: UnixStream, registry: SessionRegistry) -> Result<(), anyhow::Error> {
+    let (reader, mut writer) = stream.into_split();
+    let mut lines = BufReader::new(reader).lines();
+    while let Some(line) = lines.next_line().await? {
+        let reply = match line.trim().split_once(' ') {
+            Some(("wall", message)) if !message.trim().is_empty() => {
+                let message = message.trim();
+                let sessions = registry.sessions();
+                for (_, session) in &sessions {
+                    session.show_wall(message.to_string()).await;
+                }
+                let sessions = sessions.len();
+                info!(sessions, "Sent wall message");
+                format!("ok, sent to {} sessions", sessions)
+            }
+            _ => String::from("error: expected 'wall <message>'"),
+        };
+        writer.write_all(format!("{}\n", reply).as_bytes()).await?;
+    }
+    Ok(())
+}
+
+/// Sends one command to a running server and returns its reply.
+pub async fn send(path: &Path, command: &str) -> Result<String, anyhow::Error> {
+    let stream = UnixStream::connect(path).await.map_err(|e| {
+        anyhow::anyhow!(
+            "Failed to connect to control socket {}: {}",
+            path.display(),
+            e
+        )
+    })?;
+    let (reader, mut writer) = stream.into_split();
+    writer
+        .write_all(format!("{}\n", command.replace('\n', " ")).as_bytes())
+        .await?;
+    let reply = BufReader::new(reader)
+        .lines()
+        .next_line()
+        .await?
+        .ok_or_else(|| anyhow::anyhow!("The server closed the control connection"))?;
+    Ok(reply)
+}
@@ -0,0 +1,78 @@
+use std::net::SocketAddr;
+use std::sync::Arc;
+use std::time::Duration;
+
+use tokio::io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader};
+use tokio::net::{TcpListener, TcpStream};
+use tracing::{debug, warn};
+
+use crate::server::AppFactory;
+use crate::server::rate_limit::{RateLimiter, Verdict};
+use crate::server::shutdown::Shutdown;
+
+// Queries are a name at most, anything longer isn't one
+const MAX_QUERY_BYTES: u64 = 512;
+const QUERY_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Answers finger queries (RFC 1288) with what the app factory has to say, see
+/// `AppFactory::finger`. `finger @host` asks with an empty name.
+pub struct FingerListener<F> {
+    rate_limiter: Arc<RateLimiter>,
+    app_factory: Arc<F>,
+}
+
+impl<F: AppFactory> FingerListener<F> {
+    pub fn new(rate_limiter: Arc<RateLimiter>, app_factory: Arc<F>) -> Self {
+        Self {
+            rate_limiter,
+            app_factory,
+        }
+    }
+
+    /// Accepts connections until `shutdown` fires.
+    pub async fn run(self, listener: TcpListener, shutdown: Shutdown) {
+        let finger = Arc::new(self);
+        loop {
+            let accepted = tokio::select! {
+                accepted = listener.accept() => accepted,
+                _ = shutdown.fired() => break,
+            };
+            let (socket, peer_addr) = match accepted {
+                Ok(accepted) => accepted,
+                Err(e) => {
+                    warn!("Failed to accept finger connection: {}", e);
+                    continue;
+                }
+            };
+            tokio::spawn(finger.clone().serve(socket, peer_addr));
+        }
+    }
+
+    async fn serve(self: Arc<Self>, socket: TcpStream, peer_addr: SocketAddr) {
+        if self.rate_limiter.check(peer_addr.ip()) != Verdict::Allow {
+            return;
+        }
+        let mut socket = BufReader::new(socket);
+        let mut query = String::new();
+        let mut limited = (&mut socket).take(MAX_QUERY_BYTES);
+        let read = limited.read_line(&mut query);
+        if !matches!(tokio::time::timeout(QUERY_TIMEOUT, read).await, Ok(Ok(_))) {
+            return;
+        }
+        debug!(peer = %peer_addr, query = query.trim(), "Finger query");
+
+        let reply = self.answer(query.trim());
+        // Lines end in CRLF on the wire
+        let reply = reply.replace('\n', "\r\n") + "\r\n";
+        let _ = socket.get_mut().write_all(reply.as_bytes()).await;
+    }
+
+    fn answer(&self, query: &str) -> String {
+        // `/W` asks for a verbose answer, which is the only kind there is
+        let query = query.strip_prefix("/W").unwrap_or(query).trim();
+        if query.contains('@') {
+            return String::from("finger forwarding is not supported");
+        }
+        self.app_factory.finger(query)
+    }
+}
@@ -0,0 +1,882 @@
+use std::collections::HashMap;
+use std::net::SocketAddr;
+use std::sync::Arc;
+
+use russh::keys::{HashAlg, PublicKey};
+use russh::server::{Auth, ChannelOpenHandle, Handler, Msg, Response, Session};
+use russh::{Channel, ChannelId, ChannelOpenFailure, Pty};
+use tokio::sync::mpsc;
+use tracing::{Instrument, Span, debug, info, info_span, warn};
+
+use crate::charset::{Charset, Locale};
+use crate::color_support::{ColorSupport, TRUECOLOR_PROBE};
+use crate::config::SharedConfig;
+use crate::error::{RenderError, SessionError};
+use crate::graphics::{DEVICE_ATTRIBUTES_QUERY, Graphics};
+use crate::hyperlinks;
+use crate::server::challenge::Challenge;
+use crate::server::hooks::{AuthAttempt, AuthMethod, Hooks, PtyRequest, SharedConnectionInfo};
+use crate::server::input::InputDecoder;
+use crate::server::launch::{
+    ENABLE_INPUT_MODES_SEQUENCE, Launch, OUTPUT_QUEUE_CAPACITY, SERVER_FULL_MESSAGE,
+};
+use crate::server::public_files::PublicFiles;
+use crate::server::scp::{self, ScpRequest};
+use crate::server::session_registry::{
+    ClientSession, Link, SESSION_FAILED_MESSAGE, SessionId, SessionRegistry, Slot,
+};
+use crate::server::sftp::SftpSession;
+use crate::server::{AppFactory, Authenticator};
+
+// Typed before the app starts, kept for it. Anything past this is a paste or a
+// client that never asks for a shell, and is dropped
+const EARLY_INPUT_LIMIT: usize = 4096;
+
+/// A session channel. The app only starts once the client asks for a shell, so
+/// what the client says before that is kept here, and a channel that runs a
+/// command or the SFTP subsystem never starts one.
+struct OpenChannel {
+    session_id: Option<SessionId>,
+    // Taken by whatever the client asks for: the app, a command or SFTP
+    channel: Option<Channel<Msg>>,
+    // Handed to the app's session; commands and SFTP aren't counted as sessions
+    slot: Option<Slot>,
+    sftp: bool,
+    pty: bool,
+    term: String,
+    colorterm: Option<String>,
+    term_program: Option<String>,
+    locale: Locale,
+    size: (u32, u32),
+    // Pixels per cell, when the client told the window's size in pixels too
+    cell_size: Option<(u32, u32)>,
+    input: InputDecoder,
+    // Data that came before the shell request, replayed once the app is running
+    early_input: Vec<u8>,
+    // The client sent EOF before asking for a shell
+    input_closed: bool,
+}
+
+impl OpenChannel {
+    fn color_support(&self) -> ColorSupport {
+        ColorSupport::detect(&self.term, self.colorterm.as_deref())
+    }
+
+    fn charset(&self) -> Charset {
+        Charset::detect(&self.term, self.locale.ctype())
+    }
+
+    fn hyperlinks(&self) -> bool {
+        hyperlinks::detect(&self.term, self.colorterm.as_deref())
+    }
+
+    fn graphics(&self) -> Graphics {
+        Graphics::detect(&self.term, self.term_program.as_deref())
+    }
+}
+
+/// Per-connection handler. russh creates one for every accepted TCP connection,
+/// and each session channel opened on it gets its own entry in the registry.
+pub struct ConnectionHandler<F> {
+    registry: SessionRegistry,
+    channels: HashMap<ChannelId, OpenChannel>,
+    config: SharedConfig,
+    authenticator: Arc<Authenticator>,
+    challenge: Option<Challenge>,
+    app_factory: Arc<F>,
+    hooks: Arc<Hooks>,
+    // What the hooks are told, filled in as the visitor authenticates
+    connection: SharedConnectionInfo,
+    peer_addr: Option<SocketAddr>,
+    // Fingerprint of the key the visitor authenticated with
+    visitor: Option<String>,
+    // Authenticated with an admin key, see `Authenticator`
+    admin: bool,
+    // For what's logged before a session is opened
+    span: Span,
+}
+
+impl<F: AppFactory> ConnectionHandler<F> {
+    pub fn new(
+        registry: SessionRegistry,
+        config: SharedConfig,
+        authenticator: Arc<Authenticator>,
+        app_factory: Arc<F>,
+        hooks: Arc<Hooks>,
+        connection: SharedConnectionInfo,
+    ) -> Self {
+        let peer_addr = connection.lock().unwrap().peer_addr;
+        Self {
+            registry,
+            channels: HashMap::new(),
+            config,
+            authenticator,
+            challenge: None,
+            app_factory,
+            hooks,
+            connection,
+            peer_addr,
+            visitor: None,
+            admin: false,
+            span: info_span!("connection", peer = %describe_peer(peer_addr)),
+        }
+    }
+
+    fn key_auth_result(&self, public_key: &PublicKey) -> Auth {
+        if self.authenticator.allows_key(public_key) {
+            Auth::Accept
+        } else {
+            Auth::reject()
+        }
+    }
+
+    /// Tells the hooks about a finished attempt, which may still turn it down.
+    fn finish_auth(
+        &self,
+        user: &str,
+        method: AuthMethod,
+        fingerprint: Option<&str>,
+        result: Auth,
+    ) -> Auth {
+        let accepted = matches!(result, Auth::Accept);
+        self.connection.lock().unwrap().user = Some(user.to_string());
+        let attempt = AuthAttempt {
+            user,
+            method,
+            fingerprint,
+            accepted,
+        };
+        if !self.hooks.auth(&self.connection, &attempt) && accepted {
+            info!(parent: &self.span, user, "A hook turned the login down");
+            return Auth::reject();
+        }
+        result
+    }
+
+    fn client(&self, channel: ChannelId) -> Option<Arc<ClientSession>> {
+        let open_channel = self.channels.get(&channel)?;
+        self.registry.get(open_channel.session_id?)
+    }
+
+    /// Starts the app on a channel, once the client asked for a shell.
+    async fn start_app(
+        &mut self,
+        channel_id: ChannelId,
+        session: &mut Session,
+    ) -> Result<(), SessionError> {
+        let Some(open_channel) = self.channels.get_mut(&channel_id) else {
+            return Ok(());
+        };
+        // Already running, or the channel is used for something else
+        let (Some(channel), Some(slot)) = (open_channel.channel.take(), open_channel.slot.take())
+        else {
+            return Ok(());
+        };
+        // Input arrives through `data()`, so only the write half is kept
+        let (_, writer) = channel.split();
+        let color_support = open_channel.color_support();
+        let charset = open_channel.charset();
+        let term = open_channel.term.clone();
+        let hyperlinks = open_channel.hyperlinks();
+        let graphics = open_channel.graphics();
+        let cell_size = open_channel.cell_size;
+        let pty = open_channel.pty;
+        let (width, height) = open_channel.size;
+
+        let id = self.registry.next_id();
+        let span = info_span!("session", id, peer = %describe_peer(self.peer_addr));
+        let (sender, mut receiver) = mpsc::channel::<Vec<u8>>(OUTPUT_QUEUE_CAPACITY);
+        let registry = self.registry.clone();
+        // Unlike `Handle::data`, the channel's writer waits for the client's window
+        // instead of letting russh buffer without limit, which is what lets the
+        // queue above fill up
+        tokio::spawn(
+            async move {
+                while let Some(mut data) = receiver.recv().await {
+                    // Frames queued while the last write waited on the client go
+                    // out together, in as few packets as the client allows
+                    while let Ok(more) = receiver.try_recv() {
+                        data.extend_from_slice(&more);
+                    }
+                    if writer.data_bytes(data).await.is_err() {
+                        // The transport is gone: stop rendering for it instead of
+                        // waiting for the idle timeout
+                        debug!("Channel write failed");
+                        registry.remove(id);
+                        break;
+                    }
+                }
+            }
+            .instrument(span.clone()),
+        );
+
+        let user = self
+            .connection
+            .lock()
+            .unwrap()
+            .user
+            .clone()
+            .unwrap_or_default();
+        Launch {
+            registry: &self.registry,
+            config: &self.config,
+            app_factory: &*self.app_factory,
+            id,
+            slot,
+            via: "ssh",
+            span,
+            output: sender,
+            link: Link::Ssh {
+                handle: session.handle(),
+                channel_id,
+            },
+            peer_addr: self.peer_addr,
+            fingerprint: self.visitor.clone(),
+            user,
+            admin: self.admin,
+            term,
+            color_support,
+            charset,
+            hyperlinks,
+            graphics,
+            cell_size,
+            size: (width as u16, height as u16),
+            probe_latency: pty,
+        }
+        .start()
+        .await?;
+        let early_input = match self.channels.get_mut(&channel_id) {
+            Some(open_channel) => {
+                open_channel.session_id = Some(id);
+                std::mem::take(&mut open_channel.early_input)
+            }
+            None => Vec::new(),
+        };
+
+        if pty {
+            session.data(channel_id, ENABLE_INPUT_MODES_SEQUENCE)?;
+            if graphics == Graphics::HalfBlocks {
+                session.data(channel_id, DEVICE_ATTRIBUTES_QUERY)?;
+            }
+            // 256 color terminals often do truecolor too without saying so. Terminals
+            // that claim less may not understand the query and print it instead
+            if color_support == ColorSupport::Ansi256
+                && let Some(open_channel) = self.channels.get_mut(&channel_id)
+            {
+                open_channel.input.expect_status_report();
+                session.data(channel_id, TRUECOLOR_PROBE)?;
+            }
+        }
+        if !early_input.is_empty() {
+            self.input(channel_id, id, &early_input, session).await?;
+        }
+        Ok(())
+    }
+
+    /// Hands what the client typed to its session, and ends the session if that
+    /// made the visitor quit.
+    async fn input(
+        &mut self,
+        channel: ChannelId,
+        id: SessionId,
+        data: &[u8],
+        session: &mut Session,
+    ) -> Result<(), SessionError> {
+        let Some(open_channel) = self.channels.get_mut(&channel) else {
+            return Ok(());
+        };
+        let events = open_channel.input.feed(data);
+        let reports = open_channel.input.take_status_reports();
+        let Some(client) = self.registry.get(id) else {
+            return Ok(());
+        };
+
+        client.status_reports(&reports);
+        if reports
+            .iter()
+            .any(|report| ColorSupport::is_truecolor_reply(report))
+        {
+            let mut state = client.state().await;
+            state.color_support = ColorSupport::TrueColor;
+            state.hyperlinks.enable();
+            state.app.mark_dirty();
+            client.wake();
+        }
+        if reports
+            .iter()
+            .any(|report| Graphics::is_sixel_reply(report))
+        {
+            let mut state = client.state().await;
+            state.images.enable_sixel();
+            state.app.mark_dirty();
+            client.wake();
+        }
+
+        // Like in the render loop, a panic only ends this visitor's session
+        let handled = client.handle_input(events).await;
+        match handled {
+            Ok(false) => {}
+            Ok(true) => {
+                info!(parent: &client.span, "Visitor quit");
+                self.disconnect(channel, id, 0, b"", session)?;
+            }
+            Err(message) => {
+                warn!(
+                    parent: &client.span,
+                    "Disconnecting session: input handling panicked: {}", message
+                );
+                let error = RenderError::Panicked(message);
+                self.hooks.error(&self.connection, &error.into());
+                self.disconnect(channel, id, 1, SESSION_FAILED_MESSAGE, session)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Ends a session from the server's side: the terminal is reset, `message` is
+    /// left on the cleared screen and ssh exits with `exit_status`. A visitor who
+    /// chose to leave gets status 0, like after logging out of a shell.
+    fn disconnect(
+        &mut self,
+        channel: ChannelId,
+        id: SessionId,
+        exit_status: u32,
+        message: &[u8],
+        session: &mut Session,
+    ) -> Result<(), SessionError> {
+        if let Some(client) = self.registry.remove(id) {
+            client.restore_on(session)?;
+        }
+        if !message.is_empty() {
+            session.data(channel, message.to_vec())?;
+        }
+        self.channels.remove(&channel);
+        session.exit_status_request(channel, exit_status)?;
+        session.eof(channel)?;
+        session.close(channel)?;
+        Ok(())
+    }
+
+    fn resize(&mut self, channel: ChannelId, col_width: u32, row_height: u32) {
+        let Some(open_channel) = self.channels.get_mut(&channel) else {
+            return;
+        };
+        open_channel.size = (col_width, row_height);
+        if let Some(client) = self.client(channel) {
+            client.request_resize(col_width as u16, row_height as u16);
+        }
+    }
+}
+
+impl<F: AppFactory> Handler for ConnectionHandler<F> {
+    type Error = SessionError;
+
+    async fn channel_open_session(
+        &mut self,
+        channel: Channel<Msg>,
+        reply: ChannelOpenHandle,
+        session: &mut Session,
+    ) -> Result<(), Self::Error> {
+        let channel_id = channel.id();
+        // Each session channel could run its own copy of the app under this one
+        // connection, so past the limit they are turned down before they are opened
+        let channels_per_connection = self.config.read().unwrap().channels_per_connection;
+        if self.channels.len() >= channels_per_connection {
+            warn!(
+                parent: &self.span,
+                channels_per_connection, "Turning down a channel over the per connection limit"
+            );
+            reply.reject(ChannelOpenFailure::ResourceShortage).await;
+            return Ok(());
+        }
+        reply.accept().await;
+
+        let Some(slot) = self.registry.reserve() else {
+            let max_clients = self.config.read().unwrap().max_clients;
+            warn!(parent: &self.span, max_clients, "Server full, turning a session away");
+            // The confirmation goes out through the session's own queue, so the
+            // notice goes through the handle and is delivered right after it
+            let handle = session.handle();
+            tokio::spawn(async move {
+                let _ = handle.data(channel_id, SERVER_FULL_MESSAGE).await;
+                let _ = handle.close(channel_id).await;
+            });
+            return Ok(());
+        };
+
+        self.channels.insert(
+            channel_id,
+            OpenChannel {
+                session_id: None,
+                channel: Some(channel),
+                slot: Some(slot),
+                sftp: false,
+                pty: false,
+                term: String::new(),
+                colorterm: None,
+                term_program: None,
+                locale: Locale::default(),
+                size: (0, 0),
+                cell_size: None,
+                input: InputDecoder::new(),
+                early_input: Vec::new(),
+                input_closed: false,
+            },
+        );
+        Ok(())
+    }
+
+    // Nothing is forwarded: without an answer some clients wait on these forever,
+    // so each is turned down right away and noted in the log
+    async fn channel_open_direct_tcpip(
+        &mut self,
+        _: Channel<Msg>,
+        host_to_connect: &str,
+        port_to_connect: u32,
+        _: &str,
+        _: u32,
+        reply: ChannelOpenHandle,
+        _: &mut Session,
+    ) -> Result<(), Self::Error> {
+        info!(
+            parent: &self.span,
+            host = host_to_connect,
+            port = port_to_connect,
+            "Turning down port forwarding"
+        );
+        reply
+            .reject(ChannelOpenFailure::AdministrativelyProhibited)
+            .await;
+        Ok(())
+    }
+
+    async fn channel_open_direct_streamlocal(
+        &mut self,
+        _: Channel<Msg>,
+        socket_path: &str,
+        reply: ChannelOpenHandle,
+        _: &mut Session,
+    ) -> Result<(), Self::Error> {
+        info!(parent: &self.span, socket_path, "Turning down socket forwarding");
+        reply
+            .reject(ChannelOpenFailure::AdministrativelyProhibited)
+            .await;
+        Ok(())
+    }
+
+    async fn tcpip_forward(
+        &mut self,
+        address: &str,
+        port: &mut u32,
+        _: &mut Session,
+    ) -> Result<bool, Self::Error> {
+        info!(parent: &self.span, address, port = *port, "Turning down remote port forwarding");
+        Ok(false)
+    }
+
+    async fn streamlocal_forward(
+        &mut self,
+        socket_path: &str,
+        _: &mut Session,
+    ) -> Result<bool, Self::Error> {
+        info!(parent: &self.span, socket_path, "Turning down remote socket forwarding");
+        Ok(false)
+    }
+
+    async fn agent_request(&mut self, _: ChannelId, _: &mut Session) -> Result<bool, Self::Error> {
+        info!(parent: &self.span, "Turning down agent forwarding");
+        Ok(false)
+    }
+
+    async fn x11_request(
+        &mut self,
+        channel: ChannelId,
+        _: bool,
+        _: &str,
+        _: &str,
+        _: u32,
+        session: &mut Session,
+    ) -> Result<(), Self::Error> {
+        info!(parent: &self.span, "Turning down X11 forwarding");
+        session.channel_failure(channel)?;
+        Ok(())
+    }
+
+    async fn auth_none(&mut self, _: &str) -> Result<Auth, Self::Error> {
+        // Even anonymous visitors are asked for a key first, see `Authenticator`
+        Ok(Auth::Reject {
+            proceed_with_methods: Some(self.authenticator.methods()),
+            partial_success: false,
+        })
+    }
+
+    async fn auth_password(&mut self, user: &str, password: &str) -> Result<Auth, Self::Error> {
+        if let Some(honeypot) = self.authenticator.honeypot() {
+            debug!(parent: &self.span, user, "Logging a password attempt");
+            honeypot.record(self.peer_addr, user, password);
+        }
+        Ok(self.finish_auth(user, AuthMethod::Password, None, Auth::reject()))
+    }
+
+    async fn auth_keyboard_interactive<'a>(
+        &'a mut self,
+        user: &str,
+        _: &str,
+        response: Option<Response<'a>>,
+    ) -> Result<Auth, Self::Error> {
+        let Some(kind) = self.authenticator.challenge_kind() else {
+            let result = match self.authenticator.allows_anonymous() {
+                true => Auth::Accept,
+                false => Auth::reject(),
+            };
+            return Ok(self.finish_auth(user, AuthMethod::KeyboardInteractive, None, result));
+        };
+
+        // The first round has no response yet: send the question. The next round
+        // carries the answer, and a wrong one starts over with a fresh question
+        match (response, self.challenge.take()) {
+            (Some(mut response), Some(challenge)) => {
+                let answer = response
+                    .next()
+                    .map(|answer| String::from_utf8_lossy(&answer).into_owned())
+                    .unwrap_or_default();
+                let result = match challenge.check(&answer) {
+                    true => Auth::Accept,
+                    false => Auth::reject(),
+                };
+                Ok(self.finish_auth(user, AuthMethod::KeyboardInteractive, None, result))
+            }
+            _ => {
+                let challenge = Challenge::generate(kind);
+                let prompt = challenge.prompt.clone();
+                self.challenge = Some(challenge);
+                Ok(Auth::Partial {
+                    name: "are you human?".into(),
+                    instructions: "answer to continue to krayon.dev".into(),
+                    prompts: vec![(prompt.into(), true)].into(),
+                })
+            }
+        }
+    }
+
+    async fn auth_publickey_offered(
+        &mut self,
+        _: &str,
+        public_key: &PublicKey,
+    ) -> Result<Auth, Self::Error> {
+        Ok(self.key_auth_result(public_key))
+    }
+
+    async fn auth_publickey(
+        &mut self,
+        user: &str,
+        public_key: &PublicKey,
+    ) -> Result<Auth, Self::Error> {
+        let fingerprint = public_key.fingerprint(HashAlg::Sha256).to_string();
+        let result = self.key_auth_result(public_key);
+        let result = self.finish_auth(user, AuthMethod::PublicKey, Some(&fingerprint), result);
+        if let Auth::Accept = result {
+            self.admin = self.authenticator.is_admin(public_key);
+            debug!(parent: &self.span, %fingerprint, admin = self.admin, "Accepted public key");
+            let mut connection = self.connection.lock().unwrap();
+            connection.fingerprint = Some(fingerprint.clone());
+            connection.admin = self.admin;
+            self.visitor = Some(fingerprint);
+        }
+        Ok(result)
+    }
+
+    async fn data(
+        &mut self,
+        channel: ChannelId,
+        data: &[u8],
+        session: &mut Session,
+    ) -> Result<(), Self::Error> {
+        let Some(open_channel) = self.channels.get_mut(&channel) else {
+            return Ok(());
+        };
+        let Some(id) = open_channel.session_id else {
+            // Clients may send input as soon as the channel is open, before the
+            // shell request, and it would be lost if dropped here
+            if open_channel.channel.is_some() {
+                let room = EARLY_INPUT_LIMIT.saturating_sub(open_channel.early_input.len());
+                open_channel
+                    .early_input
+                    .extend_from_slice(&data[..data.len().min(room)]);
+            }
+            return Ok(());
+        };
+        self.input(channel, id, data, session).await
+    }
+
+    async fn window_change_request(
+        &mut self,
+        channel: ChannelId,
+        col_width: u32,
+        row_height: u32,
+        _: u32,
+        _: u32,
+        _: &mut Session,
+    ) -> Result<(), Self::Error> {
+        self.resize(channel, col_width, row_height);
+        Ok(())
+    }
+
+    async fn env_request(
+        &mut self,
+        channel: ChannelId,
+        variable_name: &str,
+        variable_value: &str,
+        session: &mut Session,
+    ) -> Result<(), Self::Error> {
+        if let Some(open_channel) = self.channels.get_mut(&channel) {
+            open_channel.locale.set(variable_name, variable_value);
+            if variable_name == "TERM_PROGRAM" {
+                open_channel.term_program = Some(variable_value.to_string());
+            }
+        }
+        if variable_name == "COLORTERM"
+            && let Some(open_channel) = self.channels.get_mut(&channel)
+        {
+            open_channel.colorterm = Some(variable_value.to_string());
+            if let Some(client) = self.client(channel) {
+                let mut state = client.state().await;
+                let detected = ColorSupport::detect("", Some(variable_value));
+                state.color_support = state.color_support.max(detected);
+                if detected == ColorSupport::TrueColor {
+                    state.hyperlinks.enable();
+                }
+                state.app.mark_dirty();
+            }
+        }
+        session.channel_success(channel)?;
+        Ok(())
+    }
+
+    async fn pty_request(
+        &mut self,
+        channel: ChannelId,
+        term: &str,
+        col_width: u32,
+        row_height: u32,
+        pix_width: u32,
+        pix_height: u32,
+        _: &[(Pty, u32)],
+        session: &mut Session,
+    ) -> Result<(), Self::Error> {
+        if let Some(open_channel) = self.channels.get_mut(&channel) {
+            open_channel.pty = true;
+            open_channel.term = term.to_string();
+            // Most clients send zeros
+            if pix_width > 0 && pix_height > 0 && col_width > 0 && row_height > 0 {
+                open_channel.cell_size = Some((pix_width / col_width, pix_height / row_height));
+            }
+        }
+        self.resize(channel, col_width, row_height);
+        let pty = PtyRequest {
+            term,
+            width: col_width,
+            height: row_height,
+        };
+        self.hooks.pty(&self.connection, &pty);
+        session.channel_success(channel)?;
+        Ok(())
+    }
+
+    async fn shell_request(
+        &mut self,
+        channel: ChannelId,
+        session: &mut Session,
+    ) -> Result<(), Self::Error> {
+        // A second shell, or one after a command or SFTP, has nothing to start
+        let unused = self
+            .channels
+            .get(&channel)
+            .is_some_and(|open_channel| open_channel.channel.is_some());
+        if !unused {
+            session.channel_failure(channel)?;
+            return Ok(());
+        }
+        session.channel_success(channel)?;
+        // Nobody could ever type into it
+        if self
+            .channels
+            .get(&channel)
+            .is_some_and(|open_channel| open_channel.input_closed)
+        {
+            self.channels.remove(&channel);
+            session.exit_status_request(channel, 0)?;
+            session.eof(channel)?;
+            session.close(channel)?;
+            return Ok(());
+        }
+        match self.start_app(channel, session).await {
+            // Only this channel is lost, the rest of the connection keeps going
+            Err(SessionError::Render(e)) => {
+                warn!(peer = %describe_peer(self.peer_addr), "Failed to start the app: {}", e);
+                self.hooks.error(&self.connection, &SessionError::Render(e));
+                session.data(channel, SESSION_FAILED_MESSAGE)?;
+                session.exit_status_request(channel, 1)?;
+                session.eof(channel)?;
+                session.close(channel)?;
+                Ok(())
+            }
+            result => result,
+        }
+    }
+
+    /// `ssh host <command>`, answered by `AppFactory::exec` unless it's SCP.
+    async fn exec_request(
+        &mut self,
+        channel: ChannelId,
+        data: &[u8],
+        session: &mut Session,
+    ) -> Result<(), Self::Error> {
+        let Some((open_channel, exec_channel)) =
+            self.channels.get_mut(&channel).and_then(|open_channel| {
+                let exec_channel = open_channel.channel.take()?;
+                Some((open_channel, exec_channel))
+            })
+        else {
+            session.channel_failure(channel)?;
+            return Ok(());
+        };
+        open_channel.slot = None;
+        let command = String::from_utf8_lossy(data).into_owned();
+        let colors = open_channel.pty.then(|| open_channel.color_support());
+        // A client with a pty has its terminal in raw mode
+        let newline = match open_channel.pty {
+            true => "\r\n",
+            false => "\n",
+        };
+        session.channel_success(channel)?;
+
+        let public_dir = self.config.read().unwrap().public_dir.clone();
+        let result = match (ScpRequest::parse(&command), public_dir) {
+            (Some(Ok(request)), Some(public_dir)) => {
+                info!(parent: &self.span, %command, "Starting SCP");
+                let files = PublicFiles::new(public_dir);
+                tokio::spawn(scp::serve(exec_channel, files, request, self.span.clone()));
+                return Ok(());
+            }
+            (Some(Ok(_)), None) => Err(String::from("scp: there are no files to download")),
+            (Some(Err(error)), _) => Err(format!("scp: {}", error)),
+            (None, _) => {
+                info!(parent: &self.span, %command, "Running command");
+                self.app_factory.exec(&command, colors)
+            }
+        };
+        let status = match result {
+            Ok(output) => {
+                let output = format!("{}\n", output).replace('\n', newline);
+                session.data(channel, output.into_bytes())?;
+                0
+            }
+            Err(error) => {
+                let error = format!("{}\n", error).replace('\n', newline);
+                session.extended_data(channel, 1, error.into_bytes())?;
+                1
+            }
+        };
+        session.exit_status_request(channel, status)?;
+        session.eof(channel)?;
+        session.close(channel)?;
+        self.channels.remove(&channel);
+        Ok(())
+    }
+
+    /// Only `sftp`, for the files in `public_dir`.
+    async fn subsystem_request(
+        &mut self,
+        channel: ChannelId,
+        name: &str,
+        session: &mut Session,
+    ) -> Result<(), Self::Error> {
+        let public_dir = self.config.read().unwrap().public_dir.clone();
+        let (Some(public_dir), "sftp") = (public_dir, name) else {
+            debug!(parent: &self.span, subsystem = name, "Refused a subsystem");
+            session.channel_failure(channel)?;
+            return Ok(());
+        };
+        let Some(open_channel) = self.channels.get_mut(&channel) else {
+            session.channel_failure(channel)?;
+            return Ok(());
+        };
+        let Some(stream) = open_channel.channel.take().map(Channel::into_stream) else {
+            session.channel_failure(channel)?;
+            return Ok(());
+        };
+        open_channel.sftp = true;
+        open_channel.slot = None;
+
+        info!(parent: &self.span, "Starting SFTP");
+        session.channel_success(channel)?;
+        let sftp = SftpSession::new(PublicFiles::new(public_dir), self.span.clone());
+        russh_sftp::server::run(stream, sftp).await;
+        Ok(())
+    }
+
+    /// The client won't send anything more, like `ssh host < /dev/null` once its
+    /// input runs out. SFTP clients check the exit status, so it's sent the way an
+    /// `sftp-server` process exiting would, and the app ends like a shell reaching
+    /// the end of its input instead of waiting for the idle timeout.
+    async fn channel_eof(
+        &mut self,
+        channel: ChannelId,
+        session: &mut Session,
+    ) -> Result<(), Self::Error> {
+        let Some(open_channel) = self.channels.get_mut(&channel) else {
+            return Ok(());
+        };
+        if open_channel.sftp {
+            session.exit_status_request(channel, 0)?;
+            session.eof(channel)?;
+            session.close(channel)?;
+            return Ok(());
+        }
+        match open_channel.session_id {
+            Some(id) => {
+                info!(parent: &self.span, "Client closed its input, ending the session");
+                self.disconnect(channel, id, 0, b"", session)?;
+            }
+            None => open_channel.input_closed = true,
+        }
+        Ok(())
+    }
+
+    async fn channel_close(
+        &mut self,
+        channel: ChannelId,
+        session: &mut Session,
+    ) -> Result<(), Self::Error> {
+        if let Some(client) = self
+            .channels
+            .remove(&channel)
+            .and_then(|open_channel| open_channel.session_id)
+            .and_then(|id| self.registry.remove(id))
+        {
+            let _ = client.restore_on(session);
+        }
+
+        session.close(channel)?;
+        Ok(())
+    }
+}
+
+fn describe_peer(peer_addr: Option<SocketAddr>) -> String {
+    peer_addr
+        .map(|addr| addr.to_string())
+        .unwrap_or_else(|| String::from("unknown"))
+}
+
+impl<F> Drop for ConnectionHandler<F> {
+    fn drop(&mut self) {
+        // Removing resets the terminal, in case the client is still reading
+        for (_, open_channel) in self.channels.drain() {
+            if let Some(id) = open_channel.session_id {
+                self.registry.remove(id);
+            }
+        }
+    }
+}
@@ -0,0 +1,63 @@
+use std::fs::{File, OpenOptions};
+use std::io::Write;
+use std::net::SocketAddr;
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde_json::json;
+use tracing::warn;
+
+use crate::stats::salted_hash;
+use crate::store::Store;
+
+/// Where password attempts go when the server offers password auth only to turn
+/// it down: one JSON object per line with the time, address, username and
+/// password. Unless `raw`, addresses and passwords are salted hashes, which
+/// still show what gets reused.
+pub struct Honeypot {
+    file: Mutex<File>,
+    raw: bool,
+    salt: String,
+}
+
+impl Honeypot {
+    pub fn open(path: &Path, raw: bool, store: &Store) -> Result<Arc<Self>, anyhow::Error> {
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .map_err(|e| {
+                anyhow::anyhow!("Failed to open honeypot log at {}: {}", path.display(), e)
+            })?;
+        Ok(Arc::new(Self {
+            file: Mutex::new(file),
+            raw,
+            salt: store.salt()?,
+        }))
+    }
+
+    pub fn record(&self, peer_addr: Option<SocketAddr>, user: &str, password: &str) {
+        let ip = peer_addr.map(|addr| addr.ip().to_canonical().to_string());
+        let (ip, password) = match self.raw {
+            true => (ip, password.to_string()),
+            false => (
+                ip.map(|ip| salted_hash(&self.salt, &ip)),
+                salted_hash(&self.salt, password),
+            ),
+        };
+        let time = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|since| since.as_secs())
+            .unwrap_or_default();
+        let line = json!({
+            "time": time,
+            "ip": ip,
+            "user": user,
+            "password": password,
+        });
+        if let Err(e) = writeln!(self.file.lock().unwrap(), "{}", line) {
+            warn!("Failed to write to the honeypot log: {}", e);
+        }
+    }
+}
@@ -0,0 +1,125 @@
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
+
+use crate::error::SessionError;
+
+/// What is known about a connection so far. It fills in as the visitor
+/// authenticates.
+#[derive(Debug, Clone)]
+pub struct ConnectionInfo {
+    pub peer_addr: Option<SocketAddr>,
+    /// The username the client logged in with.
+    pub user: Option<String>,
+    /// The fingerprint of the key the visitor authenticated with.
+    pub fingerprint: Option<String>,
+    pub admin: bool,
+    pub connected_at: Instant,
+}
+
+impl ConnectionInfo {
+    pub fn new(peer_addr: Option<SocketAddr>) -> Self {
+        Self {
+            peer_addr,
+            user: None,
+            fingerprint: None,
+            admin: false,
+            connected_at: Instant::now(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum AuthMethod {
+    PublicKey,
+    KeyboardInteractive,
+    /// Only offered to be turned down, see `honeypot_log`.
+    Password,
+}
+
+/// One finished authentication attempt. Offered keys and the question sent for
+/// keyboard-interactive aren't attempts yet.
+#[derive(Debug)]
+pub struct AuthAttempt<'a> {
+    pub user: &'a str,
+    pub method: AuthMethod,
+    pub fingerprint: Option<&'a str>,
+    pub accepted: bool,
+}
+
+#[derive(Debug)]
+pub struct PtyRequest<'a> {
+    pub term: &'a str,
+    pub width: u32,
+    pub height: u32,
+}
+
+/// Callbacks along a connection's life, for what sits beside the app: logging,
+/// analytics, limits, notifications. Every method does nothing by default.
+/// They run on the connection's task, so anything slow belongs in a task of
+/// its own.
+pub trait ConnectionHook: Send + Sync + 'static {
+    /// Before the SSH handshake, or the telnet negotiation. False drops the
+    /// connection, like the rate limiter does.
+    fn on_connect(&self, _peer_addr: SocketAddr) -> bool {
+        true
+    }
+    /// After every authentication attempt. False turns an accepted attempt down.
+    fn on_auth(&self, _connection: &ConnectionInfo, _attempt: &AuthAttempt) -> bool {
+        true
+    }
+    fn on_pty(&self, _connection: &ConnectionInfo, _pty: &PtyRequest) {}
+    /// Once the connection is gone, after `on_error` if an error ended it.
+    fn on_disconnect(&self, _connection: &ConnectionInfo) {}
+    /// A failed session, or the error that ended the connection.
+    fn on_error(&self, _connection: &ConnectionInfo, _error: &SessionError) {}
+}
+
+/// The hooks a server was built with, asked in the order they were added.
+#[derive(Default)]
+pub struct Hooks(Vec<Arc<dyn ConnectionHook>>);
+
+impl Hooks {
+    pub fn push(&mut self, hook: impl ConnectionHook) {
+        self.0.push(Arc::new(hook));
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Stops at the first hook that refuses, so the rest never see it.
+    pub fn connect(&self, peer_addr: SocketAddr) -> bool {
+        self.0.iter().all(|hook| hook.on_connect(peer_addr))
+    }
+
+    pub fn auth(&self, connection: &SharedConnectionInfo, attempt: &AuthAttempt) -> bool {
+        let connection = connection.lock().unwrap();
+        self.0.iter().all(|hook| hook.on_auth(&connection, attempt))
+    }
+
+    pub fn pty(&self, connection: &SharedConnectionInfo, pty: &PtyRequest) {
+        let connection = connection.lock().unwrap();
+        for hook in &self.0 {
+            hook.on_pty(&connection, pty);
+        }
+    }
+
+    pub fn disconnect(&self, connection: &SharedConnectionInfo) {
+        let connection = connection.lock().unwrap();
+        for hook in &self.0 {
+            hook.on_disconnect(&connection);
+        }
+    }
+
+    pub fn error(&self, connection: &SharedConnectionInfo, error: &SessionError) {
+        let connection = connection.lock().unwrap();
+        for hook in &self.0 {
+            hook.on_error(&connection, error);
+        }
+    }
+}
+
+/// Shared between the handler, which fills it in, and the connection's task,
+/// which reports how it ended.
+pub type SharedConnectionInfo = Arc<Mutex<ConnectionInfo>>;
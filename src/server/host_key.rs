@@ -0,0 +1,118 @@
+use std::fs;
+use std::path::Path;
+
+use rand::rng;
+use russh::keys::ssh_key::LineEnding;
+use russh::keys::{Algorithm, HashAlg, PrivateKey};
+use tracing::{info, warn};
+
+// ECDSA keys load fine, but the ones ssh-key writes don't read back, so those
+// have to come from `ssh-keygen -t ecdsa`
+pub fn parse_algorithm(name: &str) -> Result<Algorithm, anyhow::Error> {
+    match name {
+        "ed25519" => Ok(Algorithm::Ed25519),
+        "rsa" => Ok(Algorithm::Rsa { hash: None }),
+        other => Err(anyhow::anyhow!(
+            "Unknown key algorithm '{}', expected 'ed25519' or 'rsa'",
+            other
+        )),
+    }
+}
+
+/// The OpenSSH name for a key of this algorithm inside a host key directory.
+fn file_name(algorithm: &Algorithm) -> String {
+    let name = match algorithm {
+        Algorithm::Rsa { .. } => "rsa",
+        _ => "ed25519",
+    };
+    format!("ssh_host_{}_key", name)
+}
+
+/// Writes a fresh host key to `path`, or into it when `path` is a directory. The key
+/// file is created with 0600 permissions, like `ssh-keygen` does, and an existing
+/// key is never overwritten.
+pub fn generate(path: &Path, algorithm: Algorithm) -> Result<PrivateKey, anyhow::Error> {
+    let path = match path.is_dir() {
+        true => path.join(file_name(&algorithm)),
+        false => path.to_path_buf(),
+    };
+    if path.exists() {
+        return Err(anyhow::anyhow!(
+            "{} already exists, refusing to overwrite it",
+            path.display()
+        ));
+    }
+    if let Some(parent) = path
+        .parent()
+        .filter(|parent| !parent.as_os_str().is_empty())
+    {
+        fs::create_dir_all(parent)?;
+    }
+
+    let key = PrivateKey::random(&mut rng(), algorithm)
+        .map_err(|e| anyhow::anyhow!("Failed to generate host key: {}", e))?;
+    key.write_openssh_file(&path, LineEnding::LF)
+        .map_err(|e| anyhow::anyhow!("Failed to write host key to {}: {}", path.display(), e))?;
+
+    info!(
+        "Generated {} host key {} at {}",
+        key.algorithm(),
+        key.public_key().fingerprint(HashAlg::Sha256),
+        path.display()
+    );
+    Ok(key)
+}
+
+/// Loads the host key at `path`, or every private key in it when `path` is a
+/// directory, so clients that lack ed25519 can still negotiate RSA or ECDSA.
+pub fn load(path: &Path, auto_generate: bool) -> Result<Vec<PrivateKey>, anyhow::Error> {
+    if !path.exists() {
+        if auto_generate {
+            return Ok(vec![generate(path, Algorithm::Ed25519)?]);
+        }
+        return Err(anyhow::anyhow!(
+            "Host key not found at {}. Run `portfolio-v2 keygen` or pass --auto-generate-hostkey.",
+            path.display()
+        ));
+    }
+
+    if !path.is_dir() {
+        let key = PrivateKey::read_openssh_file(path)
+            .map_err(|e| anyhow::anyhow!("Failed to read host key: {}", e))?;
+        return Ok(vec![key]);
+    }
+
+    let mut key_paths = fs::read_dir(path)?
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| path.is_file() && !is_public_or_hidden(path))
+        .collect::<Vec<_>>();
+    key_paths.sort();
+
+    let mut keys = Vec::new();
+    for key_path in key_paths {
+        match PrivateKey::read_openssh_file(&key_path) {
+            Ok(key) => keys.push(key),
+            Err(e) => warn!("Skipping {}: {}", key_path.display(), e),
+        }
+    }
+
+    if keys.is_empty() {
+        if auto_generate {
+            return Ok(vec![generate(path, Algorithm::Ed25519)?]);
+        }
+        return Err(anyhow::anyhow!(
+            "No host keys found in {}. Run `portfolio-v2 keygen {}` first.",
+            path.display(),
+            path.display()
+        ));
+    }
+    Ok(keys)
+}
+
+fn is_public_or_hidden(path: &Path) -> bool {
+    let name = path
+        .file_name()
+        .and_then(|name| name.to_str())
+        .unwrap_or("");
+    name.starts_with('.') || name.ends_with(".pub")
+}
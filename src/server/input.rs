@@ -0,0 +1,478 @@
+use crossterm::event::{
+    Event, KeyCode, KeyEvent, KeyModifiers, MouseButton, MouseEvent, MouseEventKind,
+};
+
+const ESC: u8 = 0x1b;
+const PASTE_END: &[u8] = b"\x1b[201~";
+const DCS_START: &[u8] = b"\x1bP";
+const STRING_TERMINATOR: &[u8] = b"\x1b\\";
+// A status report longer than this is not one, so it is read as keys instead
+const MAX_REPORT_BYTES: usize = 256;
+// Anything pasted beyond this is dropped rather than buffered
+const MAX_PASTE_BYTES: usize = 16 * 1024;
+
+enum Parsed {
+    /// A complete sequence of the given length, which may or may not map to an event.
+    Complete(Option<Event>, usize),
+    /// The buffer ends in the middle of a sequence; wait for more bytes.
+    Incomplete,
+    /// `ESC [ 200 ~`: everything up to `ESC [ 201 ~` is pasted text.
+    PasteStart(usize),
+    /// `ESC [ ? ... c`, the terminal's reply to a device attributes query.
+    DeviceAttributes(usize),
+}
+
+/// Turns the raw bytes of an SSH channel into input events. Escape sequences can be
+/// split across `data()` calls, so anything incomplete is kept until the next feed.
+pub struct InputDecoder {
+    pending: Vec<u8>,
+    paste: Option<Vec<u8>>,
+    awaiting_report: bool,
+    reports: Vec<Vec<u8>>,
+}
+
+impl InputDecoder {
+    pub fn new() -> Self {
+        Self {
+            pending: Vec::new(),
+            paste: None,
+            awaiting_report: false,
+            reports: Vec::new(),
+        }
+    }
+
+    /// Reads the next `ESC P ... ESC \` as the answer to a query sent to the
+    /// terminal. Until then the same bytes would be Alt+Shift+P, so this is only
+    /// armed while a reply is actually expected.
+    pub fn expect_status_report(&mut self) {
+        self.awaiting_report = true;
+    }
+
+    /// The payloads of the status reports received so far, along with any device
+    /// attributes (`?62;4c`), which need no arming since no key looks like them.
+    pub fn take_status_reports(&mut self) -> Vec<Vec<u8>> {
+        std::mem::take(&mut self.reports)
+    }
+
+    pub fn feed(&mut self, data: &[u8]) -> Vec<Event> {
+        // A packet holding nothing but ESC is the Escape key itself, not the start
+        // of a sequence that got cut off
+        let lone_escape = self.pending.is_empty() && data == [ESC];
+        self.pending.extend_from_slice(data);
+
+        let mut events = Vec::new();
+        let mut consumed = 0;
+        while consumed < self.pending.len() {
+            if let Some(paste) = self.paste.as_mut() {
+                let rest = &self.pending[consumed..];
+                match rest.windows(PASTE_END.len()).position(|w| w == PASTE_END) {
+                    Some(end) => {
+                        extend_capped(paste, &rest[..end]);
+                        let text = String::from_utf8_lossy(paste).into_owned();
+                        events.push(Event::Paste(text));
+                        self.paste = None;
+                        consumed += end + PASTE_END.len();
+                        continue;
+                    }
+                    None => {
+                        // Hold back a tail that could be the start of the end marker
+                        let keep = (PASTE_END.len() - 1).min(rest.len());
+                        extend_capped(paste, &rest[..rest.len() - keep]);
+                        consumed += rest.len() - keep;
+                        break;
+                    }
+                }
+            }
+
+            let rest = &self.pending[consumed..];
+            if self.awaiting_report && rest.starts_with(DCS_START) {
+                let body = &rest[DCS_START.len()..];
+                // Replies open with `0$r` or `1$r`; anything else is a real key
+                let prefix_len = body.len().min(3);
+                if b"0$r"[..prefix_len] != body[..prefix_len]
+                    && b"1$r"[..prefix_len] != body[..prefix_len]
+                {
+                    self.awaiting_report = false;
+                    continue;
+                }
+                match body
+                    .windows(STRING_TERMINATOR.len())
+                    .position(|w| w == STRING_TERMINATOR)
+                {
+                    Some(end) if end <= MAX_REPORT_BYTES => {
+                        self.reports.push(body[..end].to_vec());
+                        self.awaiting_report = false;
+                        consumed += DCS_START.len() + end + STRING_TERMINATOR.len();
+                        continue;
+                    }
+                    None if body.len() <= MAX_REPORT_BYTES => break,
+                    _ => self.awaiting_report = false,
+                }
+            }
+
+            match parse(&self.pending[consumed..]) {
+                Parsed::Complete(event, len) => {
+                    events.extend(event);
+                    consumed += len;
+                }
+                Parsed::PasteStart(len) => {
+                    self.paste = Some(Vec::new());
+                    consumed += len;
+                }
+                Parsed::DeviceAttributes(len) => {
+                    // Kept without the `ESC [`
+                    let reply = &self.pending[consumed + 2..consumed + len];
+                    self.reports.push(reply.to_vec());
+                    consumed += len;
+                }
+                Parsed::Incomplete if lone_escape => {
+                    events.push(Event::Key(key(KeyCode::Esc)));
+                    consumed += 1;
+                }
+                Parsed::Incomplete => break,
+            }
+        }
+
+        self.pending.drain(..consumed);
+        events
+    }
+}
+
+fn extend_capped(paste: &mut Vec<u8>, bytes: &[u8]) {
+    let room = MAX_PASTE_BYTES.saturating_sub(paste.len());
+    paste.extend_from_slice(&bytes[..bytes.len().min(room)]);
+}
+
+fn parse(buf: &[u8]) -> Parsed {
+    match buf {
+        [] => Parsed::Incomplete,
+        [ESC] => Parsed::Incomplete,
+        [ESC, b'[', rest @ ..] => parse_csi(rest),
+        [ESC, b'O'] => Parsed::Incomplete,
+        [ESC, b'O', c, ..] => Parsed::Complete(ss3_key(*c).map(Event::Key), 3),
+        [ESC, ESC, ..] => Parsed::Complete(Some(Event::Key(key(KeyCode::Esc))), 1),
+        [ESC, rest @ ..] => match parse(rest) {
+            Parsed::Complete(Some(Event::Key(mut event)), len) => {
+                event.modifiers |= KeyModifiers::ALT;
+                Parsed::Complete(Some(Event::Key(event)), len + 1)
+            }
+            Parsed::Complete(event, len) => Parsed::Complete(event, len + 1),
+            other => other,
+        },
+        [c, ..] if *c >= 0x80 => parse_utf8(buf),
+        [c, ..] => Parsed::Complete(byte_key(*c).map(Event::Key), 1),
+    }
+}
+
+fn parse_utf8(buf: &[u8]) -> Parsed {
+    let len = match buf[0] {
+        0xc2..=0xdf => 2,
+        0xe0..=0xef => 3,
+        0xf0..=0xf4 => 4,
+        _ => return Parsed::Complete(None, 1),
+    };
+    if buf.len() < len {
+        // Continuation bytes of a multi-byte character can arrive in the next packet
+        return match buf[1..].iter().all(|b| (0x80..=0xbf).contains(b)) {
+            true => Parsed::Incomplete,
+            false => Parsed::Complete(None, 1),
+        };
+    }
+
+    match std::str::from_utf8(&buf[..len]) {
+        Ok(text) => {
+            let event = text
+                .chars()
+                .next()
+                .map(|c| Event::Key(key(KeyCode::Char(c))));
+            Parsed::Complete(event, len)
+        }
+        Err(_) => Parsed::Complete(None, 1),
+    }
+}
+
+fn parse_csi(rest: &[u8]) -> Parsed {
+    // Parameter bytes (0x30..=0x3F) and intermediates (0x20..=0x2F) run until a
+    // final byte in 0x40..=0x7E
+    let final_index = rest
+        .iter()
+        .take_while(|b| (0x20..=0x3f).contains(*b))
+        .count();
+    let Some(&final_byte) = rest.get(final_index) else {
+        return Parsed::Incomplete;
+    };
+    if !(0x40..=0x7e).contains(&final_byte) {
+        return Parsed::Complete(None, 2 + final_index);
+    }
+
+    let params = &rest[..final_index];
+    let len = 2 + final_index + 1;
+
+    if let [b'<', mouse_params @ ..] = params {
+        return Parsed::Complete(parse_sgr_mouse(mouse_params, final_byte), len);
+    }
+    if params == b"200" && final_byte == b'~' {
+        return Parsed::PasteStart(len);
+    }
+    if params.first() == Some(&b'?') && final_byte == b'c' {
+        return Parsed::DeviceAttributes(len);
+    }
+
+    let mut params = std::str::from_utf8(params)
+        .unwrap_or_default()
+        .split(';')
+        .map(|param| param.parse::<u8>().ok());
+    let first_param = params.next().flatten();
+    // xterm encodes modifiers as a second parameter: 1 + (shift | alt << 1 | ctrl << 2)
+    let modifiers = params
+        .next()
+        .flatten()
+        .map(csi_modifiers)
+        .unwrap_or(KeyModifiers::NONE);
+
+    let code = match final_byte {
+        b'A' => Some(KeyCode::Up),
+        b'B' => Some(KeyCode::Down),
+        b'C' => Some(KeyCode::Right),
+        b'D' => Some(KeyCode::Left),
+        b'H' => Some(KeyCode::Home),
+        b'F' => Some(KeyCode::End),
+        b'P' => Some(KeyCode::F(1)),
+        b'Q' => Some(KeyCode::F(2)),
+        b'R' => Some(KeyCode::F(3)),
+        b'S' => Some(KeyCode::F(4)),
+        b'Z' => {
+            let event = KeyEvent::new(KeyCode::BackTab, KeyModifiers::SHIFT);
+            return Parsed::Complete(Some(Event::Key(event)), len);
+        }
+        b'~' => first_param.and_then(tilde_key),
+        _ => None,
+    };
+
+    let event = code.map(|code| Event::Key(KeyEvent::new(code, modifiers)));
+    Parsed::Complete(event, len)
+}
+
+/// SGR (1006) mouse reports look like `ESC [ < button ; column ; row M`, with a
+/// lowercase `m` final byte for releases.
+fn parse_sgr_mouse(params: &[u8], final_byte: u8) -> Option<Event> {
+    let params = std::str::from_utf8(params).ok()?;
+    let mut values = params.split(';').map(|value| value.parse::<u16>().ok());
+    let button = values.next()??;
+    let column = values.next()??.saturating_sub(1);
+    let row = values.next()??.saturating_sub(1);
+
+    let mut modifiers = KeyModifiers::NONE;
+    if button & 4 != 0 {
+        modifiers |= KeyModifiers::SHIFT;
+    }
+    if button & 8 != 0 {
+        modifiers |= KeyModifiers::ALT;
+    }
+    if button & 16 != 0 {
+        modifiers |= KeyModifiers::CONTROL;
+    }
+
+    let pressed = match button & 0b11 {
+        0 => MouseButton::Left,
+        1 => MouseButton::Middle,
+        _ => MouseButton::Right,
+    };
+
+    let kind = if button & 64 != 0 {
+        match button & 0b11 {
+            0 => MouseEventKind::ScrollUp,
+            1 => MouseEventKind::ScrollDown,
+            2 => MouseEventKind::ScrollLeft,
+            _ => MouseEventKind::ScrollRight,
+        }
+    } else if button & 32 != 0 {
+        match button & 0b11 {
+            3 => MouseEventKind::Moved,
+            _ => MouseEventKind::Drag(pressed),
+        }
+    } else if final_byte == b'm' {
+        MouseEventKind::Up(pressed)
+    } else {
+        MouseEventKind::Down(pressed)
+    };
+
+    Some(Event::Mouse(MouseEvent {
+        kind,
+        column,
+        row,
+        modifiers,
+    }))
+}
+
+fn csi_modifiers(param: u8) -> KeyModifiers {
+    let bits = param.saturating_sub(1);
+    let mut modifiers = KeyModifiers::NONE;
+    if bits & 1 != 0 {
+        modifiers |= KeyModifiers::SHIFT;
+    }
+    if bits & 2 != 0 {
+        modifiers |= KeyModifiers::ALT;
+    }
+    if bits & 4 != 0 {
+        modifiers |= KeyModifiers::CONTROL;
+    }
+    modifiers
+}
+
+fn tilde_key(param: u8) -> Option<KeyCode> {
+    match param {
+        1 | 7 => Some(KeyCode::Home),
+        2 => Some(KeyCode::Insert),
+        3 => Some(KeyCode::Delete),
+        4 | 8 => Some(KeyCode::End),
+        5 => Some(KeyCode::PageUp),
+        6 => Some(KeyCode::PageDown),
+        11..=15 => Some(KeyCode::F(param - 10)),
+        17..=21 => Some(KeyCode::F(param - 11)),
+        23 | 24 => Some(KeyCode::F(param - 12)),
+        _ => None,
+    }
+}
+
+fn ss3_key(c: u8) -> Option<KeyEvent> {
+    let code = match c {
+        b'A' => KeyCode::Up,
+        b'B' => KeyCode::Down,
+        b'C' => KeyCode::Right,
+        b'D' => KeyCode::Left,
+        b'H' => KeyCode::Home,
+        b'F' => KeyCode::End,
+        b'P' => KeyCode::F(1),
+        b'Q' => KeyCode::F(2),
+        b'R' => KeyCode::F(3),
+        b'S' => KeyCode::F(4),
+        _ => return None,
+    };
+    Some(key(code))
+}
+
+fn byte_key(c: u8) -> Option<KeyEvent> {
+    let event = match c {
+        b'\r' | b'\n' => key(KeyCode::Enter),
+        b'\t' => key(KeyCode::Tab),
+        0x7f | 0x08 => key(KeyCode::Backspace),
+        ESC => key(KeyCode::Esc),
+        0x00 => KeyEvent::new(KeyCode::Char(' '), KeyModifiers::CONTROL),
+        0x01..=0x1a => KeyEvent::new(KeyCode::Char((b'a' + c - 1) as char), KeyModifiers::CONTROL),
+        0x1c..=0x1f => KeyEvent::new(
+            KeyCode::Char((b'4' + c - 0x1c) as char),
+            KeyModifiers::CONTROL,
+        ),
+        c if c.is_ascii_uppercase() => KeyEvent::new(KeyCode::Char(c as char), KeyModifiers::SHIFT),
+        c if c.is_ascii_graphic() || c == b' ' => key(KeyCode::Char(c as char)),
+        _ => return None,
+    };
+    Some(event)
+}
+
+fn key(code: KeyCode) -> KeyEvent {
+    KeyEvent::new(code, KeyModifiers::NONE)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn keys(events: Vec<Event>) -> Vec<KeyEvent> {
+        events
+            .into_iter()
+            .map(|event| match event {
+                Event::Key(key_event) => key_event,
+                other => panic!("expected a key, got {:?}", other),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn a_sequence_split_across_feeds_is_read_whole() {
+        let mut decoder = InputDecoder::new();
+        assert!(decoder.feed(b"\x1b[").is_empty());
+        assert_eq!(keys(decoder.feed(b"A")), [key(KeyCode::Up)]);
+        assert!(decoder.feed(b"\x1bO").is_empty());
+        assert_eq!(
+            keys(decoder.feed(b"Dx")),
+            [key(KeyCode::Left), key(KeyCode::Char('x'))]
+        );
+    }
+
+    #[test]
+    fn a_lone_escape_is_the_escape_key() {
+        let mut decoder = InputDecoder::new();
+        assert_eq!(keys(decoder.feed(b"\x1b")), [key(KeyCode::Esc)]);
+    }
+
+    #[test]
+    fn modifiers_come_from_the_second_parameter() {
+        let mut decoder = InputDecoder::new();
+        assert_eq!(
+            keys(decoder.feed(b"\x1b[1;5C\x1b[1;2A\x1b[3;3~")),
+            [
+                KeyEvent::new(KeyCode::Right, KeyModifiers::CONTROL),
+                KeyEvent::new(KeyCode::Up, KeyModifiers::SHIFT),
+                KeyEvent::new(KeyCode::Delete, KeyModifiers::ALT),
+            ]
+        );
+        // Split right after the separator, too
+        assert!(decoder.feed(b"\x1b[1;").is_empty());
+        assert_eq!(
+            keys(decoder.feed(b"6D")),
+            [KeyEvent::new(
+                KeyCode::Left,
+                KeyModifiers::CONTROL | KeyModifiers::SHIFT
+            )]
+        );
+    }
+
+    #[test]
+    fn a_character_cut_across_feeds_is_read_whole() {
+        let mut decoder = InputDecoder::new();
+        let bytes = "é€".as_bytes();
+        assert!(decoder.feed(&bytes[..1]).is_empty());
+        assert_eq!(keys(decoder.feed(&bytes[1..3])), [key(KeyCode::Char('é'))]);
+        assert_eq!(keys(decoder.feed(&bytes[3..])), [key(KeyCode::Char('€'))]);
+    }
+
+    #[test]
+    fn sgr_mouse_reports_are_read() {
+        let mut decoder = InputDecoder::new();
+        let events = decoder.feed(b"\x1b[<0;10;5M\x1b[<0;10;5m\x1b[<65;1;1M\x1b[<32;3;4M");
+        let mouse = |kind, column, row| {
+            Event::Mouse(MouseEvent {
+                kind,
+                column,
+                row,
+                modifiers: KeyModifiers::NONE,
+            })
+        };
+        assert_eq!(
+            events,
+            [
+                mouse(MouseEventKind::Down(MouseButton::Left), 9, 4),
+                mouse(MouseEventKind::Up(MouseButton::Left), 9, 4),
+                mouse(MouseEventKind::ScrollDown, 0, 0),
+                mouse(MouseEventKind::Drag(MouseButton::Left), 2, 3),
+            ]
+        );
+    }
+
+    #[test]
+    fn a_paste_broken_across_chunks_is_one_event() {
+        let mut decoder = InputDecoder::new();
+        assert!(decoder.feed(b"\x1b[200~hel").is_empty());
+        // Ends partway into the end marker
+        assert!(decoder.feed(b"lo\x1b[2").is_empty());
+        assert_eq!(
+            decoder.feed(b"01~q"),
+            [
+                Event::Paste(String::from("hello")),
+                Event::Key(key(KeyCode::Char('q'))),
+            ]
+        );
+    }
+}
@@ -0,0 +1,164 @@
+use std::net::SocketAddr;
+use std::sync::Arc;
+
+use crossterm::event::Event;
+use ratatui::backend::CrosstermBackend;
+use ratatui::layout::Rect;
+use ratatui::{Terminal, TerminalOptions, Viewport};
+use tokio::sync::mpsc;
+use tracing::{Span, info, warn};
+
+use crate::charset::Charset;
+use crate::color_support::ColorSupport;
+use crate::config::SharedConfig;
+use crate::error::RenderError;
+use crate::graphics::{Graphics, Images};
+use crate::hyperlinks::Hyperlinks;
+use crate::server::recording::Recorder;
+use crate::server::session_registry::{
+    ClientSession, Link, SESSION_FAILED_MESSAGE, SessionId, SessionRegistry, Slot,
+};
+use crate::server::{AppFactory, SessionInfo, TerminalHandle};
+
+// SGR mouse reporting (presses, drags and wheel) plus bracketed paste
+pub const ENABLE_INPUT_MODES_SEQUENCE: &[u8] = b"\x1b[?1000h\x1b[?1002h\x1b[?1006h\x1b[?2004h";
+pub const SERVER_FULL_MESSAGE: &[u8] = b"\r\nserver is full, try again later\r\n";
+// Frames waiting to be written to the client before new ones are dropped
+pub const OUTPUT_QUEUE_CAPACITY: usize = 4;
+
+/// A terminal about to get its app, from whichever listener it came in on. Its
+/// frames go out through `output`, which the listener drains at the client's
+/// pace.
+pub struct Launch<'a, F> {
+    pub registry: &'a SessionRegistry,
+    pub config: &'a SharedConfig,
+    pub app_factory: &'a F,
+    pub id: SessionId,
+    /// From `SessionRegistry::reserve`, held by the session from now on.
+    pub slot: Slot,
+    /// The listener it came in on, like `ssh`.
+    pub via: &'static str,
+    pub span: Span,
+    pub output: mpsc::Sender<Vec<u8>>,
+    pub link: Link,
+    pub peer_addr: Option<SocketAddr>,
+    pub fingerprint: Option<String>,
+    pub user: String,
+    pub admin: bool,
+    /// `TERM`, or what the listener takes it to be.
+    pub term: String,
+    pub color_support: ColorSupport,
+    pub charset: Charset,
+    pub hyperlinks: bool,
+    pub graphics: Graphics,
+    pub cell_size: Option<(u32, u32)>,
+    pub size: (u16, u16),
+    /// See `ClientSession::probe_latency`.
+    pub probe_latency: bool,
+}
+
+/// Hands input to a session on a connection of the server's own, and ends it
+/// the way the SSH handler does once the visitor quits or the app panics.
+pub async fn handle_input(
+    registry: &SessionRegistry,
+    id: SessionId,
+    client: &ClientSession,
+    events: Vec<Event>,
+) {
+    match client.handle_input(events).await {
+        Ok(false) => {}
+        Ok(true) => {
+            info!(parent: &client.span, "Visitor quit");
+            if let Some(client) = registry.remove(id) {
+                client.close_with(b"").await;
+            }
+        }
+        Err(message) => {
+            warn!(
+                parent: &client.span,
+                "Disconnecting session: input handling panicked: {}", message
+            );
+            if let Some(client) = registry.remove(id) {
+                client.close_with(SESSION_FAILED_MESSAGE).await;
+            }
+        }
+    }
+}
+
+impl<F: AppFactory> Launch<'_, F> {
+    /// Makes the app and registers the session, which starts rendering it.
+    pub async fn start(self) -> Result<Arc<ClientSession>, RenderError> {
+        let recorder = self.start_recording();
+        let terminal_handle = TerminalHandle::new_with_sender(self.output.clone(), recorder);
+        let backend = CrosstermBackend::new(terminal_handle);
+
+        let options = TerminalOptions {
+            viewport: Viewport::Fixed(Rect::default()),
+        };
+
+        let terminal = Terminal::with_options(backend, options)?;
+        // Visitors without a key are told apart by their address
+        let visitor = match (&self.fingerprint, self.peer_addr) {
+            (Some(fingerprint), _) => fingerprint.clone(),
+            (None, Some(addr)) => addr.ip().to_canonical().to_string(),
+            (None, None) => String::from("unknown"),
+        };
+        info!(
+            parent: &self.span,
+            admin = self.admin,
+            user = self.user.as_str(),
+            term = self.term.as_str(),
+            "Session opened"
+        );
+        // Whatever the factory logs, like where the visitor is, is about the session
+        let app = self.span.in_scope(|| {
+            self.app_factory.make(SessionInfo {
+                id: self.id,
+                visitor,
+                fingerprint: self.fingerprint,
+                peer_addr: self.peer_addr,
+                via: self.via,
+                user: self.user,
+                term: self.term,
+                admin: self.admin,
+                sessions: self.registry.clone(),
+            })
+        });
+
+        let mut client = ClientSession::new(
+            terminal,
+            app,
+            self.link,
+            self.peer_addr,
+            self.span,
+            self.slot,
+        );
+        client.probe_latency = self.probe_latency;
+        {
+            // The first frame is drawn at the right size, without waiting
+            let mut state = client.state().await;
+            state.color_support = self.color_support;
+            state.charset = self.charset;
+            state.hyperlinks = Hyperlinks::new(self.hyperlinks);
+            state.images = Images::new(self.graphics, self.cell_size);
+            state.resize(self.size.0, self.size.1)?;
+        }
+        Ok(self.registry.insert(self.id, client))
+    }
+
+    fn start_recording(&self) -> Option<Recorder> {
+        let (dir, keep, max_bytes) = {
+            let config = self.config.read().unwrap();
+            let dir = config.recordings_dir.clone()?;
+            (dir, config.max_recordings, config.max_recording_bytes)
+        };
+        let peer = self
+            .peer_addr
+            .map(|addr| addr.to_string())
+            .unwrap_or_else(|| String::from("unknown"));
+        let title = format!("session {} from {}", self.id, peer);
+        Recorder::create(&dir, self.id, title, keep, max_bytes)
+            .inspect_err(|e| warn!(parent: &self.span, "Not recording the session: {}", e))
+            .ok()
+    }
+}
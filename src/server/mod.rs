@@ -1,5 +1,39 @@
+pub mod admission;
 pub mod app_server;
+pub mod audit;
+pub mod auth;
+pub mod builder;
+pub mod challenge;
+pub mod control;
+pub mod finger;
+pub mod handler;
+pub mod honeypot;
+pub mod hooks;
+pub mod host_key;
+pub(crate) mod input;
+pub(crate) mod launch;
+pub mod privileges;
+pub mod proxy_protocol;
+pub mod public_files;
+pub mod rate_limit;
+pub mod recording;
+pub mod router;
+pub mod scp;
+pub mod session_registry;
+pub mod sftp;
+pub mod shutdown;
+pub mod systemd;
+pub mod telnet;
 pub mod terminal_handle;
+pub mod tui_app;
+pub mod web;
 
 pub use app_server::AppServer;
+pub use auth::Authenticator;
+pub use builder::{AppServerBuilder, SessionInfo};
+pub use handler::ConnectionHandler;
+pub use hooks::{ConnectionHook, ConnectionInfo};
+pub use router::UserRouter;
+pub use session_registry::SessionRegistry;
 pub use terminal_handle::TerminalHandle;
+pub use tui_app::{AppFactory, FrameRate, FromSession, NewApp, TuiApp};
@@ -0,0 +1,42 @@
+use nix::unistd::{Group, Uid, User, getuid, setgid, setgroups, setuid};
+use tracing::info;
+
+/// Switches the whole process to `user`, and to `group` or else the user's own
+/// group, once everything that needs root (port 22, the host key) is done. glibc
+/// applies the change to every thread, so the runtime's workers switch too.
+pub fn drop_to(user: &str, group: Option<&str>) -> Result<(), anyhow::Error> {
+    let user = User::from_name(user)?.ok_or_else(|| anyhow::anyhow!("No user '{}'", user))?;
+    let gid = match group {
+        Some(name) => {
+            Group::from_name(name)?
+                .ok_or_else(|| anyhow::anyhow!("No group '{}'", name))?
+                .gid
+        }
+        None => user.gid,
+    };
+
+    if getuid() == user.uid {
+        return Ok(());
+    }
+    if !getuid().is_root() {
+        return Err(anyhow::anyhow!(
+            "Only root can switch to user '{}', running as uid {}",
+            user.name,
+            getuid()
+        ));
+    }
+
+    // Supplementary groups first, while there is still permission to change them
+    setgroups(&[gid]).map_err(|e| anyhow::anyhow!("Failed to set groups: {}", e))?;
+    setgid(gid).map_err(|e| anyhow::anyhow!("Failed to switch to gid {}: {}", gid, e))?;
+    setuid(user.uid).map_err(|e| anyhow::anyhow!("Failed to switch to uid {}: {}", user.uid, e))?;
+    // Getting root back has to be impossible now
+    if setuid(Uid::from_raw(0)).is_ok() {
+        return Err(anyhow::anyhow!(
+            "Root privileges could be regained after dropping them"
+        ));
+    }
+
+    info!(user = %user.name, uid = %user.uid, gid = %gid, "Dropped root privileges");
+    Ok(())
+}
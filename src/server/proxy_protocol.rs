@@ -0,0 +1,168 @@
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr};
+
+use tokio::io::{AsyncRead, AsyncReadExt};
+
+const V2_SIGNATURE: &[u8; 12] = b"\r\n\r\n\0\r\nQUIT\n";
+// The longest v1 header the spec allows, CRLF included
+const V1_MAX_LENGTH: usize = 107;
+
+/// Reads the PROXY protocol header (v1 or v2) a load balancer sends before the
+/// client's own bytes, and nothing past it. Returns the client's address, or None
+/// when the proxy connected on its own behalf, like for a health check.
+pub async fn read_header(
+    stream: &mut (impl AsyncRead + Unpin),
+) -> Result<Option<SocketAddr>, anyhow::Error> {
+    // Shorter than either version's smallest header
+    let mut start = [0; 12];
+    stream.read_exact(&mut start).await?;
+    if &start == V2_SIGNATURE {
+        read_v2(stream).await
+    } else if start.starts_with(b"PROXY ") {
+        read_v1(stream, &start).await
+    } else {
+        Err(anyhow::anyhow!("Expected a PROXY protocol header"))
+    }
+}
+
+/// `PROXY TCP4 203.0.113.7 198.51.100.1 51234 22\r\n`
+async fn read_v1(
+    stream: &mut (impl AsyncRead + Unpin),
+    start: &[u8],
+) -> Result<Option<SocketAddr>, anyhow::Error> {
+    let mut line = start.to_vec();
+    while !line.ends_with(b"\r\n") {
+        if line.len() >= V1_MAX_LENGTH {
+            return Err(anyhow::anyhow!("PROXY header is too long"));
+        }
+        line.push(stream.read_u8().await?);
+    }
+
+    let line = std::str::from_utf8(&line[..line.len() - 2])?;
+    let words = line.split(' ').collect::<Vec<_>>();
+    match words[..] {
+        ["PROXY", "UNKNOWN", ..] => Ok(None),
+        ["PROXY", "TCP4" | "TCP6", source, _, port, _] => {
+            let ip = source
+                .parse::<IpAddr>()
+                .map_err(|e| anyhow::anyhow!("Invalid PROXY source '{}': {}", source, e))?;
+            let port = port
+                .parse::<u16>()
+                .map_err(|e| anyhow::anyhow!("Invalid PROXY source port '{}': {}", port, e))?;
+            Ok(Some(SocketAddr::new(ip, port)))
+        }
+        _ => Err(anyhow::anyhow!("Invalid PROXY header '{}'", line)),
+    }
+}
+
+async fn read_v2(
+    stream: &mut (impl AsyncRead + Unpin),
+) -> Result<Option<SocketAddr>, anyhow::Error> {
+    let version_command = stream.read_u8().await?;
+    let family = stream.read_u8().await?;
+    let length = stream.read_u16().await? as usize;
+    // Addresses, then TLVs that aren't needed but have to be read past
+    let mut body = vec![0; length];
+    stream.read_exact(&mut body).await?;
+
+    match version_command {
+        // LOCAL
+        0x20 => return Ok(None),
+        // PROXY
+        0x21 => {}
+        _ => {
+            return Err(anyhow::anyhow!(
+                "Unsupported PROXY v2 command {:#x}",
+                version_command
+            ));
+        }
+    }
+    let source = match family {
+        // TCP or UDP over IPv4
+        0x11 | 0x12 if body.len() >= 12 => {
+            let ip = Ipv4Addr::from(<[u8; 4]>::try_from(&body[0..4])?);
+            let port = u16::from_be_bytes([body[8], body[9]]);
+            SocketAddr::new(ip.into(), port)
+        }
+        // TCP or UDP over IPv6
+        0x21 | 0x22 if body.len() >= 36 => {
+            let ip = Ipv6Addr::from(<[u8; 16]>::try_from(&body[0..16])?);
+            let port = u16::from_be_bytes([body[32], body[33]]);
+            SocketAddr::new(ip.into(), port)
+        }
+        // Unix sockets and unspecified families have no address to use
+        _ => return Ok(None),
+    };
+    Ok(Some(source))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn v2_header(command: u8, family: u8, body: &[u8]) -> Vec<u8> {
+        let mut header = V2_SIGNATURE.to_vec();
+        header.extend([command, family]);
+        header.extend((body.len() as u16).to_be_bytes());
+        header.extend(body);
+        header
+    }
+
+    #[tokio::test]
+    async fn a_v1_header_gives_the_source_and_nothing_past_it() {
+        let mut stream: &[u8] = b"PROXY TCP4 203.0.113.7 198.51.100.1 51234 22\r\nSSH-2.0";
+        let source = read_header(&mut stream).await.unwrap();
+        assert_eq!(source, Some("203.0.113.7:51234".parse().unwrap()));
+        assert_eq!(stream, b"SSH-2.0");
+
+        let mut stream: &[u8] = b"PROXY TCP6 2001:db8::1 2001:db8::2 443 22\r\n";
+        let source = read_header(&mut stream).await.unwrap();
+        assert_eq!(source, Some("[2001:db8::1]:443".parse().unwrap()));
+
+        let mut stream: &[u8] = b"PROXY UNKNOWN\r\n";
+        assert_eq!(read_header(&mut stream).await.unwrap(), None);
+    }
+
+    #[tokio::test]
+    async fn a_v2_header_gives_the_source_and_nothing_past_it() {
+        let mut body = vec![203, 0, 113, 7, 198, 51, 100, 1];
+        body.extend(51234u16.to_be_bytes());
+        body.extend(22u16.to_be_bytes());
+        // A TLV, which is skipped
+        body.extend([0x04, 0, 1, 0xff]);
+        let mut header = v2_header(0x21, 0x11, &body);
+        header.extend(b"SSH-2.0");
+        let mut stream = &header[..];
+        let source = read_header(&mut stream).await.unwrap();
+        assert_eq!(source, Some("203.0.113.7:51234".parse().unwrap()));
+        assert_eq!(stream, b"SSH-2.0");
+
+        // A health check from the proxy itself
+        let header = v2_header(0x20, 0x00, &[]);
+        assert_eq!(read_header(&mut &header[..]).await.unwrap(), None);
+    }
+
+    #[tokio::test]
+    async fn a_truncated_header_is_an_error() {
+        let mut stream: &[u8] = b"PROXY TCP4 203.0.113.7";
+        assert!(read_header(&mut stream).await.is_err());
+
+        let header = v2_header(0x21, 0x11, &[0; 12]);
+        assert!(read_header(&mut &header[..header.len() - 4]).await.is_err());
+
+        let mut stream: &[u8] = b"\r\n\r\n";
+        assert!(read_header(&mut stream).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn an_oversize_v1_header_is_an_error() {
+        let line = format!("PROXY TCP4 {} 198.51.100.1 51234 22\r\n", "1".repeat(100));
+        let error = read_header(&mut line.as_bytes()).await.unwrap_err();
+        assert!(error.to_string().contains("too long"), "{}", error);
+    }
+
+    #[tokio::test]
+    async fn anything_else_is_not_a_header() {
+        let mut stream: &[u8] = b"SSH-2.0-OpenSSH_9.6\r\n";
+        assert!(read_header(&mut stream).await.is_err());
+    }
+}
@@ -0,0 +1,95 @@
+use std::fs::{self, Metadata};
+use std::path::PathBuf;
+
+/// The files visitors can download over SFTP, like a resume or a GPG key. Only
+/// regular files directly in the directory are offered: no subdirectories and
+/// nothing hidden, so a path can never lead anywhere else.
+#[derive(Clone)]
+pub struct PublicFiles {
+    dir: PathBuf,
+}
+
+impl PublicFiles {
+    pub fn new(dir: PathBuf) -> Self {
+        Self { dir }
+    }
+
+    pub fn dir_metadata(&self) -> Option<Metadata> {
+        fs::metadata(&self.dir).ok()
+    }
+
+    /// Sorted by name.
+    pub fn list(&self) -> Vec<(String, Metadata)> {
+        let Ok(entries) = fs::read_dir(&self.dir) else {
+            return Vec::new();
+        };
+        let mut files = entries
+            .filter_map(|entry| {
+                let name = entry.ok()?.file_name().into_string().ok()?;
+                let (_, metadata) = self.find(&name)?;
+                Some((name, metadata))
+            })
+            .collect::<Vec<_>>();
+        files.sort_by(|(a, _), (b, _)| a.cmp(b));
+        files
+    }
+
+    /// Looks up `path` as the client sent it, so `resume.pdf`, `/resume.pdf` and
+    /// `./resume.pdf` all find the same file.
+    pub fn find(&self, path: &str) -> Option<(PathBuf, Metadata)> {
+        let name = file_name(path)?;
+        let path = self.dir.join(name);
+        let metadata = fs::metadata(&path).ok().filter(Metadata::is_file)?;
+        Some((path, metadata))
+    }
+}
+
+/// The one name `path` refers to, or None for the directory itself or anything
+/// that would leave it.
+pub fn file_name(path: &str) -> Option<&str> {
+    let mut components = path
+        .split('/')
+        .filter(|part| !part.is_empty() && *part != ".");
+    let name = components.next()?;
+    match components.next() {
+        None if !name.starts_with('.') => Some(name),
+        _ => None,
+    }
+}
+
+/// Whether `path` is the directory itself, like `/`, `.` or an empty path.
+pub fn is_root(path: &str) -> bool {
+    path.split('/').all(|part| part.is_empty() || part == ".")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn a_name_in_the_directory_is_found_however_it_is_written() {
+        assert_eq!(file_name("resume.pdf"), Some("resume.pdf"));
+        assert_eq!(file_name("/resume.pdf"), Some("resume.pdf"));
+        assert_eq!(file_name("./resume.pdf"), Some("resume.pdf"));
+        assert_eq!(file_name(".//resume.pdf/"), Some("resume.pdf"));
+    }
+
+    #[test]
+    fn nothing_outside_the_directory_has_a_name() {
+        assert_eq!(file_name(".."), None);
+        assert_eq!(file_name("../etc/passwd"), None);
+        assert_eq!(file_name("a/b"), None);
+        assert_eq!(file_name("a/../b"), None);
+        assert_eq!(file_name(".ssh"), None);
+        assert_eq!(file_name("./.env"), None);
+    }
+
+    #[test]
+    fn the_directory_itself_has_no_name() {
+        for path in ["", "/", ".", "./", "//"] {
+            assert_eq!(file_name(path), None);
+            assert!(is_root(path));
+        }
+        assert!(!is_root("resume.pdf"));
+    }
+}
@@ -0,0 +1,210 @@
+use std::collections::HashMap;
+use std::net::IpAddr;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use tracing::info;
+
+use crate::config::SharedConfig;
+
+#[derive(Debug, PartialEq)]
+pub enum Verdict {
+    Allow,
+    RateLimited,
+    Banned,
+    Denied,
+}
+
+struct PeerRecord {
+    window_start: Instant,
+    connections: u32,
+    rejections: u32,
+    banned_until: Option<Instant>,
+}
+
+/// Counts new connections per IP in fixed windows. Limits are read from the shared
+/// config on every check so a reload applies to the next connection.
+pub struct RateLimiter {
+    config: SharedConfig,
+    peers: Mutex<HashMap<IpAddr, PeerRecord>>,
+}
+
+impl RateLimiter {
+    pub fn new(config: SharedConfig) -> Self {
+        Self {
+            config,
+            peers: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub fn check(&self, ip: IpAddr) -> Verdict {
+        // Dual-stack listeners report IPv4 peers as ::ffff:a.b.c.d
+        let ip = ip.to_canonical();
+        let config = self.config.read().unwrap();
+        if config.deny.iter().any(|net| net.contains(&ip)) {
+            return Verdict::Denied;
+        }
+        if config.rate_limit_connections == 0 || config.allow.iter().any(|net| net.contains(&ip)) {
+            return Verdict::Allow;
+        }
+
+        let now = Instant::now();
+        let window = Duration::from_secs(config.rate_limit_window_secs);
+        let mut peers = self.peers.lock().unwrap();
+        let peer = peers.entry(ip).or_insert(PeerRecord {
+            window_start: now,
+            connections: 0,
+            rejections: 0,
+            banned_until: None,
+        });
+
+        if let Some(banned_until) = peer.banned_until {
+            if now < banned_until {
+                return Verdict::Banned;
+            }
+            peer.banned_until = None;
+            peer.rejections = 0;
+        }
+        if now.duration_since(peer.window_start) >= window {
+            peer.window_start = now;
+            peer.connections = 0;
+        }
+
+        peer.connections += 1;
+        if peer.connections <= config.rate_limit_connections {
+            return Verdict::Allow;
+        }
+
+        peer.rejections += 1;
+        if config.ban_after_rejections > 0 && peer.rejections >= config.ban_after_rejections {
+            peer.banned_until = Some(now + Duration::from_secs(config.ban_secs));
+            info!(
+                %ip,
+                "Banned for {}s after {} rejected connections",
+                config.ban_secs,
+                peer.rejections
+            );
+        }
+        Verdict::RateLimited
+    }
+
+    /// Forgets peers whose window has passed and who aren't banned.
+    pub fn prune(&self) {
+        let window = Duration::from_secs(self.config.read().unwrap().rate_limit_window_secs);
+        let now = Instant::now();
+        self.peers.lock().unwrap().retain(|_, peer| {
+            peer.banned_until.is_some_and(|until| now < until)
+                || now.duration_since(peer.window_start) < window
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, RwLock};
+
+    use super::*;
+    use crate::config::Config;
+
+    fn limiter(configure: impl FnOnce(&mut Config)) -> RateLimiter {
+        let mut config = Config::default();
+        config.rate_limit_connections = 2;
+        config.rate_limit_window_secs = 60;
+        config.ban_after_rejections = 2;
+        config.ban_secs = 600;
+        configure(&mut config);
+        RateLimiter::new(Arc::new(RwLock::new(config)))
+    }
+
+    fn ip(ip: &str) -> IpAddr {
+        ip.parse().unwrap()
+    }
+
+    #[test]
+    fn allowed_networks_are_never_limited() {
+        let limiter = limiter(|config| config.allow = vec!["10.0.0.0/8".parse().unwrap()]);
+        for _ in 0..10 {
+            assert_eq!(limiter.check(ip("10.1.2.3")), Verdict::Allow);
+        }
+        assert_eq!(limiter.check(ip("192.0.2.1")), Verdict::Allow);
+        assert_eq!(limiter.check(ip("192.0.2.1")), Verdict::Allow);
+        assert_eq!(limiter.check(ip("192.0.2.1")), Verdict::RateLimited);
+    }
+
+    #[test]
+    fn deny_wins_over_allow() {
+        let limiter = limiter(|config| {
+            config.allow = vec!["10.0.0.0/8".parse().unwrap()];
+            config.deny = vec!["10.0.0.0/24".parse().unwrap()];
+        });
+        assert_eq!(limiter.check(ip("10.0.0.1")), Verdict::Denied);
+        assert_eq!(limiter.check(ip("10.0.1.1")), Verdict::Allow);
+    }
+
+    #[test]
+    fn repeated_rejections_end_in_a_ban_that_expires() {
+        let limiter = limiter(|config| {
+            config.rate_limit_connections = 1;
+            config.ban_secs = 1;
+        });
+        let peer = ip("192.0.2.1");
+        assert_eq!(limiter.check(peer), Verdict::Allow);
+        assert_eq!(limiter.check(peer), Verdict::RateLimited);
+        assert_eq!(limiter.check(peer), Verdict::RateLimited);
+        assert_eq!(limiter.check(peer), Verdict::Banned);
+        // Others aren't affected
+        assert_eq!(limiter.check(ip("192.0.2.2")), Verdict::Allow);
+
+        std::thread::sleep(Duration::from_millis(1100));
+        // Still over the limit in this window, but no longer banned
+        assert_eq!(limiter.check(peer), Verdict::RateLimited);
+    }
+
+    #[test]
+    fn no_ban_when_bans_are_off() {
+        let limiter = limiter(|config| {
+            config.rate_limit_connections = 1;
+            config.ban_after_rejections = 0;
+        });
+        let peer = ip("192.0.2.1");
+        assert_eq!(limiter.check(peer), Verdict::Allow);
+        for _ in 0..10 {
+            assert_eq!(limiter.check(peer), Verdict::RateLimited);
+        }
+    }
+
+    #[test]
+    fn ipv4_mapped_addresses_count_as_ipv4() {
+        let limiter = limiter(|config| config.deny = vec!["203.0.113.0/24".parse().unwrap()]);
+        assert_eq!(limiter.check(ip("::ffff:203.0.113.7")), Verdict::Denied);
+
+        // One peer, however the listener spells it
+        assert_eq!(limiter.check(ip("192.0.2.1")), Verdict::Allow);
+        assert_eq!(limiter.check(ip("::ffff:192.0.2.1")), Verdict::Allow);
+        assert_eq!(limiter.check(ip("192.0.2.1")), Verdict::RateLimited);
+    }
+
+    #[test]
+    fn a_zero_prefix_covers_the_whole_family() {
+        let limiter = limiter(|config| config.deny = vec!["0.0.0.0/0".parse().unwrap()]);
+        assert_eq!(limiter.check(ip("192.0.2.1")), Verdict::Denied);
+        assert_eq!(limiter.check(ip("255.255.255.255")), Verdict::Denied);
+        assert_eq!(limiter.check(ip("2001:db8::1")), Verdict::Allow);
+    }
+
+    #[test]
+    fn a_full_prefix_covers_one_address() {
+        let limiter = limiter(|config| config.deny = vec!["203.0.113.7/32".parse().unwrap()]);
+        assert_eq!(limiter.check(ip("203.0.113.7")), Verdict::Denied);
+        assert_eq!(limiter.check(ip("203.0.113.8")), Verdict::Allow);
+        assert_eq!(limiter.check(ip("203.0.113.6")), Verdict::Allow);
+    }
+
+    #[test]
+    fn no_limit_when_rate_limiting_is_off() {
+        let limiter = limiter(|config| config.rate_limit_connections = 0);
+        for _ in 0..10 {
+            assert_eq!(limiter.check(ip("192.0.2.1")), Verdict::Allow);
+        }
+    }
+}
@@ -0,0 +1,193 @@
+use std::fs::{self, File};
+use std::io::{BufWriter, Write};
+use std::path::Path;
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError, Sender};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use serde_json::json;
+use tracing::warn;
+
+use crate::server::session_registry::SessionId;
+
+// How long written events may sit in the buffer while the session is quiet
+const FLUSH_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Writes what a session was sent to an asciinema v2 `.cast` file, so it can be
+/// replayed with `asciinema play`. The header needs the terminal size, so nothing
+/// is written until the first resize. Recording stops once the file reaches
+/// `max_bytes`, unless that is 0. The file is written on a thread of its own, so
+/// a slow disk never holds up the render loop.
+pub struct Recorder {
+    lines: Sender<String>,
+    title: String,
+    started: Instant,
+    header_written: bool,
+    written: u64,
+    max_bytes: u64,
+    stopped: bool,
+}
+
+impl Recorder {
+    /// Starts a file in `dir` and deletes the oldest recordings beyond `keep`, if
+    /// it isn't 0.
+    pub fn create(
+        dir: &Path,
+        id: SessionId,
+        title: String,
+        keep: usize,
+        max_bytes: u64,
+    ) -> Result<Self, anyhow::Error> {
+        fs::create_dir_all(dir)?;
+        if keep > 0 {
+            prune(dir, keep - 1)?;
+        }
+
+        let millis = SystemTime::now().duration_since(UNIX_EPOCH)?.as_millis();
+        let path = dir.join(format!("{}-{}.cast", millis, id));
+        let file = File::create(&path)
+            .map_err(|e| anyhow::anyhow!("Failed to create {}: {}", path.display(), e))?;
+        let (lines, received) = mpsc::channel();
+        std::thread::Builder::new()
+            .name(format!("recording-{}", id))
+            .spawn(move || write_lines(&path, BufWriter::new(file), received))?;
+        Ok(Self {
+            lines,
+            title,
+            started: Instant::now(),
+            header_written: false,
+            written: 0,
+            max_bytes,
+            stopped: false,
+        })
+    }
+
+    /// Records a frame, once it has been sent.
+    pub fn output(&mut self, data: &[u8]) {
+        if self.header_written {
+            let event = json!([self.elapsed(), "o", String::from_utf8_lossy(data)]);
+            self.write_line(&event.to_string());
+        }
+    }
+
+    pub fn resize(&mut self, width: u16, height: u16) {
+        if self.header_written {
+            let event = json!([self.elapsed(), "r", format!("{}x{}", width, height)]);
+            self.write_line(&event.to_string());
+            return;
+        }
+
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|since| since.as_secs())
+            .unwrap_or_default();
+        let header = json!({
+            "version": 2,
+            "width": width,
+            "height": height,
+            "timestamp": timestamp,
+            "title": self.title,
+        });
+        self.header_written = true;
+        self.write_line(&header.to_string());
+    }
+
+    fn elapsed(&self) -> f64 {
+        // Millisecond precision keeps the events short
+        (self.started.elapsed().as_secs_f64() * 1000.0).round() / 1000.0
+    }
+
+    fn write_line(&mut self, line: &str) {
+        if self.stopped {
+            return;
+        }
+        // The writer is gone once a write failed
+        if self.lines.send(line.to_string()).is_err() {
+            self.stopped = true;
+        }
+        self.written += line.len() as u64 + 1;
+        if self.max_bytes > 0 && self.written >= self.max_bytes {
+            self.stopped = true;
+        }
+    }
+}
+
+/// Writes what the recorder sends until it's dropped, flushing whenever no more
+/// comes for a while and once at the end.
+fn write_lines(path: &Path, mut file: BufWriter<File>, lines: Receiver<String>) {
+    loop {
+        let result = match lines.recv_timeout(FLUSH_INTERVAL) {
+            Ok(line) => writeln!(file, "{}", line),
+            Err(RecvTimeoutError::Timeout) => file.flush(),
+            Err(RecvTimeoutError::Disconnected) => break,
+        };
+        if let Err(e) = result {
+            warn!(
+                "Failed to write {}, stopping the recording: {}",
+                path.display(),
+                e
+            );
+            return;
+        }
+    }
+    if let Err(e) = file.flush() {
+        warn!("Failed to write the end of {}: {}", path.display(), e);
+    }
+}
+
+/// Leaves the `keep` newest recordings. Their names start with the time they were
+/// started, so sorting by name sorts them by age.
+fn prune(dir: &Path, keep: usize) -> Result<(), anyhow::Error> {
+    let mut recordings = fs::read_dir(dir)?
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| {
+            path.extension()
+                .is_some_and(|extension| extension == "cast")
+        })
+        .collect::<Vec<_>>();
+    if recordings.len() <= keep {
+        return Ok(());
+    }
+    recordings.sort();
+    for path in &recordings[..recordings.len() - keep] {
+        if let Err(e) = fs::remove_file(path) {
+            warn!("Failed to delete old recording {}: {}", path.display(), e);
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn events_are_written_once_the_recorder_is_dropped() {
+        let dir =
+            std::env::temp_dir().join(format!("portfolio-v2-recording-{}", std::process::id()));
+        let mut recorder = Recorder::create(&dir, 7, String::from("test"), 0, 0).unwrap();
+        // Nothing before the header, which needs the size
+        recorder.output(b"lost");
+        recorder.resize(80, 24);
+        recorder.output(b"hello");
+        drop(recorder);
+
+        let lines = (0..100)
+            .find_map(|_| {
+                let path = fs::read_dir(&dir).ok()?.next()?.ok()?.path();
+                let lines = fs::read_to_string(path).ok()?;
+                if lines.lines().count() < 2 {
+                    std::thread::sleep(Duration::from_millis(20));
+                    return None;
+                }
+                Some(lines)
+            })
+            .expect("the recording is written");
+        let _ = fs::remove_dir_all(&dir);
+        let mut lines = lines.lines();
+        let header: serde_json::Value = serde_json::from_str(lines.next().unwrap()).unwrap();
+        assert_eq!(header["width"], 80);
+        let event: serde_json::Value = serde_json::from_str(lines.next().unwrap()).unwrap();
+        assert_eq!(event[2], "hello");
+        assert!(lines.next().is_none());
+    }
+}
@@ -0,0 +1,61 @@
+use std::collections::HashMap;
+
+use crate::color_support::ColorSupport;
+use crate::server::SessionInfo;
+use crate::server::tui_app::{AppFactory, TuiApp};
+
+/// Serves a different app depending on the username the visitor logged in
+/// with, so `ssh chat@host` and `ssh host` can be two apps on one listener.
+/// Usernames without a route get the fallback.
+///
+/// Commands and finger queries go to the fallback.
+///
+/// ```no_run
+/// # async fn serve(
+/// #     chat: impl portfolio_v2::AppFactory,
+/// #     site: impl portfolio_v2::AppFactory,
+/// # ) -> Result<(), anyhow::Error> {
+/// use portfolio_v2::{SshTuiServer, UserRouter};
+///
+/// let router = UserRouter::new(site).route("chat", chat);
+/// let mut server = SshTuiServer::builder().app_factory(router).build()?;
+/// server.run().await
+/// # }
+/// ```
+pub struct UserRouter {
+    routes: HashMap<String, Box<dyn AppFactory>>,
+    fallback: Box<dyn AppFactory>,
+}
+
+impl UserRouter {
+    pub fn new(fallback: impl AppFactory) -> Self {
+        Self {
+            routes: HashMap::new(),
+            fallback: Box::new(fallback),
+        }
+    }
+
+    /// Usernames are matched ignoring case, like the pages `App` opens for them.
+    pub fn route(mut self, user: &str, app_factory: impl AppFactory) -> Self {
+        self.routes
+            .insert(user.to_lowercase(), Box::new(app_factory));
+        self
+    }
+}
+
+impl AppFactory for UserRouter {
+    fn make(&self, session: SessionInfo) -> Box<dyn TuiApp> {
+        match self.routes.get(&session.user.to_lowercase()) {
+            Some(app_factory) => app_factory.make(session),
+            None => self.fallback.make(session),
+        }
+    }
+
+    fn exec(&self, command: &str, colors: Option<ColorSupport>) -> Result<String, String> {
+        self.fallback.exec(command, colors)
+    }
+
+    fn finger(&self, query: &str) -> String {
+        self.fallback.finger(query)
+    }
+}
@@ -0,0 +1,205 @@
+use std::fs;
+use std::time::UNIX_EPOCH;
+
+use russh::server::Msg;
+use russh::{Channel, ChannelWriteHalf};
+use tokio::io::{AsyncRead, AsyncReadExt};
+use tracing::{Span, info, warn};
+
+use crate::server::public_files::{self, PublicFiles};
+
+/// What `scp -O host:file .` runs on the server. Newer scp clients default to
+/// SFTP instead, which `SftpSession` handles.
+pub struct ScpRequest {
+    paths: Vec<String>,
+    // `-p`, send modification times too
+    preserve_times: bool,
+}
+
+impl ScpRequest {
+    /// None if `command` isn't scp at all, and an error for what scp asks for
+    /// that isn't offered, like uploads.
+    pub fn parse(command: &str) -> Option<Result<Self, String>> {
+        let mut words = shell_words(command).into_iter();
+        if words.next()? != "scp" {
+            return None;
+        }
+
+        let mut request = Self {
+            paths: Vec::new(),
+            preserve_times: false,
+        };
+        let mut source = false;
+        let mut options = true;
+        for word in words {
+            match word.strip_prefix('-') {
+                Some("-") if options => options = false,
+                Some(flags) if options => {
+                    for flag in flags.chars() {
+                        match flag {
+                            'f' => source = true,
+                            't' => return Some(Err(String::from("uploads aren't accepted"))),
+                            'p' => request.preserve_times = true,
+                            // Verbose, quiet, a directory target and recursion
+                            // don't change anything for a flat directory
+                            _ => {}
+                        }
+                    }
+                }
+                _ => request.paths.push(word),
+            }
+        }
+
+        match source && !request.paths.is_empty() {
+            true => Some(Ok(request)),
+            false => Some(Err(String::from("expected scp -f <file>"))),
+        }
+    }
+}
+
+/// Sends the requested files on `channel`, then its exit status: 1 if any of
+/// them couldn't be sent.
+pub async fn serve(channel: Channel<Msg>, files: PublicFiles, request: ScpRequest, span: Span) {
+    let (mut reader, writer) = channel.split();
+    // One reader for the whole transfer, so nothing it buffered is lost
+    let mut reader = reader.make_reader();
+    let status = match send_files(&mut reader, &writer, &files, &request, &span).await {
+        Ok(true) => 0,
+        Ok(false) => 1,
+        Err(e) => {
+            warn!(parent: &span, "SCP transfer failed: {}", e);
+            1
+        }
+    };
+    let _ = writer.exit_status(status).await;
+    let _ = writer.eof().await;
+    let _ = writer.close().await;
+}
+
+/// Whether every file was sent. A missing file is reported to the client, which
+/// carries on with the next one.
+async fn send_files(
+    reader: &mut (impl AsyncRead + Unpin),
+    writer: &ChannelWriteHalf<Msg>,
+    files: &PublicFiles,
+    request: &ScpRequest,
+    span: &Span,
+) -> Result<bool, anyhow::Error> {
+    let mut all_sent = true;
+    wait_for_ack(reader).await?;
+    for path in &request.paths {
+        // The files are small, so reading them whole doesn't hold up the runtime
+        let Some((contents, modified)) = files.find(path).and_then(|(path, metadata)| {
+            let modified = metadata.modified().ok()?.duration_since(UNIX_EPOCH).ok()?;
+            Some((fs::read(path).ok()?, modified.as_secs()))
+        }) else {
+            let message = format!("\x01scp: {}: No such file or directory\n", path);
+            writer.data(message.as_bytes()).await?;
+            all_sent = false;
+            continue;
+        };
+
+        if request.preserve_times {
+            let times = format!("T{} 0 {} 0\n", modified, modified);
+            writer.data(times.as_bytes()).await?;
+            wait_for_ack(reader).await?;
+        }
+        let name = public_files::file_name(path).unwrap_or(path);
+        let header = format!("C0444 {} {}\n", contents.len(), name);
+        writer.data(header.as_bytes()).await?;
+        wait_for_ack(reader).await?;
+
+        info!(parent: span, file = %name, "Sending file over SCP");
+        writer.data(&contents[..]).await?;
+        writer.data(&[0][..]).await?;
+        wait_for_ack(reader).await?;
+    }
+    Ok(all_sent)
+}
+
+/// The client answers every line with a zero byte, or an error message.
+async fn wait_for_ack(reader: &mut (impl AsyncRead + Unpin)) -> Result<(), anyhow::Error> {
+    match reader.read_u8().await? {
+        0 => Ok(()),
+        _ => {
+            let mut message = Vec::new();
+            while let Ok(byte) = reader.read_u8().await
+                && byte != b'\n'
+            {
+                message.push(byte);
+            }
+            Err(anyhow::anyhow!(
+                "The client refused: {}",
+                String::from_utf8_lossy(&message)
+            ))
+        }
+    }
+}
+
+/// Splits `command` the way the shell would have: paths with spaces or other
+/// shell characters arrive in single quotes, with `'\''` for a quote of their own.
+fn shell_words(command: &str) -> Vec<String> {
+    let mut words = Vec::new();
+    let mut word: Option<String> = None;
+    let mut quoted = false;
+    let mut chars = command.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\'' => {
+                quoted = !quoted;
+                word.get_or_insert_default();
+            }
+            '\\' if !quoted => word.get_or_insert_default().extend(chars.next()),
+            c if c.is_whitespace() && !quoted => words.extend(word.take()),
+            c => word.get_or_insert_default().push(c),
+        }
+    }
+    words.extend(word);
+    words
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(command: &str) -> Result<ScpRequest, String> {
+        ScpRequest::parse(command).expect("is an scp command")
+    }
+
+    #[test]
+    fn other_commands_are_not_scp() {
+        assert!(ScpRequest::parse("ls").is_none());
+        assert!(ScpRequest::parse("").is_none());
+        assert!(ScpRequest::parse("scpx -f resume.pdf").is_none());
+    }
+
+    #[test]
+    fn downloads_are_parsed() {
+        let request = parse("scp -f resume.pdf").unwrap();
+        assert_eq!(request.paths, ["resume.pdf"]);
+        assert!(!request.preserve_times);
+
+        let request = parse("scp -v -pf -- 'my key.asc' -x").unwrap();
+        assert_eq!(request.paths, ["my key.asc", "-x"]);
+        assert!(request.preserve_times);
+
+        let request = parse("scp -f 'it'\\''s.txt' ''").unwrap();
+        assert_eq!(request.paths, ["it's.txt", ""]);
+    }
+
+    #[test]
+    fn uploads_and_missing_files_are_refused() {
+        assert_eq!(
+            parse("scp -t .").err().as_deref(),
+            Some("uploads aren't accepted")
+        );
+        assert_eq!(
+            parse("scp -f").err().as_deref(),
+            Some("expected scp -f <file>")
+        );
+        assert_eq!(
+            parse("scp resume.pdf").err().as_deref(),
+            Some("expected scp -f <file>")
+        );
+    }
+}
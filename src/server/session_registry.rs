@@ -0,0 +1,759 @@
+use std::io::Write;
+use std::net::SocketAddr;
+use std::ops::Deref;
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering};
+use std::time::{Duration, Instant};
+
+use crossterm::event::Event;
+use dashmap::DashMap;
+use ratatui::Terminal;
+use ratatui::backend::CrosstermBackend;
+use ratatui::layout::Rect;
+use russh::ChannelId;
+use russh::server::{Handle, Session};
+use tokio::sync::{Mutex, MutexGuard, Notify, mpsc};
+use tokio::task::JoinHandle;
+use tracing::{Instrument, Span, info, warn};
+
+use crate::charset::Charset;
+use crate::color_support::ColorSupport;
+use crate::config::{ByteCapAction, SharedConfig};
+use crate::error::RenderError;
+use crate::graphics::{DEVICE_ATTRIBUTES_QUERY, Graphics, Images};
+use crate::hyperlinks::Hyperlinks;
+use crate::logging::panic_message;
+use crate::server::TerminalHandle;
+use crate::server::tui_app::{FrameRate, TuiApp};
+
+// Undoes the input modes, scroll region and hidden cursor the app sets up, and
+// clears what it drew
+const RESET_SEQUENCE: &[u8] =
+    b"\x1b[?2004l\x1b[?1006l\x1b[?1002l\x1b[?1000l\x1b[0m\x1b[2J\x1b[H\x1b[r\x1b[?25h";
+// How long a client has to stop sending new sizes before the session resizes,
+// and how long it waits at most while they keep coming
+const RESIZE_SETTLE_TIME: Duration = Duration::from_millis(50);
+const RESIZE_MAX_DELAY: Duration = Duration::from_millis(250);
+pub const SESSION_FAILED_MESSAGE: &[u8] = b"something broke on this page, sorry!\r\n";
+pub const GOODBYE_MESSAGE: &[u8] = b"server is restarting, see you soon!\r\n";
+// How often a session's terminal is asked for its attributes, to time the reply
+const PROBE_INTERVAL: Duration = Duration::from_secs(10);
+const BYTE_CAP_MESSAGE: &[u8] = b"this session has used up its bandwidth, come back later\r\n";
+// How long before the time limit visitors are warned
+const SESSION_END_WARNING: Duration = Duration::from_secs(60);
+const TIME_LIMIT_MESSAGE: &[u8] = b"this session has reached its time limit, come back anytime\r\n";
+
+pub type SshTerminal = Terminal<CrosstermBackend<TerminalHandle>>;
+pub type SessionId = usize;
+
+pub struct SessionState {
+    pub terminal: SshTerminal,
+    pub app: Box<dyn TuiApp>,
+    pub color_support: ColorSupport,
+    pub charset: Charset,
+    pub hyperlinks: Hyperlinks,
+    pub images: Images,
+    // How long the last frame took, taken by the render loop
+    draw_time: Option<Duration>,
+}
+
+impl SessionState {
+    fn render(
+        &mut self,
+        tick: u64,
+        disconnect_countdown: Option<u64>,
+        session_ending: Option<u64>,
+        visitors_online: usize,
+    ) -> Result<FrameRate, RenderError> {
+        self.app.set_disconnect_countdown(disconnect_countdown);
+        self.app.set_session_ending(session_ending);
+        self.app.set_visitors_online(visitors_online);
+        let animating = self.app.handle_tick(tick);
+        let dirty = self.app.take_dirty();
+
+        let frames_dropped = self
+            .terminal
+            .backend_mut()
+            .writer_mut()
+            .take_frames_dropped();
+        if frames_dropped {
+            self.terminal.clear()?;
+            self.hyperlinks.forget();
+            self.images.forget();
+        }
+        if animating || dirty || frames_dropped {
+            let started = Instant::now();
+            let app = &mut self.app;
+            let color_support = self.color_support;
+            let charset = self.charset;
+            let frame = self.terminal.draw(|f| {
+                app.draw(f);
+                color_support.degrade(f.buffer_mut());
+                charset.degrade(f.buffer_mut());
+            })?;
+            let links = self.hyperlinks.frame(frame.buffer)?;
+            let images = self.images.frame(app.images())?;
+            let writer = self.terminal.backend_mut().writer_mut();
+            writer.write_all(&links)?;
+            writer.write_all(&images)?;
+            writer.set_classic_colors(color_support == ColorSupport::Ansi16);
+            // Only fails once the channel's writer is gone, and that removes the
+            // session itself
+            let _ = writer.send_frame();
+            self.draw_time = Some(started.elapsed());
+        }
+
+        Ok(self.app.frame_rate())
+    }
+
+    /// Asks the terminal for its attributes, on its own so it isn't held back
+    /// until the next frame. See `ClientSession::status_reports`.
+    fn probe(&mut self) {
+        let writer = self.terminal.backend_mut().writer_mut();
+        let _ = writer.write_all(DEVICE_ATTRIBUTES_QUERY);
+        let _ = writer.send_query();
+    }
+
+    pub fn resize(&mut self, width: u16, height: u16) -> Result<(), RenderError> {
+        self.terminal.resize(Rect::new(0, 0, width, height))?;
+        self.hyperlinks.forget();
+        self.images.forget();
+        self.terminal
+            .backend_mut()
+            .writer_mut()
+            .resized(width, height);
+        self.app.handle_resize(width, height);
+        Ok(())
+    }
+}
+
+/// How slow a session is, smoothed over its last frames and probes.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Latency {
+    /// From drawing a frame to it being queued for the client.
+    pub draw: Option<Duration>,
+    /// From a `DEVICE_ATTRIBUTES_QUERY` to the terminal's answer, which takes in
+    /// the network, the client and anything still queued ahead of the query.
+    pub rtt: Option<Duration>,
+}
+
+impl Latency {
+    // Like TCP's smoothed round trip time, each sample counts for an eighth
+    fn smooth(average: Option<Duration>, sample: Duration) -> Option<Duration> {
+        Some(match average {
+            Some(average) => (average * 7 + sample) / 8,
+            None => sample,
+        })
+    }
+}
+
+#[derive(Default)]
+struct Probe {
+    sent_at: Option<Instant>,
+    answered: bool,
+}
+
+/// The newest size a client asked for, not applied yet.
+struct PendingResize {
+    width: u16,
+    height: u16,
+    first_requested: Instant,
+    last_requested: Instant,
+}
+
+impl PendingResize {
+    /// Once the client stopped sending new sizes, or has been at it for too long
+    /// to leave the old layout up.
+    fn due(&self) -> Instant {
+        (self.last_requested + RESIZE_SETTLE_TIME).min(self.first_requested + RESIZE_MAX_DELAY)
+    }
+}
+
+/// How a session reaches its visitor besides its frames: the terminal reset and
+/// goodbye once it ends, and hanging up.
+#[derive(Clone)]
+pub enum Link {
+    Ssh {
+        handle: Handle,
+        channel_id: ChannelId,
+    },
+    /// A connection of the server's own, like the web gateway's. Its task writes
+    /// the data that arrives here, and hangs up on `None` or once every sender
+    /// is gone.
+    Stream(mpsc::UnboundedSender<Option<Vec<u8>>>),
+}
+
+impl Link {
+    // Both fail quietly once the client is gone
+    async fn data(&self, data: Vec<u8>) {
+        match self {
+            Link::Ssh { handle, channel_id } => {
+                let _ = handle.data(*channel_id, data).await;
+            }
+            Link::Stream(sender) => {
+                let _ = sender.send(Some(data));
+            }
+        }
+    }
+
+    async fn close(&self) {
+        match self {
+            Link::Ssh { handle, channel_id } => {
+                let _ = handle.close(*channel_id).await;
+            }
+            Link::Stream(sender) => {
+                let _ = sender.send(None);
+            }
+        }
+    }
+}
+
+/// A live session. The terminal and app sit behind their own lock so input,
+/// resizes and rendering for one client never wait on another client.
+pub struct ClientSession {
+    pub link: Link,
+    pub peer_addr: Option<SocketAddr>,
+    pub connected_at: Instant,
+    // Given back when the session is removed, though closing it takes a while
+    slot: std::sync::Mutex<Option<Slot>>,
+    /// Whether the client is a terminal that answers `DEVICE_ATTRIBUTES_QUERY`,
+    /// so it can be probed for its round trip time. Not for SSH without a pty.
+    pub probe_latency: bool,
+    /// Carries the session id and peer address for everything logged about it.
+    pub span: Span,
+    state: Mutex<SessionState>,
+    // Shared with the terminal's writer, which counts up as frames go out
+    bytes_sent: Arc<AtomicU64>,
+    latency: std::sync::Mutex<Latency>,
+    probe: std::sync::Mutex<Probe>,
+    // Copied out of the app after every frame, so the admin view can list it
+    // without waiting for the session's lock
+    current_page: std::sync::Mutex<String>,
+    last_activity: std::sync::Mutex<Instant>,
+    pending_resize: std::sync::Mutex<Option<PendingResize>>,
+    render_task: std::sync::Mutex<Option<JoinHandle<()>>>,
+    wake: Notify,
+}
+
+impl ClientSession {
+    pub fn new(
+        terminal: SshTerminal,
+        app: Box<dyn TuiApp>,
+        link: Link,
+        peer_addr: Option<SocketAddr>,
+        span: Span,
+        slot: Slot,
+    ) -> Self {
+        Self {
+            link,
+            peer_addr,
+            span,
+            connected_at: Instant::now(),
+            slot: std::sync::Mutex::new(Some(slot)),
+            probe_latency: false,
+            latency: std::sync::Mutex::new(Latency::default()),
+            probe: std::sync::Mutex::new(Probe::default()),
+            bytes_sent: terminal.backend().writer().bytes_sent(),
+            current_page: std::sync::Mutex::new(app.current_page()),
+            state: Mutex::new(SessionState {
+                terminal,
+                app,
+                color_support: ColorSupport::TrueColor,
+                charset: Charset::Unicode,
+                hyperlinks: Hyperlinks::default(),
+                images: Images::default(),
+                draw_time: None,
+            }),
+            last_activity: std::sync::Mutex::new(Instant::now()),
+            pending_resize: std::sync::Mutex::new(None),
+            render_task: std::sync::Mutex::new(None),
+            wake: Notify::new(),
+        }
+    }
+
+    pub async fn state(&self) -> MutexGuard<'_, SessionState> {
+        self.state.lock().await
+    }
+
+    pub fn touch(&self) {
+        *self.last_activity.lock().unwrap() = Instant::now();
+    }
+
+    pub fn idle_for(&self) -> Duration {
+        self.last_activity.lock().unwrap().elapsed()
+    }
+
+    /// Bytes of frames sent so far, not counting the reset and goodbye.
+    pub fn bytes_sent(&self) -> u64 {
+        self.bytes_sent.load(Ordering::Relaxed)
+    }
+
+    pub fn latency(&self) -> Latency {
+        *self.latency.lock().unwrap()
+    }
+
+    /// Times the answer to the last probe, if one of the terminal's `reports` is
+    /// it. Only the first answer counts, in case an earlier query's comes late.
+    pub fn status_reports(&self, reports: &[Vec<u8>]) {
+        if !reports
+            .iter()
+            .any(|report| Graphics::is_device_attributes_reply(report))
+        {
+            return;
+        }
+        let mut probe = self.probe.lock().unwrap();
+        let Some(sent_at) = probe.sent_at.filter(|_| !probe.answered) else {
+            return;
+        };
+        probe.answered = true;
+        let mut latency = self.latency.lock().unwrap();
+        latency.rtt = Latency::smooth(latency.rtt, sent_at.elapsed());
+    }
+
+    fn drew(&self, draw_time: Duration) {
+        let mut latency = self.latency.lock().unwrap();
+        latency.draw = Latency::smooth(latency.draw, draw_time);
+    }
+
+    /// True when it's time for another probe, which is then counted as sent.
+    fn start_probe(&self) -> bool {
+        if !self.probe_latency {
+            return false;
+        }
+        let mut probe = self.probe.lock().unwrap();
+        if probe
+            .sent_at
+            .is_some_and(|sent_at| sent_at.elapsed() < PROBE_INTERVAL)
+        {
+            return false;
+        }
+        *probe = Probe {
+            sent_at: Some(Instant::now()),
+            answered: false,
+        };
+        true
+    }
+
+    pub fn current_page(&self) -> String {
+        self.current_page.lock().unwrap().clone()
+    }
+
+    /// Hands the visitor's input to the app. True once they asked to leave; a
+    /// panic comes back as its message, and should end the session like one in
+    /// the render loop does.
+    pub async fn handle_input(&self, events: Vec<Event>) -> Result<bool, String> {
+        if events.is_empty() {
+            return Ok(false);
+        }
+        self.touch();
+        let handled = {
+            let mut state = self.state().await;
+            panic::catch_unwind(AssertUnwindSafe(|| {
+                events.into_iter().any(|event| match event {
+                    Event::Key(key) => {
+                        state.app.handle_key(key);
+                        state.app.wants_close()
+                    }
+                    Event::Mouse(mouse) => {
+                        state.app.handle_mouse(mouse);
+                        false
+                    }
+                    Event::Paste(text) => {
+                        state.app.handle_paste(&text);
+                        false
+                    }
+                    _ => false,
+                })
+            }))
+        };
+        self.wake();
+        handled.map_err(|payload| panic_message(&*payload).to_string())
+    }
+
+    /// Shows the operator's message on this session only.
+    pub async fn show_wall(&self, message: String) {
+        self.state().await.app.show_wall(message);
+        self.wake();
+    }
+
+    /// Resizes on a later frame, once the client settles on a size: dragging a
+    /// window's corner sends dozens of sizes a second.
+    pub fn request_resize(&self, width: u16, height: u16) {
+        let now = Instant::now();
+        let mut pending = self.pending_resize.lock().unwrap();
+        let first_requested = pending
+            .as_ref()
+            .map_or(now, |pending| pending.first_requested);
+        *pending = Some(PendingResize {
+            width,
+            height,
+            first_requested,
+            last_requested: now,
+        });
+        drop(pending);
+        self.wake();
+    }
+
+    fn take_due_resize(&self) -> Option<(u16, u16)> {
+        let mut pending = self.pending_resize.lock().unwrap();
+        if pending.as_ref()?.due() > Instant::now() {
+            return None;
+        }
+        pending
+            .take()
+            .map(|pending| (pending.width, pending.height))
+    }
+
+    fn resize_due(&self) -> Option<Instant> {
+        self.pending_resize
+            .lock()
+            .unwrap()
+            .as_ref()
+            .map(PendingResize::due)
+    }
+
+    /// Renders the next frame without waiting out an idle frame interval, so input
+    /// on a quiet page still shows up right away.
+    pub fn wake(&self) {
+        self.wake.notify_one();
+    }
+
+    fn stop_rendering(&self) {
+        if let Some(render_task) = self.render_task.lock().unwrap().take() {
+            render_task.abort();
+        }
+    }
+}
+
+/// A session just taken out of the registry. The visitor's terminal is still in
+/// the app's modes, so it gets reset exactly once: by one of the methods below,
+/// or when the guard is dropped. Sending fails quietly once the client is gone.
+pub struct RemovedSession {
+    session: Arc<ClientSession>,
+    restored: bool,
+}
+
+impl RemovedSession {
+    /// Resets the terminal through the connection's own session, so it goes out
+    /// before anything the caller sends on the channel after it, like its close.
+    /// Only SSH sessions have one.
+    pub fn restore_on(mut self, session: &mut Session) -> Result<(), russh::Error> {
+        self.restored = true;
+        match &self.link {
+            Link::Ssh { channel_id, .. } => session.data(*channel_id, RESET_SEQUENCE),
+            Link::Stream(sender) => {
+                let _ = sender.send(Some(RESET_SEQUENCE.to_vec()));
+                Ok(())
+            }
+        }
+    }
+
+    /// Resets the terminal, prints `message` on the cleared screen and closes the
+    /// channel.
+    pub async fn close_with(mut self, message: &[u8]) {
+        self.restored = true;
+        let mut data = RESET_SEQUENCE.to_vec();
+        data.extend_from_slice(message);
+        self.link.data(data).await;
+        self.link.close().await;
+    }
+}
+
+impl Deref for RemovedSession {
+    type Target = ClientSession;
+
+    fn deref(&self) -> &ClientSession {
+        &self.session
+    }
+}
+
+impl Drop for RemovedSession {
+    fn drop(&mut self) {
+        if self.restored {
+            return;
+        }
+        // Dropped outside the runtime only while it shuts down, when there's no
+        // connection left to write to
+        let Ok(runtime) = tokio::runtime::Handle::try_current() else {
+            return;
+        };
+        let link = self.link.clone();
+        runtime.spawn(async move { link.data(RESET_SEQUENCE.to_vec()).await });
+    }
+}
+
+/// A place among the `max_clients` sessions, taken when a client is let in and
+/// given back once its session is removed, or it never gets one.
+pub struct Slot(Arc<AtomicUsize>);
+
+impl Drop for Slot {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::AcqRel);
+    }
+}
+
+/// State shared by every connection: the live sessions and the id allocator.
+#[derive(Clone)]
+pub struct SessionRegistry {
+    sessions: Arc<DashMap<SessionId, Arc<ClientSession>>>,
+    next_id: Arc<AtomicUsize>,
+    // Slots taken, which counts clients let in that have no session yet too
+    reserved: Arc<AtomicUsize>,
+    // Set by `remove_all`, after which sessions are closed as soon as they start
+    closing: Arc<AtomicBool>,
+    config: SharedConfig,
+}
+
+impl SessionRegistry {
+    pub fn new(config: SharedConfig) -> Self {
+        Self {
+            sessions: Arc::new(DashMap::new()),
+            next_id: Arc::new(AtomicUsize::new(0)),
+            reserved: Arc::new(AtomicUsize::new(0)),
+            closing: Arc::new(AtomicBool::new(false)),
+            config,
+        }
+    }
+
+    fn frame_interval(&self, frame_rate: FrameRate) -> Duration {
+        let config = self.config.read().unwrap();
+        match frame_rate {
+            FrameRate::Full => config.frame_interval(),
+            FrameRate::Half => config.frame_interval() * 2,
+            FrameRate::Idle => config.idle_frame_interval(),
+        }
+    }
+
+    /// What to do with a session that has been sent more than the cap, if any.
+    fn over_byte_cap(&self, session: &ClientSession) -> Option<ByteCapAction> {
+        let config = self.config.read().unwrap();
+        (config.session_byte_cap > 0 && session.bytes_sent() > config.session_byte_cap)
+            .then_some(config.byte_cap_action)
+    }
+
+    /// Whole seconds left before an idle session is closed, once inside the
+    /// warning period.
+    fn disconnect_countdown(&self, session: &ClientSession) -> Option<u64> {
+        let config = self.config.read().unwrap();
+        let idle_timeout = config.idle_timeout();
+        if idle_timeout.is_zero() {
+            return None;
+        }
+
+        let remaining = idle_timeout.saturating_sub(session.idle_for());
+        (remaining <= config.idle_warning()).then(|| remaining.as_secs_f32().ceil() as u64)
+    }
+
+    /// How long a session has left before its time limit, if there is one.
+    fn time_left(&self, session: &ClientSession) -> Option<Duration> {
+        let max_session = self.config.read().unwrap().max_session();
+        (!max_session.is_zero()).then(|| max_session.saturating_sub(session.connected_at.elapsed()))
+    }
+
+    pub fn next_id(&self) -> SessionId {
+        self.next_id.fetch_add(1, Ordering::Relaxed)
+    }
+
+    /// A slot for a new client, or None with the server full. Taken at once, so
+    /// clients let in together can't all count on the last one.
+    pub fn reserve(&self) -> Option<Slot> {
+        let max_clients = self.config.read().unwrap().max_clients;
+        self.reserved
+            .fetch_update(Ordering::AcqRel, Ordering::Acquire, |reserved| {
+                (reserved < max_clients).then_some(reserved + 1)
+            })
+            .ok()?;
+        Some(Slot(self.reserved.clone()))
+    }
+
+    pub fn len(&self) -> usize {
+        self.sessions.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.sessions.is_empty()
+    }
+
+    pub fn get(&self, id: SessionId) -> Option<Arc<ClientSession>> {
+        self.sessions.get(&id).map(|entry| entry.value().clone())
+    }
+
+    /// Registers the session and starts its render loop. The loop lives exactly as
+    /// long as the entry: removing the session aborts it.
+    pub fn insert(&self, id: SessionId, session: ClientSession) -> Arc<ClientSession> {
+        let session = Arc::new(session);
+        self.sessions.insert(id, session.clone());
+        // Let in before the server started shutting down, but too late for `remove_all`
+        if self.closing.load(Ordering::SeqCst) {
+            if let Some(session) = self.remove(id) {
+                tokio::spawn(session.close_with(GOODBYE_MESSAGE));
+            }
+            return session;
+        }
+
+        let render_task =
+            tokio::spawn(Self::render_loop(self.clone(), id).instrument(session.span.clone()));
+        *session.render_task.lock().unwrap() = Some(render_task);
+        session
+    }
+
+    /// Takes the session out and stops rendering it, before its terminal is reset,
+    /// so no frame is drawn over the reset.
+    pub fn remove(&self, id: SessionId) -> Option<RemovedSession> {
+        let (_, session) = self.sessions.remove(&id)?;
+        session.stop_rendering();
+        session.slot.lock().unwrap().take();
+        let latency = session.latency();
+        info!(
+            parent: &session.span,
+            duration = ?session.connected_at.elapsed(),
+            draw_ms = latency.draw.map(|draw| draw.as_secs_f64() * 1000.0),
+            rtt_ms = latency.rtt.map(|rtt| rtt.as_secs_f64() * 1000.0),
+            "Session closed"
+        );
+        Some(RemovedSession {
+            session,
+            restored: false,
+        })
+    }
+
+    /// Takes every session out, for shutting down. Sessions inserted after this
+    /// are closed with `GOODBYE_MESSAGE` right away.
+    pub fn remove_all(&self) -> Vec<RemovedSession> {
+        self.closing.store(true, Ordering::SeqCst);
+        let ids = self
+            .sessions
+            .iter()
+            .map(|entry| *entry.key())
+            .collect::<Vec<_>>();
+        ids.into_iter().filter_map(|id| self.remove(id)).collect()
+    }
+
+    /// Every live session, oldest first.
+    pub fn sessions(&self) -> Vec<(SessionId, Arc<ClientSession>)> {
+        let mut sessions = self
+            .sessions
+            .iter()
+            .map(|entry| (*entry.key(), entry.value().clone()))
+            .collect::<Vec<_>>();
+        sessions.sort_by_key(|(id, _)| *id);
+        sessions
+    }
+
+    pub fn idle_longer_than(&self, timeout: Duration) -> Vec<(SessionId, Arc<ClientSession>)> {
+        self.sessions
+            .iter()
+            .filter(|entry| entry.value().idle_for() > timeout)
+            .map(|entry| (*entry.key(), entry.value().clone()))
+            .collect()
+    }
+
+    async fn render_loop(registry: SessionRegistry, id: SessionId) {
+        let mut tick: u64 = 0;
+        let mut last_frame = Instant::now();
+        let mut throttled = false;
+        loop {
+            let Some(session) = registry.get(id) else {
+                break;
+            };
+
+            // Pages count time in full rate ticks, so a frame after an idle
+            // interval advances the tick by as many as it skipped
+            let full_interval = registry.frame_interval(FrameRate::Full);
+            let skipped = last_frame.elapsed().as_secs_f64() / full_interval.as_secs_f64();
+            tick = tick.wrapping_add((skipped.round() as u64).max(1));
+            last_frame = Instant::now();
+
+            let time_left = registry.time_left(&session);
+            if time_left == Some(Duration::ZERO) {
+                info!("Disconnecting session: at the time limit");
+                if let Some(session) = registry.remove(id) {
+                    tokio::spawn(session.close_with(TIME_LIMIT_MESSAGE));
+                }
+                break;
+            }
+            let session_ending = time_left
+                .filter(|left| *left <= SESSION_END_WARNING)
+                .map(|left| left.as_secs_f32().ceil() as u64);
+            let countdown = registry.disconnect_countdown(&session);
+            let resize = session.take_due_resize();
+            let rendered = {
+                let mut state = session.state().await;
+                // A page that panics takes down its own session only. The state is
+                // dropped along with the session, so nothing sees it half updated
+                let rendered = panic::catch_unwind(AssertUnwindSafe(|| {
+                    if let Some((width, height)) = resize {
+                        state.resize(width, height)?;
+                    }
+                    state.render(tick, countdown, session_ending, registry.len())
+                }))
+                .unwrap_or_else(|payload| {
+                    Err(RenderError::Panicked(panic_message(&*payload).to_string()))
+                });
+                if let Some(draw_time) = state.draw_time.take() {
+                    session.drew(draw_time);
+                }
+                if rendered.is_ok() && session.start_probe() {
+                    state.probe();
+                }
+                *session.current_page.lock().unwrap() = state.app.current_page();
+                rendered
+            };
+            let mut frame_rate = match rendered {
+                Ok(frame_rate) => frame_rate,
+                Err(e) => {
+                    warn!("Disconnecting session: {}", e);
+                    // Removing aborts this task, so the goodbye has to go out from
+                    // another one
+                    if let Some(session) = registry.remove(id) {
+                        tokio::spawn(session.close_with(SESSION_FAILED_MESSAGE));
+                    }
+                    break;
+                }
+            };
+
+            match registry.over_byte_cap(&session) {
+                Some(ByteCapAction::Disconnect) => {
+                    warn!(
+                        bytes_sent = session.bytes_sent(),
+                        "Disconnecting session: over the byte cap"
+                    );
+                    if let Some(session) = registry.remove(id) {
+                        tokio::spawn(session.close_with(BYTE_CAP_MESSAGE));
+                    }
+                    break;
+                }
+                Some(ByteCapAction::Throttle) => {
+                    if !throttled {
+                        warn!(
+                            bytes_sent = session.bytes_sent(),
+                            "Throttling session: over the byte cap"
+                        );
+                    }
+                    throttled = true;
+                    frame_rate = FrameRate::Idle;
+                }
+                // The cap went up or away on a reload
+                None => throttled = false,
+            }
+
+            let mut next_frame = last_frame + registry.frame_interval(frame_rate);
+            if let Some(resize_due) = session.resize_due() {
+                next_frame = next_frame.min(resize_due);
+            }
+            tokio::select! {
+                _ = tokio::time::sleep_until(next_frame.into()) => {}
+                _ = session.wake.notified() => {
+                    // Never draw faster than the full frame rate, however fast
+                    // input arrives, or than the idle one once throttled
+                    let interval = match throttled {
+                        true => registry.frame_interval(FrameRate::Idle),
+                        false => full_interval,
+                    };
+                    tokio::time::sleep_until((last_frame + interval).into()).await;
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,183 @@
+use std::collections::HashMap;
+use std::fs::{self, Metadata};
+use std::io::{Read, Seek, SeekFrom};
+
+use russh_sftp::protocol::{
+    Attrs, Data, File, FileAttributes, Handle, Name, OpenFlags, Status, StatusCode,
+};
+use tracing::{Span, info};
+
+use crate::server::public_files::{self, PublicFiles};
+
+// Larger reads are cut short, clients ask again for the rest
+const MAX_READ_BYTES: u32 = 64 * 1024;
+
+enum OpenHandle {
+    File(fs::File),
+    // Whether the listing was already sent
+    Dir(bool),
+}
+
+/// A read-only SFTP server for one channel, seeing `PublicFiles` as its only
+/// directory, `/`.
+pub struct SftpSession {
+    files: PublicFiles,
+    handles: HashMap<String, OpenHandle>,
+    next_handle: u64,
+    span: Span,
+}
+
+impl SftpSession {
+    pub fn new(files: PublicFiles, span: Span) -> Self {
+        Self {
+            files,
+            handles: HashMap::new(),
+            next_handle: 0,
+            span,
+        }
+    }
+
+    fn add_handle(&mut self, handle: OpenHandle) -> String {
+        let name = self.next_handle.to_string();
+        self.next_handle += 1;
+        self.handles.insert(name.clone(), handle);
+        name
+    }
+
+    fn attributes(&self, path: &str) -> Result<FileAttributes, StatusCode> {
+        if public_files::is_root(path) {
+            let metadata = self.files.dir_metadata().ok_or(StatusCode::NoSuchFile)?;
+            return Ok(read_only(&metadata));
+        }
+        let (_, metadata) = self.files.find(path).ok_or(StatusCode::NoSuchFile)?;
+        Ok(read_only(&metadata))
+    }
+}
+
+impl russh_sftp::server::Handler for SftpSession {
+    type Error = StatusCode;
+
+    fn unimplemented(&self) -> Self::Error {
+        // Everything that would change a file ends up here
+        StatusCode::PermissionDenied
+    }
+
+    async fn realpath(&mut self, id: u32, path: String) -> Result<Name, Self::Error> {
+        let path = match public_files::file_name(&path) {
+            Some(name) => format!("/{}", name),
+            None => String::from("/"),
+        };
+        Ok(Name {
+            id,
+            files: vec![File::dummy(path)],
+        })
+    }
+
+    async fn stat(&mut self, id: u32, path: String) -> Result<Attrs, Self::Error> {
+        let attrs = self.attributes(&path)?;
+        Ok(Attrs { id, attrs })
+    }
+
+    async fn lstat(&mut self, id: u32, path: String) -> Result<Attrs, Self::Error> {
+        self.stat(id, path).await
+    }
+
+    async fn fstat(&mut self, id: u32, handle: String) -> Result<Attrs, Self::Error> {
+        let attrs = match self.handles.get(&handle) {
+            Some(OpenHandle::File(file)) => {
+                let metadata = file.metadata().map_err(|_| StatusCode::Failure)?;
+                read_only(&metadata)
+            }
+            Some(OpenHandle::Dir(_)) => self.attributes("/")?,
+            None => return Err(StatusCode::Failure),
+        };
+        Ok(Attrs { id, attrs })
+    }
+
+    async fn opendir(&mut self, id: u32, path: String) -> Result<Handle, Self::Error> {
+        if !public_files::is_root(&path) {
+            return Err(StatusCode::NoSuchFile);
+        }
+        let handle = self.add_handle(OpenHandle::Dir(false));
+        Ok(Handle { id, handle })
+    }
+
+    async fn readdir(&mut self, id: u32, handle: String) -> Result<Name, Self::Error> {
+        let Some(OpenHandle::Dir(listed)) = self.handles.get_mut(&handle) else {
+            return Err(StatusCode::Failure);
+        };
+        if *listed {
+            return Err(StatusCode::Eof);
+        }
+        *listed = true;
+        let files = self
+            .files
+            .list()
+            .into_iter()
+            .map(|(name, metadata)| File::new(name, read_only(&metadata)))
+            .collect();
+        Ok(Name { id, files })
+    }
+
+    async fn open(
+        &mut self,
+        id: u32,
+        filename: String,
+        pflags: OpenFlags,
+        _: FileAttributes,
+    ) -> Result<Handle, Self::Error> {
+        if pflags.intersects(!OpenFlags::READ) {
+            return Err(StatusCode::PermissionDenied);
+        }
+        let (path, _) = self.files.find(&filename).ok_or(StatusCode::NoSuchFile)?;
+        let file = fs::File::open(&path).map_err(|_| StatusCode::Failure)?;
+
+        info!(parent: &self.span, file = %filename, "Sending file over SFTP");
+        let handle = self.add_handle(OpenHandle::File(file));
+        Ok(Handle { id, handle })
+    }
+
+    async fn read(
+        &mut self,
+        id: u32,
+        handle: String,
+        offset: u64,
+        len: u32,
+    ) -> Result<Data, Self::Error> {
+        let Some(OpenHandle::File(file)) = self.handles.get_mut(&handle) else {
+            return Err(StatusCode::Failure);
+        };
+        // The files are small, so these reads don't hold up the runtime for long
+        let mut data = Vec::new();
+        file.seek(SeekFrom::Start(offset))
+            .and_then(|_| {
+                file.take(len.min(MAX_READ_BYTES) as u64)
+                    .read_to_end(&mut data)
+            })
+            .map_err(|_| StatusCode::Failure)?;
+        if data.is_empty() {
+            return Err(StatusCode::Eof);
+        }
+        Ok(Data { id, data })
+    }
+
+    async fn close(&mut self, id: u32, handle: String) -> Result<Status, Self::Error> {
+        self.handles.remove(&handle).ok_or(StatusCode::Failure)?;
+        Ok(Status {
+            id,
+            status_code: StatusCode::Ok,
+            error_message: String::from("Ok"),
+            language_tag: String::from("en-US"),
+        })
+    }
+}
+
+/// Leaves out the server's user and group ids, and the write bits, since nothing
+/// can be written anyway.
+fn read_only(metadata: &Metadata) -> FileAttributes {
+    let mut attrs = FileAttributes::from(metadata);
+    attrs.uid = None;
+    attrs.gid = None;
+    attrs.permissions = attrs.permissions.map(|mode| mode & !0o222);
+    attrs
+}
@@ -0,0 +1,34 @@
+use std::sync::Arc;
+
+use tokio::sync::watch;
+
+/// Fired once the server starts draining, so the listeners beside the SSH one
+/// stop taking connections too. Clones share it.
+#[derive(Clone)]
+pub struct Shutdown {
+    fired: Arc<watch::Sender<bool>>,
+}
+
+impl Shutdown {
+    pub fn new() -> Self {
+        Self {
+            fired: Arc::new(watch::Sender::new(false)),
+        }
+    }
+
+    pub fn fire(&self) {
+        self.fired.send_replace(true);
+    }
+
+    /// Resolves once `fire` has been called, right away if it already was.
+    pub async fn fired(&self) {
+        let mut fired = self.fired.subscribe();
+        let _ = fired.wait_for(|fired| *fired).await;
+    }
+}
+
+impl Default for Shutdown {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,47 @@
+use std::net::TcpListener;
+use std::os::fd::FromRawFd;
+
+use sd_notify::NotifyState;
+use tracing::{debug, info};
+
+/// The listening socket systemd passed in, when the service is socket activated.
+/// Only the first one is used.
+pub fn inherited_listener() -> Result<Option<TcpListener>, anyhow::Error> {
+    let Some(fd) = sd_notify::listen_fds()?.next() else {
+        return Ok(None);
+    };
+    // SAFETY: systemd hands over the descriptors from LISTEN_FDS to this process
+    // only, and `listen_fds` makes sure they were meant for this pid
+    let listener = unsafe { TcpListener::from_raw_fd(fd) };
+    listener.set_nonblocking(true)?;
+    Ok(Some(listener))
+}
+
+/// Tells systemd startup is done, for `Type=notify` units, and starts the
+/// watchdog pings if the unit sets `WatchdogSec`. The pings come from a task on
+/// the runtime, so they stop when it hangs and systemd restarts the service.
+/// Does nothing when the server wasn't started by systemd.
+pub fn notify_ready() {
+    notify(&[NotifyState::Ready]);
+    let Some(timeout) = sd_notify::watchdog_enabled() else {
+        return;
+    };
+    info!(?timeout, "Pinging the systemd watchdog");
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(timeout / 2);
+        loop {
+            interval.tick().await;
+            notify(&[NotifyState::Watchdog]);
+        }
+    });
+}
+
+pub fn notify_stopping() {
+    notify(&[NotifyState::Stopping]);
+}
+
+fn notify(state: &[NotifyState]) {
+    if let Err(e) = sd_notify::notify(state) {
+        debug!("Failed to notify systemd: {}", e);
+    }
+}
@@ -0,0 +1,446 @@
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime};
+
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::mpsc;
+use tracing::{debug, info_span, warn};
+
+use crate::charset::Charset;
+use crate::color_support::ColorSupport;
+use crate::config::SharedConfig;
+use crate::graphics::Graphics;
+use crate::hyperlinks;
+use crate::server::AppFactory;
+use crate::server::admission::Admission;
+use crate::server::audit::{ByteCounts, CountingStream};
+use crate::server::hooks::ConnectionInfo;
+use crate::server::input::InputDecoder;
+use crate::server::launch::{
+    ENABLE_INPUT_MODES_SEQUENCE, Launch, OUTPUT_QUEUE_CAPACITY, SERVER_FULL_MESSAGE, handle_input,
+};
+use crate::server::session_registry::{Link, SESSION_FAILED_MESSAGE, SessionRegistry};
+use crate::server::shutdown::Shutdown;
+
+const IAC: u8 = 255;
+const DONT: u8 = 254;
+const DO: u8 = 253;
+const WONT: u8 = 252;
+const WILL: u8 = 251;
+const SB: u8 = 250;
+const SE: u8 = 240;
+const ECHO: u8 = 1;
+const SUPPRESS_GO_AHEAD: u8 = 3;
+const TERMINAL_TYPE: u8 = 24;
+const NAWS: u8 = 31;
+const TERMINAL_TYPE_IS: u8 = 0;
+const TERMINAL_TYPE_SEND: u8 = 1;
+
+// The server echoes (by drawing), keys arrive one at a time, and the client is
+// asked for its window size and terminal type
+const NEGOTIATION: &[u8] = &[
+    IAC,
+    WILL,
+    ECHO,
+    IAC,
+    WILL,
+    SUPPRESS_GO_AHEAD,
+    IAC,
+    DO,
+    NAWS,
+    IAC,
+    DO,
+    TERMINAL_TYPE,
+];
+const TERMINAL_TYPE_REQUEST: &[u8] = &[IAC, SB, TERMINAL_TYPE, TERMINAL_TYPE_SEND, IAC, SE];
+const WARNING: &[u8] = b"\r\n\x1b[1;31m  !! this connection is not encrypted !!\x1b[0m\r\n\r\n  \
+everything you type and see crosses the network in plain text. use\r\n  \
+`ssh krayon.dev` instead if you can.\r\n\r\n  press any key to continue anyway\r\n";
+// How long the warning waits for a key before hanging up
+const WARNING_TIMEOUT: Duration = Duration::from_secs(60);
+// Clients that never say get the classic size
+const DEFAULT_SIZE: (u16, u16) = (80, 24);
+// Longer than any window size or terminal name (RFC 1091 allows 40 characters).
+// Past it the rest is thrown away and so is the subnegotiation, rather than
+// kept for as long as the client goes without IAC SE
+const MAX_SUBNEGOTIATION: usize = 64;
+
+/// What the client told about itself between its keys.
+#[derive(Debug, PartialEq)]
+enum Negotiation {
+    Size(u16, u16),
+    /// The client is willing to name its terminal, once asked.
+    OffersTerminalType,
+    TerminalType(String),
+}
+
+#[derive(Clone, Copy)]
+enum State {
+    Data,
+    Iac,
+    Command(u8),
+    Subnegotiation,
+    SubnegotiationIac,
+}
+
+/// Takes the telnet protocol out of what the client sends, leaving the keys
+/// for `InputDecoder`. Enter arrives as CR LF or CR NUL and leaves as CR.
+struct TelnetDecoder {
+    state: State,
+    subnegotiation: Vec<u8>,
+    // The subnegotiation went past `MAX_SUBNEGOTIATION`
+    oversized: bool,
+    after_cr: bool,
+}
+
+impl TelnetDecoder {
+    fn new() -> Self {
+        Self {
+            state: State::Data,
+            subnegotiation: Vec::new(),
+            oversized: false,
+            after_cr: false,
+        }
+    }
+
+    fn feed(&mut self, input: &[u8]) -> (Vec<u8>, Vec<Negotiation>) {
+        let mut data = Vec::with_capacity(input.len());
+        let mut negotiations = Vec::new();
+        for &byte in input {
+            self.state = match (self.state, byte) {
+                (State::Data, IAC) => State::Iac,
+                (State::Data, b'\n' | 0) if self.after_cr => {
+                    self.after_cr = false;
+                    State::Data
+                }
+                (State::Data, byte) => {
+                    self.after_cr = byte == b'\r';
+                    data.push(byte);
+                    State::Data
+                }
+                // A doubled IAC is the byte itself
+                (State::Iac, IAC) => {
+                    data.push(IAC);
+                    State::Data
+                }
+                (State::Iac, WILL | WONT | DO | DONT) => State::Command(byte),
+                (State::Iac, SB) => {
+                    self.subnegotiation.clear();
+                    self.oversized = false;
+                    State::Subnegotiation
+                }
+                (State::Iac, _) => State::Data,
+                (State::Command(WILL), TERMINAL_TYPE) => {
+                    negotiations.push(Negotiation::OffersTerminalType);
+                    State::Data
+                }
+                (State::Command(_), _) => State::Data,
+                (State::Subnegotiation, IAC) => State::SubnegotiationIac,
+                (State::Subnegotiation, byte) => {
+                    self.push_subnegotiation(byte);
+                    State::Subnegotiation
+                }
+                (State::SubnegotiationIac, SE) => {
+                    negotiations.extend(self.finish_subnegotiation());
+                    State::Data
+                }
+                (State::SubnegotiationIac, byte) => {
+                    self.push_subnegotiation(byte);
+                    State::Subnegotiation
+                }
+            };
+        }
+        (data, negotiations)
+    }
+
+    fn push_subnegotiation(&mut self, byte: u8) {
+        match self.subnegotiation.len() < MAX_SUBNEGOTIATION {
+            true => self.subnegotiation.push(byte),
+            false => self.oversized = true,
+        }
+    }
+
+    fn finish_subnegotiation(&self) -> Option<Negotiation> {
+        if self.oversized {
+            return None;
+        }
+        match self.subnegotiation.as_slice() {
+            [NAWS, w1, w2, h1, h2] => Some(Negotiation::Size(
+                u16::from_be_bytes([*w1, *w2]),
+                u16::from_be_bytes([*h1, *h2]),
+            )),
+            [TERMINAL_TYPE, TERMINAL_TYPE_IS, name @ ..] => Some(Negotiation::TerminalType(
+                String::from_utf8_lossy(name).to_lowercase(),
+            )),
+            _ => None,
+        }
+    }
+}
+
+/// Doubles the bytes that telnet would otherwise read as commands.
+fn escape(data: &[u8]) -> Vec<u8> {
+    let mut escaped = Vec::with_capacity(data.len());
+    for &byte in data {
+        escaped.push(byte);
+        if byte == IAC {
+            escaped.push(IAC);
+        }
+    }
+    escaped
+}
+
+/// A plain telnet listener for retro clients that can't do SSH. Sessions get
+/// the same app and render loop, after a warning the visitor has to get past:
+/// nothing on this port is encrypted.
+pub struct TelnetListener<F> {
+    registry: SessionRegistry,
+    config: SharedConfig,
+    admission: Arc<Admission>,
+    app_factory: Arc<F>,
+}
+
+impl<F: AppFactory> TelnetListener<F> {
+    pub fn new(
+        registry: SessionRegistry,
+        config: SharedConfig,
+        admission: Arc<Admission>,
+        app_factory: Arc<F>,
+    ) -> Self {
+        Self {
+            registry,
+            config,
+            admission,
+            app_factory,
+        }
+    }
+
+    /// Accepts connections until `shutdown` fires.
+    pub async fn run(self, listener: TcpListener, shutdown: Shutdown) {
+        let telnet = Arc::new(self);
+        loop {
+            let accepted = tokio::select! {
+                accepted = listener.accept() => accepted,
+                _ = shutdown.fired() => break,
+            };
+            let (socket, peer_addr) = match accepted {
+                Ok(accepted) => accepted,
+                Err(e) => {
+                    warn!("Failed to accept telnet connection: {}", e);
+                    continue;
+                }
+            };
+            let _ = socket.set_nodelay(true);
+            tokio::spawn(telnet.clone().serve(socket, peer_addr));
+        }
+    }
+
+    /// Runs the session past the same checks SSH connections get, and records it
+    /// in the audit log like them.
+    async fn serve(self: Arc<Self>, socket: TcpStream, peer_addr: SocketAddr) {
+        let connected_at = SystemTime::now();
+        let connection = Arc::new(Mutex::new(ConnectionInfo::new(Some(peer_addr))));
+        let counts = Arc::new(ByteCounts::default());
+        let reason = match self.admission.refusal(peer_addr) {
+            Some(reason) => reason,
+            None => {
+                let socket = CountingStream::new(socket, counts.clone());
+                self.run_session(socket, peer_addr).await;
+                self.admission.hooks().disconnect(&connection);
+                "closed"
+            }
+        };
+        self.admission
+            .audit("telnet", connected_at, &connection, &counts, reason);
+    }
+
+    async fn run_session<S>(&self, mut socket: S, peer_addr: SocketAddr)
+    where
+        S: AsyncRead + AsyncWrite + Unpin,
+    {
+        let Some(slot) = self.registry.reserve() else {
+            let max_clients = self.config.read().unwrap().max_clients;
+            warn!(peer = %peer_addr, max_clients, "Server full, turning a telnet session away");
+            let _ = socket.write_all(SERVER_FULL_MESSAGE).await;
+            return;
+        };
+
+        let mut decoder = TelnetDecoder::new();
+        let mut size = DEFAULT_SIZE;
+        let mut term = String::new();
+        if socket.write_all(NEGOTIATION).await.is_err() || socket.write_all(WARNING).await.is_err()
+        {
+            return;
+        }
+        // The client's answers come in while the warning is up
+        let mut buf = [0u8; 4096];
+        let acknowledged = tokio::time::timeout(WARNING_TIMEOUT, async {
+            loop {
+                let read = match socket.read(&mut buf).await {
+                    Ok(0) | Err(_) => return false,
+                    Ok(read) => read,
+                };
+                let (data, negotiations) = decoder.feed(&buf[..read]);
+                for negotiation in negotiations {
+                    match negotiation {
+                        Negotiation::Size(width, height) => size = (width, height),
+                        Negotiation::OffersTerminalType => {
+                            let _ = socket.write_all(TERMINAL_TYPE_REQUEST).await;
+                        }
+                        Negotiation::TerminalType(name) => term = name,
+                    }
+                }
+                if !data.is_empty() {
+                    return true;
+                }
+            }
+        })
+        .await;
+        if acknowledged != Ok(true) {
+            return;
+        }
+
+        let id = self.registry.next_id();
+        let span = info_span!("session", id, peer = %peer_addr, via = "telnet");
+        let (output, mut frames) = mpsc::channel::<Vec<u8>>(OUTPUT_QUEUE_CAPACITY);
+        let (link, mut link_messages) = mpsc::unbounded_channel();
+        let launched = Launch {
+            registry: &self.registry,
+            config: &self.config,
+            app_factory: &*self.app_factory,
+            id,
+            slot,
+            via: "telnet",
+            span: span.clone(),
+            output,
+            link: Link::Stream(link),
+            peer_addr: Some(peer_addr),
+            fingerprint: None,
+            user: String::new(),
+            admin: false,
+            term: term.clone(),
+            color_support: ColorSupport::detect(&term, None),
+            charset: Charset::detect(&term, None),
+            hyperlinks: hyperlinks::detect(&term, None),
+            graphics: Graphics::detect(&term, None),
+            cell_size: None,
+            size,
+            probe_latency: true,
+        }
+        .start()
+        .await;
+        let client = match launched {
+            Ok(client) => client,
+            Err(e) => {
+                warn!(parent: &span, "Failed to start the app: {}", e);
+                let _ = socket.write_all(SESSION_FAILED_MESSAGE).await;
+                return;
+            }
+        };
+        if socket.write_all(ENABLE_INPUT_MODES_SEQUENCE).await.is_err() {
+            self.registry.remove(id);
+            return;
+        }
+
+        let (mut reader, mut writer) = tokio::io::split(socket);
+        let mut input = InputDecoder::new();
+        loop {
+            tokio::select! {
+                // Frames first, so a goodbye never overtakes the last of them
+                biased;
+                Some(frame) = frames.recv() => {
+                    if writer.write_all(&escape(&frame)).await.is_err() {
+                        debug!(parent: &span, "Telnet write failed");
+                        break;
+                    }
+                }
+                message = link_messages.recv() => match message {
+                    Some(Some(data)) => {
+                        let _ = writer.write_all(&escape(&data)).await;
+                    }
+                    _ => break,
+                },
+                read = reader.read(&mut buf) => {
+                    let read = match read {
+                        Ok(0) | Err(_) => break,
+                        Ok(read) => read,
+                    };
+                    let (data, negotiations) = decoder.feed(&buf[..read]);
+                    for negotiation in negotiations {
+                        if let Negotiation::Size(width, height) = negotiation {
+                            client.request_resize(width, height);
+                        }
+                    }
+                    let events = input.feed(&data);
+                    client.status_reports(&input.take_status_reports());
+                    handle_input(&self.registry, id, &client, events).await;
+                }
+            }
+        }
+        self.registry.remove(id);
+        let _ = writer.shutdown().await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn a_doubled_iac_is_the_byte_itself() {
+        let mut decoder = TelnetDecoder::new();
+        assert_eq!(decoder.feed(&[b'a', IAC, IAC, b'b']).0, [b'a', IAC, b'b']);
+    }
+
+    #[test]
+    fn enter_arrives_as_a_lone_cr() {
+        let mut decoder = TelnetDecoder::new();
+        assert_eq!(decoder.feed(b"a\r\nb\r\0c").0, b"a\rb\rc");
+        // Even when the LF comes in the next read
+        assert_eq!(decoder.feed(b"\r").0, b"\r");
+        assert_eq!(decoder.feed(b"\nd").0, b"d");
+    }
+
+    #[test]
+    fn a_window_size_split_across_reads_is_read_whole() {
+        let mut decoder = TelnetDecoder::new();
+        let (data, negotiations) = decoder.feed(&[b'x', IAC, SB, NAWS, 0]);
+        assert_eq!(data, b"x");
+        assert!(negotiations.is_empty());
+        let (data, negotiations) = decoder.feed(&[120, 0, 40, IAC]);
+        assert!(data.is_empty());
+        assert!(negotiations.is_empty());
+        let (data, negotiations) = decoder.feed(&[SE, b'y']);
+        assert_eq!(data, b"y");
+        assert_eq!(negotiations, [Negotiation::Size(120, 40)]);
+    }
+
+    #[test]
+    fn a_terminal_type_is_lower_cased() {
+        let mut decoder = TelnetDecoder::new();
+        let mut input = vec![IAC, SB, TERMINAL_TYPE, TERMINAL_TYPE_IS];
+        input.extend_from_slice(b"XTERM-256COLOR");
+        input.extend_from_slice(&[IAC, SE]);
+        assert_eq!(
+            decoder.feed(&input).1,
+            [Negotiation::TerminalType(String::from("xterm-256color"))]
+        );
+    }
+
+    #[test]
+    fn an_oversized_subnegotiation_is_dropped() {
+        let mut decoder = TelnetDecoder::new();
+        decoder.feed(&[IAC, SB, TERMINAL_TYPE, TERMINAL_TYPE_IS]);
+        for _ in 0..1000 {
+            decoder.feed(&[b'x'; 1024]);
+        }
+        assert_eq!(decoder.subnegotiation.len(), MAX_SUBNEGOTIATION);
+        let (data, negotiations) = decoder.feed(&[IAC, SE, b'k']);
+        assert!(negotiations.is_empty());
+        assert_eq!(data, b"k");
+
+        // The next one is read as usual
+        let (_, negotiations) = decoder.feed(&[IAC, SB, NAWS, 0, 80, 0, 24, IAC, SE]);
+        assert_eq!(negotiations, [Negotiation::Size(80, 24)]);
+    }
+}
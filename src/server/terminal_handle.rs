@@ -1,16 +1,108 @@
 use std::io;
-use tokio::sync::mpsc::UnboundedSender;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
 
+use tokio::sync::mpsc::Sender;
+use tokio::sync::mpsc::error::TrySendError;
+
+use crate::color_support::classic_sgr;
+use crate::server::recording::Recorder;
+
+/// The ratatui backend's writer. Everything written for a frame is collected and
+/// sent as one message for the SSH channel; when the client can't keep up and the
+/// queue is full the frame is dropped rather than buffered, and the next one is
+/// redrawn from scratch. A frame that's byte for byte the last one sent isn't sent
+/// again: ratatui positions every change absolutely, so repeating it would leave
+/// the screen as it is. What does go out is counted in `bytes_sent`.
 pub struct TerminalHandle {
-    sender: UnboundedSender<Vec<u8>>,
+    sender: Sender<Vec<u8>>,
+    bytes_sent: Arc<AtomicU64>,
     sink: Vec<u8>,
+    last_frame: Vec<u8>,
+    frames_dropped: bool,
+    classic_colors: bool,
+    recorder: Option<Recorder>,
 }
 
 impl TerminalHandle {
-    pub fn new_with_sender(sender: UnboundedSender<Vec<u8>>) -> Self {
+    pub fn new_with_sender(sender: Sender<Vec<u8>>, recorder: Option<Recorder>) -> Self {
         Self {
             sender,
+            bytes_sent: Arc::new(AtomicU64::new(0)),
             sink: Vec::new(),
+            last_frame: Vec::new(),
+            frames_dropped: false,
+            classic_colors: false,
+            recorder,
+        }
+    }
+
+    /// Bytes of frames handed to the channel so far, counting up as more are sent.
+    pub fn bytes_sent(&self) -> Arc<AtomicU64> {
+        self.bytes_sent.clone()
+    }
+
+    /// Notes the new size in the recording, if the session is recorded.
+    pub fn resized(&mut self, width: u16, height: u16) {
+        if let Some(recorder) = &mut self.recorder {
+            recorder.resize(width, height);
+        }
+    }
+
+    /// Rewrites colors for terminals that only know the 16 classic SGR colors.
+    pub fn set_classic_colors(&mut self, enabled: bool) {
+        self.classic_colors = enabled;
+    }
+
+    /// Whether output was dropped since the last call. The client's screen no longer
+    /// matches ratatui's buffer, so it needs a full redraw.
+    pub fn take_frames_dropped(&mut self) -> bool {
+        std::mem::take(&mut self.frames_dropped)
+    }
+
+    pub fn send_frame(&mut self) -> io::Result<()> {
+        if self.sink == self.last_frame {
+            self.sink.clear();
+            return Ok(());
+        }
+        self.last_frame.clone_from(&self.sink);
+        self.send()
+    }
+
+    /// Sends what was written even if it repeats, for queries the terminal answers
+    /// every time they're asked.
+    pub fn send_query(&mut self) -> io::Result<()> {
+        self.send()
+    }
+
+    fn send(&mut self) -> io::Result<()> {
+        if self.sink.is_empty() {
+            return Ok(());
+        }
+
+        let mut frame = std::mem::take(&mut self.sink);
+        if self.classic_colors {
+            frame = classic_sgr(&frame);
+        }
+        // Reserved first, so the recording only gets frames that are sent
+        match self.sender.try_reserve() {
+            Ok(permit) => {
+                if let Some(recorder) = &mut self.recorder {
+                    recorder.output(&frame);
+                }
+                self.bytes_sent
+                    .fetch_add(frame.len() as u64, Ordering::Relaxed);
+                permit.send(frame);
+                Ok(())
+            }
+            Err(TrySendError::Full(_)) => {
+                self.frames_dropped = true;
+                self.last_frame.clear();
+                Ok(())
+            }
+            Err(err @ TrySendError::Closed(_)) => {
+                Err(io::Error::new(io::ErrorKind::BrokenPipe, err.to_string()))
+            }
         }
     }
 }
@@ -21,16 +113,9 @@ impl std::io::Write for TerminalHandle {
         Ok(buf.len())
     }
 
+    // The crossterm backend flushes after every cursor move and clear, so a flush
+    // is not a frame boundary. `send_frame` is called once the frame is drawn
     fn flush(&mut self) -> io::Result<()> {
-        let result = self.sender.send(self.sink.clone());
-        if result.is_err() {
-            return Err(io::Error::new(
-                io::ErrorKind::BrokenPipe,
-                result.unwrap_err(),
-            ));
-        }
-
-        self.sink.clear();
         Ok(())
     }
 }
@@ -0,0 +1,113 @@
+use std::marker::PhantomData;
+
+use crossterm::event::{KeyEvent, MouseEvent};
+use ratatui::Frame;
+
+use crate::color_support::ColorSupport;
+use crate::graphics::Placement;
+use crate::server::SessionInfo;
+
+/// The pace an app wants to be redrawn at: full speed while something moves or
+/// the visitor is typing, a slow idle cadence otherwise.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FrameRate {
+    Full,
+    /// Half of full speed, for visitors who asked for fewer frames.
+    Half,
+    Idle,
+}
+
+/// What the server runs for every session: one instance per visitor, drawn on
+/// their terminal and fed their input. Only the first four methods are needed;
+/// the rest let an app take part in what the server offers, like the admin's
+/// wall messages or terminal graphics.
+pub trait TuiApp: Send + 'static {
+    fn draw(&mut self, frame: &mut Frame);
+    fn handle_key(&mut self, key_event: KeyEvent);
+    /// Called before every frame with the number of full rate frames so far.
+    /// True while something moves, which redraws the frame.
+    fn handle_tick(&mut self, tick: u64) -> bool;
+    /// Whether the visitor is done, checked after their input. The session is
+    /// then closed.
+    fn wants_close(&self) -> bool;
+
+    fn handle_mouse(&mut self, _mouse_event: MouseEvent) {}
+    fn handle_paste(&mut self, _text: &str) {}
+    fn handle_resize(&mut self, _width: u16, _height: u16) {}
+    /// Whether anything changed since the last call that needs a redraw, besides
+    /// what `handle_tick` says. Without it the app is redrawn after every input.
+    fn take_dirty(&mut self) -> bool {
+        true
+    }
+    /// Asks for a redraw, e.g. once the terminal turned out to show more colors.
+    fn mark_dirty(&mut self) {}
+    fn frame_rate(&self) -> FrameRate {
+        FrameRate::Full
+    }
+    /// The images drawn in the last frame, for terminals with graphics.
+    fn images(&self) -> Vec<Placement> {
+        vec![]
+    }
+    /// What the visitor is looking at, for the admin view.
+    fn current_page(&self) -> String {
+        String::new()
+    }
+    /// The operator's message to every visitor, from `portfolio-v2 wall`.
+    fn show_wall(&mut self, _message: String) {}
+    /// Seconds until an idle session is closed, while the warning should show.
+    fn set_disconnect_countdown(&mut self, _seconds: Option<u64>) {}
+    /// Seconds until the session reaches its time limit, in its last minute.
+    fn set_session_ending(&mut self, _seconds: Option<u64>) {}
+    fn set_visitors_online(&mut self, _count: usize) {}
+}
+
+/// An app that makes itself from the session alone, which `NewApp` serves.
+/// Apps that share more between sessions get a factory of their own instead.
+pub trait FromSession: TuiApp + Sized {
+    /// The app for a visitor who just opened a shell.
+    fn new(session: SessionInfo) -> Self;
+}
+
+/// Makes the app for every session, once its visitor asks for a shell. Any
+/// `Fn(SessionInfo) -> impl TuiApp` is one, and `NewApp` uses `FromSession::new`.
+/// The other methods answer what visitors ask for without a shell.
+pub trait AppFactory: Send + Sync + 'static {
+    fn make(&self, session: SessionInfo) -> Box<dyn TuiApp>;
+
+    /// What `ssh host <command>` prints, or the error it fails with. Colors are
+    /// only set for clients that asked for a pty.
+    fn exec(&self, _command: &str, _colors: Option<ColorSupport>) -> Result<String, String> {
+        Err(String::from("connect without a command for the app"))
+    }
+
+    /// The answer to `finger <query>@host`, with an empty query for `finger @host`.
+    /// Only asked with `finger_listen` set.
+    fn finger(&self, _query: &str) -> String {
+        String::from("nothing to say over finger")
+    }
+}
+
+impl<F, A> AppFactory for F
+where
+    F: Fn(SessionInfo) -> A + Send + Sync + 'static,
+    A: TuiApp,
+{
+    fn make(&self, session: SessionInfo) -> Box<dyn TuiApp> {
+        Box::new(self(session))
+    }
+}
+
+/// The factory for an app that makes itself with `FromSession::new`.
+pub struct NewApp<A>(PhantomData<fn() -> A>);
+
+impl<A> Default for NewApp<A> {
+    fn default() -> Self {
+        Self(PhantomData)
+    }
+}
+
+impl<A: FromSession> AppFactory for NewApp<A> {
+    fn make(&self, session: SessionInfo) -> Box<dyn TuiApp> {
+        Box::new(A::new(session))
+    }
+}
@@ -0,0 +1,373 @@
+use std::net::SocketAddr;
+use std::sync::Arc;
+use std::time::Duration;
+
+use futures_util::{SinkExt, StreamExt};
+use serde::Deserialize;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::mpsc;
+use tokio_tungstenite::WebSocketStream;
+use tokio_tungstenite::tungstenite::Message;
+use tokio_tungstenite::tungstenite::handshake::derive_accept_key;
+use tokio_tungstenite::tungstenite::protocol::Role;
+use tracing::{debug, info_span, warn};
+
+use crate::charset::Charset;
+use crate::color_support::ColorSupport;
+use crate::config::SharedConfig;
+use crate::graphics::Graphics;
+use crate::server::AppFactory;
+use crate::server::input::InputDecoder;
+use crate::server::launch::{
+    ENABLE_INPUT_MODES_SEQUENCE, Launch, OUTPUT_QUEUE_CAPACITY, SERVER_FULL_MESSAGE, handle_input,
+};
+use crate::server::rate_limit::{RateLimiter, Verdict};
+use crate::server::session_registry::{Link, SESSION_FAILED_MESSAGE, SessionRegistry};
+use crate::server::shutdown::Shutdown;
+
+const PAGE: &str = include_str!("web.html");
+// Requests bigger than this aren't from the page
+const MAX_REQUEST_BYTES: usize = 8 * 1024;
+// How long a browser has to send its request, and then the terminal's size
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// What the page sends whenever the terminal changes size, and once right after
+/// connecting, before anything is drawn.
+#[derive(Deserialize)]
+struct Size {
+    cols: u16,
+    rows: u16,
+}
+
+/// The request line and the headers the gateway cares about.
+struct Request {
+    path: String,
+    websocket_key: Option<String>,
+    host: Option<String>,
+    origin: Option<String>,
+}
+
+/// An HTTP listener for visitors without an SSH client: it serves a page with
+/// xterm.js, and the page's websocket gets a session like an SSH channel does,
+/// with the same app and render loop. Every visitor is anonymous here, and the
+/// address is the TCP peer's, so a proxy in front should keep it.
+pub struct WebGateway<F> {
+    registry: SessionRegistry,
+    config: SharedConfig,
+    rate_limiter: Arc<RateLimiter>,
+    app_factory: Arc<F>,
+}
+
+impl<F: AppFactory> WebGateway<F> {
+    pub fn new(
+        registry: SessionRegistry,
+        config: SharedConfig,
+        rate_limiter: Arc<RateLimiter>,
+        app_factory: Arc<F>,
+    ) -> Self {
+        Self {
+            registry,
+            config,
+            rate_limiter,
+            app_factory,
+        }
+    }
+
+    /// Accepts connections until `shutdown` fires.
+    pub async fn run(self, listener: TcpListener, shutdown: Shutdown) {
+        let gateway = Arc::new(self);
+        loop {
+            let accepted = tokio::select! {
+                accepted = listener.accept() => accepted,
+                _ = shutdown.fired() => break,
+            };
+            let (socket, peer_addr) = match accepted {
+                Ok(accepted) => accepted,
+                Err(e) => {
+                    warn!("Failed to accept web connection: {}", e);
+                    continue;
+                }
+            };
+            let _ = socket.set_nodelay(true);
+            tokio::spawn(gateway.clone().serve(socket, peer_addr));
+        }
+    }
+
+    async fn serve(self: Arc<Self>, mut socket: TcpStream, peer_addr: SocketAddr) {
+        let request = match tokio::time::timeout(REQUEST_TIMEOUT, read_request(&mut socket)).await {
+            Ok(Some(request)) => request,
+            _ => return,
+        };
+        let path = request.path.split('?').next().unwrap_or_default();
+
+        // The websocket is at `/ws`, followed by the page's own path
+        let start_page = path
+            .strip_prefix("/ws")
+            .filter(|rest| rest.is_empty() || rest.starts_with('/'));
+        let (Some(websocket_key), Some(start_page)) = (request.websocket_key, start_page) else {
+            // The page, at `/` or a start page's path like `/projects`
+            let page_path = path.trim_start_matches('/');
+            let response = match page_path.contains(['/', '.']) {
+                false => response("200 OK", "text/html; charset=utf-8", PAGE),
+                true => response("404 Not Found", "text/plain", "not found\n"),
+            };
+            let _ = socket.write_all(response.as_bytes()).await;
+            return;
+        };
+        // Another site's page could otherwise run a session in its visitor's browser
+        let web_host = self.config.read().unwrap().web_host.clone();
+        let expected_host = web_host.as_deref().or(request.host.as_deref());
+        if !origin_allowed(request.origin.as_deref(), expected_host) {
+            debug!(peer = %peer_addr, origin = request.origin, "Refusing a websocket from another site");
+            let response = response("403 Forbidden", "text/plain", "forbidden\n");
+            let _ = socket.write_all(response.as_bytes()).await;
+            return;
+        }
+        // Only sessions count, not the page loads before them
+        if self.rate_limiter.check(peer_addr.ip()) != Verdict::Allow {
+            let response = response("429 Too Many Requests", "text/plain", "slow down\n");
+            let _ = socket.write_all(response.as_bytes()).await;
+            return;
+        }
+
+        let accept = derive_accept_key(websocket_key.as_bytes());
+        let upgrade = format!(
+            "HTTP/1.1 101 Switching Protocols\r\nUpgrade: websocket\r\nConnection: Upgrade\r\nSec-WebSocket-Accept: {}\r\n\r\n",
+            accept
+        );
+        if socket.write_all(upgrade.as_bytes()).await.is_err() {
+            return;
+        }
+        let websocket = WebSocketStream::from_raw_socket(socket, Role::Server, None).await;
+        let user = start_page.trim_start_matches('/').to_string();
+        self.run_session(websocket, peer_addr, user).await;
+    }
+
+    async fn run_session(
+        &self,
+        websocket: WebSocketStream<TcpStream>,
+        peer_addr: SocketAddr,
+        user: String,
+    ) {
+        let (mut sink, mut stream) = websocket.split();
+        let size = match tokio::time::timeout(REQUEST_TIMEOUT, stream.next()).await {
+            Ok(Some(Ok(Message::Text(text)))) => match serde_json::from_str::<Size>(&text) {
+                Ok(size) => size,
+                Err(_) => return,
+            },
+            _ => return,
+        };
+
+        let Some(slot) = self.registry.reserve() else {
+            let max_clients = self.config.read().unwrap().max_clients;
+            warn!(max_clients, "Server full, turning a web session away");
+            let _ = sink.send(Message::binary(SERVER_FULL_MESSAGE)).await;
+            let _ = sink.close().await;
+            return;
+        };
+
+        let id = self.registry.next_id();
+        let span = info_span!("session", id, peer = %peer_addr, via = "web");
+        let (output, mut frames) = mpsc::channel::<Vec<u8>>(OUTPUT_QUEUE_CAPACITY);
+        let (link, mut link_messages) = mpsc::unbounded_channel();
+        // xterm.js does truecolor and OSC 8 links, but no graphics protocol
+        let launched = Launch {
+            registry: &self.registry,
+            config: &self.config,
+            app_factory: &*self.app_factory,
+            id,
+            slot,
+            via: "web",
+            span: span.clone(),
+            output,
+            link: Link::Stream(link),
+            peer_addr: Some(peer_addr),
+            fingerprint: None,
+            user,
+            admin: false,
+            term: String::from("xterm.js"),
+            color_support: ColorSupport::TrueColor,
+            charset: Charset::Unicode,
+            hyperlinks: true,
+            graphics: Graphics::HalfBlocks,
+            cell_size: None,
+            size: (size.cols, size.rows),
+            probe_latency: true,
+        }
+        .start()
+        .await;
+        let client = match launched {
+            Ok(client) => client,
+            Err(e) => {
+                warn!(parent: &span, "Failed to start the app: {}", e);
+                let _ = sink.send(Message::binary(SESSION_FAILED_MESSAGE)).await;
+                let _ = sink.close().await;
+                return;
+            }
+        };
+        if sink
+            .send(Message::binary(ENABLE_INPUT_MODES_SEQUENCE))
+            .await
+            .is_err()
+        {
+            self.registry.remove(id);
+            return;
+        }
+
+        let mut input = InputDecoder::new();
+        loop {
+            tokio::select! {
+                // Frames first, so a goodbye never overtakes the last of them
+                biased;
+                Some(frame) = frames.recv() => {
+                    if sink.send(Message::binary(frame)).await.is_err() {
+                        debug!(parent: &span, "Websocket write failed");
+                        break;
+                    }
+                }
+                message = link_messages.recv() => match message {
+                    Some(Some(data)) => {
+                        let _ = sink.send(Message::binary(data)).await;
+                    }
+                    _ => break,
+                },
+                message = stream.next() => match message {
+                    Some(Ok(Message::Binary(data))) => {
+                        let events = input.feed(&data);
+                        client.status_reports(&input.take_status_reports());
+                        handle_input(&self.registry, id, &client, events).await;
+                    }
+                    Some(Ok(Message::Text(text))) => {
+                        if let Ok(size) = serde_json::from_str::<Size>(&text) {
+                            client.request_resize(size.cols, size.rows);
+                        }
+                    }
+                    Some(Ok(Message::Close(_))) | Some(Err(_)) | None => break,
+                    Some(Ok(_)) => {}
+                },
+            }
+        }
+        self.registry.remove(id);
+        let _ = sink.close().await;
+    }
+}
+
+/// Reads up to the blank line after the headers. None for anything that isn't a
+/// GET under `MAX_REQUEST_BYTES`.
+async fn read_request(socket: &mut TcpStream) -> Option<Request> {
+    let mut head = Vec::new();
+    let mut buf = [0u8; 1024];
+    while !head.windows(4).any(|window| window == b"\r\n\r\n") {
+        let read = socket.read(&mut buf).await.ok()?;
+        if read == 0 || head.len() + read > MAX_REQUEST_BYTES {
+            return None;
+        }
+        head.extend_from_slice(&buf[..read]);
+    }
+
+    let head = String::from_utf8_lossy(&head);
+    let mut lines = head.split("\r\n");
+    let mut request_line = lines.next()?.split(' ');
+    if request_line.next()? != "GET" {
+        return None;
+    }
+    let path = request_line.next()?.to_string();
+
+    let mut upgrade = false;
+    let mut websocket_key = None;
+    let mut host = None;
+    let mut origin = None;
+    for line in lines {
+        let Some((name, value)) = line.split_once(':') else {
+            continue;
+        };
+        let value = value.trim();
+        match name.trim().to_ascii_lowercase().as_str() {
+            "upgrade" => upgrade = value.eq_ignore_ascii_case("websocket"),
+            "sec-websocket-key" => websocket_key = Some(value.to_string()),
+            "host" => host = Some(value.to_string()),
+            "origin" => origin = Some(value.to_string()),
+            _ => {}
+        }
+    }
+    Some(Request {
+        path,
+        websocket_key: websocket_key.filter(|_| upgrade),
+        host,
+        origin,
+    })
+}
+
+/// Whether a websocket from a page at `origin` may open a session: only when
+/// the page is on `expected_host`, whatever the scheme and port. No origin at
+/// all is a client other than a browser, which no other site can drive.
+fn origin_allowed(origin: Option<&str>, expected_host: Option<&str>) -> bool {
+    let Some(origin) = origin else {
+        return true;
+    };
+    let Some(expected_host) = expected_host else {
+        return false;
+    };
+    let origin_host = origin.split_once("://").map_or(origin, |(_, host)| host);
+    without_port(origin_host).eq_ignore_ascii_case(without_port(expected_host))
+}
+
+/// `krayon.dev` for `krayon.dev:8080`, and `[::1]` for `[::1]:8080`.
+fn without_port(host: &str) -> &str {
+    match host.strip_prefix('[') {
+        Some(rest) => rest.find(']').map_or(host, |end| &host[..end + 2]),
+        None => host.split(':').next().unwrap_or(host),
+    }
+}
+
+fn response(status: &str, content_type: &str, body: &str) -> String {
+    format!(
+        "HTTP/1.1 {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        status,
+        content_type,
+        body.len(),
+        body
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pages_on_the_expected_host_may_open_sessions() {
+        assert!(origin_allowed(
+            Some("https://krayon.dev"),
+            Some("krayon.dev")
+        ));
+        assert!(origin_allowed(
+            Some("http://KRAYON.dev:8080"),
+            Some("krayon.dev")
+        ));
+        assert!(origin_allowed(
+            Some("http://[::1]:8080"),
+            Some("[::1]:8080")
+        ));
+    }
+
+    #[test]
+    fn pages_elsewhere_are_refused() {
+        assert!(!origin_allowed(
+            Some("https://evil.example"),
+            Some("krayon.dev")
+        ));
+        assert!(!origin_allowed(
+            Some("https://krayon.dev.evil.example"),
+            Some("krayon.dev")
+        ));
+        assert!(!origin_allowed(Some("null"), Some("krayon.dev")));
+        assert!(!origin_allowed(Some("https://krayon.dev"), None));
+    }
+
+    #[test]
+    fn clients_without_an_origin_are_let_in() {
+        assert!(origin_allowed(None, Some("krayon.dev")));
+        assert!(origin_allowed(None, None));
+    }
+}
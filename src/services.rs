@@ -0,0 +1,88 @@
+use std::sync::Arc;
+
+use notify::RecommendedWatcher;
+use tracing::warn;
+
+use crate::chat::ChatRoom;
+use crate::config::SharedConfig;
+use crate::contact::Contact;
+use crate::content::Content;
+use crate::event_bus::EventBus;
+use crate::geoip::GeoIp;
+use crate::github::GitHub;
+use crate::guestbook::Guestbook;
+use crate::high_scores::HighScores;
+use crate::notifier::Notifier;
+use crate::now_playing::NowPlaying;
+use crate::poll::Poll;
+use crate::stats::Stats;
+use crate::store::Store;
+use crate::tic_tac_toe::TicTacToe;
+use crate::weather::Weather;
+
+/// Everything sessions share with each other, handed to every new `App`.
+#[derive(Clone)]
+pub struct Services {
+    pub config: SharedConfig,
+    pub content: Arc<Content>,
+    pub store: Arc<Store>,
+    pub guestbook: Arc<Guestbook>,
+    pub contact: Arc<Contact>,
+    pub notifier: Arc<Notifier>,
+    pub github: Arc<GitHub>,
+    pub now_playing: Arc<NowPlaying>,
+    pub weather: Arc<Weather>,
+    pub high_scores: Arc<HighScores>,
+    pub poll: Arc<Poll>,
+    pub stats: Arc<Stats>,
+    pub geoip: Arc<GeoIp>,
+    pub events: EventBus,
+    pub chat: Arc<ChatRoom>,
+    pub tic_tac_toe: Arc<TicTacToe>,
+}
+
+impl Services {
+    /// `record_stats` is off for the local TUI so the owner's browsing isn't counted.
+    pub fn new(config: SharedConfig, record_stats: bool) -> Result<Self, anyhow::Error> {
+        let (content_dir, database, geoip_database) = {
+            let config = config.read().unwrap();
+            (
+                config.content_dir.clone(),
+                config.database.clone(),
+                config.geoip_database.clone(),
+            )
+        };
+        let store = Arc::new(Store::open(&database)?);
+        let events = EventBus::new();
+        Ok(Self {
+            content: Content::load(&content_dir),
+            github: GitHub::new(config.clone()),
+            now_playing: NowPlaying::new(config.clone()),
+            weather: Weather::new(config.clone()),
+            contact: Contact::new(config.clone()),
+            notifier: Notifier::new(config.clone()),
+            guestbook: Guestbook::new(store.clone(), config.clone()),
+            high_scores: HighScores::new(store.clone()),
+            poll: Poll::new(store.clone())?,
+            stats: Stats::new(store.clone(), record_stats)?,
+            geoip: GeoIp::open(geoip_database.as_deref())?,
+            store,
+            chat: ChatRoom::new(events.clone()),
+            tic_tac_toe: TicTacToe::new(events.clone()),
+            events,
+            config,
+        })
+    }
+
+    /// Starts fetching what the pages show from elsewhere, and reloading the
+    /// content pages while the returned watcher is kept alive.
+    pub fn start(&self) -> Option<RecommendedWatcher> {
+        self.github.refresh_periodically();
+        self.now_playing.refresh_periodically();
+        self.weather.refresh_periodically();
+        self.content
+            .watch()
+            .inspect_err(|e| warn!("Content changes won't be picked up: {}", e))
+            .ok()
+    }
+}
@@ -0,0 +1,125 @@
+use std::net::IpAddr;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use sha2::{Digest, Sha256};
+use tracing::warn;
+
+use crate::geoip::Location;
+use crate::store::Store;
+
+// Pages, countries, cities and terminals listed in the summary
+const PLACES: usize = 10;
+// How far back the summary counts visits by the hour
+const HOURS: usize = 24;
+
+pub struct Summary {
+    /// Since the server started.
+    pub uptime: Duration,
+    /// Open sessions, unknown outside the server.
+    pub online: Option<usize>,
+    pub visits: u64,
+    pub unique_visitors: u64,
+    /// Visits in each of the last 24 hours, oldest first.
+    pub hourly_visits: Vec<u64>,
+    /// Most viewed first.
+    pub page_views: Vec<(String, u64)>,
+    /// Where visits came from, most first. Empty unless the server has a GeoIP
+    /// database.
+    pub countries: Vec<(String, u64)>,
+    pub cities: Vec<(String, u64)>,
+    /// Visits per `TERM`, most first.
+    pub terminals: Vec<(String, u64)>,
+}
+
+/// Visit and page view counts, kept in the store so they survive restarts. IPs
+/// are only stored as salted hashes, enough to tell returning visitors apart, and
+/// where visits came from only as counts per city.
+pub struct Stats {
+    store: Arc<Store>,
+    salt: String,
+    // Off in the local TUI, where the only visitor is the owner
+    recording: bool,
+    started_at: Instant,
+    online: Mutex<Option<usize>>,
+}
+
+impl Stats {
+    pub fn new(store: Arc<Store>, recording: bool) -> Result<Arc<Self>, anyhow::Error> {
+        let salt = store.salt()?;
+        Ok(Arc::new(Self {
+            store,
+            salt,
+            recording,
+            started_at: Instant::now(),
+            online: Mutex::new(None),
+        }))
+    }
+
+    /// Kept up to date by the sessions, which the server tells how many there are.
+    pub fn set_online(&self, count: usize) {
+        *self.online.lock().unwrap() = Some(count);
+    }
+
+    pub fn record_visit(&self, ip: Option<IpAddr>, location: &Location, term: &str) {
+        if !self.recording {
+            return;
+        }
+        let visitor = match ip {
+            Some(ip) => self.hash(ip),
+            None => String::from("unknown"),
+        };
+        if let Err(e) = self.store.add_visit(&visitor) {
+            warn!("Failed to record visit: {}", e);
+        }
+        if let Some(country) = &location.country
+            && let Err(e) = self
+                .store
+                .add_place_visit(country, location.city.as_deref())
+        {
+            warn!("Failed to record where a visit came from: {}", e);
+        }
+        if !term.is_empty()
+            && let Err(e) = self.store.add_terminal_visit(term)
+        {
+            warn!("Failed to record the terminal of a visit: {}", e);
+        }
+    }
+
+    pub fn record_page_view(&self, page: &str) {
+        if !self.recording {
+            return;
+        }
+        if let Err(e) = self.store.add_page_view(page) {
+            warn!("Failed to record page view of {}: {}", page, e);
+        }
+    }
+
+    pub fn summary(&self) -> Result<Summary, anyhow::Error> {
+        let (visits, unique_visitors) = self.store.visit_counts()?;
+        Ok(Summary {
+            uptime: self.started_at.elapsed(),
+            online: *self.online.lock().unwrap(),
+            visits,
+            unique_visitors,
+            hourly_visits: self.store.hourly_visits(HOURS)?,
+            page_views: self.store.page_views(PLACES)?,
+            countries: self.store.country_visits(PLACES)?,
+            cities: self.store.city_visits(PLACES)?,
+            terminals: self.store.terminal_visits(PLACES)?,
+        })
+    }
+
+    fn hash(&self, ip: IpAddr) -> String {
+        salted_hash(&self.salt, &ip.to_canonical().to_string())
+    }
+}
+
+/// Hex SHA-256 of `value` after `salt`, see `Store::salt`.
+pub(crate) fn salted_hash(salt: &str, value: &str) -> String {
+    let digest = Sha256::new()
+        .chain_update(salt.as_bytes())
+        .chain_update(value.as_bytes())
+        .finalize();
+    digest.iter().map(|b| format!("{:02x}", b)).collect()
+}
@@ -0,0 +1,416 @@
+use std::path::Path;
+use std::sync::Mutex;
+
+use rand::Rng;
+use rusqlite::{Connection, OptionalExtension, params};
+
+pub struct GuestbookEntry {
+    pub name: String,
+    pub message: String,
+    /// `YYYY-MM-DD`, in UTC.
+    pub date: String,
+}
+
+pub struct HighScore {
+    pub name: String,
+    pub score: u32,
+    /// `YYYY-MM-DD`, in UTC.
+    pub date: String,
+}
+
+/// Everything the server keeps between restarts, in a single SQLite file.
+pub struct Store {
+    connection: Mutex<Connection>,
+}
+
+impl Store {
+    pub fn open(path: &Path) -> Result<Self, anyhow::Error> {
+        let connection = Connection::open(path)
+            .map_err(|e| anyhow::anyhow!("Failed to open database at {}: {}", path.display(), e))?;
+        connection.execute_batch(
+            "CREATE TABLE IF NOT EXISTS visitors (
+                fingerprint TEXT PRIMARY KEY,
+                theme TEXT
+            );
+            CREATE TABLE IF NOT EXISTS guestbook (
+                id INTEGER PRIMARY KEY,
+                name TEXT NOT NULL,
+                message TEXT NOT NULL,
+                created_at INTEGER NOT NULL DEFAULT (strftime('%s', 'now'))
+            );
+            CREATE TABLE IF NOT EXISTS visits (
+                id INTEGER PRIMARY KEY,
+                visitor TEXT NOT NULL,
+                created_at INTEGER NOT NULL DEFAULT (strftime('%s', 'now'))
+            );
+            CREATE TABLE IF NOT EXISTS places (
+                country TEXT NOT NULL,
+                city TEXT NOT NULL,
+                visits INTEGER NOT NULL,
+                PRIMARY KEY (country, city)
+            );
+            CREATE TABLE IF NOT EXISTS terminals (
+                term TEXT PRIMARY KEY,
+                visits INTEGER NOT NULL
+            );
+            CREATE TABLE IF NOT EXISTS page_views (
+                page TEXT PRIMARY KEY,
+                views INTEGER NOT NULL
+            );
+            CREATE TABLE IF NOT EXISTS high_scores (
+                id INTEGER PRIMARY KEY,
+                game TEXT NOT NULL,
+                name TEXT NOT NULL,
+                score INTEGER NOT NULL,
+                created_at INTEGER NOT NULL DEFAULT (strftime('%s', 'now'))
+            );
+            CREATE TABLE IF NOT EXISTS personal_bests (
+                owner TEXT NOT NULL,
+                game TEXT NOT NULL,
+                best INTEGER NOT NULL,
+                PRIMARY KEY (owner, game)
+            );
+            CREATE TABLE IF NOT EXISTS meta (
+                key TEXT PRIMARY KEY,
+                value TEXT NOT NULL
+            );
+            CREATE TABLE IF NOT EXISTS preferences (
+                owner TEXT PRIMARY KEY,
+                saved TEXT NOT NULL
+            );
+            CREATE TABLE IF NOT EXISTS snapshots (
+                fingerprint TEXT PRIMARY KEY,
+                saved TEXT NOT NULL
+            );
+            CREATE TABLE IF NOT EXISTS votes (
+                poll TEXT NOT NULL,
+                voter TEXT NOT NULL,
+                choice INTEGER NOT NULL,
+                created_at INTEGER NOT NULL DEFAULT (strftime('%s', 'now')),
+                PRIMARY KEY (poll, voter)
+            );
+            -- Themes saved before the other preferences were
+            INSERT OR IGNORE INTO preferences (owner, saved)
+                SELECT fingerprint, json_object('theme', theme) FROM visitors
+                WHERE theme IS NOT NULL;",
+        )?;
+
+        Ok(Self {
+            connection: Mutex::new(connection),
+        })
+    }
+
+    /// A visitor's preferences as JSON, saved under their key's fingerprint or
+    /// their code.
+    pub fn preferences(&self, owner: &str) -> Result<Option<String>, anyhow::Error> {
+        let connection = self.connection.lock().unwrap();
+        let saved = connection
+            .query_row(
+                "SELECT saved FROM preferences WHERE owner = ?1",
+                params![owner],
+                |row| row.get(0),
+            )
+            .optional()?;
+        Ok(saved)
+    }
+
+    pub fn set_preferences(&self, owner: &str, saved: &str) -> Result<(), anyhow::Error> {
+        let connection = self.connection.lock().unwrap();
+        connection.execute(
+            "INSERT INTO preferences (owner, saved) VALUES (?1, ?2)
+             ON CONFLICT(owner) DO UPDATE SET saved = excluded.saved",
+            params![owner, saved],
+        )?;
+        Ok(())
+    }
+
+    /// Where a visitor with a key left off, as JSON.
+    pub fn snapshot(&self, fingerprint: &str) -> Result<Option<String>, anyhow::Error> {
+        let connection = self.connection.lock().unwrap();
+        let saved = connection
+            .query_row(
+                "SELECT saved FROM snapshots WHERE fingerprint = ?1",
+                params![fingerprint],
+                |row| row.get(0),
+            )
+            .optional()?;
+        Ok(saved)
+    }
+
+    pub fn set_snapshot(&self, fingerprint: &str, saved: &str) -> Result<(), anyhow::Error> {
+        let connection = self.connection.lock().unwrap();
+        connection.execute(
+            "INSERT INTO snapshots (fingerprint, saved) VALUES (?1, ?2)
+             ON CONFLICT(fingerprint) DO UPDATE SET saved = excluded.saved",
+            params![fingerprint, saved],
+        )?;
+        Ok(())
+    }
+
+    /// The newest `limit` entries, newest first.
+    pub fn guestbook_entries(&self, limit: usize) -> Result<Vec<GuestbookEntry>, anyhow::Error> {
+        let connection = self.connection.lock().unwrap();
+        let mut statement = connection.prepare(
+            "SELECT name, message, date(created_at, 'unixepoch') FROM guestbook
+             ORDER BY id DESC LIMIT ?1",
+        )?;
+        let entries = statement
+            .query_map(params![limit as i64], |row| {
+                Ok(GuestbookEntry {
+                    name: row.get(0)?,
+                    message: row.get(1)?,
+                    date: row.get(2)?,
+                })
+            })?
+            .collect::<Result<_, _>>()?;
+        Ok(entries)
+    }
+
+    pub fn add_guestbook_entry(&self, name: &str, message: &str) -> Result<(), anyhow::Error> {
+        let connection = self.connection.lock().unwrap();
+        connection.execute(
+            "INSERT INTO guestbook (name, message) VALUES (?1, ?2)",
+            params![name, message],
+        )?;
+        Ok(())
+    }
+
+    /// A random value created with the database, mixed into hashed IPs so they
+    /// can't be compared with hashes from anywhere else.
+    pub fn salt(&self) -> Result<String, anyhow::Error> {
+        let connection = self.connection.lock().unwrap();
+        let mut bytes = [0u8; 16];
+        rand::rng().fill_bytes(&mut bytes);
+        let fresh: String = bytes.iter().map(|b| format!("{:02x}", b)).collect();
+        connection.execute(
+            "INSERT OR IGNORE INTO meta (key, value) VALUES ('salt', ?1)",
+            params![fresh],
+        )?;
+        let salt =
+            connection.query_row("SELECT value FROM meta WHERE key = 'salt'", [], |row| {
+                row.get(0)
+            })?;
+        Ok(salt)
+    }
+
+    pub fn add_visit(&self, visitor: &str) -> Result<(), anyhow::Error> {
+        let connection = self.connection.lock().unwrap();
+        connection.execute("INSERT INTO visits (visitor) VALUES (?1)", params![visitor])?;
+        Ok(())
+    }
+
+    /// Total visits and distinct visitors.
+    pub fn visit_counts(&self) -> Result<(u64, u64), anyhow::Error> {
+        let connection = self.connection.lock().unwrap();
+        let counts = connection.query_row(
+            "SELECT COUNT(*), COUNT(DISTINCT visitor) FROM visits",
+            [],
+            |row| Ok((row.get::<_, i64>(0)? as u64, row.get::<_, i64>(1)? as u64)),
+        )?;
+        Ok(counts)
+    }
+
+    /// Visits in each of the last `hours` hours, oldest first, the last one being
+    /// the hour up to now.
+    pub fn hourly_visits(&self, hours: usize) -> Result<Vec<u64>, anyhow::Error> {
+        let connection = self.connection.lock().unwrap();
+        let mut statement = connection.prepare(
+            "SELECT (strftime('%s', 'now') - created_at) / 3600 AS ago, COUNT(*) FROM visits
+             WHERE ago < ?1 GROUP BY ago",
+        )?;
+        let mut visits = vec![0; hours];
+        let counts = statement.query_map(params![hours as i64], |row| {
+            Ok((row.get::<_, i64>(0)?, row.get::<_, i64>(1)? as u64))
+        })?;
+        for count in counts {
+            let (ago, count) = count?;
+            // Visits stamped ahead of the clock count towards now
+            let ago = ago.clamp(0, hours as i64 - 1) as usize;
+            visits[hours - 1 - ago] += count;
+        }
+        Ok(visits)
+    }
+
+    pub fn add_page_view(&self, page: &str) -> Result<(), anyhow::Error> {
+        let connection = self.connection.lock().unwrap();
+        connection.execute(
+            "INSERT INTO page_views (page, views) VALUES (?1, 1)
+             ON CONFLICT(page) DO UPDATE SET views = views + 1",
+            params![page],
+        )?;
+        Ok(())
+    }
+
+    /// Views of the `limit` most viewed pages, most first.
+    pub fn page_views(&self, limit: usize) -> Result<Vec<(String, u64)>, anyhow::Error> {
+        let connection = self.connection.lock().unwrap();
+        let mut statement = connection
+            .prepare("SELECT page, views FROM page_views ORDER BY views DESC, page LIMIT ?1")?;
+        let views = statement
+            .query_map(params![limit as i64], |row| {
+                Ok((row.get(0)?, row.get::<_, i64>(1)? as u64))
+            })?
+            .collect::<Result<_, _>>()?;
+        Ok(views)
+    }
+
+    /// Counts a visit from `city` in `country`, or from the country alone when the
+    /// city isn't known.
+    pub fn add_place_visit(&self, country: &str, city: Option<&str>) -> Result<(), anyhow::Error> {
+        let connection = self.connection.lock().unwrap();
+        connection.execute(
+            "INSERT INTO places (country, city, visits) VALUES (?1, ?2, 1)
+             ON CONFLICT(country, city) DO UPDATE SET visits = visits + 1",
+            params![country, city.unwrap_or_default()],
+        )?;
+        Ok(())
+    }
+
+    /// The `limit` countries most visits came from, most first.
+    pub fn country_visits(&self, limit: usize) -> Result<Vec<(String, u64)>, anyhow::Error> {
+        let connection = self.connection.lock().unwrap();
+        let mut statement = connection.prepare(
+            "SELECT country, SUM(visits) AS total FROM places GROUP BY country
+             ORDER BY total DESC, country LIMIT ?1",
+        )?;
+        let visits = statement
+            .query_map(params![limit as i64], |row| {
+                Ok((row.get(0)?, row.get::<_, i64>(1)? as u64))
+            })?
+            .collect::<Result<_, _>>()?;
+        Ok(visits)
+    }
+
+    /// Like `country_visits` for cities, as `city, country`.
+    pub fn city_visits(&self, limit: usize) -> Result<Vec<(String, u64)>, anyhow::Error> {
+        let connection = self.connection.lock().unwrap();
+        let mut statement = connection.prepare(
+            "SELECT city || ', ' || country, visits FROM places WHERE city != ''
+             ORDER BY visits DESC, city LIMIT ?1",
+        )?;
+        let visits = statement
+            .query_map(params![limit as i64], |row| {
+                Ok((row.get(0)?, row.get::<_, i64>(1)? as u64))
+            })?
+            .collect::<Result<_, _>>()?;
+        Ok(visits)
+    }
+
+    pub fn add_terminal_visit(&self, term: &str) -> Result<(), anyhow::Error> {
+        let connection = self.connection.lock().unwrap();
+        connection.execute(
+            "INSERT INTO terminals (term, visits) VALUES (?1, 1)
+             ON CONFLICT(term) DO UPDATE SET visits = visits + 1",
+            params![term],
+        )?;
+        Ok(())
+    }
+
+    /// The `limit` terminal types most visits came from, most first.
+    pub fn terminal_visits(&self, limit: usize) -> Result<Vec<(String, u64)>, anyhow::Error> {
+        let connection = self.connection.lock().unwrap();
+        let mut statement = connection
+            .prepare("SELECT term, visits FROM terminals ORDER BY visits DESC, term LIMIT ?1")?;
+        let visits = statement
+            .query_map(params![limit as i64], |row| {
+                Ok((row.get(0)?, row.get::<_, i64>(1)? as u64))
+            })?
+            .collect::<Result<_, _>>()?;
+        Ok(visits)
+    }
+
+    /// The best `limit` scores for `game`, highest first. Ties go to whoever got
+    /// there first.
+    pub fn high_scores(&self, game: &str, limit: usize) -> Result<Vec<HighScore>, anyhow::Error> {
+        let connection = self.connection.lock().unwrap();
+        let mut statement = connection.prepare(
+            "SELECT name, score, date(created_at, 'unixepoch') FROM high_scores
+             WHERE game = ?1 ORDER BY score DESC, id LIMIT ?2",
+        )?;
+        let scores = statement
+            .query_map(params![game, limit as i64], |row| {
+                Ok(HighScore {
+                    name: row.get(0)?,
+                    score: row.get(1)?,
+                    date: row.get(2)?,
+                })
+            })?
+            .collect::<Result<_, _>>()?;
+        Ok(scores)
+    }
+
+    pub fn add_high_score(&self, game: &str, name: &str, score: u32) -> Result<(), anyhow::Error> {
+        let connection = self.connection.lock().unwrap();
+        connection.execute(
+            "INSERT INTO high_scores (game, name, score) VALUES (?1, ?2, ?3)",
+            params![game, name, score],
+        )?;
+        Ok(())
+    }
+
+    /// The best one visitor did at `game`, saved under the same owner as their
+    /// preferences.
+    pub fn personal_best(&self, owner: &str, game: &str) -> Result<Option<u32>, anyhow::Error> {
+        let connection = self.connection.lock().unwrap();
+        let best = connection
+            .query_row(
+                "SELECT best FROM personal_bests WHERE owner = ?1 AND game = ?2",
+                params![owner, game],
+                |row| row.get(0),
+            )
+            .optional()?;
+        Ok(best)
+    }
+
+    /// Keeps `best` unless the visitor already did better.
+    pub fn set_personal_best(
+        &self,
+        owner: &str,
+        game: &str,
+        best: u32,
+    ) -> Result<(), anyhow::Error> {
+        let connection = self.connection.lock().unwrap();
+        connection.execute(
+            "INSERT INTO personal_bests (owner, game, best) VALUES (?1, ?2, ?3)
+             ON CONFLICT(owner, game) DO UPDATE SET best = max(best, excluded.best)",
+            params![owner, game, best],
+        )?;
+        Ok(())
+    }
+
+    /// Counts `voter`'s vote unless they already voted in `poll`. False if they
+    /// had.
+    pub fn add_vote(&self, poll: &str, voter: &str, choice: usize) -> Result<bool, anyhow::Error> {
+        let connection = self.connection.lock().unwrap();
+        let added = connection.execute(
+            "INSERT OR IGNORE INTO votes (poll, voter, choice) VALUES (?1, ?2, ?3)",
+            params![poll, voter, choice as i64],
+        )?;
+        Ok(added > 0)
+    }
+
+    pub fn vote(&self, poll: &str, voter: &str) -> Result<Option<usize>, anyhow::Error> {
+        let connection = self.connection.lock().unwrap();
+        let choice = connection
+            .query_row(
+                "SELECT choice FROM votes WHERE poll = ?1 AND voter = ?2",
+                params![poll, voter],
+                |row| row.get::<_, i64>(0),
+            )
+            .optional()?;
+        Ok(choice.map(|choice| choice as usize))
+    }
+
+    /// Votes per choice in `poll`, for the choices anyone picked.
+    pub fn tallies(&self, poll: &str) -> Result<Vec<(usize, u64)>, anyhow::Error> {
+        let connection = self.connection.lock().unwrap();
+        let mut statement = connection
+            .prepare("SELECT choice, COUNT(*) FROM votes WHERE poll = ?1 GROUP BY choice")?;
+        let tallies = statement
+            .query_map(params![poll], |row| {
+                Ok((row.get::<_, i64>(0)? as usize, row.get::<_, i64>(1)? as u64))
+            })?
+            .collect::<Result<_, _>>()?;
+        Ok(tallies)
+    }
+}
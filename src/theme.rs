@@ -0,0 +1,95 @@
+use ratatui::buffer::Buffer;
+use ratatui::style::Color;
+
+use crate::pages::style::{BLACK, DIMMED_LINK_COLOR, DIMMED_WHITE, GRAY, LINK_COLOR, WHITE};
+
+/// A color scheme. Pages draw with the palette in `pages::style`, and the theme
+/// swaps those colors for its own once the frame is drawn, so pages don't need to
+/// know which theme is active.
+#[derive(Debug, PartialEq)]
+pub struct Theme {
+    pub name: &'static str,
+    pub background: Color,
+    pub text: Color,
+    pub dimmed_text: Color,
+    /// Secondary text and borders.
+    pub muted: Color,
+    pub accent: Color,
+    pub dimmed_accent: Color,
+}
+
+pub const DARK: Theme = Theme {
+    name: "dark",
+    background: Color::Reset,
+    text: WHITE,
+    dimmed_text: DIMMED_WHITE,
+    muted: GRAY,
+    accent: LINK_COLOR,
+    dimmed_accent: DIMMED_LINK_COLOR,
+};
+
+pub const THEMES: [&Theme; 4] = [
+    &DARK,
+    &Theme {
+        name: "light",
+        background: Color::Rgb(250, 250, 248),
+        text: Color::Rgb(28, 28, 28),
+        dimmed_text: Color::Rgb(70, 70, 70),
+        muted: Color::Rgb(125, 125, 125),
+        accent: Color::Rgb(0, 110, 150),
+        dimmed_accent: Color::Rgb(0, 90, 125),
+    },
+    &Theme {
+        name: "solarized",
+        background: Color::Rgb(0, 43, 54),
+        text: Color::Rgb(238, 232, 213),
+        dimmed_text: Color::Rgb(147, 161, 161),
+        muted: Color::Rgb(101, 123, 131),
+        accent: Color::Rgb(42, 161, 152),
+        dimmed_accent: Color::Rgb(38, 139, 210),
+    },
+    &Theme {
+        name: "monochrome",
+        background: Color::Reset,
+        text: Color::Rgb(255, 255, 255),
+        dimmed_text: Color::Rgb(200, 200, 200),
+        muted: Color::Rgb(128, 128, 128),
+        accent: Color::Rgb(255, 255, 255),
+        dimmed_accent: Color::Rgb(200, 200, 200),
+    },
+];
+
+impl Theme {
+    pub fn by_name(name: &str) -> Option<&'static Theme> {
+        THEMES.into_iter().find(|theme| theme.name == name)
+    }
+
+    /// Recolors a frame drawn with the default palette.
+    pub fn apply(&self, buffer: &mut Buffer) {
+        if self == &DARK {
+            return;
+        }
+
+        for cell in buffer.content.iter_mut() {
+            cell.fg = self.map(cell.fg, self.text);
+            cell.bg = self.map(cell.bg, self.background);
+        }
+    }
+
+    fn map(&self, color: Color, reset: Color) -> Color {
+        match color {
+            Color::Reset => reset,
+            WHITE => self.text,
+            DIMMED_WHITE => self.dimmed_text,
+            GRAY => self.muted,
+            LINK_COLOR => self.accent,
+            DIMMED_LINK_COLOR => self.dimmed_accent,
+            // Text on a selected row, drawn on top of the text color
+            BLACK => match self.background {
+                Color::Reset => BLACK,
+                background => background,
+            },
+            other => other,
+        }
+    }
+}
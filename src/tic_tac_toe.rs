@@ -0,0 +1,531 @@
+use std::collections::{HashMap, VecDeque};
+use std::fmt;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+
+use crate::event_bus::{Event, EventBus};
+
+pub type PlayerId = u64;
+type MatchId = u64;
+
+// Rows, columns, then the diagonals, as squares numbered from the top left
+const LINES: [[usize; 3]; 8] = [
+    [0, 1, 2],
+    [3, 4, 5],
+    [6, 7, 8],
+    [0, 3, 6],
+    [1, 4, 7],
+    [2, 5, 8],
+    [0, 4, 8],
+    [2, 4, 6],
+];
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Mark {
+    X,
+    O,
+}
+
+impl Mark {
+    fn other(self) -> Self {
+        match self {
+            Self::X => Self::O,
+            Self::O => Self::X,
+        }
+    }
+}
+
+impl fmt::Display for Mark {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::X => write!(f, "x"),
+            Self::O => write!(f, "o"),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Outcome {
+    Won(Mark),
+    Draw,
+    /// The player with this mark resigned or left.
+    Forfeited(Mark),
+}
+
+pub type Board = [Option<Mark>; 9];
+
+/// A match as one of its players sees it.
+#[derive(Clone, Debug)]
+pub struct MatchView {
+    pub board: Board,
+    pub mark: Mark,
+    pub opponent: String,
+    pub turn: Mark,
+    pub outcome: Option<Outcome>,
+}
+
+impl MatchView {
+    pub fn my_move(&self) -> bool {
+        self.outcome.is_none() && self.turn == self.mark
+    }
+}
+
+#[derive(Clone, Debug)]
+pub enum Status {
+    Idle,
+    Waiting,
+    Playing(MatchView),
+}
+
+#[derive(Debug)]
+pub enum MoveError {
+    NotPlaying,
+    NotYourMove,
+    Taken,
+}
+
+impl fmt::Display for MoveError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NotPlaying => write!(f, "there's no game going"),
+            Self::NotYourMove => write!(f, "it's not your move yet"),
+            Self::Taken => write!(f, "that square is taken"),
+        }
+    }
+}
+
+struct Match {
+    board: Board,
+    // X, who waited longer and moves first, then O
+    players: [PlayerId; 2],
+    names: [String; 2],
+    turn: Mark,
+    outcome: Option<Outcome>,
+}
+
+impl Match {
+    fn mark_of(&self, player: PlayerId) -> Mark {
+        match self.players[0] == player {
+            true => Mark::X,
+            false => Mark::O,
+        }
+    }
+
+    fn opponent_of(&self, player: PlayerId) -> PlayerId {
+        match self.players[0] == player {
+            true => self.players[1],
+            false => self.players[0],
+        }
+    }
+
+    fn opponent_name(&self, player: PlayerId) -> &str {
+        match self.players[0] == player {
+            true => &self.names[1],
+            false => &self.names[0],
+        }
+    }
+}
+
+fn outcome(board: &Board) -> Option<Outcome> {
+    let won = LINES.iter().find_map(|line| {
+        let mark = board[line[0]]?;
+        line.iter()
+            .all(|&square| board[square] == Some(mark))
+            .then_some(Outcome::Won(mark))
+    });
+    won.or_else(|| board.iter().all(Option::is_some).then_some(Outcome::Draw))
+}
+
+#[derive(Default)]
+struct Tables {
+    names: HashMap<PlayerId, String>,
+    // Longest waiting first
+    waiting: VecDeque<PlayerId>,
+    matches: HashMap<MatchId, Match>,
+    // Finished matches stay here until their players move on, so both can
+    // still see how they ended
+    playing: HashMap<PlayerId, MatchId>,
+    next_match: MatchId,
+}
+
+impl Tables {
+    /// Ends the player's match in the opponent's favour if it's still going, and
+    /// returns the opponent to tell.
+    fn forfeit(&mut self, player: PlayerId) -> Option<PlayerId> {
+        let game = self.matches.get_mut(self.playing.get(&player)?)?;
+        if game.outcome.is_some() {
+            return None;
+        }
+        game.outcome = Some(Outcome::Forfeited(game.mark_of(player)));
+        Some(game.opponent_of(player))
+    }
+
+    /// Takes the player out of the queue and their match, forfeiting it if it
+    /// wasn't over. Returns who else has to hear about it.
+    fn leave(&mut self, player: PlayerId) -> Option<PlayerId> {
+        self.waiting.retain(|&waiting| waiting != player);
+        let opponent = self.forfeit(player);
+        if let Some(id) = self.playing.remove(&player) {
+            let abandoned = self
+                .matches
+                .get(&id)
+                .is_some_and(|game| !self.playing.contains_key(&game.opponent_of(player)));
+            if abandoned {
+                self.matches.remove(&id);
+            }
+        }
+        opponent
+    }
+}
+
+/// Matches visitors who want a game of tic-tac-toe with each other, in the
+/// order they asked. Every change is published on the event bus for the players
+/// it concerns, and every lobby hears about it.
+pub struct TicTacToe {
+    events: EventBus,
+    tables: Mutex<Tables>,
+    next_player: AtomicU64,
+}
+
+impl TicTacToe {
+    pub fn new(events: EventBus) -> Arc<Self> {
+        Arc::new(Self {
+            events,
+            tables: Mutex::new(Tables::default()),
+            next_player: AtomicU64::new(1),
+        })
+    }
+
+    /// A place at the tables under a fresh name. Dropping it, as a session does
+    /// when it ends, forfeits whatever game it was in.
+    pub fn seat(self: &Arc<Self>) -> Seat {
+        let id = self.next_player.fetch_add(1, Ordering::Relaxed);
+        let name = format!("player{}", id);
+        self.tables.lock().unwrap().names.insert(id, name.clone());
+        Seat {
+            service: self.clone(),
+            id,
+            name,
+        }
+    }
+
+    /// How many are waiting for an opponent, and how many games are going.
+    pub fn lobby(&self) -> (usize, usize) {
+        let tables = self.tables.lock().unwrap();
+        let going = tables
+            .matches
+            .values()
+            .filter(|game| game.outcome.is_none())
+            .count();
+        (tables.waiting.len(), going)
+    }
+
+    pub fn status(&self, player: PlayerId) -> Status {
+        let tables = self.tables.lock().unwrap();
+        if tables.waiting.contains(&player) {
+            return Status::Waiting;
+        }
+        let Some(game) = tables
+            .playing
+            .get(&player)
+            .and_then(|id| tables.matches.get(id))
+        else {
+            return Status::Idle;
+        };
+        Status::Playing(MatchView {
+            board: game.board,
+            mark: game.mark_of(player),
+            opponent: game.opponent_name(player).to_string(),
+            turn: game.turn,
+            outcome: game.outcome,
+        })
+    }
+
+    /// What to tell a player away from the game about the change they were just
+    /// told of, if it needs them.
+    pub fn notice(&self, player: PlayerId) -> Option<String> {
+        let Status::Playing(game) = self.status(player) else {
+            return None;
+        };
+        match game.outcome {
+            Some(Outcome::Forfeited(mark)) if mark != game.mark => Some(format!(
+                "{} gave up the game of tic-tac-toe, so you win",
+                game.opponent
+            )),
+            _ if !game.my_move() => None,
+            _ if game.board.iter().all(Option::is_none) => Some(format!(
+                "{} joined you for tic-tac-toe, and it's your move",
+                game.opponent
+            )),
+            _ => Some(format!(
+                "{} moved in tic-tac-toe, it's your move",
+                game.opponent
+            )),
+        }
+    }
+
+    fn publish(&self, players: impl IntoIterator<Item = PlayerId>) {
+        for player in players {
+            self.events.publish(Event::TicTacToe(player));
+        }
+    }
+}
+
+/// One session's place at the tables.
+pub struct Seat {
+    service: Arc<TicTacToe>,
+    id: PlayerId,
+    name: String,
+}
+
+impl Seat {
+    pub fn id(&self) -> PlayerId {
+        self.id
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn status(&self) -> Status {
+        self.service.status(self.id)
+    }
+
+    pub fn lobby(&self) -> (usize, usize) {
+        self.service.lobby()
+    }
+
+    /// Starts a game with whoever has waited longest, or waits for the next one
+    /// to ask. A finished game is left behind.
+    pub fn find_opponent(&self) {
+        let players = {
+            let mut tables = self.service.tables.lock().unwrap();
+            if tables.waiting.contains(&self.id) {
+                return;
+            }
+            let mut players = vec![self.id];
+            players.extend(tables.leave(self.id));
+            match tables.waiting.pop_front() {
+                Some(opponent) => {
+                    let names = [&opponent, &self.id]
+                        .map(|player| tables.names.get(player).cloned().unwrap_or_default());
+                    let id = tables.next_match;
+                    tables.next_match += 1;
+                    tables.matches.insert(
+                        id,
+                        Match {
+                            board: [None; 9],
+                            players: [opponent, self.id],
+                            names,
+                            turn: Mark::X,
+                            outcome: None,
+                        },
+                    );
+                    tables.playing.insert(opponent, id);
+                    tables.playing.insert(self.id, id);
+                    players.push(opponent);
+                }
+                None => tables.waiting.push_back(self.id),
+            }
+            players
+        };
+        self.service.publish(players);
+    }
+
+    pub fn stop_looking(&self) {
+        self.service
+            .tables
+            .lock()
+            .unwrap()
+            .waiting
+            .retain(|&waiting| waiting != self.id);
+        self.service.publish([self.id]);
+    }
+
+    /// Puts the player's mark on `square`, numbered 0 to 8 from the top left.
+    pub fn play(&self, square: usize) -> Result<(), MoveError> {
+        let opponent = {
+            let mut tables = self.service.tables.lock().unwrap();
+            let id = *tables.playing.get(&self.id).ok_or(MoveError::NotPlaying)?;
+            let game = tables.matches.get_mut(&id).ok_or(MoveError::NotPlaying)?;
+            let mark = game.mark_of(self.id);
+            if game.outcome.is_some() {
+                return Err(MoveError::NotPlaying);
+            }
+            if game.turn != mark {
+                return Err(MoveError::NotYourMove);
+            }
+            if game.board[square].is_some() {
+                return Err(MoveError::Taken);
+            }
+            game.board[square] = Some(mark);
+            game.turn = mark.other();
+            game.outcome = outcome(&game.board);
+            game.opponent_of(self.id)
+        };
+        self.service.publish([self.id, opponent]);
+        Ok(())
+    }
+
+    pub fn resign(&self) {
+        let opponent = self.service.tables.lock().unwrap().forfeit(self.id);
+        self.service
+            .publish(std::iter::once(self.id).chain(opponent));
+    }
+}
+
+impl Drop for Seat {
+    fn drop(&mut self) {
+        let opponent = {
+            let mut tables = self.service.tables.lock().unwrap();
+            tables.names.remove(&self.id);
+            tables.leave(self.id)
+        };
+        self.service
+            .publish(std::iter::once(self.id).chain(opponent));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn board(marks: &str) -> Board {
+        let mut board = [None; 9];
+        for (square, mark) in marks.chars().enumerate() {
+            board[square] = match mark {
+                'x' => Some(Mark::X),
+                'o' => Some(Mark::O),
+                _ => None,
+            };
+        }
+        board
+    }
+
+    fn playing(seat: &Seat) -> MatchView {
+        match seat.status() {
+            Status::Playing(game) => game,
+            status => panic!("not playing: {:?}", status),
+        }
+    }
+
+    /// Two seats in a game, the first of them moving first as X.
+    fn matched(service: &Arc<TicTacToe>) -> (Seat, Seat) {
+        let (first, second) = (service.seat(), service.seat());
+        first.find_opponent();
+        assert!(matches!(first.status(), Status::Waiting));
+        second.find_opponent();
+        (first, second)
+    }
+
+    #[test]
+    fn three_in_a_line_wins() {
+        assert_eq!(outcome(&board("xxxoo....")), Some(Outcome::Won(Mark::X)));
+        assert_eq!(outcome(&board("o..o..o.x")), Some(Outcome::Won(Mark::O)));
+        assert_eq!(outcome(&board("x.o.x.o.x")), Some(Outcome::Won(Mark::X)));
+        assert_eq!(outcome(&board("ooxoxxx..")), Some(Outcome::Won(Mark::X)));
+    }
+
+    #[test]
+    fn a_full_board_without_a_line_is_a_draw() {
+        assert_eq!(outcome(&board("xoxxoooxx")), Some(Outcome::Draw));
+    }
+
+    #[test]
+    fn a_game_without_a_line_goes_on() {
+        assert_eq!(outcome(&board(".........")), None);
+        assert_eq!(outcome(&board("xoxxoo.x.")), None);
+    }
+
+    #[test]
+    fn forfeiting_hands_the_game_to_the_opponent_once() {
+        let service = TicTacToe::new(EventBus::new());
+        let (x, o) = matched(&service);
+        let mut tables = service.tables.lock().unwrap();
+        assert_eq!(tables.forfeit(o.id()), Some(x.id()));
+        let id = tables.playing[&x.id()];
+        assert_eq!(
+            tables.matches[&id].outcome,
+            Some(Outcome::Forfeited(Mark::O))
+        );
+        // Over already, so nobody else needs telling
+        assert_eq!(tables.forfeit(x.id()), None);
+    }
+
+    #[test]
+    fn a_match_is_kept_until_both_players_leave() {
+        let service = TicTacToe::new(EventBus::new());
+        let (x, o) = matched(&service);
+        let mut tables = service.tables.lock().unwrap();
+        assert_eq!(tables.leave(x.id()), Some(o.id()));
+        assert_eq!(tables.matches.len(), 1);
+        assert!(!tables.playing.contains_key(&x.id()));
+
+        assert_eq!(tables.leave(o.id()), None);
+        assert!(tables.matches.is_empty());
+        assert!(tables.playing.is_empty());
+    }
+
+    #[test]
+    fn seats_play_a_game_to_the_end() {
+        let service = TicTacToe::new(EventBus::new());
+        let (x, o) = matched(&service);
+        assert_eq!(service.lobby(), (0, 1));
+        assert_eq!(playing(&x).mark, Mark::X);
+        assert_eq!(playing(&o).opponent, x.name());
+        assert!(playing(&x).my_move());
+        assert!(matches!(o.play(0), Err(MoveError::NotYourMove)));
+
+        for (seat, square) in [(&x, 0), (&o, 3), (&x, 1), (&o, 4)] {
+            seat.play(square).unwrap();
+        }
+        assert!(matches!(x.play(3), Err(MoveError::Taken)));
+        x.play(2).unwrap();
+        assert_eq!(playing(&o).outcome, Some(Outcome::Won(Mark::X)));
+        assert!(matches!(o.play(8), Err(MoveError::NotPlaying)));
+        assert_eq!(service.lobby(), (0, 0));
+    }
+
+    #[test]
+    fn resigning_ends_the_game_for_both() {
+        let service = TicTacToe::new(EventBus::new());
+        let (x, o) = matched(&service);
+        x.resign();
+        assert_eq!(playing(&o).outcome, Some(Outcome::Forfeited(Mark::X)));
+        assert!(service.notice(o.id()).unwrap().contains("you win"));
+        assert_eq!(playing(&x).outcome, Some(Outcome::Forfeited(Mark::X)));
+    }
+
+    #[test]
+    fn dropping_a_seat_forfeits_without_leaking_the_match() {
+        let service = TicTacToe::new(EventBus::new());
+        let (x, o) = matched(&service);
+        let mut events = service.events.subscribe();
+        drop(x);
+
+        assert_eq!(playing(&o).outcome, Some(Outcome::Forfeited(Mark::X)));
+        let told = [events.try_recv().unwrap(), events.try_recv().unwrap()];
+        assert!(
+            told.iter()
+                .any(|event| matches!(event, Event::TicTacToe(id) if *id == o.id()))
+        );
+
+        drop(o);
+        let tables = service.tables.lock().unwrap();
+        assert!(tables.matches.is_empty());
+        assert!(tables.playing.is_empty());
+        assert!(tables.names.is_empty());
+    }
+
+    #[test]
+    fn looking_again_leaves_a_finished_game_behind() {
+        let service = TicTacToe::new(EventBus::new());
+        let (x, o) = matched(&service);
+        x.resign();
+        o.find_opponent();
+        assert!(matches!(o.status(), Status::Waiting));
+        x.find_opponent();
+        assert_eq!(playing(&o).mark, Mark::X);
+        assert_eq!(service.tables.lock().unwrap().matches.len(), 1);
+    }
+}
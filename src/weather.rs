@@ -0,0 +1,178 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use serde::Deserialize;
+
+use crate::config::{Config, SharedConfig};
+use crate::poller::{self, Poller};
+
+const OPEN_METEO_FORECAST: &str = "https://api.open-meteo.com/v1/forecast";
+// How long conditions are kept through failed fetches
+const STALE_AFTER: Duration = Duration::from_secs(3 * 3600);
+
+/// The weather codes Open-Meteo reports, grouped by how they're drawn.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Sky {
+    Clear,
+    PartlyCloudy,
+    Overcast,
+    Fog,
+    Drizzle,
+    Rain,
+    Snow,
+    Thunderstorm,
+}
+
+impl Sky {
+    /// From a WMO weather interpretation code, with the ones Open-Meteo never
+    /// sends read as overcast.
+    fn from_code(code: u8) -> Self {
+        match code {
+            0 => Self::Clear,
+            1 | 2 => Self::PartlyCloudy,
+            45 | 48 => Self::Fog,
+            51..=57 => Self::Drizzle,
+            61..=67 | 80..=82 => Self::Rain,
+            71..=77 | 85 | 86 => Self::Snow,
+            95..=99 => Self::Thunderstorm,
+            _ => Self::Overcast,
+        }
+    }
+
+    pub fn glyph(self, day: bool) -> char {
+        match self {
+            Self::Clear if day => '☀',
+            Self::Clear => '☾',
+            // ⛅ would be the one, but it's two cells wide in some terminals
+            Self::PartlyCloudy | Self::Overcast => '☁',
+            Self::Fog => '≡',
+            Self::Drizzle | Self::Rain => '☂',
+            Self::Snow => '❄',
+            Self::Thunderstorm => '☈',
+        }
+    }
+
+    pub fn description(self) -> &'static str {
+        match self {
+            Self::Clear => "clear",
+            Self::PartlyCloudy => "partly cloudy",
+            Self::Overcast => "overcast",
+            Self::Fog => "foggy",
+            Self::Drizzle => "drizzling",
+            Self::Rain => "raining",
+            Self::Snow => "snowing",
+            Self::Thunderstorm => "stormy",
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Conditions {
+    pub sky: Sky,
+    pub day: bool,
+    /// Rounded, in `unit`.
+    pub temperature: i32,
+    pub unit: char,
+    pub place: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct Forecast {
+    current: Current,
+}
+
+#[derive(Deserialize)]
+struct Current {
+    temperature_2m: f64,
+    weather_code: u8,
+    is_day: u8,
+}
+
+/// The weather where the owner lives, checked in the background and shared by
+/// every session, for the about page.
+pub struct Weather {
+    config: SharedConfig,
+    agent: ureq::Agent,
+    conditions: Arc<Poller<Option<Conditions>>>,
+}
+
+impl Weather {
+    pub fn new(config: SharedConfig) -> Arc<Self> {
+        Arc::new(Self {
+            config,
+            agent: poller::agent(),
+            conditions: Poller::new(Some(STALE_AFTER)),
+        })
+    }
+
+    /// None until the first fetch, or when no place is set.
+    pub fn conditions(&self) -> Option<Conditions> {
+        self.conditions.get()
+    }
+
+    pub fn generation(&self) -> u64 {
+        self.conditions.generation()
+    }
+
+    /// Checks now and then every `weather_refresh_secs`.
+    pub fn refresh_periodically(self: &Arc<Self>) {
+        let weather = self.clone();
+        self.conditions.start(
+            "the weather",
+            self.config.clone(),
+            Config::weather_refresh,
+            move || weather.current(),
+        );
+    }
+
+    fn current(&self) -> Result<Option<Conditions>, anyhow::Error> {
+        let (location, fahrenheit, place) = {
+            let config = self.config.read().unwrap();
+            (
+                config.weather_latitude.zip(config.weather_longitude),
+                config.weather_fahrenheit,
+                config.weather_place.clone(),
+            )
+        };
+        // Set up no longer after a reload
+        let Some((latitude, longitude)) = location else {
+            return Ok(None);
+        };
+        let current = self.fetch(latitude, longitude, fahrenheit)?;
+        Ok(Some(Conditions {
+            sky: Sky::from_code(current.weather_code),
+            day: current.is_day != 0,
+            temperature: current.temperature_2m.round() as i32,
+            unit: match fahrenheit {
+                true => 'f',
+                false => 'c',
+            },
+            place,
+        }))
+    }
+
+    fn fetch(
+        &self,
+        latitude: f64,
+        longitude: f64,
+        fahrenheit: bool,
+    ) -> Result<Current, anyhow::Error> {
+        let forecast: Forecast = self
+            .agent
+            .get(OPEN_METEO_FORECAST)
+            .query("latitude", latitude.to_string())
+            .query("longitude", longitude.to_string())
+            .query("current", "temperature_2m,weather_code,is_day")
+            .query(
+                "temperature_unit",
+                match fahrenheit {
+                    true => "fahrenheit",
+                    false => "celsius",
+                },
+            )
+            .call()?
+            .body_mut()
+            .read_json()?;
+        Ok(forecast.current)
+    }
+}
@@ -0,0 +1,752 @@
+//! Runs the server binary on an ephemeral port and drives it like a visitor
+//! would, through an SSH client with a pty.
+
+use std::borrow::Cow;
+use std::io::{BufRead, BufReader};
+use std::net::SocketAddr;
+use std::path::PathBuf;
+use std::process::{Child, Command, Stdio};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Duration;
+
+use russh::client::{self, Handle, KeyboardInteractiveAuthResponse, Msg};
+use russh::keys::PublicKeyOrCertificate;
+use russh::{ChannelMsg, Preferred, compression};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpStream;
+
+// The app asks for at least 150 columns
+const WIDTH: u32 = 160;
+const HEIGHT: u32 = 45;
+const TOO_NARROW: &[u8] = b"Terminal too narrow";
+const SKIP_INTRO: &[u8] = b"press any key to skip";
+// On the home page
+const HOME: &[u8] = b"my name is";
+// Turned on once the app starts, and off again by the reset on the way out
+const ENABLE_MOUSE: &[u8] = b"\x1b[?1000h";
+const SHOW_CURSOR: &[u8] = b"\x1b[?25h";
+const TIMEOUT: Duration = Duration::from_secs(10);
+
+static NEXT_DIR: AtomicUsize = AtomicUsize::new(0);
+
+/// The server, killed when dropped.
+struct TestServer {
+    process: Child,
+    addr: SocketAddr,
+    // With WEB_LISTEN_ADDR, TELNET_LISTEN_ADDR and FINGER_LISTEN_ADDR set
+    web_addr: Option<SocketAddr>,
+    telnet_addr: Option<SocketAddr>,
+    finger_addr: Option<SocketAddr>,
+    dir: PathBuf,
+}
+
+impl TestServer {
+    fn start(env: &[(&str, &str)]) -> Self {
+        let dir = std::env::temp_dir().join(format!(
+            "portfolio-v2-test-{}-{}",
+            std::process::id(),
+            NEXT_DIR.fetch_add(1, Ordering::Relaxed)
+        ));
+        std::fs::create_dir_all(&dir).unwrap();
+
+        let mut process = Command::new(env!("CARGO_BIN_EXE_portfolio-v2"))
+            .args([
+                "serve",
+                "--listen",
+                "127.0.0.1:0",
+                "--auto-generate-hostkey",
+            ])
+            .env_clear()
+            .env("SECRETS_LOCATION", dir.join("host_key"))
+            .env("DATABASE_PATH", dir.join("portfolio.db"))
+            .env(
+                "CONTENT_DIR",
+                concat!(env!("CARGO_MANIFEST_DIR"), "/content"),
+            )
+            .env("LOG_FORMAT", "json")
+            .envs(env.iter().copied())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .spawn()
+            .expect("the server binary runs");
+
+        let mut lines = BufReader::new(process.stdout.take().unwrap()).lines();
+        let mut listening_on = |message: &str| {
+            lines.by_ref().map_while(Result::ok).find_map(|line| {
+                let event: serde_json::Value = serde_json::from_str(&line).ok()?;
+                (event["fields"]["message"] == message)
+                    .then(|| event["fields"]["listen"].as_str()?.parse().ok())?
+            })
+        };
+        let addr = listening_on("Accepting connections")
+            .expect("the server logs the address it listens on");
+        let mut listening_if_set = |name: &str, message: &str| {
+            env.iter()
+                .any(|(set, _)| *set == name)
+                .then(|| listening_on(message))
+                .flatten()
+        };
+        let web_addr = listening_if_set("WEB_LISTEN_ADDR", "Serving the web terminal");
+        let telnet_addr = listening_if_set("TELNET_LISTEN_ADDR", "Serving unencrypted telnet");
+        let finger_addr = listening_if_set("FINGER_LISTEN_ADDR", "Answering finger queries");
+        // Keep reading, so a full pipe never blocks the server's logging
+        std::thread::spawn(move || lines.for_each(drop));
+
+        Self {
+            process,
+            addr,
+            web_addr,
+            telnet_addr,
+            finger_addr,
+            dir,
+        }
+    }
+
+    async fn connect(&self) -> Visitor {
+        self.connect_as("visitor").await
+    }
+
+    async fn connect_as(&self, user: &str) -> Visitor {
+        self.connect_with(user, client::Config::default()).await
+    }
+
+    async fn connect_with(&self, user: &str, config: client::Config) -> Visitor {
+        let (handle, channel) = self.open_pty(user, config).await;
+        channel.request_shell(true).await.unwrap();
+        Visitor::new(handle, channel)
+    }
+
+    /// A session channel with a pty, before the shell is asked for.
+    async fn open_pty(
+        &self,
+        user: &str,
+        config: client::Config,
+    ) -> (Handle<AcceptAnyHostKey>, russh::Channel<Msg>) {
+        let config = Arc::new(config);
+        let mut handle = client::connect(config, self.addr, AcceptAnyHostKey)
+            .await
+            .expect("connects");
+        // With auth_mode none, keyboard-interactive lets anyone in without a prompt
+        let auth = handle
+            .authenticate_keyboard_interactive_start(user, None)
+            .await
+            .unwrap();
+        assert!(matches!(auth, KeyboardInteractiveAuthResponse::Success));
+
+        let channel = handle.channel_open_session().await.unwrap();
+        channel
+            .request_pty(true, "xterm-256color", WIDTH, HEIGHT, 0, 0, &[])
+            .await
+            .unwrap();
+        (handle, channel)
+    }
+}
+
+impl Drop for TestServer {
+    fn drop(&mut self) {
+        let _ = self.process.kill();
+        let _ = self.process.wait();
+        let _ = std::fs::remove_dir_all(&self.dir);
+    }
+}
+
+struct AcceptAnyHostKey;
+
+impl client::Handler for AcceptAnyHostKey {
+    type Error = russh::Error;
+
+    async fn check_server_key(&mut self, _: &PublicKeyOrCertificate) -> Result<bool, Self::Error> {
+        Ok(true)
+    }
+}
+
+/// One SSH session, and everything the server sent on it so far.
+struct Visitor {
+    _handle: Handle<AcceptAnyHostKey>,
+    channel: russh::Channel<Msg>,
+    output: Vec<u8>,
+    exit_status: Option<u32>,
+    closed: bool,
+}
+
+impl Visitor {
+    fn new(handle: Handle<AcceptAnyHostKey>, channel: russh::Channel<Msg>) -> Self {
+        Self {
+            _handle: handle,
+            channel,
+            output: Vec::new(),
+            exit_status: None,
+            closed: false,
+        }
+    }
+
+    async fn send(&self, keys: &str) {
+        self.channel.data(keys.as_bytes()).await.unwrap();
+    }
+
+    /// Waits for the intro and skips it, leaving the visitor on the home page.
+    async fn skip_intro(&mut self) {
+        self.wait_for(0, SKIP_INTRO).await;
+        let from = self.output.len();
+        self.send(" ").await;
+        self.wait_for(from, HOME).await;
+    }
+
+    /// Reads until the output since `from` contains `needle`, and panics with what
+    /// arrived if it never does.
+    async fn wait_for(&mut self, from: usize, needle: &[u8]) {
+        let found = tokio::time::timeout(TIMEOUT, async {
+            while !contains(&self.output[from..], needle) {
+                if !self.next_message().await {
+                    return false;
+                }
+            }
+            true
+        })
+        .await;
+        assert!(
+            found == Ok(true),
+            "expected {:?} in {:?}",
+            String::from_utf8_lossy(needle),
+            String::from_utf8_lossy(&self.output[from..])
+        );
+    }
+
+    /// Reads until the server closes the channel.
+    async fn wait_for_close(&mut self) {
+        let closed =
+            tokio::time::timeout(TIMEOUT, async { while self.next_message().await {} }).await;
+        assert!(closed.is_ok(), "the server never closed the channel");
+        assert!(self.closed);
+    }
+
+    /// False once the channel is closed.
+    async fn next_message(&mut self) -> bool {
+        match self.channel.wait().await {
+            Some(ChannelMsg::Data { data }) => self.output.extend_from_slice(&data),
+            Some(ChannelMsg::ExitStatus { exit_status }) => self.exit_status = Some(exit_status),
+            Some(ChannelMsg::Close) | None => {
+                self.closed = true;
+                return false;
+            }
+            Some(_) => {}
+        }
+        true
+    }
+}
+
+fn contains(haystack: &[u8], needle: &[u8]) -> bool {
+    haystack
+        .windows(needle.len())
+        .any(|window| window == needle)
+}
+
+/// crossterm's cursor move to the start of `row`, counting from 1.
+fn row_start(row: u32) -> Vec<u8> {
+    format!("\x1b[{};1H", row).into_bytes()
+}
+
+#[tokio::test]
+async fn connect_draws_the_app() {
+    let server = TestServer::start(&[]);
+    let mut visitor = server.connect().await;
+
+    visitor.wait_for(0, ENABLE_MOUSE).await;
+    visitor.skip_intro().await;
+    visitor.wait_for(0, &row_start(HEIGHT)).await;
+    assert!(!contains(&visitor.output, &row_start(HEIGHT + 1)));
+    assert!(!contains(&visitor.output, TOO_NARROW));
+}
+
+#[tokio::test]
+async fn compressed_sessions_draw_the_app() {
+    let server = TestServer::start(&[]);
+    let config = client::Config {
+        preferred: Preferred {
+            compression: Cow::Borrowed(&[compression::ZLIB_LEGACY]),
+            ..Preferred::default()
+        },
+        ..client::Config::default()
+    };
+    let mut visitor = server.connect_with("visitor", config).await;
+    visitor.skip_intro().await;
+}
+
+#[tokio::test]
+async fn keys_sent_before_the_shell_reach_the_app() {
+    let server = TestServer::start(&[]);
+    let (handle, channel) = server.open_pty("visitor", client::Config::default()).await;
+    // Skips the intro and jumps to the settings through the command palette
+    channel.data(&b" :sett\r"[..]).await.unwrap();
+    channel.request_shell(true).await.unwrap();
+    let mut visitor = Visitor::new(handle, channel);
+    visitor.wait_for(0, b"[ settings ]").await;
+}
+
+#[tokio::test]
+async fn closing_the_input_ends_the_session() {
+    let server = TestServer::start(&[]);
+    let mut visitor = server.connect().await;
+    visitor.wait_for(0, SKIP_INTRO).await;
+
+    visitor.channel.eof().await.unwrap();
+    visitor.wait_for_close().await;
+    assert_eq!(visitor.exit_status, Some(0));
+}
+
+#[tokio::test]
+async fn extra_channels_on_a_connection_are_turned_down() {
+    let server = TestServer::start(&[]);
+    let (handle, channel) = server.open_pty("visitor", client::Config::default()).await;
+    assert!(matches!(
+        handle.channel_open_session().await,
+        Err(russh::Error::ChannelOpenFailure(
+            russh::ChannelOpenFailure::ResourceShortage
+        ))
+    ));
+
+    // The first one still works
+    channel.request_shell(true).await.unwrap();
+    let mut visitor = Visitor::new(handle, channel);
+    visitor.wait_for(0, SKIP_INTRO).await;
+}
+
+#[tokio::test]
+async fn resize_redraws_at_the_new_size() {
+    let server = TestServer::start(&[]);
+    let mut visitor = server.connect().await;
+    visitor.skip_intro().await;
+    visitor.wait_for(0, &row_start(HEIGHT)).await;
+
+    let from = visitor.output.len();
+    visitor
+        .channel
+        .window_change(WIDTH + 40, HEIGHT + 10, 0, 0)
+        .await
+        .unwrap();
+    visitor.wait_for(from, &row_start(HEIGHT + 10)).await;
+
+    let from = visitor.output.len();
+    visitor.channel.window_change(80, 24, 0, 0).await.unwrap();
+    visitor.wait_for(from, TOO_NARROW).await;
+}
+
+#[tokio::test]
+async fn a_burst_of_resizes_redraws_once() {
+    let server = TestServer::start(&[]);
+    let mut visitor = server.connect().await;
+    visitor.skip_intro().await;
+    visitor.wait_for(0, &row_start(HEIGHT)).await;
+
+    // Like dragging a window's corner back and forth, ending up a bit larger
+    let from = visitor.output.len();
+    for height in (HEIGHT + 1..=HEIGHT + 10).rev() {
+        visitor
+            .channel
+            .window_change(WIDTH, height, 0, 0)
+            .await
+            .unwrap();
+    }
+    visitor
+        .channel
+        .window_change(WIDTH, HEIGHT + 5, 0, 0)
+        .await
+        .unwrap();
+    visitor.wait_for(from, &row_start(HEIGHT + 5)).await;
+    assert!(!contains(&visitor.output[from..], &row_start(HEIGHT + 10)));
+}
+
+#[tokio::test]
+async fn the_intro_plays_out_into_the_home_page() {
+    let server = TestServer::start(&[]);
+    let mut visitor = server.connect().await;
+    visitor.wait_for(0, SKIP_INTRO).await;
+    assert!(!contains(&visitor.output, HOME));
+
+    let from = visitor.output.len();
+    visitor.wait_for(from, HOME).await;
+}
+
+#[tokio::test]
+async fn navigation_opens_the_next_page() {
+    let server = TestServer::start(&[]);
+    let mut visitor = server.connect().await;
+    visitor.skip_intro().await;
+
+    let from = visitor.output.len();
+    visitor.send("j").await;
+    visitor.wait_for(from, b"experience").await;
+}
+
+#[tokio::test]
+async fn the_command_palette_jumps_to_a_page() {
+    let server = TestServer::start(&[]);
+    let mut visitor = server.connect().await;
+    visitor.skip_intro().await;
+
+    let from = visitor.output.len();
+    visitor.send(":").await;
+    visitor.wait_for(from, b"anything").await;
+
+    let from = visitor.output.len();
+    visitor.send("sett\r").await;
+    visitor.wait_for(from, b"[ settings ]").await;
+}
+
+#[tokio::test]
+async fn the_username_picks_the_start_page() {
+    let server = TestServer::start(&[]);
+    let mut visitor = server.connect_as("projects").await;
+
+    visitor.wait_for(0, b"[ projects ]").await;
+    assert!(!contains(&visitor.output, SKIP_INTRO));
+}
+
+#[tokio::test]
+async fn visitors_start_with_the_configured_theme() {
+    let server = TestServer::start(&[("THEME", "light")]);
+    let mut visitor = server.connect_as("projects").await;
+
+    // The light theme's background, in the 256 colors xterm-256color gets
+    visitor.wait_for(0, b"48;5;231m").await;
+}
+
+#[tokio::test]
+async fn visitors_are_matched_for_tic_tac_toe() {
+    let server = TestServer::start(&[]);
+    let mut first = server.connect_as("tic-tac-toe").await;
+    first.wait_for(0, b"find an opponent").await;
+    let from = first.output.len();
+    first.send("\r").await;
+    first.wait_for(from, b"meanwhile").await;
+
+    let mut second = server.connect_as("tic-tac-toe").await;
+    second.wait_for(0, b"find an opponent").await;
+    let from = first.output.len();
+    second.send("\r").await;
+    // The board is new on both screens, where the words might only partly be
+    second.wait_for(0, "───┼".as_bytes()).await;
+    first.wait_for(from, "───┼".as_bytes()).await;
+
+    // Leaving the site gives the game up
+    let from = first.output.len();
+    drop(second);
+    first.wait_for(from, b"so you win").await;
+}
+
+#[tokio::test]
+async fn visitors_vote_once_in_the_poll() {
+    let server = TestServer::start(&[]);
+    let mut first = server.connect_as("poll").await;
+    first.wait_for(0, b"one vote each").await;
+    let from = first.output.len();
+    first.send("\r").await;
+    first.wait_for(from, "✔ yours".as_bytes()).await;
+
+    // Coming back from the same address, the vote is still theirs
+    let mut second = server.connect_as("poll").await;
+    second.wait_for(0, b"thanks for voting").await;
+}
+
+#[tokio::test]
+async fn the_web_gateway_serves_the_terminal_page() {
+    let server = TestServer::start(&[("WEB_LISTEN_ADDR", "127.0.0.1:0")]);
+    let mut stream = TcpStream::connect(server.web_addr.unwrap()).await.unwrap();
+    stream
+        .write_all(b"GET / HTTP/1.1\r\nHost: localhost\r\n\r\n")
+        .await
+        .unwrap();
+
+    let mut response = Vec::new();
+    stream.read_to_end(&mut response).await.unwrap();
+    assert!(response.starts_with(b"HTTP/1.1 200 OK"));
+    assert!(contains(&response, b"xterm.min.js"));
+}
+
+#[tokio::test]
+async fn websockets_from_other_sites_are_refused() {
+    let server = TestServer::start(&[
+        ("WEB_LISTEN_ADDR", "127.0.0.1:0"),
+        ("WEB_HOST", "krayon.dev"),
+    ]);
+    let mut stream = TcpStream::connect(server.web_addr.unwrap()).await.unwrap();
+    stream
+        .write_all(
+            b"GET /ws HTTP/1.1\r\nHost: krayon.dev\r\nOrigin: https://evil.example\r\n\
+Upgrade: websocket\r\nConnection: Upgrade\r\nSec-WebSocket-Key: dGhlIHNhbXBsZSBub25jZQ==\r\n\
+Sec-WebSocket-Version: 13\r\n\r\n",
+        )
+        .await
+        .unwrap();
+
+    let mut response = Vec::new();
+    stream.read_to_end(&mut response).await.unwrap();
+    assert!(response.starts_with(b"HTTP/1.1 403 Forbidden"));
+}
+
+#[tokio::test]
+async fn telnet_visitors_are_warned_first() {
+    let server = TestServer::start(&[("TELNET_LISTEN_ADDR", "127.0.0.1:0")]);
+    let mut stream = TcpStream::connect(server.telnet_addr.unwrap())
+        .await
+        .unwrap();
+
+    let mut output = Vec::new();
+    let mut buf = [0u8; 1024];
+    while !contains(&output, b"press any key") {
+        let read = tokio::time::timeout(TIMEOUT, stream.read(&mut buf))
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(read > 0, "the server hung up");
+        output.extend_from_slice(&buf[..read]);
+    }
+    assert!(contains(&output, b"not encrypted"));
+}
+
+#[tokio::test]
+async fn finger_answers_with_the_about_page() {
+    let server = TestServer::start(&[("FINGER_LISTEN_ADDR", "127.0.0.1:0")]);
+    let mut stream = TcpStream::connect(server.finger_addr.unwrap())
+        .await
+        .unwrap();
+    stream.write_all(b"\r\n").await.unwrap();
+
+    let mut response = Vec::new();
+    stream.read_to_end(&mut response).await.unwrap();
+    assert!(contains(&response, HOME));
+}
+
+/// Answers one request on `listener` and returns its body.
+async fn next_post(listener: &tokio::net::TcpListener) -> String {
+    let (mut stream, _) = tokio::time::timeout(TIMEOUT, listener.accept())
+        .await
+        .expect("nothing was posted")
+        .unwrap();
+    let mut request = Vec::new();
+    let mut buf = [0u8; 4096];
+    let body_start = loop {
+        let read = stream.read(&mut buf).await.unwrap();
+        request.extend_from_slice(&buf[..read]);
+        if let Some(end) = request.windows(4).position(|window| window == b"\r\n\r\n") {
+            break end + 4;
+        }
+    };
+    let head = String::from_utf8_lossy(&request[..body_start]).to_lowercase();
+    let length: usize = head
+        .split("content-length:")
+        .nth(1)
+        .and_then(|rest| rest.lines().next())
+        .and_then(|length| length.trim().parse().ok())
+        .unwrap();
+    while request.len() < body_start + length {
+        let read = stream.read(&mut buf).await.unwrap();
+        request.extend_from_slice(&buf[..read]);
+    }
+    stream
+        .write_all(b"HTTP/1.1 204 No Content\r\nConnection: close\r\n\r\n")
+        .await
+        .unwrap();
+    String::from_utf8_lossy(&request[body_start..]).into_owned()
+}
+
+#[tokio::test]
+async fn visits_are_posted_to_the_notify_webhook() {
+    let webhook = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("http://{}/hook", webhook.local_addr().unwrap());
+    let server = TestServer::start(&[("NOTIFY_WEBHOOK", &url), ("NOTIFY_INTERVAL_SECS", "0")]);
+    let mut visitor = server.connect().await;
+    assert!(
+        next_post(&webhook)
+            .await
+            .contains("someone connected over ssh")
+    );
+
+    visitor.skip_intro().await;
+    drop(visitor);
+    let left = next_post(&webhook).await;
+    assert!(left.contains("a visitor left after"), "{}", left);
+    assert!(left.contains("about"), "{}", left);
+}
+
+#[tokio::test]
+async fn password_attempts_are_turned_down_and_logged() {
+    let log = std::env::temp_dir().join(format!("portfolio-v2-honeypot-{}", std::process::id()));
+    let server = TestServer::start(&[
+        ("HONEYPOT_LOG", log.to_str().unwrap()),
+        ("HONEYPOT_RAW", "true"),
+    ]);
+    let config = Arc::new(client::Config::default());
+    let mut handle = client::connect(config, server.addr, AcceptAnyHostKey)
+        .await
+        .unwrap();
+    let auth = handle
+        .authenticate_password("root", "hunter2")
+        .await
+        .unwrap();
+    assert!(!auth.success());
+
+    let logged = std::fs::read_to_string(&log).unwrap();
+    let _ = std::fs::remove_file(&log);
+    assert!(logged.contains(r#""user":"root""#), "{}", logged);
+    assert!(logged.contains(r#""password":"hunter2""#), "{}", logged);
+}
+
+#[tokio::test]
+async fn finished_connections_are_audited() {
+    let log = std::env::temp_dir().join(format!("portfolio-v2-audit-{}", std::process::id()));
+    let server = TestServer::start(&[("AUDIT_LOG", log.to_str().unwrap())]);
+    let mut visitor = server.connect_as("auditor").await;
+    visitor.skip_intro().await;
+    visitor.send("q").await;
+    visitor.wait_for_close().await;
+    drop(visitor);
+
+    // The entry is written once the server notices the connection is gone
+    let logged = tokio::time::timeout(TIMEOUT, async {
+        loop {
+            match std::fs::read_to_string(&log) {
+                Ok(logged) if !logged.is_empty() => return logged,
+                _ => tokio::time::sleep(Duration::from_millis(50)).await,
+            }
+        }
+    })
+    .await
+    .expect("the connection is audited");
+    let _ = std::fs::remove_file(&log);
+    let entry: serde_json::Value = serde_json::from_str(logged.trim()).unwrap();
+    assert_eq!(entry["user"], "auditor");
+    assert_eq!(entry["ip"], "127.0.0.1");
+    assert!(entry["bytes_out"].as_u64() > Some(0), "{}", logged);
+    assert!(entry["reason"].is_string(), "{}", logged);
+}
+
+#[tokio::test]
+async fn telnet_connections_are_audited() {
+    let log =
+        std::env::temp_dir().join(format!("portfolio-v2-audit-telnet-{}", std::process::id()));
+    let server = TestServer::start(&[
+        ("AUDIT_LOG", log.to_str().unwrap()),
+        ("TELNET_LISTEN_ADDR", "127.0.0.1:0"),
+    ]);
+    let mut stream = TcpStream::connect(server.telnet_addr.unwrap())
+        .await
+        .unwrap();
+    let mut output = Vec::new();
+    let mut buf = [0u8; 1024];
+    while !contains(&output, b"press any key") {
+        let read = tokio::time::timeout(TIMEOUT, stream.read(&mut buf))
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(read > 0, "the server hung up");
+        output.extend_from_slice(&buf[..read]);
+    }
+    drop(stream);
+
+    let logged = tokio::time::timeout(TIMEOUT, async {
+        loop {
+            match std::fs::read_to_string(&log) {
+                Ok(logged) if !logged.is_empty() => return logged,
+                _ => tokio::time::sleep(Duration::from_millis(50)).await,
+            }
+        }
+    })
+    .await
+    .expect("the connection is audited");
+    let _ = std::fs::remove_file(&log);
+    let entry: serde_json::Value = serde_json::from_str(logged.trim()).unwrap();
+    assert_eq!(entry["via"], "telnet");
+    assert_eq!(entry["ip"], "127.0.0.1");
+    assert!(entry["bytes_out"].as_u64() > Some(0), "{}", logged);
+}
+
+#[test]
+fn export_html_writes_the_pages_in_color() {
+    let dir = std::env::temp_dir().join(format!("portfolio-v2-export-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let status = Command::new(env!("CARGO_BIN_EXE_portfolio-v2"))
+        .arg("export-html")
+        .arg(dir.join("site"))
+        .env_clear()
+        .env("DATABASE_PATH", dir.join("portfolio.db"))
+        .env("CONTENT_DIR", dir.join("content"))
+        .stdout(Stdio::null())
+        .status()
+        .unwrap();
+    assert!(status.success());
+
+    let index = std::fs::read_to_string(dir.join("site/index.html")).unwrap();
+    assert!(index.contains("my name is"));
+    assert!(index.contains("<span style=\"color:#"));
+    assert!(dir.join("site/projects.html").exists());
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[tokio::test]
+async fn idle_sessions_are_disconnected() {
+    let server = TestServer::start(&[("IDLE_TIMEOUT_SECS", "2"), ("IDLE_WARNING_SECS", "1")]);
+    let mut visitor = server.connect().await;
+    visitor.wait_for(0, ENABLE_MOUSE).await;
+
+    let from = visitor.output.len();
+    visitor.wait_for_close().await;
+    assert!(contains(&visitor.output[from..], SHOW_CURSOR));
+}
+
+#[tokio::test]
+async fn sessions_over_the_byte_cap_are_disconnected() {
+    let server = TestServer::start(&[("SESSION_BYTE_CAP", "1"), ("BYTE_CAP_ACTION", "disconnect")]);
+    let mut visitor = server.connect().await;
+    visitor.wait_for_close().await;
+    assert!(contains(&visitor.output, b"used up its bandwidth"));
+}
+
+#[tokio::test]
+async fn quitting_restores_the_terminal() {
+    let server = TestServer::start(&[]);
+    let mut visitor = server.connect().await;
+    visitor.skip_intro().await;
+
+    let from = visitor.output.len();
+    visitor.send("q").await;
+    visitor.wait_for_close().await;
+    assert!(contains(&visitor.output[from..], SHOW_CURSOR));
+    assert_eq!(visitor.exit_status, Some(0));
+}
+
+#[tokio::test]
+async fn easter_eggs_leave_bound_keys_alone() {
+    let server = TestServer::start(&[]);
+    let mut visitor = server.connect().await;
+    visitor.skip_intro().await;
+
+    // `l` still focuses the page on its way to `sl`
+    let from = visitor.output.len();
+    visitor.send("sl").await;
+    visitor.wait_for(from, b"[ about ]").await;
+    visitor.send("\r").await;
+    visitor.wait_for(from, b"you typed sl").await;
+}
+
+#[tokio::test]
+async fn a_client_let_in_holds_its_place_before_its_shell() {
+    let server = TestServer::start(&[("MAX_CLIENTS", "1")]);
+    let (first_handle, first_channel) = server.open_pty("visitor", client::Config::default()).await;
+
+    // The first client hasn't asked for its shell yet, and still has the place
+    let mut second = server.connect().await;
+    second.wait_for_close().await;
+    assert!(contains(&second.output, b"server is full"));
+
+    first_channel.request_shell(true).await.unwrap();
+    let mut first = Visitor::new(first_handle, first_channel);
+    first.skip_intro().await;
+    first.send("q").await;
+    first.wait_for_close().await;
+
+    // And gives it back on the way out
+    let mut third = server.connect().await;
+    third.wait_for(0, SKIP_INTRO).await;
+}